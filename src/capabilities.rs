@@ -0,0 +1,450 @@
+//! Printer capabilities (the values supported for each job attribute) and validation of print options
+//!
+//! ```rust
+//! use printers::capabilities::{self, Capabilities};
+//! use printers::document::DocumentFormat;
+//! use printers::print_options::{PrintOptions, Sides};
+//!
+//! let mut capabilities = Capabilities::default();
+//! capabilities.set("sides", vec!["one-sided".to_string()]);
+//!
+//! let options = PrintOptions {
+//!     sides: Some(Sides::TwoSidedLongEdge),
+//!     ..PrintOptions::default()
+//! };
+//! let violations = capabilities::validate(&capabilities, &options, DocumentFormat::PDF);
+//!
+//! assert_eq!(violations[0].suggestion.as_deref(), Some("one-sided"));
+//! ```
+
+use crate::document::DocumentFormat;
+use crate::media::MediaSize;
+use crate::print_options::{
+    ColorMode, Finishing, MediaSource, Orientation, OutputBin, PrintOptions, PrintQuality,
+    Resolution,
+};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/**
+ * Job attributes queried for the printer capabilities
+ */
+pub(crate) const ATTRIBUTES: [&str; 12] = [
+    "document-format",
+    "copies",
+    "media",
+    "media-source",
+    "output-bin",
+    "sides",
+    "finishings",
+    "print-color-mode",
+    "print-quality",
+    "printer-resolution",
+    "print-scaling",
+    "orientation-requested",
+];
+
+/**
+ * Print color modes from the least to the most color use, the nearest supported mode is suggested
+ */
+const COLOR_MODES: [&str; 7] = [
+    "bi-level",
+    "process-bi-level",
+    "monochrome",
+    "process-monochrome",
+    "auto-monochrome",
+    "auto",
+    "color",
+];
+
+/**
+ * Document formats suggested when the document format is not supported, on preference order
+ */
+const DOCUMENT_FORMATS: [&str; 4] = [
+    "application/pdf",
+    "image/pwg-raster",
+    "image/urf",
+    "application/postscript",
+];
+
+/**
+ * Capabilities are the values supported by a printer for the job attributes (IPP "*-supported")
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Capabilities {
+    supported: BTreeMap<String, Vec<String>>,
+}
+
+impl Capabilities {
+    /**
+     * Set the values supported for a job attribute, like "media" with the values of "media-supported"
+     */
+    pub fn set(&mut self, attribute: &str, values: Vec<String>) -> &mut Self {
+        self.supported.insert(attribute.to_string(), values);
+        self
+    }
+
+    /**
+     * Returns the values supported for a job attribute (empty when the printer does not report them)
+     */
+    pub fn supported(&self, attribute: &str) -> &[String] {
+        self.supported.get(attribute).map_or(&[], Vec::as_slice)
+    }
+
+    /**
+     * Returns the range of copies supported (copies-supported)
+     */
+    pub fn copies(&self) -> Option<(u32, u32)> {
+        let range = self.supported("copies").first()?;
+        match range.split_once('-') {
+            Some((min, max)) => Some((min.trim().parse().ok()?, max.trim().parse().ok()?)),
+            None => {
+                let copies = range.trim().parse().ok()?;
+                Some((1, copies))
+            }
+        }
+    }
+
+    /**
+     * Returns the media sizes supported (media-supported without the custom size range)
+     */
+    pub fn media_sizes(&self) -> Vec<MediaSize> {
+        self.supported("media")
+            .iter()
+            .filter(|name| !name.starts_with("custom_min_") && !name.starts_with("custom_max_"))
+            .filter_map(|name| MediaSize::parse(name).ok())
+            .collect()
+    }
+
+    /**
+     * Returns the minimum and maximum custom media sizes ("custom_min_*" and "custom_max_*" media)
+     */
+    pub fn custom_media_range(&self) -> Option<(MediaSize, MediaSize)> {
+        let find = |prefix: &str| {
+            self.supported("media")
+                .iter()
+                .find(|name| name.starts_with(prefix))
+                .and_then(|name| MediaSize::parse(name).ok())
+        };
+
+        Some((find("custom_min_")?, find("custom_max_")?))
+    }
+
+    /**
+     * Returns the input trays supported (media-source-supported)
+     */
+    pub fn media_sources(&self) -> Vec<MediaSource> {
+        self.supported("media-source")
+            .iter()
+            .filter_map(|keyword| MediaSource::from_keyword(keyword))
+            .collect()
+    }
+
+    /**
+     * Returns the output bins supported (output-bin-supported)
+     */
+    pub fn output_bins(&self) -> Vec<OutputBin> {
+        self.supported("output-bin")
+            .iter()
+            .filter_map(|keyword| OutputBin::from_keyword(keyword))
+            .collect()
+    }
+
+    /**
+     * Returns the finishings supported (finishings-supported)
+     */
+    pub fn finishings(&self) -> Vec<Finishing> {
+        self.supported("finishings")
+            .iter()
+            .filter_map(|keyword| Finishing::from_keyword(keyword))
+            .collect()
+    }
+
+    /**
+     * Returns the color modes supported (print-color-mode-supported)
+     */
+    pub fn color_modes(&self) -> Vec<ColorMode> {
+        self.supported("print-color-mode")
+            .iter()
+            .filter_map(|keyword| ColorMode::from_keyword(keyword))
+            .collect()
+    }
+
+    /**
+     * Returns the resolutions supported (printer-resolution-supported)
+     */
+    pub fn resolutions(&self) -> Vec<Resolution> {
+        self.supported("printer-resolution")
+            .iter()
+            .filter_map(|resolution| Resolution::parse(resolution).ok())
+            .collect()
+    }
+
+    /**
+     * Check if a value is supported for a job attribute (values of attributes not reported are accepted)
+     */
+    pub fn is_supported(&self, attribute: &str, value: &str) -> bool {
+        let supported = self.supported(attribute);
+        if supported.is_empty() {
+            return true;
+        }
+
+        match attribute {
+            "copies" => match (value.trim().parse::<u32>(), self.copies()) {
+                (Ok(copies), Some((min, max))) => copies >= min && copies <= max,
+                (Err(_), _) => false,
+                _ => true,
+            },
+            "media" => match MediaSize::parse(value) {
+                Ok(media) => {
+                    let fits = match self.custom_media_range() {
+                        Some((min, max)) => {
+                            media.width >= min.width
+                                && media.height >= min.height
+                                && media.width <= max.width
+                                && media.height <= max.height
+                        }
+                        None => false,
+                    };
+
+                    fits || self
+                        .media_sizes()
+                        .iter()
+                        .any(|supported| supported.same_size(&media))
+                }
+                Err(_) => supported
+                    .iter()
+                    .any(|candidate| candidate.eq_ignore_ascii_case(value)),
+            },
+            "printer-resolution" => match Resolution::parse(value) {
+                Ok(resolution) => self.resolutions().contains(&resolution),
+                Err(_) => false,
+            },
+            "orientation-requested" => match Orientation::from_keyword(value) {
+                Some(orientation) => supported
+                    .iter()
+                    .any(|candidate| Orientation::from_keyword(candidate) == Some(orientation)),
+                None => false,
+            },
+            "finishings" => match Finishing::from_keyword(value) {
+                Some(finishing) => supported
+                    .iter()
+                    .any(|candidate| Finishing::from_keyword(candidate) == Some(finishing)),
+                None => false,
+            },
+            "print-quality" => match PrintQuality::from_keyword(value) {
+                Some(quality) => supported
+                    .iter()
+                    .any(|candidate| PrintQuality::from_keyword(candidate) == Some(quality)),
+                None => false,
+            },
+            _ => supported
+                .iter()
+                .any(|candidate| candidate.eq_ignore_ascii_case(value)),
+        }
+    }
+}
+
+/**
+ * Violation is a job attribute value not supported by the printer
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /**
+     * IPP name of the job attribute, like "media" or "sides"
+     */
+    pub attribute: String,
+
+    /**
+     * Value requested
+     */
+    pub value: String,
+
+    /**
+     * Nearest value supported by the printer (when one is known)
+     */
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            fmt,
+            "{} {} is not supported by the printer",
+            self.attribute, self.value
+        )?;
+
+        match &self.suggestion {
+            Some(suggestion) => write!(fmt, ", the nearest supported value is {}", suggestion),
+            None => Ok(()),
+        }
+    }
+}
+
+/**
+ * Check print options and the document format against the printer capabilities
+ * Returns every violation (empty when the job is valid)
+ */
+pub fn validate(
+    capabilities: &Capabilities,
+    options: &PrintOptions,
+    format: DocumentFormat,
+) -> Vec<Violation> {
+    let mut attributes = Vec::new();
+    let format = options.document_format.unwrap_or(format);
+
+    if format != DocumentFormat::AUTO {
+        attributes.push((
+            "document-format".to_string(),
+            format.mime_type().to_string(),
+        ));
+    }
+    attributes.extend(options.ipp_attributes());
+
+    let mut violations = Vec::new();
+    for (attribute, value) in attributes {
+        // Finishings are a set of values checked one by one
+        let values: Vec<&str> = if attribute == "finishings" {
+            value.split(',').map(str::trim).collect()
+        } else {
+            vec![value.as_str()]
+        };
+
+        for value in values {
+            if !capabilities.is_supported(&attribute, value) {
+                violations.push(violation(capabilities, &attribute, value));
+            }
+        }
+    }
+
+    violations
+}
+
+/**
+ * Returns the violation of an unsupported value with the nearest supported value
+ */
+pub(crate) fn violation(capabilities: &Capabilities, attribute: &str, value: &str) -> Violation {
+    Violation {
+        attribute: attribute.to_string(),
+        value: value.to_string(),
+        suggestion: suggest(capabilities, attribute, value),
+    }
+}
+
+/**
+ * Find the supported value nearest to an unsupported value
+ */
+fn suggest(capabilities: &Capabilities, attribute: &str, value: &str) -> Option<String> {
+    let supported = capabilities.supported(attribute);
+
+    match attribute {
+        "copies" => {
+            let (min, max) = capabilities.copies()?;
+            let copies: u32 = value.trim().parse().unwrap_or(min);
+            Some(copies.max(min).min(max).to_string())
+        }
+        "document-format" => DOCUMENT_FORMATS
+            .iter()
+            .find(|format| supported.iter().any(|candidate| candidate == *format))
+            .map(|format| format.to_string()),
+        "media" => match MediaSize::parse(value) {
+            Ok(media) => media
+                .closest(&capabilities.media_sizes())
+                .map(|closest| closest.name.clone()),
+            Err(_) => nearest_keyword(supported, value),
+        },
+        "printer-resolution" => {
+            let resolution = Resolution::parse(value).ok()?;
+            nearest_by(supported, |candidate| {
+                Resolution::parse(candidate).ok().map(|candidate| {
+                    (candidate.x as f64 - resolution.x as f64).abs()
+                        + (candidate.y as f64 - resolution.y as f64).abs()
+                })
+            })
+        }
+        "print-color-mode" => {
+            let rank = COLOR_MODES.iter().position(|mode| *mode == value)?;
+            nearest_by(supported, |candidate| {
+                COLOR_MODES
+                    .iter()
+                    .position(|mode| *mode == candidate)
+                    .map(|position| (position as f64 - rank as f64).abs())
+            })
+        }
+        "print-quality" => {
+            let rank = PrintQuality::from_keyword(value)? as i32;
+            nearest_by(supported, |candidate| {
+                PrintQuality::from_keyword(candidate)
+                    .map(|quality| (quality as i32 - rank).abs() as f64)
+            })
+        }
+        "sides" if supported.iter().any(|candidate| candidate == "one-sided") => {
+            Some("one-sided".to_string())
+        }
+        "media-source" | "output-bin" => {
+            nearest_numbered(supported, value).or_else(|| nearest_keyword(supported, value))
+        }
+        _ => nearest_keyword(supported, value),
+    }
+}
+
+/**
+ * Returns the candidate with the same prefix and the nearest number, like "tray-2" for "tray-3"
+ */
+fn nearest_numbered(candidates: &[String], value: &str) -> Option<String> {
+    let split = |keyword: &str| {
+        let (prefix, number) = keyword.rsplit_once('-')?;
+        Some((prefix.to_string(), number.parse::<f64>().ok()?))
+    };
+
+    let (prefix, number) = split(value)?;
+    nearest_by(candidates, |candidate| match split(candidate) {
+        Some((candidate_prefix, candidate_number)) if candidate_prefix == prefix => {
+            Some((candidate_number - number).abs())
+        }
+        _ => None,
+    })
+}
+
+/**
+ * Returns the candidate with the lowest distance, candidates without distance are skipped
+ */
+fn nearest_by<F>(candidates: &[String], distance: F) -> Option<String>
+where
+    F: Fn(&str) -> Option<f64>,
+{
+    candidates
+        .iter()
+        .filter_map(|candidate| distance(candidate).map(|distance| (distance, candidate)))
+        .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(_, candidate)| candidate.clone())
+}
+
+/**
+ * Returns the candidate with the lowest edit distance, for misspelled keywords
+ */
+fn nearest_keyword(candidates: &[String], value: &str) -> Option<String> {
+    let value = value.to_ascii_lowercase();
+    nearest_by(candidates, |candidate| {
+        Some(edit_distance(&candidate.to_ascii_lowercase(), &value) as f64)
+    })
+}
+
+/**
+ * Returns the Levenshtein distance between two strings
+ */
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
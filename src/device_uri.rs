@@ -0,0 +1,284 @@
+/**
+ * DeviceUri is the typed representation of a printer device URI (the CUPS "device-uri")
+ * Components are percent-decoded
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceUri {
+    /**
+     * Internet Printing Protocol (ipp:// or http://)
+     */
+    Ipp {
+        host: String,
+        port: u16,
+        path: String,
+    },
+
+    /**
+     * Internet Printing Protocol over TLS (ipps:// or https://)
+     */
+    Ipps {
+        host: String,
+        port: u16,
+        path: String,
+    },
+
+    /**
+     * AppSocket/JetDirect (socket://host:9100)
+     */
+    Socket { host: String, port: u16 },
+
+    /**
+     * Line Printer Daemon (lpd://host/queue)
+     */
+    Lpd {
+        host: String,
+        port: u16,
+        queue: String,
+    },
+
+    /**
+     * USB printer (usb://make/model?serial=...)
+     */
+    Usb {
+        make: String,
+        model: String,
+        serial: Option<String>,
+    },
+
+    /**
+     * Printer discovered by DNS-SD (dnssd://name._ipp._tcp.local./)
+     */
+    DnsSd {
+        service_name: String,
+        service_type: String,
+        domain: String,
+    },
+
+    /**
+     * Local file or device (file:///dev/usb/lp0)
+     */
+    File { path: String },
+
+    /**
+     * Any other URI (like smb://, hp:/, cups-pdf:/ or an empty URI)
+     */
+    Other { scheme: String, uri: String },
+}
+
+impl DeviceUri {
+    /**
+     * Parse a device URI (URIs that can't be parsed by their scheme are returned as Other)
+     */
+    pub fn parse(uri: &str) -> DeviceUri {
+        let other = || DeviceUri::Other {
+            scheme: uri
+                .split_once(':')
+                .map(|(scheme, _)| scheme.to_ascii_lowercase())
+                .unwrap_or_default(),
+            uri: uri.to_string(),
+        };
+
+        let (scheme, rest) = match uri.split_once(':') {
+            Some((scheme, rest)) => (scheme.to_ascii_lowercase(), rest),
+            None => return other(),
+        };
+
+        if scheme == "file" {
+            let path = rest.strip_prefix("//").unwrap_or(rest);
+            return DeviceUri::File {
+                path: percent_decode(path.split('?').next().unwrap_or("")),
+            };
+        }
+
+        let rest = match rest.strip_prefix("//") {
+            Some(rest) => rest,
+            None => return other(),
+        };
+
+        let (rest, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let (authority, path) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => (rest, ""),
+        };
+
+        // User information is not part of the device address (ipp://user@host/...)
+        let authority = authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host);
+        let (raw_host, port) = match split_host_port(authority) {
+            Some(address) => address,
+            None => return other(),
+        };
+
+        let host = percent_decode(raw_host);
+        let path = percent_decode(path);
+
+        match scheme.as_str() {
+            "ipp" | "http" | "ipps" | "https" if !host.is_empty() => {
+                let default_port = match scheme.as_str() {
+                    "http" => 80,
+                    "https" => 443,
+                    _ => 631,
+                };
+                let port = port.unwrap_or(default_port);
+
+                if scheme == "ipp" || scheme == "http" {
+                    DeviceUri::Ipp { host, port, path }
+                } else {
+                    DeviceUri::Ipps { host, port, path }
+                }
+            }
+            "socket" if !host.is_empty() => DeviceUri::Socket {
+                host,
+                port: port.unwrap_or(9100),
+            },
+            "lpd" if !host.is_empty() => DeviceUri::Lpd {
+                host,
+                port: port.unwrap_or(515),
+                queue: path.trim_start_matches('/').to_string(),
+            },
+            "usb" => DeviceUri::Usb {
+                make: host,
+                model: path.trim_start_matches('/').to_string(),
+                serial: query_value(query, "serial"),
+            },
+            "dnssd" => match split_dnssd_host(raw_host) {
+                Some((service_name, service_type, domain)) => DeviceUri::DnsSd {
+                    service_name,
+                    service_type,
+                    domain,
+                },
+                None => other(),
+            },
+            _ => other(),
+        }
+    }
+
+    /**
+     * Returns the URI scheme, used as transport name (ipp, ipps, socket, lpd, usb, dnssd, file...)
+     */
+    pub fn scheme(&self) -> &str {
+        match self {
+            DeviceUri::Ipp { .. } => "ipp",
+            DeviceUri::Ipps { .. } => "ipps",
+            DeviceUri::Socket { .. } => "socket",
+            DeviceUri::Lpd { .. } => "lpd",
+            DeviceUri::Usb { .. } => "usb",
+            DeviceUri::DnsSd { .. } => "dnssd",
+            DeviceUri::File { .. } => "file",
+            DeviceUri::Other { scheme, .. } => scheme,
+        }
+    }
+
+    /**
+     * Returns the network host of the device (none for local and DNS-SD devices)
+     */
+    pub fn host(&self) -> Option<&str> {
+        match self {
+            DeviceUri::Ipp { host, .. }
+            | DeviceUri::Ipps { host, .. }
+            | DeviceUri::Socket { host, .. }
+            | DeviceUri::Lpd { host, .. } => Some(host),
+            _ => None,
+        }
+    }
+
+    /**
+     * Returns the network port of the device (none for local and DNS-SD devices)
+     */
+    pub fn port(&self) -> Option<u16> {
+        match self {
+            DeviceUri::Ipp { port, .. }
+            | DeviceUri::Ipps { port, .. }
+            | DeviceUri::Socket { port, .. }
+            | DeviceUri::Lpd { port, .. } => Some(*port),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Decode the %XX sequences of an URI component (invalid sequences are kept as is)
+ */
+pub fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && i + 2 < bytes.len()
+            && bytes[i + 1].is_ascii_hexdigit()
+            && bytes[i + 2].is_ascii_hexdigit()
+        {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/**
+ * Split the host and port of an URI authority (IPv6 addresses are enclosed by brackets)
+ * Returns none when the port is not a number
+ */
+fn split_host_port(authority: &str) -> Option<(&str, Option<u16>)> {
+    let (host, port) = match authority.strip_prefix('[') {
+        Some(authority) => {
+            let (host, rest) = authority.split_once(']')?;
+            (host, rest.strip_prefix(':'))
+        }
+        None => match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        },
+    };
+
+    match port {
+        Some(port) if !port.is_empty() => Some((host, Some(port.parse().ok()?))),
+        _ => Some((host, None)),
+    }
+}
+
+/**
+ * Split the host of a CUPS dnssd URI (still percent-encoded) in service name, type and domain
+ * like "HP%20LaserJet._ipp._tcp.local." (the trailing dot of the domain is optional)
+ */
+fn split_dnssd_host(raw_host: &str) -> Option<(String, String, String)> {
+    // Dots inside the service name are percent-encoded, so the first "._x._tcp" starts the type
+    for (index, _) in raw_host.match_indices("._") {
+        let labels: Vec<&str> = raw_host[index + 1..].splitn(3, '.').collect();
+
+        if labels.len() >= 2 && (labels[1] == "_tcp" || labels[1] == "_udp") {
+            let domain = labels.get(2).unwrap_or(&"").trim_end_matches('.');
+            let domain = if domain.is_empty() { "local" } else { domain };
+
+            return Some((
+                percent_decode(&raw_host[..index]),
+                format!("{}.{}", labels[0], labels[1]),
+                percent_decode(domain),
+            ));
+        }
+    }
+
+    None
+}
+
+/**
+ * Returns the percent-decoded value of a query parameter
+ */
+fn query_value(query: &str, name: &str) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| percent_decode(value))
+}
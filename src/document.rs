@@ -0,0 +1,370 @@
+use std::fs::File;
+use std::io::Read;
+
+/**
+ * Number of leading bytes inspected when sniffing a document format
+ */
+const SNIFF_LENGTH: usize = 4096;
+
+/**
+ * Enum of the document formats known by the lib
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentFormat {
+    /**
+     * Portable Document Format (application/pdf)
+     */
+    PDF,
+
+    /**
+     * Adobe PostScript (application/postscript)
+     */
+    POSTSCRIPT,
+
+    /**
+     * HP Printer Command Language (application/vnd.hp-pcl)
+     */
+    PCL,
+
+    /**
+     * PWG Raster for IPP Everywhere printers (image/pwg-raster)
+     */
+    PWG,
+
+    /**
+     * Apple Raster for AirPrint printers (image/urf)
+     */
+    URF,
+
+    /**
+     * JPEG image (image/jpeg)
+     */
+    JPEG,
+
+    /**
+     * PNG image (image/png)
+     */
+    PNG,
+
+    /**
+     * Plain text (text/plain)
+     */
+    TEXT,
+
+    /**
+     * Zebra ZPL II label commands, sent untouched to the printer
+     */
+    ZPL,
+
+    /**
+     * Epson ESC/POS receipt commands, sent untouched to the printer
+     */
+    ESCPOS,
+
+    /**
+     * Printer ready data sent untouched to the printer, bypassing the print system filters
+     */
+    RAW,
+
+    /**
+     * Unknown content, the print system will try to detect the format (application/octet-stream)
+     */
+    AUTO,
+}
+
+impl DocumentFormat {
+    /**
+     * Returns the MIME type used when the document is submitted to the printer
+     * (printer languages without a registered type are submitted as CUPS raw data)
+     */
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            DocumentFormat::PDF => "application/pdf",
+            DocumentFormat::POSTSCRIPT => "application/postscript",
+            DocumentFormat::PCL => "application/vnd.hp-pcl",
+            DocumentFormat::PWG => "image/pwg-raster",
+            DocumentFormat::URF => "image/urf",
+            DocumentFormat::JPEG => "image/jpeg",
+            DocumentFormat::PNG => "image/png",
+            DocumentFormat::TEXT => "text/plain",
+            DocumentFormat::ZPL | DocumentFormat::ESCPOS | DocumentFormat::RAW => {
+                "application/vnd.cups-raw"
+            }
+            DocumentFormat::AUTO => "application/octet-stream",
+        }
+    }
+
+    /**
+     * Returns the format of a MIME type (unknown types are returned as AUTO)
+     */
+    pub fn from_mime_type(mime_type: &str) -> DocumentFormat {
+        let mime_type = mime_type.split(';').next().unwrap_or("").trim();
+        match mime_type.to_ascii_lowercase().as_str() {
+            "application/pdf" => DocumentFormat::PDF,
+            "application/postscript" => DocumentFormat::POSTSCRIPT,
+            "application/vnd.hp-pcl" => DocumentFormat::PCL,
+            "image/pwg-raster" => DocumentFormat::PWG,
+            "image/urf" => DocumentFormat::URF,
+            "image/jpeg" => DocumentFormat::JPEG,
+            "image/png" => DocumentFormat::PNG,
+            "text/plain" => DocumentFormat::TEXT,
+            "application/vnd.cups-raw" => DocumentFormat::RAW,
+            _ => DocumentFormat::AUTO,
+        }
+    }
+
+    /**
+     * Detect the document format from the magic bytes at the beginning of the buffer
+     */
+    pub fn detect(buffer: &[u8]) -> DocumentFormat {
+        let head = &buffer[..buffer.len().min(SNIFF_LENGTH)];
+
+        if head.starts_with(b"%PDF-") {
+            return DocumentFormat::PDF;
+        }
+
+        if head.starts_with(b"%!") || head.starts_with(b"\x04%!") {
+            return DocumentFormat::POSTSCRIPT;
+        }
+
+        if head.starts_with(b"RaS2") {
+            return DocumentFormat::PWG;
+        }
+
+        if head.starts_with(b"UNIRAST\0") {
+            return DocumentFormat::URF;
+        }
+
+        if head.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            return DocumentFormat::JPEG;
+        }
+
+        if head.starts_with(b"\x89PNG\r\n\x1a\n") {
+            return DocumentFormat::PNG;
+        }
+
+        if head.starts_with(b"\x1b%-12345X") || head.starts_with(b"@PJL") {
+            return detect_pjl_language(head);
+        }
+
+        if head.first() == Some(&0x1b) || head.first() == Some(&0x1d) {
+            return detect_escape_language(head);
+        }
+
+        if is_zpl(head) {
+            return DocumentFormat::ZPL;
+        }
+
+        if is_text(head) {
+            return DocumentFormat::TEXT;
+        }
+
+        DocumentFormat::AUTO
+    }
+
+    /**
+     * Detect the document format of a file reading only their first bytes
+     */
+    pub fn detect_file(file_path: &str) -> Result<DocumentFormat, String> {
+        let file = File::open(file_path).map_err(|error| error.to_string())?;
+        let mut head = Vec::with_capacity(SNIFF_LENGTH);
+
+        file.take(SNIFF_LENGTH as u64)
+            .read_to_end(&mut head)
+            .map_err(|error| error.to_string())?;
+
+        Ok(DocumentFormat::detect(&head))
+    }
+
+    /**
+     * Returns if the format is printer ready data that must bypass the print system filters
+     */
+    pub fn is_raw(&self) -> bool {
+        matches!(
+            self,
+            DocumentFormat::ZPL | DocumentFormat::ESCPOS | DocumentFormat::RAW
+        )
+    }
+}
+
+/**
+ * Document is the content of a print job with its format
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Document {
+    /**
+     * Bytes of the document
+     */
+    pub data: Vec<u8>,
+
+    /**
+     * Format of the document
+     */
+    pub format: DocumentFormat,
+}
+
+impl Document {
+    /**
+     * Create a document with the format detected from the content
+     */
+    pub fn new(data: Vec<u8>) -> Document {
+        let format = DocumentFormat::detect(&data);
+        Document { data, format }
+    }
+
+    /**
+     * Create a document reading all bytes of a file, the format is detected from the content
+     */
+    pub fn from_file(file_path: &str) -> Result<Document, String> {
+        std::fs::read(file_path)
+            .map(Document::new)
+            .map_err(|error| error.to_string())
+    }
+}
+
+impl From<&[u8]> for Document {
+    fn from(data: &[u8]) -> Document {
+        Document::new(data.to_vec())
+    }
+}
+
+impl From<Vec<u8>> for Document {
+    fn from(data: Vec<u8>) -> Document {
+        Document::new(data)
+    }
+}
+
+/**
+ * RawDocument is a printer ready document (ZPL, ESC/POS, PCL...) sent untouched to the printer
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawDocument {
+    /**
+     * Bytes sent to the printer
+     */
+    pub data: Vec<u8>,
+}
+
+impl RawDocument {
+    /**
+     * Create a raw document from the printer ready bytes
+     */
+    pub fn new(data: Vec<u8>) -> RawDocument {
+        RawDocument { data }
+    }
+
+    /**
+     * Create a raw document reading all bytes of a file
+     */
+    pub fn from_file(file_path: &str) -> Result<RawDocument, String> {
+        std::fs::read(file_path)
+            .map(RawDocument::new)
+            .map_err(|error| error.to_string())
+    }
+}
+
+impl From<&[u8]> for RawDocument {
+    fn from(data: &[u8]) -> RawDocument {
+        RawDocument::new(data.to_vec())
+    }
+}
+
+impl From<Vec<u8>> for RawDocument {
+    fn from(data: Vec<u8>) -> RawDocument {
+        RawDocument::new(data)
+    }
+}
+
+/**
+ * Resolve the language selected by a PJL job header (PCL when it is not declared)
+ */
+fn detect_pjl_language(head: &[u8]) -> DocumentFormat {
+    let text = String::from_utf8_lossy(head).to_ascii_uppercase();
+
+    match text.find("ENTER LANGUAGE") {
+        Some(index) => {
+            let language = text[index..]
+                .split_once('=')
+                .map(|(_, language)| language.trim_start())
+                .unwrap_or("");
+
+            if language.starts_with("POSTSCRIPT") {
+                DocumentFormat::POSTSCRIPT
+            } else if language.starts_with("PDF") {
+                DocumentFormat::PDF
+            } else if language.starts_with("ZPL") {
+                DocumentFormat::ZPL
+            } else {
+                DocumentFormat::PCL
+            }
+        }
+        None => DocumentFormat::PCL,
+    }
+}
+
+/**
+ * Split documents starting with ESC or GS between PCL and ESC/POS
+ */
+fn detect_escape_language(head: &[u8]) -> DocumentFormat {
+    match head {
+        // ESC/POS group separator commands (GS v 0, GS V, GS k...)
+        [0x1d, ..] => DocumentFormat::ESCPOS,
+
+        // ESC @ initializes ESC/POS printers
+        [0x1b, b'@', ..] => DocumentFormat::ESCPOS,
+
+        // ESC E n is "emphasized" on ESC/POS (n is 0 or 1) and "reset" on PCL
+        [0x1b, b'E', 0x00..=0x01, ..] => DocumentFormat::ESCPOS,
+        [0x1b, b'E', ..] => DocumentFormat::PCL,
+
+        // PCL parameterized commands (ESC & l, ESC * t, ESC ( s, ...)
+        [0x1b, 0x21..=0x2f, ..] => DocumentFormat::PCL,
+
+        _ => DocumentFormat::ESCPOS,
+    }
+}
+
+/**
+ * Check if the buffer looks like ZPL II commands (^XA format start or ~ control commands)
+ */
+fn is_zpl(head: &[u8]) -> bool {
+    let start = head
+        .iter()
+        .position(|byte| !byte.is_ascii_whitespace())
+        .unwrap_or(head.len());
+
+    let head = &head[start..];
+
+    if head.starts_with(b"^XA") || head.starts_with(b"^xa") {
+        return true;
+    }
+
+    head.len() > 3
+        && head[0] == b'~'
+        && head[1].is_ascii_uppercase()
+        && head[2].is_ascii_uppercase()
+        && head
+            .windows(3)
+            .any(|window| window.eq_ignore_ascii_case(b"^XA"))
+}
+
+/**
+ * Check if the buffer is readable UTF-8 text (a truncated trailing character is accepted)
+ */
+fn is_text(head: &[u8]) -> bool {
+    if head.is_empty() {
+        return false;
+    }
+
+    let valid = match std::str::from_utf8(head) {
+        Ok(text) => text,
+        Err(error) if error.error_len().is_none() => {
+            std::str::from_utf8(&head[..error.valid_up_to()]).unwrap_or("")
+        }
+        Err(_) => return false,
+    };
+
+    valid
+        .chars()
+        .all(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r' | '\x0c'))
+}
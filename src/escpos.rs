@@ -0,0 +1,473 @@
+//! ESC/POS command builder for Epson and compatible receipt printers
+//! The built bytes must be sent untouched to the printer (see Printer::print_raw)
+//!
+//! ```rust
+//! use printers::escpos::{Alignment, Cut, EscPos};
+//!
+//! let receipt = EscPos::new()
+//!     .initialize()
+//!     .align(Alignment::Center)
+//!     .bold(true)
+//!     .line("ACME STORE")
+//!     .unwrap()
+//!     .bold(false)
+//!     .feed(2)
+//!     .cut(Cut::Partial)
+//!     .build();
+//! ```
+
+use crate::raster::Bitmap;
+
+const ESC: u8 = 0x1b;
+const GS: u8 = 0x1d;
+const LF: u8 = 0x0a;
+
+/**
+ * Max rows of each GS v 0 command, taller images are sent in bands
+ */
+const IMAGE_BAND_HEIGHT: u32 = 256;
+
+/**
+ * Text alignment (ESC a)
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    Left = 0,
+    Center = 1,
+    Right = 2,
+}
+
+/**
+ * Underline mode (ESC -)
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Underline {
+    None = 0,
+    Single = 1,
+    Double = 2,
+}
+
+/**
+ * Paper cut mode (GS V)
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cut {
+    Full = 0,
+    Partial = 1,
+}
+
+/**
+ * Cash drawer connector pin (ESC p)
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawerPin {
+    Pin2 = 0,
+    Pin5 = 1,
+}
+
+/**
+ * Character code table (ESC t), used to encode the text
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodePage {
+    /**
+     * USA, standard Europe
+     */
+    PC437 = 0,
+
+    /**
+     * Multilingual
+     */
+    PC850 = 2,
+
+    /**
+     * Windows Latin-1
+     */
+    WPC1252 = 16,
+
+    /**
+     * Multilingual with euro sign
+     */
+    PC858 = 19,
+}
+
+/**
+ * Barcode system (GS k, function B)
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Barcode {
+    UpcA = 65,
+    UpcE = 66,
+    Ean13 = 67,
+    Ean8 = 68,
+    Code39 = 69,
+    Itf = 70,
+    Codabar = 71,
+    Code93 = 72,
+    Code128 = 73,
+}
+
+/**
+ * Position of the human readable text of barcodes (GS H)
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarcodeText {
+    None = 0,
+    Above = 1,
+    Below = 2,
+    Both = 3,
+}
+
+/**
+ * QR code error correction level (GS ( k, function 169)
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QrErrorCorrection {
+    L = 48,
+    M = 49,
+    Q = 50,
+    H = 51,
+}
+
+/**
+ * EscPos is a builder of ESC/POS commands
+ */
+#[derive(Debug, Clone)]
+pub struct EscPos {
+    buffer: Vec<u8>,
+    code_page: CodePage,
+    replacement: Option<char>,
+}
+
+impl Default for EscPos {
+    fn default() -> EscPos {
+        EscPos::new()
+    }
+}
+
+impl EscPos {
+    /**
+     * Create an empty command buffer (text is encoded with PC437 until a code page is selected)
+     */
+    pub fn new() -> EscPos {
+        EscPos {
+            buffer: Vec::new(),
+            code_page: CodePage::PC437,
+            replacement: None,
+        }
+    }
+
+    /**
+     * Reset the printer settings to their defaults (ESC @)
+     */
+    pub fn initialize(&mut self) -> &mut EscPos {
+        self.code_page = CodePage::PC437;
+        self.raw(&[ESC, b'@'])
+    }
+
+    /**
+     * Append bytes without any processing
+     */
+    pub fn raw(&mut self, bytes: &[u8]) -> &mut EscPos {
+        self.buffer.extend_from_slice(bytes);
+        self
+    }
+
+    /**
+     * Select the code page used by the printer and to encode the next texts (ESC t)
+     */
+    pub fn code_page(&mut self, code_page: CodePage) -> &mut EscPos {
+        self.code_page = code_page;
+        self.raw(&[ESC, b't', code_page as u8])
+    }
+
+    /**
+     * Print a replacement character (like '?') instead of the characters out of the selected code page,
+     * which are rejected by default
+     */
+    pub fn replace_unmappable(&mut self, replacement: char) -> &mut EscPos {
+        self.replacement = Some(replacement);
+        self
+    }
+
+    /**
+     * Append text encoded with the selected code page
+     * Returns an error for characters out of the code page, unless a replacement character is set
+     */
+    pub fn text(&mut self, text: &str) -> Result<&mut EscPos, String> {
+        let mut encoded = Vec::with_capacity(text.len());
+        for c in text.chars() {
+            let byte = match (encode_char(c, self.code_page), self.replacement) {
+                (Some(byte), _) => byte,
+                (None, Some(replacement)) => encode_char(replacement, self.code_page)
+                    .ok_or_else(|| unmappable(replacement, self.code_page))?,
+                (None, None) => return Err(unmappable(c, self.code_page)),
+            };
+            encoded.push(byte);
+        }
+        Ok(self.raw(&encoded))
+    }
+
+    /**
+     * Append text followed by a line feed
+     */
+    pub fn line(&mut self, text: &str) -> Result<&mut EscPos, String> {
+        Ok(self.text(text)?.raw(&[LF]))
+    }
+
+    /**
+     * Print the buffer and feed a line (LF)
+     */
+    pub fn line_feed(&mut self) -> &mut EscPos {
+        self.raw(&[LF])
+    }
+
+    /**
+     * Print the buffer and feed n lines (ESC d)
+     */
+    pub fn feed(&mut self, lines: u8) -> &mut EscPos {
+        self.raw(&[ESC, b'd', lines])
+    }
+
+    /**
+     * Turn emphasized (bold) mode on or off (ESC E)
+     */
+    pub fn bold(&mut self, enabled: bool) -> &mut EscPos {
+        self.raw(&[ESC, b'E', enabled as u8])
+    }
+
+    /**
+     * Select the underline mode (ESC -)
+     */
+    pub fn underline(&mut self, underline: Underline) -> &mut EscPos {
+        self.raw(&[ESC, b'-', underline as u8])
+    }
+
+    /**
+     * Select the character size as width and height multipliers from 1 to 8 (GS !)
+     */
+    pub fn size(&mut self, width: u8, height: u8) -> &mut EscPos {
+        let width = width.clamp(1, 8) - 1;
+        let height = height.clamp(1, 8) - 1;
+        self.raw(&[GS, b'!', (width << 4) | height])
+    }
+
+    /**
+     * Turn double width and height on or off
+     */
+    pub fn double_size(&mut self, enabled: bool) -> &mut EscPos {
+        if enabled {
+            self.size(2, 2)
+        } else {
+            self.size(1, 1)
+        }
+    }
+
+    /**
+     * Select the justification of the next lines (ESC a)
+     */
+    pub fn align(&mut self, alignment: Alignment) -> &mut EscPos {
+        self.raw(&[ESC, b'a', alignment as u8])
+    }
+
+    /**
+     * Cut the paper (GS V)
+     */
+    pub fn cut(&mut self, cut: Cut) -> &mut EscPos {
+        self.raw(&[GS, b'V', cut as u8])
+    }
+
+    /**
+     * Kick the cash drawer with a pulse of on_time and off_time (units of 2 ms) (ESC p)
+     */
+    pub fn cash_drawer(&mut self, pin: DrawerPin, on_time: u8, off_time: u8) -> &mut EscPos {
+        self.raw(&[ESC, b'p', pin as u8, on_time, off_time])
+    }
+
+    /**
+     * Print a barcode with height in dots, module width from 2 to 6 and the text position
+     * (GS h, GS w, GS H and GS k function B)
+     * Code128 data without code set selection ("{A", "{B" or "{C") is printed with code set B
+     */
+    pub fn barcode(
+        &mut self,
+        barcode: Barcode,
+        data: &str,
+        height: u8,
+        width: u8,
+        text: BarcodeText,
+    ) -> Result<&mut EscPos, String> {
+        let data = match barcode {
+            Barcode::Code128 if !data.starts_with('{') => format!("{{B{}", data),
+            _ => data.to_string(),
+        };
+
+        validate_barcode(barcode, &data)?;
+
+        self.raw(&[GS, b'h', height])
+            .raw(&[GS, b'w', width.clamp(2, 6)])
+            .raw(&[GS, b'H', text as u8])
+            .raw(&[GS, b'k', barcode as u8, data.len() as u8])
+            .raw(data.as_bytes());
+
+        Ok(self)
+    }
+
+    /**
+     * Print a QR code (model 2) with module size from 1 to 16 dots (GS ( k)
+     */
+    pub fn qr_code(
+        &mut self,
+        data: &str,
+        module_size: u8,
+        error_correction: QrErrorCorrection,
+    ) -> Result<&mut EscPos, String> {
+        if data.is_empty() || data.len() > 7089 {
+            return Err(format!(
+                "QR code data must have from 1 to 7089 bytes, got {}",
+                data.len()
+            ));
+        }
+
+        let length = (data.len() + 3) as u16;
+        let [length_low, length_high] = length.to_le_bytes();
+
+        self.raw(&[GS, b'(', b'k', 4, 0, 49, 65, 50, 0])
+            .raw(&[GS, b'(', b'k', 3, 0, 49, 67, module_size.clamp(1, 16)])
+            .raw(&[GS, b'(', b'k', 3, 0, 49, 69, error_correction as u8])
+            .raw(&[GS, b'(', b'k', length_low, length_high, 49, 80, 48])
+            .raw(data.as_bytes())
+            .raw(&[GS, b'(', b'k', 3, 0, 49, 81, 48]);
+
+        Ok(self)
+    }
+
+    /**
+     * Print a raster bit image (GS v 0)
+     * Data is packed one bit per dot (1 is black), MSB first, each row padded to a whole byte
+     */
+    pub fn image(&mut self, width: u32, height: u32, data: &[u8]) -> Result<&mut EscPos, String> {
+        let row_bytes = width.div_ceil(8);
+
+        if width == 0 || height == 0 || row_bytes > 0xffff {
+            return Err(format!("invalid image size {}x{}", width, height));
+        }
+
+        if data.len() != (row_bytes * height) as usize {
+            return Err(format!(
+                "image data must have {} bytes for {}x{} dots, got {}",
+                row_bytes * height,
+                width,
+                height,
+                data.len()
+            ));
+        }
+
+        for rows in data.chunks((row_bytes * IMAGE_BAND_HEIGHT) as usize) {
+            let band_height = (rows.len() as u32 / row_bytes) as u16;
+            let [x_low, x_high] = (row_bytes as u16).to_le_bytes();
+            let [y_low, y_high] = band_height.to_le_bytes();
+
+            self.raw(&[GS, b'v', b'0', 0, x_low, x_high, y_low, y_high])
+                .raw(rows);
+        }
+
+        Ok(self)
+    }
+
+    /**
+     * Print a bitmap made by the raster module (GS v 0)
+     */
+    pub fn bitmap(&mut self, bitmap: &Bitmap) -> Result<&mut EscPos, String> {
+        self.image(bitmap.width, bitmap.height, &bitmap.data)
+    }
+
+    /**
+     * Returns the built commands
+     */
+    pub fn build(&self) -> Vec<u8> {
+        self.buffer.clone()
+    }
+}
+
+/**
+ * Check the barcode data length and characters accepted by the barcode system
+ */
+fn validate_barcode(barcode: Barcode, data: &str) -> Result<(), String> {
+    let digits = data.bytes().all(|c| c.is_ascii_digit());
+    let length = data.len();
+
+    let valid = match barcode {
+        Barcode::UpcA => digits && (11..=12).contains(&length),
+        Barcode::UpcE => digits && [6, 7, 8, 11, 12].contains(&length),
+        Barcode::Ean13 => digits && (12..=13).contains(&length),
+        Barcode::Ean8 => digits && (7..=8).contains(&length),
+        Barcode::Itf => digits && length >= 2 && length & 1 == 0,
+        Barcode::Code39 => {
+            length >= 1
+                && data.bytes().all(|c| {
+                    c.is_ascii_digit() || c.is_ascii_uppercase() || b" $%*+-./".contains(&c)
+                })
+        }
+        Barcode::Codabar => {
+            length >= 2
+                && data
+                    .bytes()
+                    .all(|c| c.is_ascii_digit() || b"ABCDabcd$+-./:".contains(&c))
+        }
+        Barcode::Code93 | Barcode::Code128 => length >= 2 && data.is_ascii(),
+    };
+
+    if !valid || length > 255 {
+        return Err(format!("invalid data {:?} for barcode {:?}", data, barcode));
+    }
+
+    Ok(())
+}
+
+/**
+ * Upper half (0x80 to 0xFF) of the PC437 code page
+ */
+const PC437: &str = "ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜ¢£¥₧ƒáíóúñÑªº¿⌐¬½¼¡«»░▒▓│┤╡╢╖╕╣║╗╝╜╛┐\
+                     └┴┬├─┼╞╟╚╔╩╦╠═╬╧╨╤╥╙╘╒╓╫╪┘┌█▄▌▐▀αßΓπΣσµτΦΘΩδ∞φε∩≡±≥≤⌠⌡÷≈°∙·√ⁿ²■\u{a0}";
+
+/**
+ * Upper half (0x80 to 0xFF) of the PC850 code page
+ */
+const PC850: &str = "ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜø£Ø×ƒáíóúñÑªº¿®¬½¼¡«»░▒▓│┤ÁÂÀ©╣║╗╝¢¥┐\
+                     └┴┬├─┼ãÃ╚╔╩╦╠═╬¤ðÐÊËÈıÍÎÏ┘┌█▄¦Ì▀ÓßÔÒõÕµþÞÚÛÙýÝ¯´\u{ad}±‗¾¶§÷¸°¨·¹³²■\u{a0}";
+
+/**
+ * Characters 0x80 to 0x9F of the WPC1252 code page (undefined positions are NUL)
+ */
+const WPC1252: &str = "€\0‚ƒ„…†‡ˆ‰Š‹Œ\0Ž\0\0‘’“”•–—˜™š›œ\0žŸ";
+
+/**
+ * Encode a character on a code page (None when the code page does not have it)
+ */
+fn encode_char(c: char, code_page: CodePage) -> Option<u8> {
+    if c.is_ascii() {
+        return Some(c as u8);
+    }
+
+    let position = match code_page {
+        CodePage::PC437 => PC437.chars().position(|other| other == c),
+        CodePage::PC850 => PC850.chars().position(|other| other == c),
+        CodePage::PC858 if c == '€' => Some(0x55),
+        CodePage::PC858 if c == 'ı' => None,
+        CodePage::PC858 => PC850.chars().position(|other| other == c),
+        CodePage::WPC1252 => match c as u32 {
+            0xa0..=0xff => Some(c as usize - 0x80),
+            _ => WPC1252.chars().position(|other| other == c),
+        },
+    };
+
+    position.map(|position| 0x80 + position as u8)
+}
+
+fn unmappable(c: char, code_page: CodePage) -> String {
+    format!(
+        "character {:?} (U+{:04X}) is out of the code page {:?}",
+        c, c as u32, code_page
+    )
+}
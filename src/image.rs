@@ -0,0 +1,643 @@
+//! JPEG and PNG images to PDF, one image per page
+//! JPEG files are embedded without recompression, PNG files are decoded (every color type,
+//! bit depth and interlacing) and their transparency is kept as a soft mask
+//!
+//! ```rust,no_run
+//! use printers::image::{self, ImageOptions, Placement};
+//!
+//! let scan = std::fs::read("/path/to/proof.jpg").unwrap();
+//! let options = ImageOptions {
+//!     placement: Placement::Fill,
+//!     ..ImageOptions::default()
+//! };
+//!
+//! let pdf = image::images_to_pdf(&[&scan], &options).unwrap();
+//! ```
+
+use crate::document::DocumentFormat;
+use crate::pdf::{number, Margins, PageSize, PdfWriter};
+use crate::raster::PixelFormat;
+use miniz_oxide::inflate::decompress_to_vec_zlib;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/**
+ * Largest decoded image in samples, counted with 4 channels per pixel (8192x8192 pixels)
+ */
+const MAX_SAMPLES: usize = 1 << 28;
+
+/**
+ * Origin and step of the columns and rows of each Adam7 interlacing pass
+ */
+const ADAM7: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+/**
+ * Placement of the image on the printable area (images are always centered)
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    /**
+     * Scale to the largest size showing the whole image
+     */
+    Fit,
+
+    /**
+     * Scale to cover the whole area, the image borders out of the area are cut
+     */
+    Fill,
+
+    /**
+     * Print on the image resolution, the image borders out of the area are cut
+     */
+    ActualSize,
+
+    /**
+     * Print on the image resolution, reduced to fit when larger than the area
+     */
+    Center,
+}
+
+/**
+ * Layout of the images on the pages
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageOptions {
+    /**
+     * Size of the pages
+     */
+    pub page_size: PageSize,
+
+    /**
+     * Blank space around the printable area
+     */
+    pub margins: Margins,
+
+    /**
+     * Placement of each image on the printable area
+     */
+    pub placement: Placement,
+
+    /**
+     * Rotate 90 degrees the landscape images on portrait pages and the opposite
+     */
+    pub auto_rotate: bool,
+
+    /**
+     * Resolution in dpi of the images printed on actual size, when empty the resolution
+     * is read from the image (JFIF density or PNG pHYs), otherwise 72 dpi
+     */
+    pub resolution: Option<u32>,
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        ImageOptions {
+            page_size: PageSize::A4,
+            margins: Margins::uniform(18.0),
+            placement: Placement::Fit,
+            auto_rotate: true,
+            resolution: None,
+        }
+    }
+}
+
+/**
+ * Image is a decoded image with 8 bits per channel, rows without padding
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub format: PixelFormat,
+    pub data: Vec<u8>,
+
+    /**
+     * Resolution in dots per inch, when stored on the file
+     */
+    pub resolution: Option<u32>,
+}
+
+/**
+ * Header of a JPEG file
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct JpegInfo {
+    width: u32,
+    height: u32,
+    components: u8,
+    inverted: bool,
+    resolution: Option<u32>,
+}
+
+/**
+ * Wrap images (JPEG or PNG) in a PDF document, each image on its own page
+ */
+pub fn images_to_pdf(images: &[&[u8]], options: &ImageOptions) -> Result<Vec<u8>, String> {
+    if images.is_empty() {
+        return Err("no images given".to_string());
+    }
+
+    let size = options.page_size;
+    let margins = &options.margins;
+    let area = (
+        margins.left,
+        margins.bottom,
+        size.width - margins.left - margins.right,
+        size.height - margins.top - margins.bottom,
+    );
+
+    if area.2 <= 0.0 || area.3 <= 0.0 {
+        return Err("the page is too small for the margins".to_string());
+    }
+
+    let mut writer = PdfWriter::new();
+    let pages_id = writer.reserve();
+    let mut page_ids = Vec::new();
+
+    for (index, bytes) in images.iter().enumerate() {
+        let image_id = writer.reserve();
+
+        let (width, height, resolution) = match DocumentFormat::detect(bytes) {
+            DocumentFormat::JPEG => {
+                let info =
+                    parse_jpeg(bytes).map_err(|error| format!("image {}: {}", index, error))?;
+                let color_space = match info.components {
+                    1 => "/DeviceGray",
+                    3 => "/DeviceRGB",
+                    _ => "/DeviceCMYK",
+                };
+                let decode = if info.inverted {
+                    " /Decode [1 0 1 0 1 0 1 0]"
+                } else {
+                    ""
+                };
+
+                writer.stream(
+                    image_id,
+                    &format!(
+                        "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace {} /BitsPerComponent 8{} /Filter /DCTDecode",
+                        info.width, info.height, color_space, decode
+                    ),
+                    bytes,
+                );
+
+                (info.width, info.height, info.resolution)
+            }
+            DocumentFormat::PNG => {
+                let image =
+                    decode_png(bytes).map_err(|error| format!("image {}: {}", index, error))?;
+                write_image(&mut writer, image_id, &image);
+                (image.width, image.height, image.resolution)
+            }
+            format => {
+                return Err(format!(
+                    "image {} is {}, expected JPEG or PNG",
+                    index,
+                    format.mime_type()
+                ))
+            }
+        };
+
+        let resolution = options.resolution.or(resolution).unwrap_or(72).max(1) as f64;
+        let mut natural = (
+            width as f64 * 72.0 / resolution,
+            height as f64 * 72.0 / resolution,
+        );
+
+        let rotate = options.auto_rotate
+            && width != height
+            && (width > height) != (area.2 > area.3)
+            && area.2 != area.3;
+        if rotate {
+            natural = (natural.1, natural.0);
+        }
+
+        let fit = (area.2 / natural.0).min(area.3 / natural.1);
+        let scale = match options.placement {
+            Placement::Fit => fit,
+            Placement::Fill => (area.2 / natural.0).max(area.3 / natural.1),
+            Placement::ActualSize => 1.0,
+            Placement::Center => fit.min(1.0),
+        };
+
+        let (box_width, box_height) = (natural.0 * scale, natural.1 * scale);
+        let x = area.0 + (area.2 - box_width) / 2.0;
+        let y = area.1 + (area.3 - box_height) / 2.0;
+
+        // Rotated images are turned clockwise, the top of the image on the right side of the page
+        let matrix = if rotate {
+            [0.0, -box_height, box_width, 0.0, x, y + box_height]
+        } else {
+            [box_width, 0.0, 0.0, box_height, x, y]
+        };
+        let matrix: Vec<String> = matrix.iter().map(|&value| number(value)).collect();
+
+        let content = format!(
+            "q {} {} {} {} re W n {} cm /Im1 Do Q\n",
+            number(area.0),
+            number(area.1),
+            number(area.2),
+            number(area.3),
+            matrix.join(" ")
+        );
+
+        let content_id = writer.reserve();
+        writer.stream(content_id, "", content.as_bytes());
+
+        let page_id = writer.reserve();
+        writer.object(
+            page_id,
+            &format!(
+                "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {} {}] /Resources << /XObject << /Im1 {} 0 R >> >> /Contents {} 0 R >>",
+                pages_id,
+                number(size.width),
+                number(size.height),
+                image_id,
+                content_id
+            ),
+        );
+        page_ids.push(page_id);
+    }
+
+    Ok(writer.finish(pages_id, &page_ids))
+}
+
+/**
+ * Decode a PNG image to 8 bits gray, RGB or RGBA pixels (RGBA when the image has transparency)
+ */
+pub fn decode_png(bytes: &[u8]) -> Result<Image, String> {
+    if !bytes.starts_with(PNG_SIGNATURE) {
+        return Err("not a PNG image".to_string());
+    }
+
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut transparency: &[u8] = &[];
+    let mut compressed = Vec::new();
+    let mut resolution = None;
+    let mut position = PNG_SIGNATURE.len();
+
+    while position + 8 <= bytes.len() {
+        let length = read_u32(bytes, position) as usize;
+        let kind = &bytes[position + 4..position + 8];
+        let data = bytes
+            .get(position + 8..position + 8 + length)
+            .ok_or("truncated PNG chunk")?;
+        position += length + 12;
+
+        match kind {
+            b"IHDR" if data.len() >= 13 => header = Some(data),
+            b"PLTE" => palette = data,
+            b"tRNS" => transparency = data,
+            b"IDAT" => compressed.extend_from_slice(data),
+            b"pHYs" if data.len() >= 9 && data[8] == 1 => {
+                let dpi = (read_u32(data, 0) as f64 * 0.0254).round() as u32;
+                if dpi > 0 {
+                    resolution = Some(dpi);
+                }
+            }
+            b"IEND" => break,
+            _ => {}
+        }
+    }
+
+    let header = header.ok_or("PNG without header")?;
+    let width = read_u32(header, 0) as usize;
+    let height = read_u32(header, 4) as usize;
+    let depth = header[8] as usize;
+    let color_type = header[9];
+    let interlaced = header[12] == 1;
+
+    let channels = match (color_type, depth) {
+        (0, 1) | (0, 2) | (0, 4) | (0, 8) | (0, 16) => 1,
+        (3, 1) | (3, 2) | (3, 4) | (3, 8) => 1,
+        (4, 8) | (4, 16) => 2,
+        (2, 8) | (2, 16) => 3,
+        (6, 8) | (6, 16) => 4,
+        _ => {
+            return Err(format!(
+                "invalid PNG color type {} with bit depth {}",
+                color_type, depth
+            ))
+        }
+    };
+
+    // The size comes from the file, it is checked before the samples are allocated
+    match width
+        .checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(4))
+    {
+        Some(samples) if samples > 0 && samples <= MAX_SAMPLES => {}
+        _ => return Err(format!("invalid PNG size {}x{}", width, height)),
+    }
+
+    if color_type == 3 && palette.is_empty() {
+        return Err("PNG without palette".to_string());
+    }
+
+    let inflated =
+        decompress_to_vec_zlib(&compressed).map_err(|_| "invalid PNG compressed data")?;
+
+    // Raw samples of each pixel, unpacked from the scanlines of the image or of each pass
+    let mut samples = vec![0u16; width * height * channels];
+    let passes: Vec<(usize, usize, usize, usize)> = if interlaced {
+        ADAM7.to_vec()
+    } else {
+        vec![(0, 0, 1, 1)]
+    };
+
+    let bits_per_pixel = channels * depth;
+    let filter_step = bits_per_pixel.div_ceil(8);
+    let mut offset = 0;
+
+    for (x0, y0, dx, dy) in passes {
+        let pass_width = (width + dx - 1 - x0) / dx;
+        let pass_height = (height + dy - 1 - y0) / dy;
+        if x0 >= width || y0 >= height || pass_width == 0 || pass_height == 0 {
+            continue;
+        }
+
+        let row_bytes = (pass_width * bits_per_pixel).div_ceil(8);
+        let mut previous = vec![0u8; row_bytes];
+
+        for row in 0..pass_height {
+            let filter = *inflated.get(offset).ok_or("truncated PNG image data")?;
+            let mut line = inflated
+                .get(offset + 1..offset + 1 + row_bytes)
+                .ok_or("truncated PNG image data")?
+                .to_vec();
+            offset += row_bytes + 1;
+
+            unfilter(filter, &mut line, &previous, filter_step)?;
+
+            for column in 0..pass_width {
+                let pixel = ((y0 + row * dy) * width + x0 + column * dx) * channels;
+                for channel in 0..channels {
+                    let index = column * channels + channel;
+                    samples[pixel + channel] = match depth {
+                        16 => u16::from_be_bytes([line[index * 2], line[index * 2 + 1]]),
+                        8 => line[index] as u16,
+                        _ => {
+                            let bit = index * depth;
+                            let shift = 8 - depth - bit % 8;
+                            ((line[bit / 8] >> shift) & ((1 << depth) - 1) as u8) as u16
+                        }
+                    };
+                }
+            }
+
+            previous = line;
+        }
+    }
+
+    let scale = |sample: u16| -> u8 {
+        match depth {
+            16 => (sample >> 8) as u8,
+            8 => sample as u8,
+            _ => (sample as u32 * 255 / ((1 << depth) - 1)) as u8,
+        }
+    };
+
+    let key = |index: usize| -> u16 {
+        transparency
+            .get(index * 2..index * 2 + 2)
+            .map_or(u16::MAX, |bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+    };
+
+    let has_alpha = matches!(color_type, 4 | 6) || !transparency.is_empty();
+    let format = match (has_alpha, color_type) {
+        (true, _) => PixelFormat::Rgba,
+        (false, 0) => PixelFormat::Gray,
+        _ => PixelFormat::Rgb,
+    };
+
+    let mut data = Vec::with_capacity(width * height * format.channels());
+
+    for pixel in samples.chunks_exact(channels) {
+        let (rgb, alpha) = match color_type {
+            0 => {
+                let gray = scale(pixel[0]);
+                let alpha = if transparency.len() >= 2 && pixel[0] == key(0) {
+                    0
+                } else {
+                    255
+                };
+                ([gray, gray, gray], alpha)
+            }
+            2 => {
+                let transparent = transparency.len() >= 6
+                    && pixel[0] == key(0)
+                    && pixel[1] == key(1)
+                    && pixel[2] == key(2);
+                (
+                    [scale(pixel[0]), scale(pixel[1]), scale(pixel[2])],
+                    if transparent { 0 } else { 255 },
+                )
+            }
+            3 => {
+                let index = pixel[0] as usize;
+                let color = palette
+                    .get(index * 3..index * 3 + 3)
+                    .ok_or("PNG palette index out of range")?;
+                (
+                    [color[0], color[1], color[2]],
+                    transparency.get(index).copied().unwrap_or(255),
+                )
+            }
+            4 => {
+                let gray = scale(pixel[0]);
+                ([gray, gray, gray], scale(pixel[1]))
+            }
+            _ => (
+                [scale(pixel[0]), scale(pixel[1]), scale(pixel[2])],
+                scale(pixel[3]),
+            ),
+        };
+
+        match format {
+            PixelFormat::Gray => data.push(rgb[0]),
+            PixelFormat::Rgb => data.extend_from_slice(&rgb),
+            PixelFormat::Rgba => {
+                data.extend_from_slice(&rgb);
+                data.push(alpha);
+            }
+        }
+    }
+
+    Ok(Image {
+        width: width as u32,
+        height: height as u32,
+        format,
+        data,
+        resolution,
+    })
+}
+
+/**
+ * Write a decoded image as an image object, the alpha channel as its soft mask
+ */
+fn write_image(writer: &mut PdfWriter, id: usize, image: &Image) {
+    let dictionary = |color_space: &str| {
+        format!(
+            "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace {} /BitsPerComponent 8",
+            image.width, image.height, color_space
+        )
+    };
+
+    match image.format {
+        PixelFormat::Gray => writer.stream(id, &dictionary("/DeviceGray"), &image.data),
+        PixelFormat::Rgb => writer.stream(id, &dictionary("/DeviceRGB"), &image.data),
+        PixelFormat::Rgba => {
+            let mut color = Vec::with_capacity(image.data.len() / 4 * 3);
+            let mut alpha = Vec::with_capacity(image.data.len() / 4);
+            for pixel in image.data.chunks_exact(4) {
+                color.extend_from_slice(&pixel[..3]);
+                alpha.push(pixel[3]);
+            }
+
+            let mask_id = writer.reserve();
+            writer.stream(mask_id, &dictionary("/DeviceGray"), &alpha);
+            writer.stream(
+                id,
+                &format!("{} /SMask {} 0 R", dictionary("/DeviceRGB"), mask_id),
+                &color,
+            );
+        }
+    }
+}
+
+/**
+ * Reverse the filter of a PNG scanline with the previous (unfiltered) scanline
+ */
+pub(crate) fn unfilter(
+    filter: u8,
+    line: &mut [u8],
+    previous: &[u8],
+    step: usize,
+) -> Result<(), String> {
+    for i in 0..line.len() {
+        let left = if i >= step { line[i - step] } else { 0 };
+        let up = previous[i];
+        let up_left = if i >= step { previous[i - step] } else { 0 };
+
+        let predictor = match filter {
+            0 => 0,
+            1 => left,
+            2 => up,
+            3 => ((left as u16 + up as u16) / 2) as u8,
+            4 => paeth(left, up, up_left),
+            _ => return Err(format!("invalid PNG filter {}", filter)),
+        };
+
+        line[i] = line[i].wrapping_add(predictor);
+    }
+
+    Ok(())
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - up_left as i16;
+    let distance_left = (estimate - left as i16).abs();
+    let distance_up = (estimate - up as i16).abs();
+    let distance_up_left = (estimate - up_left as i16).abs();
+
+    if distance_left <= distance_up && distance_left <= distance_up_left {
+        left
+    } else if distance_up <= distance_up_left {
+        up
+    } else {
+        up_left
+    }
+}
+
+/**
+ * Read the size, components and resolution of a JPEG file from its markers
+ */
+fn parse_jpeg(bytes: &[u8]) -> Result<JpegInfo, String> {
+    let mut position = 2;
+    let mut inverted = false;
+    let mut resolution = None;
+
+    while position + 4 <= bytes.len() {
+        if bytes[position] != 0xff {
+            return Err("invalid JPEG marker".to_string());
+        }
+
+        let marker = bytes[position + 1];
+        if marker == 0xff {
+            position += 1;
+            continue;
+        }
+
+        if (0xd0..=0xd9).contains(&marker) || marker == 0x01 {
+            position += 2;
+            continue;
+        }
+
+        let length = ((bytes[position + 2] as usize) << 8) | bytes[position + 3] as usize;
+        let data = bytes
+            .get(position + 4..position + 2 + length)
+            .ok_or("truncated JPEG segment")?;
+
+        match marker {
+            // JFIF density with unit 1 (dots per inch) or 2 (dots per centimeter)
+            0xe0 if data.starts_with(b"JFIF\0") && data.len() >= 12 => {
+                let density = ((data[8] as u32) << 8) | data[9] as u32;
+                resolution = match data[7] {
+                    1 if density > 0 => Some(density),
+                    2 if density > 0 => Some((density as f64 * 2.54).round() as u32),
+                    _ => None,
+                };
+            }
+            // Adobe CMYK images are stored inverted
+            0xee if data.starts_with(b"Adobe") => inverted = true,
+            0xc0..=0xcf if marker != 0xc4 && marker != 0xc8 && marker != 0xcc => {
+                if data.len() < 6 {
+                    return Err("truncated JPEG frame header".to_string());
+                }
+
+                let height = ((data[1] as u32) << 8) | data[2] as u32;
+                let width = ((data[3] as u32) << 8) | data[4] as u32;
+                let components = data[5];
+
+                if width == 0 || height == 0 || ![1, 3, 4].contains(&components) {
+                    return Err(format!(
+                        "unsupported JPEG {}x{} with {} components",
+                        width, height, components
+                    ));
+                }
+
+                return Ok(JpegInfo {
+                    width,
+                    height,
+                    components,
+                    inverted: inverted && components == 4,
+                    resolution,
+                });
+            }
+            0xda => break,
+            _ => {}
+        }
+
+        position += 2 + length;
+    }
+
+    Err("JPEG without frame header".to_string())
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
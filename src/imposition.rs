@@ -0,0 +1,472 @@
+//! Client-side imposition of PDF documents: N-up layouts, booklets, page borders and
+//! page ranges, so the output does not depend on the printer number-up support
+//!
+//! ```rust
+//! use printers::imposition::{self, ImpositionOptions};
+//! use printers::text::{self, TextOptions};
+//!
+//! let pdf = text::render_pdf("one\x0ctwo\x0cthree", &TextOptions::default()).unwrap();
+//! let options = ImpositionOptions {
+//!     number_up: 2,
+//!     borders: true,
+//!     ..ImpositionOptions::default()
+//! };
+//!
+//! let imposed = imposition::impose(&pdf, &options).unwrap();
+//! ```
+
+use crate::pdf::reader::{Dictionary, Object, Page, PdfDocument};
+use crate::pdf::{PageSize, PdfWriter};
+use std::collections::HashMap;
+
+/**
+ * Pages per sheet supported by the layouts
+ */
+const NUMBER_UP: [u32; 5] = [1, 2, 4, 6, 9];
+
+/**
+ * Options of the imposition
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImpositionOptions {
+    /**
+     * Pages per sheet side: 1, 2, 4, 6 or 9 (placed left to right, top to bottom)
+     */
+    pub number_up: u32,
+
+    /**
+     * Order the pages as a saddle-stitched booklet, 2 pages per side, to print on both
+     * sides flipping on the short edge, fold and staple (number_up is ignored)
+     */
+    pub booklet: bool,
+
+    /**
+     * Draw a border around each page
+     */
+    pub borders: bool,
+
+    /**
+     * Pages to keep, like "1-3,5,8-" (when empty every page is kept)
+     */
+    pub page_ranges: Option<String>,
+
+    /**
+     * Size of the sheets (when empty the size of the first page), turned to the orientation
+     * fitting the pages larger
+     */
+    pub sheet_size: Option<PageSize>,
+}
+
+impl Default for ImpositionOptions {
+    fn default() -> Self {
+        ImpositionOptions {
+            number_up: 1,
+            booklet: false,
+            borders: false,
+            page_ranges: None,
+            sheet_size: None,
+        }
+    }
+}
+
+/**
+ * Impose the pages of a PDF document on a new PDF document
+ */
+pub fn impose(pdf: &[u8], options: &ImpositionOptions) -> Result<Vec<u8>, String> {
+    let number_up = if options.booklet {
+        2
+    } else {
+        options.number_up
+    };
+
+    if !NUMBER_UP.contains(&number_up) {
+        return Err(format!(
+            "unsupported number-up {}, expected one of {:?}",
+            number_up, NUMBER_UP
+        ));
+    }
+
+    let document = PdfDocument::parse(pdf)?;
+    if document.is_encrypted() {
+        return Err("encrypted PDF documents can not be imposed".to_string());
+    }
+
+    let pages = document.pages()?;
+    let selected = match &options.page_ranges {
+        Some(ranges) => parse_page_ranges(ranges, pages.len())?,
+        None => (0..pages.len()).collect(),
+    };
+
+    if selected.is_empty() {
+        return Err("no pages to impose".to_string());
+    }
+
+    let page_size = pages[selected[0]].size();
+    let sheet = options
+        .sheet_size
+        .unwrap_or_else(|| PageSize::new(page_size.0, page_size.1));
+
+    let (slots, sheet, columns, rows) = if options.booklet {
+        let sheet = if sheet.is_landscape() {
+            sheet
+        } else {
+            sheet.rotated()
+        };
+        (booklet_order(&selected), sheet, 2, 1)
+    } else {
+        let (sheet, columns, rows) = grid(number_up, sheet, page_size);
+        (
+            selected.into_iter().map(Some).collect(),
+            sheet,
+            columns,
+            rows,
+        )
+    };
+
+    let mut writer = PdfWriter::new();
+    let pages_id = writer.reserve();
+    let mut copier = Copier {
+        document: &document,
+        ids: HashMap::new(),
+        pending: Vec::new(),
+    };
+    let mut forms: HashMap<usize, usize> = HashMap::new();
+    let mut page_ids = Vec::new();
+
+    let cell = (sheet.width / columns as f64, sheet.height / rows as f64);
+
+    for side in slots.chunks(number_up as usize) {
+        let mut content = String::new();
+        let mut xobjects = Vec::new();
+
+        for (slot, source) in side.iter().enumerate() {
+            let index = match source {
+                Some(index) => *index,
+                None => continue,
+            };
+
+            let page = &pages[index];
+            let form = match forms.get(&index) {
+                Some(form) => *form,
+                None => {
+                    let form = write_form(&mut writer, &mut copier, page)?;
+                    forms.insert(index, form);
+                    form
+                }
+            };
+
+            let column = slot % columns;
+            let row = slot / columns;
+            let (width, height) = page.size();
+            let scale = (cell.0 / width).min(cell.1 / height);
+            let (box_width, box_height) = (width * scale, height * scale);
+            let x = column as f64 * cell.0 + (cell.0 - box_width) / 2.0;
+            let y = sheet.height - (row + 1) as f64 * cell.1 + (cell.1 - box_height) / 2.0;
+
+            let matrix = multiply(
+                multiply(
+                    [1.0, 0.0, 0.0, 1.0, -page.bounds[0], -page.bounds[1]],
+                    rotation(page),
+                ),
+                [scale, 0.0, 0.0, scale, x, y],
+            );
+            let matrix: Vec<String> = matrix.iter().map(|&value| real(value)).collect();
+
+            content.push_str(&format!("q {} cm /P{} Do Q\n", matrix.join(" "), index));
+            xobjects.push(format!("/P{} {} 0 R", index, form));
+
+            if options.borders {
+                content.push_str(&format!(
+                    "q 0.5 w {} {} {} {} re S Q\n",
+                    real(x),
+                    real(y),
+                    real(box_width),
+                    real(box_height)
+                ));
+            }
+        }
+
+        let content_id = writer.reserve();
+        writer.stream(content_id, "", content.as_bytes());
+
+        let page_id = writer.reserve();
+        writer.object(
+            page_id,
+            &format!(
+                "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {} {}] /Resources << /XObject << {} >> >> /Contents {} 0 R >>",
+                pages_id,
+                real(sheet.width),
+                real(sheet.height),
+                xobjects.join(" "),
+                content_id
+            ),
+        );
+        page_ids.push(page_id);
+    }
+
+    copier.flush(&mut writer);
+    Ok(writer.finish(pages_id, &page_ids))
+}
+
+/**
+ * Returns the page indexes (from 0) of page ranges like "1-3,5,8-" (numbered from 1),
+ * on the given order
+ * Ranges are clamped to the document, pages past its end are skipped like on CUPS page-ranges
+ */
+pub fn parse_page_ranges(ranges: &str, count: usize) -> Result<Vec<usize>, String> {
+    let mut pages = Vec::new();
+    let invalid = || format!("invalid page ranges \"{}\"", ranges);
+
+    for range in ranges.split(',') {
+        let range = range.trim();
+        let (first, last) = match range.split_once('-') {
+            Some((first, last)) => (first.trim(), last.trim()),
+            None => (range, range),
+        };
+
+        let first: usize = if first.is_empty() {
+            1
+        } else {
+            first.parse().map_err(|_| invalid())?
+        };
+        let last: Option<usize> = if last.is_empty() {
+            None
+        } else {
+            Some(last.parse().map_err(|_| invalid())?)
+        };
+
+        if first == 0 || matches!(last, Some(last) if first > last) {
+            return Err(invalid());
+        }
+
+        let last = last.unwrap_or(count).min(count);
+        pages.extend((first..=last).map(|page| page - 1));
+    }
+
+    Ok(pages)
+}
+
+/**
+ * Returns the slots of a booklet: pages padded with blanks to a multiple of 4, each sheet
+ * with the outer pages on the front and the next inner pages on the back
+ */
+fn booklet_order(selected: &[usize]) -> Vec<Option<usize>> {
+    let count = selected.len().div_ceil(4) * 4;
+    let page = |index: usize| selected.get(index).copied();
+    let mut slots = Vec::with_capacity(count);
+
+    for sheet in 0..count / 4 {
+        slots.push(page(count - 1 - 2 * sheet));
+        slots.push(page(2 * sheet));
+        slots.push(page(2 * sheet + 1));
+        slots.push(page(count - 2 - 2 * sheet));
+    }
+
+    slots
+}
+
+/**
+ * Returns the sheet orientation, columns and rows placing the pages larger
+ */
+fn grid(number_up: u32, sheet: PageSize, page: (f64, f64)) -> (PageSize, usize, usize) {
+    let grids: &[(usize, usize)] = match number_up {
+        2 => &[(2, 1), (1, 2)],
+        4 => &[(2, 2)],
+        6 => &[(3, 2), (2, 3)],
+        9 => &[(3, 3)],
+        _ => &[(1, 1)],
+    };
+
+    let mut best = (sheet, 1, 1);
+    let mut best_scale = 0.0;
+
+    for candidate in [sheet, sheet.rotated()] {
+        for &(columns, rows) in grids {
+            let scale = (candidate.width / columns as f64 / page.0)
+                .min(candidate.height / rows as f64 / page.1);
+            if scale > best_scale + 1e-9 {
+                best = (candidate, columns, rows);
+                best_scale = scale;
+            }
+        }
+    }
+
+    best
+}
+
+/**
+ * Write a page as a form XObject with its resources, returns the object number
+ */
+fn write_form(writer: &mut PdfWriter, copier: &mut Copier, page: &Page) -> Result<usize, String> {
+    let content = copier.document.page_content(page)?;
+    let resources = match copier.document.resolve(&page.resources) {
+        Object::Null => "<< >>".to_string(),
+        _ => copier.serialize(writer, &page.resources),
+    };
+
+    let id = writer.reserve();
+    let bounds: Vec<String> = page.bounds.iter().map(|&value| real(value)).collect();
+    writer.stream(
+        id,
+        &format!(
+            "/Type /XObject /Subtype /Form /BBox [{}] /Resources {}",
+            bounds.join(" "),
+            resources
+        ),
+        &content,
+    );
+
+    Ok(id)
+}
+
+/**
+ * Returns the matrix turning the page content (moved to the origin) to its displayed rotation
+ */
+fn rotation(page: &Page) -> [f64; 6] {
+    let width = page.bounds[2] - page.bounds[0];
+    let height = page.bounds[3] - page.bounds[1];
+
+    match page.rotate {
+        90 => [0.0, -1.0, 1.0, 0.0, 0.0, width],
+        180 => [-1.0, 0.0, 0.0, -1.0, width, height],
+        270 => [0.0, 1.0, -1.0, 0.0, height, 0.0],
+        _ => [1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+    }
+}
+
+/**
+ * Returns the matrix applying the first then the second transformation
+ */
+fn multiply(first: [f64; 6], second: [f64; 6]) -> [f64; 6] {
+    [
+        first[0] * second[0] + first[1] * second[2],
+        first[0] * second[1] + first[1] * second[3],
+        first[2] * second[0] + first[3] * second[2],
+        first[2] * second[1] + first[3] * second[3],
+        first[4] * second[0] + first[5] * second[2] + second[4],
+        first[4] * second[1] + first[5] * second[3] + second[5],
+    ]
+}
+
+/**
+ * Returns a number with at most 6 decimals and no trailing zeros
+ */
+fn real(value: f64) -> String {
+    let text = format!("{:.6}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+/**
+ * Copier writes the objects referenced by the copied pages with new numbers
+ */
+struct Copier<'a> {
+    document: &'a PdfDocument,
+    ids: HashMap<u32, usize>,
+    pending: Vec<u32>,
+}
+
+impl Copier<'_> {
+    /**
+     * Returns the object syntax, referenced objects are queued to be written by flush
+     */
+    fn serialize(&mut self, writer: &mut PdfWriter, object: &Object) -> String {
+        match object {
+            Object::Null => "null".to_string(),
+            Object::Boolean(value) => value.to_string(),
+            Object::Integer(value) => value.to_string(),
+            Object::Real(value) => real(*value),
+            Object::Name(name) => name_syntax(name),
+            Object::String(bytes) => {
+                let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+                format!("<{}>", hex)
+            }
+            Object::Array(items) => {
+                let items: Vec<String> = items
+                    .iter()
+                    .map(|item| self.serialize(writer, item))
+                    .collect();
+                format!("[{}]", items.join(" "))
+            }
+            Object::Dictionary(dictionary) | Object::Stream(dictionary, _) => {
+                format!("<< {} >>", self.entries(writer, dictionary))
+            }
+            Object::Reference(number) => {
+                let id = match self.ids.get(number) {
+                    Some(id) => *id,
+                    None => {
+                        let id = writer.reserve();
+                        self.ids.insert(*number, id);
+                        self.pending.push(*number);
+                        id
+                    }
+                };
+                format!("{} 0 R", id)
+            }
+        }
+    }
+
+    fn entries(&mut self, writer: &mut PdfWriter, dictionary: &Dictionary) -> String {
+        let entries: Vec<String> = dictionary
+            .iter()
+            .filter(|(key, _)| key.as_slice() != b"Length")
+            .map(|(key, value)| format!("{} {}", name_syntax(key), self.serialize(writer, value)))
+            .collect();
+        entries.join(" ")
+    }
+
+    /**
+     * Write the queued objects (and the objects they reference)
+     */
+    fn flush(&mut self, writer: &mut PdfWriter) {
+        let document = self.document;
+
+        while let Some(number) = self.pending.pop() {
+            let id = self.ids[&number];
+            let object = document.objects.get(&number).unwrap_or(&Object::Null);
+
+            match object {
+                Object::Stream(dictionary, raw) => {
+                    let dictionary = self.entries(writer, dictionary);
+                    writer.stream(id, &dictionary, raw);
+                }
+                // Links to pages of the source document (like annotation parents) are dropped
+                Object::Dictionary(dictionary)
+                    if matches!(
+                        dictionary.get("Type").and_then(Object::as_name),
+                        Some("Page") | Some("Pages")
+                    ) =>
+                {
+                    writer.object(id, "null")
+                }
+                object => {
+                    let syntax = self.serialize(writer, object);
+                    writer.object(id, &syntax);
+                }
+            }
+        }
+    }
+}
+
+/**
+ * Returns the name syntax, escaping delimiters and bytes out of printable ASCII as #xx
+ */
+fn name_syntax(name: &[u8]) -> String {
+    let mut syntax = String::from("/");
+
+    for &byte in name {
+        match byte {
+            b'#' | b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%' => {
+                syntax.push_str(&format!("#{:02x}", byte))
+            }
+            0x21..=0x7e => syntax.push(byte as char),
+            _ => syntax.push_str(&format!("#{:02x}", byte)),
+        }
+    }
+
+    syntax
+}
@@ -0,0 +1,499 @@
+//! Inspect documents before submission: format, page count, page sizes, color use and encryption
+//!
+//! ```rust
+//! use printers::inspect;
+//! use printers::text::{self, TextOptions};
+//!
+//! let pdf = text::render_pdf("Hello\x0cWorld", &TextOptions::default()).unwrap();
+//! let info = inspect::inspect_document(&pdf).unwrap();
+//!
+//! assert_eq!(info.page_count, 2);
+//! assert!(!info.encrypted);
+//! ```
+
+use crate::document::DocumentFormat;
+use crate::pdf::reader::{Dictionary, Object, Page, PdfDocument};
+use crate::pdf::PageSize;
+use crate::pwg::{self, ColorSpace, PageHeader, RasterPage};
+
+/**
+ * Maximum depth of nested form XObjects checked for color
+ */
+const MAX_FORM_DEPTH: usize = 8;
+
+/**
+ * Enum of the color use of a page or document
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorUsage {
+    /**
+     * Only black, white and gray are used
+     */
+    Monochrome,
+
+    /**
+     * At least one color other than gray is used
+     */
+    Color,
+
+    /**
+     * The content could not be checked (like encrypted PDF pages)
+     */
+    Unknown,
+}
+
+/**
+ * Information of a single page
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageInfo {
+    /**
+     * Displayed size of the page (when the document declares it)
+     */
+    pub size: Option<PageSize>,
+
+    /**
+     * Color use of the page
+     */
+    pub color: ColorUsage,
+}
+
+/**
+ * Information of a document, returned by inspect_document
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentInfo {
+    /**
+     * Detected format of the document
+     */
+    pub format: DocumentFormat,
+
+    /**
+     * Number of pages of the document
+     */
+    pub page_count: usize,
+
+    /**
+     * Information of every page on document order
+     */
+    pub pages: Vec<PageInfo>,
+
+    /**
+     * Color use of the whole document (color when any page is color)
+     */
+    pub color: ColorUsage,
+
+    /**
+     * If the document is encrypted (the pages can be counted, but not their content checked)
+     */
+    pub encrypted: bool,
+}
+
+impl DocumentInfo {
+    /**
+     * Returns the number of pages using color
+     */
+    pub fn color_page_count(&self) -> usize {
+        self.pages
+            .iter()
+            .filter(|page| page.color == ColorUsage::Color)
+            .count()
+    }
+}
+
+/**
+ * Inspect a PDF, PostScript (with DSC comments), PWG raster or URF document
+ */
+pub fn inspect_document(buffer: &[u8]) -> Result<DocumentInfo, String> {
+    let format = DocumentFormat::detect(buffer);
+
+    let (pages, encrypted) = match format {
+        DocumentFormat::PDF => inspect_pdf(buffer)?,
+        DocumentFormat::POSTSCRIPT => (inspect_postscript(buffer)?, false),
+        DocumentFormat::PWG => (raster_pages(pwg::decode_pwg(buffer)?), false),
+        DocumentFormat::URF => (raster_pages(pwg::decode_urf(buffer)?), false),
+        _ => {
+            return Err(format!(
+                "document inspection is not supported for {} ({:?}) documents",
+                format.mime_type(),
+                format
+            ))
+        }
+    };
+
+    let color = if pages.iter().any(|page| page.color == ColorUsage::Color) {
+        ColorUsage::Color
+    } else if pages.iter().any(|page| page.color == ColorUsage::Unknown) {
+        ColorUsage::Unknown
+    } else {
+        ColorUsage::Monochrome
+    };
+
+    Ok(DocumentInfo {
+        format,
+        page_count: pages.len(),
+        pages,
+        color,
+        encrypted,
+    })
+}
+
+/**
+ * Inspect a document file, see inspect_document
+ */
+pub fn inspect_document_file(file_path: &str) -> Result<DocumentInfo, String> {
+    let buffer = std::fs::read(file_path).map_err(|error| error.to_string())?;
+    inspect_document(&buffer)
+}
+
+/**
+ * Returns the pages of a PDF document and if it is encrypted
+ */
+fn inspect_pdf(buffer: &[u8]) -> Result<(Vec<PageInfo>, bool), String> {
+    let document = PdfDocument::parse(buffer)?;
+    let encrypted = document.is_encrypted();
+
+    let pages = document
+        .pages()?
+        .iter()
+        .map(|page| {
+            let (width, height) = page.size();
+            let color = if encrypted {
+                ColorUsage::Unknown
+            } else {
+                pdf_page_color(&document, page)
+            };
+
+            PageInfo {
+                size: Some(PageSize::new(width, height)),
+                color,
+            }
+        })
+        .collect();
+
+    Ok((pages, encrypted))
+}
+
+/**
+ * Check the color operators and images of a PDF page
+ */
+fn pdf_page_color(document: &PdfDocument, page: &Page) -> ColorUsage {
+    let content = match document.page_content(page) {
+        Ok(content) => content,
+        Err(_) => return ColorUsage::Unknown,
+    };
+
+    let resources = document.resolve(&page.resources).as_dictionary();
+    pdf_content_color(document, &content, resources, 0)
+}
+
+/**
+ * Check the color of a content stream and of the XObjects on its resources
+ */
+fn pdf_content_color(
+    document: &PdfDocument,
+    content: &[u8],
+    resources: Option<&Dictionary>,
+    depth: usize,
+) -> ColorUsage {
+    if content_color(content) == ColorUsage::Color {
+        return ColorUsage::Color;
+    }
+
+    let xobjects = match resources.and_then(|resources| {
+        document
+            .get(resources, "XObject")
+            .as_dictionary()
+            .map(|xobjects| xobjects.values())
+    }) {
+        Some(xobjects) => xobjects,
+        None => return ColorUsage::Monochrome,
+    };
+
+    let mut usage = ColorUsage::Monochrome;
+    for xobject in xobjects {
+        let (dictionary, raw) = match document.resolve(xobject) {
+            Object::Stream(dictionary, raw) => (dictionary, raw),
+            _ => continue,
+        };
+
+        let xobject_usage = match document.get(dictionary, "Subtype").as_name() {
+            Some("Image") => image_color(document, dictionary),
+            Some("Form") if depth < MAX_FORM_DEPTH => match document.decode(dictionary, raw) {
+                Ok(content) => pdf_content_color(
+                    document,
+                    &content,
+                    document.get(dictionary, "Resources").as_dictionary(),
+                    depth + 1,
+                ),
+                Err(_) => ColorUsage::Unknown,
+            },
+            _ => ColorUsage::Monochrome,
+        };
+
+        match xobject_usage {
+            ColorUsage::Color => return ColorUsage::Color,
+            ColorUsage::Unknown => usage = ColorUsage::Unknown,
+            ColorUsage::Monochrome => {}
+        }
+    }
+
+    usage
+}
+
+/**
+ * Returns the color use of an image from its color space (image masks and gray spaces are monochrome)
+ */
+fn image_color(document: &PdfDocument, dictionary: &Dictionary) -> ColorUsage {
+    if let Object::Boolean(true) = document.get(dictionary, "ImageMask") {
+        return ColorUsage::Monochrome;
+    }
+
+    if document.get(dictionary, "ColorSpace") == &Object::Null {
+        // JPX images may carry the color space in the image data
+        return ColorUsage::Unknown;
+    }
+
+    color_space_usage(document, document.get(dictionary, "ColorSpace"))
+}
+
+/**
+ * Returns the color use of a PDF color space
+ */
+fn color_space_usage(document: &PdfDocument, space: &Object) -> ColorUsage {
+    if let Some(name) = space.as_name() {
+        return match name {
+            "DeviceGray" | "CalGray" | "G" => ColorUsage::Monochrome,
+            _ => ColorUsage::Color,
+        };
+    }
+
+    let items = match space.as_array() {
+        Some(items) if !items.is_empty() => items,
+        _ => return ColorUsage::Unknown,
+    };
+
+    match items[0].as_name() {
+        Some("CalGray") => ColorUsage::Monochrome,
+        Some("ICCBased") => {
+            let components = items
+                .get(1)
+                .and_then(|stream| document.resolve(stream).as_dictionary())
+                .and_then(|stream| document.get(stream, "N").as_number());
+
+            match components {
+                Some(components) if components <= 1.0 => ColorUsage::Monochrome,
+                Some(_) => ColorUsage::Color,
+                None => ColorUsage::Unknown,
+            }
+        }
+        Some("Indexed") | Some("I") => match items.get(1) {
+            Some(base) => color_space_usage(document, document.resolve(base)),
+            None => ColorUsage::Unknown,
+        },
+        Some("Separation") => match items.get(1).and_then(|name| name.as_name()) {
+            Some("Black") | Some("All") => ColorUsage::Monochrome,
+            _ => ColorUsage::Color,
+        },
+        _ => ColorUsage::Color,
+    }
+}
+
+/**
+ * Check the color operators of PDF content or PostScript code (both use postfix operators)
+ */
+fn content_color(content: &[u8]) -> ColorUsage {
+    let mut operands: Vec<f64> = Vec::new();
+
+    for token in content
+        .split(|&byte| byte.is_ascii_whitespace() || matches!(byte, b'[' | b']' | b'{' | b'}'))
+        .filter(|token| !token.is_empty())
+    {
+        let token = String::from_utf8_lossy(token);
+        if let Ok(value) = token.parse::<f64>() {
+            operands.push(value);
+            continue;
+        }
+
+        let count = match token.as_ref() {
+            "rg" | "RG" | "setrgbcolor" => 3,
+            "k" | "K" | "setcmykcolor" => 4,
+            "sc" | "SC" | "scn" | "SCN" => operands.len().min(4),
+            "sethsbcolor" => {
+                // Without saturation the color is gray
+                if operands.len() >= 3 && operands[operands.len() - 2] > 0.0 {
+                    return ColorUsage::Color;
+                }
+                0
+            }
+            "colorimage" | "sh" | "shfill" => return ColorUsage::Color,
+            _ => 0,
+        };
+
+        if count > 0 && operands.len() >= count {
+            let values = &operands[operands.len() - count..];
+            let gray = match count {
+                3 => values[0] == values[1] && values[1] == values[2],
+                4 => values[0] == 0.0 && values[1] == 0.0 && values[2] == 0.0,
+                _ => true,
+            };
+
+            if !gray {
+                return ColorUsage::Color;
+            }
+        }
+
+        operands.clear();
+    }
+
+    ColorUsage::Monochrome
+}
+
+/**
+ * Returns the pages of a PostScript document from its DSC comments
+ */
+fn inspect_postscript(buffer: &[u8]) -> Result<Vec<PageInfo>, String> {
+    let text = String::from_utf8_lossy(buffer);
+
+    let mut declared_pages = None;
+    let mut media = None;
+    let mut process_colors = None;
+    let mut sections: Vec<String> = Vec::new();
+    let mut current: Option<String> = None;
+
+    for line in text.lines() {
+        // The trailer values replace the "(atend)" values of the header
+        if let Some(value) = line.strip_prefix("%%Pages:") {
+            if let Ok(count) = value.trim().parse::<usize>() {
+                declared_pages = Some(count);
+            }
+        } else if let Some(value) = line.strip_prefix("%%DocumentMedia:") {
+            if media.is_none() {
+                media = document_media_size(value);
+            }
+        } else if let Some(value) = line.strip_prefix("%%DocumentProcessColors:") {
+            if value.trim() != "(atend)" {
+                process_colors = Some(value.to_string());
+            }
+        } else if line.starts_with("%%Page:") {
+            if let Some(section) = current.take() {
+                sections.push(section);
+            }
+            current = Some(String::new());
+            continue;
+        } else if line.starts_with("%%Trailer") || line.starts_with("%%EOF") {
+            if let Some(section) = current.take() {
+                sections.push(section);
+            }
+        }
+
+        if let Some(section) = current.as_mut() {
+            section.push_str(line);
+            section.push('\n');
+        } else if media.is_none() {
+            media = page_device_size(line);
+        }
+    }
+    if let Some(section) = current.take() {
+        sections.push(section);
+    }
+
+    let page_count = match declared_pages {
+        Some(count) => count,
+        None if !sections.is_empty() => sections.len(),
+        None => return Err("the PostScript document has no DSC page comments".to_string()),
+    };
+
+    let document_color = process_colors.map(|colors| {
+        if colors
+            .split_whitespace()
+            .any(|color| matches!(color, "Cyan" | "Magenta" | "Yellow"))
+        {
+            ColorUsage::Color
+        } else {
+            ColorUsage::Monochrome
+        }
+    });
+
+    Ok((0..page_count)
+        .map(|index| {
+            let section = sections.get(index);
+            let size = section
+                .and_then(|section| section.lines().find_map(page_device_size))
+                .or(media);
+            let color = match (document_color, section) {
+                (Some(color), _) => color,
+                (None, Some(section)) => content_color(section.as_bytes()),
+                (None, None) => ColorUsage::Unknown,
+            };
+
+            PageInfo { size, color }
+        })
+        .collect())
+}
+
+/**
+ * Returns the size of the first medium of a "%%DocumentMedia: name width height weight color type" comment
+ */
+fn document_media_size(value: &str) -> Option<PageSize> {
+    let fields: Vec<&str> = value.split_whitespace().collect();
+    let width = fields.get(1)?.parse().ok()?;
+    let height = fields.get(2)?.parse().ok()?;
+    Some(PageSize::new(width, height))
+}
+
+/**
+ * Returns the size of a "<< /PageSize [width height] >> setpagedevice" line
+ */
+fn page_device_size(line: &str) -> Option<PageSize> {
+    if !line.contains("setpagedevice") {
+        return None;
+    }
+
+    let start = line.find("/PageSize")?;
+    let values = &line[start..];
+    let values = &values[values.find('[')? + 1..values.find(']')?];
+    let fields: Vec<f64> = values
+        .split_whitespace()
+        .filter_map(|field| field.parse().ok())
+        .collect();
+
+    match fields[..] {
+        [width, height] => Some(PageSize::new(width, height)),
+        _ => None,
+    }
+}
+
+/**
+ * Returns the pages of a decoded raster stream, the color is checked on the pixels
+ */
+fn raster_pages(pages: Vec<(PageHeader, RasterPage)>) -> Vec<PageInfo> {
+    pages
+        .iter()
+        .map(|(header, page)| {
+            let color = match page.color_space {
+                ColorSpace::Srgb8 => {
+                    if page
+                        .data
+                        .chunks_exact(3)
+                        .any(|pixel| pixel[0] != pixel[1] || pixel[1] != pixel[2])
+                    {
+                        ColorUsage::Color
+                    } else {
+                        ColorUsage::Monochrome
+                    }
+                }
+                ColorSpace::Sgray8 | ColorSpace::Black1 => ColorUsage::Monochrome,
+            };
+
+            let resolution = header.resolution.max(1) as f64;
+            PageInfo {
+                size: Some(PageSize::new(
+                    page.width as f64 * 72.0 / resolution,
+                    page.height as f64 * 72.0 / resolution,
+                )),
+                color,
+            }
+        })
+        .collect()
+}
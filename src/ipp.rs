@@ -0,0 +1,361 @@
+//! Printer attributes of IPP responses (application/ipp), like the Get-Printer-Attributes response
+//! of an IPP Everywhere printer
+//!
+//! ```rust
+//! use printers::ipp::PrinterAttributes;
+//!
+//! let mut attributes = PrinterAttributes::default();
+//! attributes
+//!     .set("media-supported", &["iso_a4_210x297mm", "na_letter_8.5x11in"])
+//!     .set("sides-supported", &["one-sided"]);
+//!
+//! assert_eq!(attributes.first("sides-supported"), Some("one-sided"));
+//! assert_eq!(attributes.capabilities().media_sizes().len(), 2);
+//! ```
+
+use crate::capabilities::Capabilities;
+use std::collections::BTreeMap;
+
+/**
+ * Delimiter tags of the attribute groups
+ */
+const TAG_OPERATION: u8 = 0x01;
+const TAG_END: u8 = 0x03;
+const TAG_PRINTER: u8 = 0x04;
+
+/**
+ * Get-Printer-Attributes operation
+ */
+const OPERATION_GET_PRINTER_ATTRIBUTES: u16 = 0x000b;
+
+/**
+ * Value tags (out-of-band values are below 0x20)
+ */
+const TAG_INTEGER: u8 = 0x21;
+const TAG_BOOLEAN: u8 = 0x22;
+const TAG_ENUM: u8 = 0x23;
+const TAG_DATE_TIME: u8 = 0x31;
+const TAG_RESOLUTION: u8 = 0x32;
+const TAG_RANGE: u8 = 0x33;
+const TAG_BEGIN_COLLECTION: u8 = 0x34;
+const TAG_TEXT_WITH_LANGUAGE: u8 = 0x35;
+const TAG_NAME_WITH_LANGUAGE: u8 = 0x36;
+const TAG_END_COLLECTION: u8 = 0x37;
+const TAG_KEYWORD: u8 = 0x44;
+const TAG_URI: u8 = 0x45;
+const TAG_CHARSET: u8 = 0x47;
+const TAG_NATURAL_LANGUAGE: u8 = 0x48;
+const TAG_MEMBER_NAME: u8 = 0x4a;
+
+/**
+ * Deepest collection nesting read from responses, deeper messages are rejected
+ */
+const MAX_COLLECTION_DEPTH: usize = 32;
+
+/**
+ * Attributes read by the print system, with their values (name, values)
+ */
+pub(crate) type AttributeValues = Vec<(String, Vec<String>)>;
+
+/**
+ * Printer attributes by IPP name, with the values formatted as strings the way CUPS does
+ * ("600dpi", "1-99", "{media-size={x-dimension=21000 y-dimension=29700}}")
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PrinterAttributes {
+    attributes: BTreeMap<String, Vec<String>>,
+}
+
+impl PrinterAttributes {
+    /**
+     * Decode the printer attributes group of an IPP response
+     * Returns an error when the response is malformed or its status is not successful
+     */
+    pub fn from_response(data: &[u8]) -> Result<PrinterAttributes, String> {
+        let mut reader = Reader {
+            data,
+            position: 0,
+            depth: 0,
+        };
+        let _version = reader.bytes(2)?;
+        let status = reader.u16()?;
+        let _request_id = reader.bytes(4)?;
+
+        let mut printer = PrinterAttributes::default();
+        let mut operation = PrinterAttributes::default();
+        let mut group = 0;
+        let mut name = String::new();
+
+        loop {
+            let tag = reader.u8()?;
+            if tag == TAG_END {
+                break;
+            }
+            if tag < 0x10 {
+                group = tag;
+                continue;
+            }
+
+            let attribute_name = reader.string()?;
+            let value = reader.value(tag)?;
+            if !attribute_name.is_empty() {
+                name = attribute_name;
+            }
+
+            let attributes = match group {
+                TAG_PRINTER => &mut printer,
+                TAG_OPERATION => &mut operation,
+                _ => continue,
+            };
+            attributes
+                .attributes
+                .entry(name.clone())
+                .or_default()
+                .push(value);
+        }
+
+        if status > 0x00ff {
+            return Err(format!(
+                "the printer returned the IPP status 0x{:04x} {}",
+                status,
+                operation.first("status-message").unwrap_or("")
+            )
+            .trim_end()
+            .to_string());
+        }
+
+        Ok(printer)
+    }
+
+    /**
+     * Set the values of an attribute, like "sides-supported"
+     */
+    pub fn set(&mut self, name: &str, values: &[&str]) -> &mut Self {
+        self.attributes.insert(
+            name.to_string(),
+            values.iter().map(|value| value.to_string()).collect(),
+        );
+        self
+    }
+
+    /**
+     * Returns the values of an attribute (empty when the printer does not report it)
+     */
+    pub fn get(&self, name: &str) -> &[String] {
+        self.attributes.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /**
+     * Returns the first value of an attribute
+     */
+    pub fn first(&self, name: &str) -> Option<&str> {
+        self.get(name).first().map(String::as_str)
+    }
+
+    /**
+     * Returns the names of the attributes
+     */
+    pub fn names(&self) -> Vec<&str> {
+        self.attributes.keys().map(String::as_str).collect()
+    }
+
+    /**
+     * Returns the capabilities of the printer, the values of the "*-supported" attributes
+     */
+    pub fn capabilities(&self) -> Capabilities {
+        let mut capabilities = Capabilities::default();
+        for (name, values) in &self.attributes {
+            if let Some(attribute) = name.strip_suffix("-supported") {
+                capabilities.set(attribute, values.clone());
+            }
+        }
+        capabilities
+    }
+}
+
+/**
+ * Returns a Get-Printer-Attributes request (application/ipp) for a printer URI, asking some attributes
+ * (all of them when the list is empty)
+ */
+pub fn get_printer_attributes_request(printer_uri: &str, requested: &[&str]) -> Vec<u8> {
+    let mut request = vec![2, 0];
+    request.extend_from_slice(&OPERATION_GET_PRINTER_ATTRIBUTES.to_be_bytes());
+    request.extend_from_slice(&1u32.to_be_bytes());
+    request.push(TAG_OPERATION);
+
+    write_attribute(&mut request, TAG_CHARSET, "attributes-charset", b"utf-8");
+    write_attribute(
+        &mut request,
+        TAG_NATURAL_LANGUAGE,
+        "attributes-natural-language",
+        b"en",
+    );
+    write_attribute(&mut request, TAG_URI, "printer-uri", printer_uri.as_bytes());
+    for (index, name) in requested.iter().enumerate() {
+        // Additional values have an empty name
+        let attribute = if index == 0 {
+            "requested-attributes"
+        } else {
+            ""
+        };
+        write_attribute(&mut request, TAG_KEYWORD, attribute, name.as_bytes());
+    }
+
+    request.push(TAG_END);
+    request
+}
+
+/**
+ * Append an attribute to an IPP message: its value tag, its name (empty for the additional values of
+ * the previous attribute) and its raw value
+ */
+pub fn write_attribute(message: &mut Vec<u8>, tag: u8, name: &str, value: &[u8]) {
+    message.push(tag);
+    message.extend_from_slice(&(name.len() as u16).to_be_bytes());
+    message.extend_from_slice(name.as_bytes());
+    message.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    message.extend_from_slice(value);
+}
+
+/**
+ * Reader of the big endian fields of an IPP message
+ */
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+
+    /**
+     * Number of collections being read, nested in each other
+     */
+    depth: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, length: usize) -> Result<&'a [u8], String> {
+        let end = self.position + length;
+        if end > self.data.len() {
+            return Err("invalid IPP response, the message is truncated".to_string());
+        }
+
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        let bytes = self.bytes(4)?;
+        Ok(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /**
+     * Read a string with a two bytes length
+     */
+    fn string(&mut self) -> Result<String, String> {
+        let length = self.u16()? as usize;
+        Ok(String::from_utf8_lossy(self.bytes(length)?).to_string())
+    }
+
+    /**
+     * Read the value of an attribute, with the members of a collection
+     */
+    fn value(&mut self, tag: u8) -> Result<String, String> {
+        let length = self.u16()? as usize;
+        let start = self.position;
+
+        let value = match tag {
+            TAG_INTEGER | TAG_ENUM if length == 4 => self.i32()?.to_string(),
+            TAG_BOOLEAN if length == 1 => (self.u8()? != 0).to_string(),
+            TAG_RANGE if length == 8 => format!("{}-{}", self.i32()?, self.i32()?),
+            TAG_RESOLUTION if length == 9 => {
+                let (x, y) = (self.i32()?, self.i32()?);
+                let units = if self.u8()? == 4 { "dpcm" } else { "dpi" };
+                if x == y {
+                    format!("{}{}", x, units)
+                } else {
+                    format!("{}x{}{}", x, y, units)
+                }
+            }
+            TAG_DATE_TIME if length == 11 => {
+                let bytes = self.bytes(11)?;
+                format!(
+                    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                    u16::from_be_bytes([bytes[0], bytes[1]]),
+                    bytes[2],
+                    bytes[3],
+                    bytes[4],
+                    bytes[5],
+                    bytes[6]
+                )
+            }
+            TAG_TEXT_WITH_LANGUAGE | TAG_NAME_WITH_LANGUAGE => {
+                let _language = self.string()?;
+                self.string()?
+            }
+            TAG_BEGIN_COLLECTION => {
+                self.bytes(length)?;
+                return self.collection();
+            }
+            tag if tag < 0x20 => {
+                self.bytes(length)?;
+                String::new()
+            }
+            _ => String::from_utf8_lossy(self.bytes(length)?).to_string(),
+        };
+
+        // Values with an unexpected length are skipped
+        self.position = start + length;
+        if self.position > self.data.len() {
+            return Err("invalid IPP response, the message is truncated".to_string());
+        }
+        Ok(value)
+    }
+
+    /**
+     * Read the members of a collection, formatted like "{name=value name=value}"
+     */
+    fn collection(&mut self) -> Result<String, String> {
+        if self.depth >= MAX_COLLECTION_DEPTH {
+            return Err("invalid IPP response, the collections are nested too deep".to_string());
+        }
+        self.depth += 1;
+
+        let mut members: Vec<String> = Vec::new();
+        let mut name = String::new();
+
+        loop {
+            let tag = self.u8()?;
+            let _name = self.string()?;
+
+            match tag {
+                TAG_END_COLLECTION => {
+                    let length = self.u16()? as usize;
+                    self.bytes(length)?;
+                    break;
+                }
+                TAG_MEMBER_NAME => name = self.string()?,
+                tag => {
+                    let value = self.value(tag)?;
+                    match members.last_mut() {
+                        Some(member) if name.is_empty() => {
+                            member.push(',');
+                            member.push_str(&value);
+                        }
+                        _ => members.push(format!("{}={}", std::mem::take(&mut name), value)),
+                    }
+                }
+            }
+        }
+
+        self.depth -= 1;
+        Ok(format!("{{{}}}", members.join(" ")))
+    }
+}
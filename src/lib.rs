@@ -29,6 +29,115 @@ pub mod printer;
 pub mod printer_job;
 pub mod shared;
 
+/**
+ * Printer capabilities and validation of print options against them
+ */
+pub mod capabilities;
+
+/**
+ * Typed printer device URIs (ipp, ipps, socket, lpd, usb, dnssd...)
+ */
+pub mod device_uri;
+
+/**
+ * Documents to print and detection of their format
+ */
+pub mod document;
+
+/**
+ * ESC/POS command builder for receipt printers
+ */
+pub mod escpos;
+
+/**
+ * JPEG and PNG images to PDF
+ */
+pub mod image;
+
+/**
+ * Client-side imposition of PDF documents (N-up, booklets)
+ */
+pub mod imposition;
+
+/**
+ * Inspection of documents before submission
+ */
+pub mod inspect;
+
+/**
+ * IPP requests and printer attributes of IPP responses
+ */
+pub mod ipp;
+
+/**
+ * Option strings in the `lp -o` format
+ */
+pub mod lp_options;
+
+/**
+ * PWG media sizes
+ */
+pub mod media;
+
+/**
+ * Network backends (socket, lpd), SNMP status and health checks
+ */
+pub mod network;
+
+/**
+ * PCL 5 job builder for laser printers
+ */
+pub mod pcl;
+
+/**
+ * PDF primitives shared by the document renderers
+ */
+pub mod pdf;
+
+/**
+ * PPD files of CUPS queues
+ */
+pub mod ppd;
+
+/**
+ * Typed print options
+ */
+pub mod print_options;
+
+/**
+ * Named print profiles loaded from TOML or JSON files
+ */
+#[cfg(feature = "profiles")]
+pub mod profiles;
+
+/**
+ * PWG Raster and Apple Raster encoders
+ */
+pub mod pwg;
+
+/**
+ * Image rasterization for thermal and label printers
+ */
+pub mod raster;
+
+/**
+ * Printer supplies and their levels
+ */
+pub mod supplies;
+
+/**
+ * Plain text rendering to PDF and PostScript
+ */
+pub mod text;
+
+/**
+ * ZPL II label builder for label printers
+ */
+pub mod zpl;
+
+use capabilities::{Capabilities, Violation};
+use document::{Document, DocumentFormat, RawDocument};
+use print_options::PrintOptions;
 use printer_job::PrintJob;
 
 #[cfg(target_family = "unix")]
@@ -41,6 +150,22 @@ mod windows;
  * Print bytes on specific printer
  */
 pub fn print(printer_name: &str, buffer: &[u8], job_name: Option<&str>) -> Result<bool, String> {
+    print_with_options(printer_name, buffer, job_name, &PrintOptions::default())
+}
+
+/**
+ * Print bytes on specific printer with print options
+ */
+pub fn print_with_options(
+    printer_name: &str,
+    buffer: &[u8],
+    job_name: Option<&str>,
+    options: &PrintOptions,
+) -> Result<bool, String> {
+    if network::is_network_uri(printer_name) {
+        return network::print(printer_name, buffer, job_name, options).map(|_| true);
+    }
+
     let time = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
@@ -56,7 +181,39 @@ pub fn print(printer_name: &str, buffer: &[u8], job_name: Option<&str>) -> Resul
         return Err(error.to_string());
     }
 
-    return print_file(printer_name, tmp_file_path.to_str().unwrap(), job_name);
+    // The format is detected from the buffer to avoid reading back the temp file
+    let mut options = options.clone();
+    if options.document_format.is_none() {
+        options.document_format = Some(DocumentFormat::detect(buffer));
+    }
+
+    print_file_with_options(
+        printer_name,
+        tmp_file_path.to_str().unwrap(),
+        job_name,
+        &options,
+    )
+}
+
+/**
+ * Print bytes with a named print profile (see the profiles module for the config file)
+ */
+#[cfg(feature = "profiles")]
+pub fn print_with_profile(profile_name: &str, buffer: &[u8]) -> Result<bool, String> {
+    print_with_profile_options(profile_name, buffer, None, &PrintOptions::default())
+}
+
+/**
+ * Print bytes with a named print profile, overriding the profile options with job options
+ */
+#[cfg(feature = "profiles")]
+pub fn print_with_profile_options(
+    profile_name: &str,
+    buffer: &[u8],
+    job_name: Option<&str>,
+    overrides: &PrintOptions,
+) -> Result<bool, String> {
+    profiles::registry()?.print(profile_name, buffer, job_name, overrides)
 }
 
 /**
@@ -67,11 +224,259 @@ pub fn print_file(
     file_path: &str,
     job_name: Option<&str>,
 ) -> Result<bool, String> {
+    print_file_with_options(printer_name, file_path, job_name, &PrintOptions::default())
+}
+
+/**
+ * Print specific file on a specific printer with print options
+ */
+pub fn print_file_with_options(
+    printer_name: &str,
+    file_path: &str,
+    job_name: Option<&str>,
+    options: &PrintOptions,
+) -> Result<bool, String> {
+    if network::is_network_uri(printer_name) {
+        let buffer = std::fs::read(file_path).map_err(|error| error.to_string())?;
+        return network::print(printer_name, &buffer, job_name, options).map(|_| true);
+    }
+
+    let format = match options.document_format {
+        Some(format) => format,
+        None => DocumentFormat::detect_file(file_path)?,
+    };
+
+    check_document_format(printer_name, format)?;
+
+    let mut options = options.clone();
+    options.document_format = Some(format);
+
     #[cfg(target_family = "unix")]
-    return unix::print(printer_name, file_path, job_name);
+    return unix::print(printer_name, file_path, job_name, &options);
 
     #[cfg(target_family = "windows")]
-    return windows::print(printer_name, file_path, job_name);
+    return windows::print(printer_name, file_path, job_name, &options);
+}
+
+/**
+ * Inspect a file before printing it on a specific printer (format, page count, page sizes, color use and encryption)
+ * Fails when the document can not be inspected or its format is not supported by the printer
+ */
+pub fn preflight_file(
+    printer_name: &str,
+    file_path: &str,
+) -> Result<inspect::DocumentInfo, String> {
+    let info = inspect::inspect_document_file(file_path)?;
+
+    if !network::is_network_uri(printer_name) {
+        check_document_format(printer_name, info.format)?;
+    }
+
+    Ok(info)
+}
+
+/**
+ * Print a raw document (ZPL, ESC/POS, PCL...) on a specific printer bypassing the print system filters
+ * Fails when the printer queue driver would process the document
+ */
+pub fn print_raw(
+    printer_name: &str,
+    document: &RawDocument,
+    job_name: Option<&str>,
+) -> Result<bool, String> {
+    if network::is_network_uri(printer_name) {
+        return network::print(
+            printer_name,
+            &document.data,
+            job_name,
+            &PrintOptions::default(),
+        )
+        .map(|_| true);
+    }
+
+    #[cfg(target_family = "unix")]
+    return unix::print_raw(printer_name, &document.data, job_name);
+
+    #[cfg(target_family = "windows")]
+    return windows::print_raw(printer_name, &document.data, job_name);
+}
+
+/**
+ * Check the document format against the formats supported by the printer
+ * (the check is skipped when the printer does not report them or the format is unknown)
+ */
+fn check_document_format(printer_name: &str, format: DocumentFormat) -> Result<(), String> {
+    if format == DocumentFormat::AUTO {
+        return Ok(());
+    }
+
+    #[cfg(target_family = "unix")]
+    let supported = unix::get_supported_document_formats(printer_name);
+
+    #[cfg(target_family = "windows")]
+    let supported = windows::get_supported_document_formats(printer_name);
+
+    if supported.is_empty() || supported.iter().any(|mime| mime == format.mime_type()) {
+        return Ok(());
+    }
+
+    Err(format!(
+        "the document format {} ({:?}) is not supported by printer {}, supported formats are: {}",
+        format.mime_type(),
+        format,
+        printer_name,
+        supported.join(", ")
+    ))
+}
+
+/**
+ * Get the values supported by a specific printer for the job attributes
+ * (attributes not reported by the printer are empty, like on windows and network printers)
+ */
+pub fn get_capabilities(printer_name: &str) -> Capabilities {
+    get_supported_values(printer_name, &capabilities::ATTRIBUTES)
+}
+
+/**
+ * Get the PPD file of a specific printer (CUPS queues with a PPD driver)
+ */
+pub fn get_ppd(printer_name: &str) -> Result<ppd::Ppd, String> {
+    if network::is_network_uri(printer_name) {
+        return Err(format!(
+            "{} is a network printer without PPD file",
+            printer_name
+        ));
+    }
+
+    #[cfg(target_family = "unix")]
+    let data = unix::get_ppd(printer_name)?;
+    #[cfg(target_family = "windows")]
+    let data = windows::get_ppd(printer_name)?;
+
+    ppd::Ppd::parse(&data)
+}
+
+/**
+ * Get the supplies of a specific printer (toner, ink, waste containers, drums) with their levels
+ * Network printers are asked with SNMP, CUPS printers without marker-* attributes are asked their
+ * printer-supply attribute. The list is empty when the printer does not report them
+ */
+pub fn get_supplies(printer_name: &str) -> Vec<supplies::Supply> {
+    if network::is_network_uri(printer_name) {
+        return network::snmp::status_from_uri(printer_name)
+            .map(|status| status.supplies)
+            .unwrap_or_default();
+    }
+
+    const PRINTER_SUPPLY: [&str; 2] = ["printer-supply", "printer-supply-description"];
+
+    #[cfg(target_family = "unix")]
+    let mut values = unix::get_attributes(printer_name, "marker-");
+    #[cfg(target_family = "windows")]
+    let mut values = windows::get_attributes(printer_name, "marker-");
+
+    if !values.iter().any(|(name, _)| name == "marker-names") {
+        #[cfg(target_family = "unix")]
+        let supply = unix::get_printer_attributes(printer_name, &PRINTER_SUPPLY);
+        #[cfg(target_family = "windows")]
+        let supply = windows::get_printer_attributes(printer_name, &PRINTER_SUPPLY);
+
+        values.extend(supply.unwrap_or_default());
+    }
+
+    let mut attributes = ipp::PrinterAttributes::default();
+    for (name, values) in &values {
+        let values: Vec<&str> = values.iter().map(String::as_str).collect();
+        attributes.set(name, &values);
+    }
+
+    supplies::from_attributes(&attributes)
+}
+
+/**
+ * Get the state of a print queue (printer-state, printer-state-reasons and printer-is-accepting-jobs),
+ * empty when the print system does not report it
+ */
+pub(crate) fn get_queue_state(printer_name: &str) -> ipp::PrinterAttributes {
+    #[cfg(target_family = "unix")]
+    let values = unix::get_attributes(printer_name, "printer-");
+    #[cfg(target_family = "windows")]
+    let values = windows::get_attributes(printer_name, "printer-");
+
+    let mut attributes = ipp::PrinterAttributes::default();
+    for (name, values) in &values {
+        let values: Vec<&str> = values.iter().map(String::as_str).collect();
+        attributes.set(name, &values);
+    }
+    attributes
+}
+
+/**
+ * Get the capabilities of a specific printer for a list of job attributes
+ */
+pub(crate) fn get_supported_values(printer_name: &str, attributes: &[&str]) -> Capabilities {
+    let mut capabilities = Capabilities::default();
+    if network::is_network_uri(printer_name) {
+        return capabilities;
+    }
+
+    #[cfg(target_family = "unix")]
+    let values = unix::get_supported_values(printer_name, attributes);
+    #[cfg(target_family = "windows")]
+    let values = windows::get_supported_values(printer_name, attributes);
+
+    for (attribute, values) in attributes.iter().zip(values) {
+        capabilities.set(attribute, values);
+    }
+
+    capabilities
+}
+
+/**
+ * Validate print options and a document against the capabilities of a specific printer, before printing
+ * The printer is asked with an IPP Validate-Job request when available, together with the local checks
+ * Returns every violation with the nearest supported value (empty when the job is valid)
+ */
+pub fn validate(
+    printer_name: &str,
+    options: &PrintOptions,
+    document: &Document,
+) -> Result<Vec<Violation>, String> {
+    let format = options.document_format.unwrap_or(document.format);
+    let job_attributes = options.ipp_attributes();
+
+    // Attributes given as raw options are queried too
+    let mut attributes = capabilities::ATTRIBUTES.to_vec();
+    for (attribute, _) in &job_attributes {
+        if !attributes.contains(&attribute.as_str()) {
+            attributes.push(attribute);
+        }
+    }
+
+    let capabilities = get_supported_values(printer_name, &attributes);
+    let mut violations = capabilities::validate(&capabilities, options, format);
+
+    if network::is_network_uri(printer_name) {
+        return Ok(violations);
+    }
+
+    #[cfg(target_family = "unix")]
+    let response = unix::validate_job(printer_name, format, options);
+    #[cfg(target_family = "windows")]
+    let response = windows::validate_job(printer_name, format, options);
+
+    if let Some(response) = response {
+        for (attribute, value) in response? {
+            if !violations
+                .iter()
+                .any(|violation| violation.attribute == attribute)
+            {
+                violations.push(capabilities::violation(&capabilities, &attribute, &value));
+            }
+        }
+    }
+
+    Ok(violations)
 }
 
 /**
@@ -102,6 +507,10 @@ pub fn get_printer_by_name(name: &str) -> Option<printer::Printer> {
 }
 
 pub fn print_queue(printer_system_name: &str, myjobs: i32, whichjobs: i32) -> Vec<PrintJob> {
+    if network::is_network_uri(printer_system_name) {
+        return network::print_queue(printer_system_name);
+    }
+
     #[cfg(target_family = "unix")]
     return unix::print_queue(printer_system_name, myjobs, whichjobs);
 
@@ -110,6 +519,10 @@ pub fn print_queue(printer_system_name: &str, myjobs: i32, whichjobs: i32) -> Ve
 }
 
 pub fn cancel_job(printer_system_name: &str, job_id: i32) -> bool {
+    if network::is_network_uri(printer_system_name) {
+        return network::cancel_job(printer_system_name, job_id);
+    }
+
     #[cfg(target_family = "unix")]
     return unix::cancel_job(printer_system_name, job_id);
 
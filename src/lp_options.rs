@@ -0,0 +1,166 @@
+//! Option strings in the `lp -o` format, like "media=A4 sides=two-sided-long-edge fit-to-page",
+//! parsed as `cupsParseOptions` does
+//!
+//! ```rust
+//! use printers::lp_options;
+//! use printers::print_options::PrintOptions;
+//!
+//! let options = lp_options::parse("-o media=A4 -o job-name='Monthly report' nocollate");
+//! assert_eq!(options[1], ("job-name".to_string(), "Monthly report".to_string()));
+//! assert_eq!(options[2], ("collate".to_string(), "false".to_string()));
+//! assert_eq!(
+//!     lp_options::format(&options),
+//!     "media=A4 job-name=Monthly\\ report nocollate"
+//! );
+//!
+//! let options = PrintOptions::from_lp_options("media=A4 copies=2");
+//! assert_eq!(options.copies, Some(2));
+//! ```
+
+/**
+ * Parse an option string into (name, value) pairs
+ * Values are separated by whitespace and may be quoted with single or double quotes, a backslash
+ * escapes the next character and collection values in braces are kept as is.
+ * An option without value is a boolean: "fit-to-page" is true and "nofit-to-page" is false.
+ * The "-o" flags of the lp command line are skipped and a repeated option replaces the previous one.
+ */
+pub fn parse(text: &str) -> Vec<(String, String)> {
+    let mut options: Vec<(String, String)> = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    loop {
+        while chars.peek().map(|c| c.is_whitespace()) == Some(true) {
+            chars.next();
+        }
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut name = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() || c == '=' {
+                break;
+            }
+            name.push(c);
+            chars.next();
+        }
+
+        if name == "-o" {
+            continue;
+        }
+        let name = name.strip_prefix("-o").unwrap_or(&name).to_string();
+
+        let value = if chars.peek() == Some(&'=') {
+            chars.next();
+            parse_value(&mut chars)
+        } else if negated(&name) {
+            add(&mut options, &name[2..], "false");
+            continue;
+        } else {
+            "true".to_string()
+        };
+
+        if !name.is_empty() {
+            add(&mut options, &name, &value);
+        }
+    }
+
+    options
+}
+
+/**
+ * Format (name, value) pairs as an option string, the reverse of parse
+ * Boolean options are written as "name" and "noname", special characters are escaped.
+ */
+pub fn format(options: &[(String, String)]) -> String {
+    let formatted: Vec<String> = options
+        .iter()
+        .map(|(name, value)| match value.as_str() {
+            "true" if !negated(name) && !name.is_empty() => name.clone(),
+            "false" => format!("no{}", name),
+            _ if value.is_empty() => format!("{}=''", name),
+            _ if value.starts_with('{') && value.ends_with('}') => {
+                format!("{}={}", name, value)
+            }
+            _ => format!("{}={}", name, escape(value)),
+        })
+        .collect();
+
+    formatted.join(" ")
+}
+
+/**
+ * Parse a value up to the next whitespace outside quotes and braces
+ */
+fn parse_value(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut value = String::new();
+    let mut quote: Option<char> = None;
+    let mut depth = 0;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    if depth > 0 {
+                        value.push(c);
+                    }
+                    value.push(escaped);
+                }
+            }
+            '\'' | '"' if depth == 0 => match quote {
+                Some(open) if open == c => quote = None,
+                Some(_) => value.push(c),
+                None => quote = Some(c),
+            },
+            '{' if quote.is_none() => {
+                depth += 1;
+                value.push(c);
+            }
+            '}' if quote.is_none() && depth > 0 => {
+                depth -= 1;
+                value.push(c);
+            }
+            c if c.is_whitespace() && quote.is_none() && depth == 0 => break,
+            c => value.push(c),
+        }
+    }
+
+    value
+}
+
+/**
+ * Returns whether the name of an option without value is a negated boolean, like "nocollate"
+ */
+fn negated(name: &str) -> bool {
+    match name.get(..2) {
+        Some(prefix) => name.len() > 2 && prefix.eq_ignore_ascii_case("no"),
+        None => false,
+    }
+}
+
+/**
+ * Escape the whitespace, quotes and backslashes of a value with backslashes
+ */
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c.is_whitespace() || c == '\'' || c == '"' || c == '\\' || c == '{' {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/**
+ * Add an option, replacing the value of a previous option with the same name (case insensitive)
+ */
+pub(crate) fn add(options: &mut Vec<(String, String)>, name: &str, value: &str) {
+    match options
+        .iter_mut()
+        .find(|(option, _)| option.eq_ignore_ascii_case(name))
+    {
+        Some(option) => option.1 = value.to_string(),
+        None => options.push((name.to_string(), value.to_string())),
+    }
+}
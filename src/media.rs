@@ -0,0 +1,387 @@
+//! Media sizes with PWG 5101.1 self-describing names, like "iso_a4_210x297mm" or "na_letter_8.5x11in"
+//!
+//! ```rust
+//! use printers::media::{MediaSize, Unit};
+//!
+//! let a4 = MediaSize::parse("A4").unwrap();
+//! assert_eq!(a4.name, "iso_a4_210x297mm");
+//! assert_eq!(a4.width_in(Unit::Millimeters), 210.0);
+//!
+//! let label = MediaSize::custom(4.0, 6.0, Unit::Inches);
+//! assert_eq!(label.name, "custom_4x6in_4x6in");
+//! ```
+
+use crate::pdf::PageSize;
+use std::fmt;
+
+/**
+ * Standard media sizes as PWG self-describing name and PPD (Adobe) name
+ */
+const STANDARD: [(&str, &str); 91] = [
+    // ISO A
+    ("iso_a0_841x1189mm", "A0"),
+    ("iso_a1_594x841mm", "A1"),
+    ("iso_a2_420x594mm", "A2"),
+    ("iso_a3_297x420mm", "A3"),
+    ("iso_a4_210x297mm", "A4"),
+    ("iso_a5_148x210mm", "A5"),
+    ("iso_a6_105x148mm", "A6"),
+    ("iso_a7_74x105mm", "A7"),
+    ("iso_a8_52x74mm", "A8"),
+    ("iso_a9_37x52mm", "A9"),
+    ("iso_a10_26x37mm", "A10"),
+    ("iso_ra3_305x430mm", "RA3"),
+    ("iso_ra4_215x305mm", "RA4"),
+    ("iso_sra3_320x450mm", "SRA3"),
+    ("iso_sra4_225x320mm", "SRA4"),
+    // ISO B
+    ("iso_b0_1000x1414mm", "ISOB0"),
+    ("iso_b1_707x1000mm", "ISOB1"),
+    ("iso_b2_500x707mm", "ISOB2"),
+    ("iso_b3_353x500mm", "ISOB3"),
+    ("iso_b4_250x353mm", "ISOB4"),
+    ("iso_b5_176x250mm", "ISOB5"),
+    ("iso_b6_125x176mm", "ISOB6"),
+    ("iso_b7_88x125mm", "ISOB7"),
+    ("iso_b8_62x88mm", "ISOB8"),
+    ("iso_b9_44x62mm", "ISOB9"),
+    ("iso_b10_31x44mm", "ISOB10"),
+    // ISO C and envelopes
+    ("iso_c0_917x1297mm", "EnvC0"),
+    ("iso_c1_648x917mm", "EnvC1"),
+    ("iso_c2_458x648mm", "EnvC2"),
+    ("iso_c3_324x458mm", "EnvC3"),
+    ("iso_c4_229x324mm", "EnvC4"),
+    ("iso_c5_162x229mm", "EnvC5"),
+    ("iso_c6_114x162mm", "EnvC6"),
+    ("iso_c6c5_114x229mm", "EnvC65"),
+    ("iso_c7_81x114mm", "EnvC7"),
+    ("iso_dl_110x220mm", "EnvDL"),
+    ("iso_b4-envelope_250x353mm", "EnvISOB4"),
+    ("iso_b5-envelope_176x250mm", "EnvISOB5"),
+    ("iso_b6-envelope_125x176mm", "EnvISOB6"),
+    // JIS B
+    ("jis_b0_1030x1456mm", "B0"),
+    ("jis_b1_728x1030mm", "B1"),
+    ("jis_b2_515x728mm", "B2"),
+    ("jis_b3_364x515mm", "B3"),
+    ("jis_b4_257x364mm", "B4"),
+    ("jis_b5_182x257mm", "B5"),
+    ("jis_b6_128x182mm", "B6"),
+    ("jis_b7_91x128mm", "B7"),
+    ("jis_b8_64x91mm", "B8"),
+    ("jis_b9_45x64mm", "B9"),
+    ("jis_b10_32x45mm", "B10"),
+    // Japanese postcards and envelopes
+    ("jpn_hagaki_100x148mm", "Postcard"),
+    ("jpn_oufuku_148x200mm", "DoublePostcardRotated"),
+    ("jpn_chou2_111.1x146mm", "EnvChou2"),
+    ("jpn_chou3_120x235mm", "EnvChou3"),
+    ("jpn_chou4_90x205mm", "EnvChou4"),
+    ("jpn_kaku2_240x332mm", "EnvKaku2"),
+    ("jpn_kaku3_216x277mm", "EnvKaku3"),
+    ("jpn_you4_105x235mm", "EnvYou4"),
+    // North American
+    ("na_letter_8.5x11in", "Letter"),
+    ("na_legal_8.5x14in", "Legal"),
+    ("na_executive_7.25x10.5in", "Executive"),
+    ("na_ledger_11x17in", "Tabloid"),
+    ("na_invoice_5.5x8.5in", "Statement"),
+    ("na_foolscap_8.5x13in", "FanFoldGermanLegal"),
+    ("na_oficio_8.5x13.4in", "Oficio"),
+    ("na_super-b_13x19in", "SuperB"),
+    ("na_arch-a_9x12in", "ARCHA"),
+    ("na_arch-b_12x18in", "ARCHB"),
+    ("na_arch-c_18x24in", "ARCHC"),
+    ("na_arch-d_24x36in", "ARCHD"),
+    ("na_arch-e_36x48in", "ARCHE"),
+    ("na_c_17x22in", "AnsiC"),
+    ("na_d_22x34in", "AnsiD"),
+    ("na_e_34x44in", "AnsiE"),
+    // North American envelopes
+    ("na_number-9_3.875x8.875in", "Env9"),
+    ("na_number-10_4.125x9.5in", "Env10"),
+    ("na_number-11_4.5x10.375in", "Env11"),
+    ("na_number-12_4.75x11in", "Env12"),
+    ("na_number-14_5x11.5in", "Env14"),
+    ("na_monarch_3.875x7.5in", "EnvMonarch"),
+    ("na_personal_3.625x6.5in", "EnvPersonal"),
+    ("na_a2_4.375x5.75in", "EnvA2"),
+    ("na_6x9_6x9in", "Env6x9"),
+    ("na_10x13_10x13in", "Env10x13"),
+    // Photo and index cards
+    ("na_index-3x5_3x5in", "3x5"),
+    ("na_index-4x6_4x6in", "4x6"),
+    ("na_5x7_5x7in", "5x7"),
+    ("na_index-5x8_5x8in", "5x8"),
+    ("na_govt-letter_8x10in", "8x10"),
+    ("oe_photo-l_3.5x5in", "3.5x5"),
+    ("om_small-photo_100x150mm", "100x150mm"),
+];
+
+/**
+ * Enum of the units of media dimensions
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Millimeters,
+    Inches,
+
+    /**
+     * PostScript points (1/72 inch)
+     */
+    Points,
+
+    /**
+     * Hundredths of millimeters, as on the IPP media-size collection
+     */
+    Hundredths,
+}
+
+impl Unit {
+    /**
+     * Returns the number of hundredths of millimeters of one unit
+     */
+    fn hundredths(&self) -> f64 {
+        match self {
+            Unit::Millimeters => 100.0,
+            Unit::Inches => 2540.0,
+            Unit::Points => 2540.0 / 72.0,
+            Unit::Hundredths => 1.0,
+        }
+    }
+
+    /**
+     * Convert a length from this unit to another unit
+     */
+    pub fn convert(&self, value: f64, to: Unit) -> f64 {
+        value * self.hundredths() / to.hundredths()
+    }
+}
+
+/**
+ * MediaSize is a media size with its PWG self-describing name
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaSize {
+    /**
+     * PWG self-describing name, like "iso_a4_210x297mm"
+     */
+    pub name: String,
+
+    /**
+     * Width in hundredths of millimeters
+     */
+    pub width: u32,
+
+    /**
+     * Height in hundredths of millimeters
+     */
+    pub height: u32,
+}
+
+impl MediaSize {
+    /**
+     * Parse a media size from a PWG self-describing name ("iso_a4_210x297mm", "custom_label_100x150mm"),
+     * or from the legacy name of a standard size ("A4", "Letter", "Env10" or "na_letter")
+     */
+    pub fn parse(name: &str) -> Result<MediaSize, String> {
+        let name = name.trim();
+
+        if let Some((width, height)) = self_describing_size(name) {
+            return Ok(MediaSize {
+                name: name.to_string(),
+                width,
+                height,
+            });
+        }
+
+        // PPD names, then the class and media name without size ("na_letter") or the media name only ("letter")
+        let lowercase = name.to_ascii_lowercase();
+        STANDARD
+            .iter()
+            .find(|(_, ppd)| ppd.eq_ignore_ascii_case(name))
+            .or_else(|| {
+                STANDARD.iter().find(|(pwg, _)| {
+                    let (class, media) = class_and_media(pwg);
+                    lowercase == media || lowercase == format!("{}_{}", class, media)
+                })
+            })
+            .map(|(pwg, _)| MediaSize::from_standard(pwg))
+            .ok_or_else(|| format!("unknown media size {}", name))
+    }
+
+    /**
+     * Create a custom media size, named as a PWG custom size ("custom_100x150mm_100x150mm")
+     * Sizes given in inches are named in inches, the others in millimeters
+     */
+    pub fn custom(width: f64, height: f64, unit: Unit) -> MediaSize {
+        let (name_unit, suffix, decimals) = match unit {
+            Unit::Inches => (Unit::Inches, "in", 4),
+            _ => (Unit::Millimeters, "mm", 2),
+        };
+
+        let size = format!(
+            "{}x{}{}",
+            trimmed(unit.convert(width, name_unit), decimals),
+            trimmed(unit.convert(height, name_unit), decimals),
+            suffix
+        );
+
+        MediaSize {
+            name: format!("custom_{}_{}", size, size),
+            width: unit.convert(width, Unit::Hundredths).round() as u32,
+            height: unit.convert(height, Unit::Hundredths).round() as u32,
+        }
+    }
+
+    /**
+     * Returns the standard media sizes (ISO A/B/C, JIS B, North American, envelopes and photo sizes)
+     */
+    pub fn standard() -> Vec<MediaSize> {
+        STANDARD
+            .iter()
+            .map(|(pwg, _)| MediaSize::from_standard(pwg))
+            .collect()
+    }
+
+    fn from_standard(name: &str) -> MediaSize {
+        let (width, height) = self_describing_size(name).unwrap_or_default();
+        MediaSize {
+            name: name.to_string(),
+            width,
+            height,
+        }
+    }
+
+    /**
+     * Returns the width in a unit
+     */
+    pub fn width_in(&self, unit: Unit) -> f64 {
+        Unit::Hundredths.convert(self.width as f64, unit)
+    }
+
+    /**
+     * Returns the height in a unit
+     */
+    pub fn height_in(&self, unit: Unit) -> f64 {
+        Unit::Hundredths.convert(self.height as f64, unit)
+    }
+
+    /**
+     * Returns the page size in points, used by the document renderers
+     */
+    pub fn page_size(&self) -> PageSize {
+        PageSize::new(self.width_in(Unit::Points), self.height_in(Unit::Points))
+    }
+
+    /**
+     * Returns if the size is a PWG custom size
+     */
+    pub fn is_custom(&self) -> bool {
+        self.name.starts_with("custom_")
+    }
+
+    /**
+     * Returns the PPD name of the size ("A4", "Letter"), custom sizes are like "Custom.100x150mm"
+     */
+    pub fn ppd_name(&self) -> String {
+        let standard = STANDARD
+            .iter()
+            .find(|(pwg, _)| *pwg == self.name)
+            .or_else(|| {
+                STANDARD
+                    .iter()
+                    .find(|(pwg, _)| MediaSize::from_standard(pwg).same_size(self))
+            });
+
+        match standard {
+            Some((_, ppd)) => ppd.to_string(),
+            None => format!(
+                "Custom.{}x{}mm",
+                trimmed(self.width_in(Unit::Millimeters), 2),
+                trimmed(self.height_in(Unit::Millimeters), 2)
+            ),
+        }
+    }
+
+    /**
+     * Returns if both sizes are equal, with the PWG tolerance of 0.5 millimeters on each dimension
+     */
+    pub fn same_size(&self, other: &MediaSize) -> bool {
+        (self.width as i64 - other.width as i64).abs() <= 50
+            && (self.height as i64 - other.height as i64).abs() <= 50
+    }
+
+    /**
+     * Returns the candidate nearest to this size (like the media supported by a printer),
+     * sizes large enough to hold this size are preferred
+     */
+    pub fn closest<'a>(&self, candidates: &'a [MediaSize]) -> Option<&'a MediaSize> {
+        let distance = |media: &MediaSize| {
+            (media.width as i64 - self.width as i64).abs()
+                + (media.height as i64 - self.height as i64).abs()
+        };
+        let holds =
+            |media: &MediaSize| media.width + 50 >= self.width && media.height + 50 >= self.height;
+
+        candidates
+            .iter()
+            .min_by_key(|media| (!holds(media), distance(media)))
+    }
+}
+
+impl fmt::Display for MediaSize {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{}", self.name)
+    }
+}
+
+/**
+ * Returns the class and media name of a self-describing name ("na" and "letter" for "na_letter_8.5x11in")
+ */
+fn class_and_media(name: &str) -> (&str, &str) {
+    let mut parts = name.splitn(3, '_');
+    (parts.next().unwrap_or(""), parts.next().unwrap_or(""))
+}
+
+/**
+ * Returns the size in hundredths of millimeters of a self-describing name, the size is the last part
+ * ("210x297mm" or "8.5x11in")
+ */
+fn self_describing_size(name: &str) -> Option<(u32, u32)> {
+    let parts: Vec<&str> = name.split('_').collect();
+    if parts.len() < 3 || parts.iter().any(|part| part.is_empty()) {
+        return None;
+    }
+
+    let size = parts[parts.len() - 1];
+    let (size, unit) = match size.strip_suffix("mm") {
+        Some(size) => (size, Unit::Millimeters),
+        None => (size.strip_suffix("in")?, Unit::Inches),
+    };
+
+    let (width, height) = size.split_once('x')?;
+    let width: f64 = width.parse().ok()?;
+    let height: f64 = height.parse().ok()?;
+    if width <= 0.0 || height <= 0.0 {
+        return None;
+    }
+
+    Some((
+        unit.convert(width, Unit::Hundredths).round() as u32,
+        unit.convert(height, Unit::Hundredths).round() as u32,
+    ))
+}
+
+/**
+ * Returns a number with at most the given decimals and no trailing zeros
+ */
+fn trimmed(value: f64, decimals: usize) -> String {
+    let text = format!("{:.*}", decimals, value);
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        text
+    }
+}
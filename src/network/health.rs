@@ -0,0 +1,488 @@
+use crate::device_uri::DeviceUri;
+use crate::ipp::{self, PrinterAttributes};
+use crate::printer::PrinterState;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/**
+ * Printer attributes asked to IPP printers for the health check
+ */
+const REQUESTED_ATTRIBUTES: [&str; 4] = [
+    "printer-state",
+    "printer-state-reasons",
+    "printer-state-message",
+    "printer-is-accepting-jobs",
+];
+
+/**
+ * Maximal size of the HTTP response to the Get-Printer-Attributes request
+ */
+const MAX_RESPONSE_SIZE: usize = 1 << 20;
+
+/**
+ * Diagnosis of a printer health check
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum Diagnosis {
+    /**
+     * The printer is reachable and accepts jobs
+     */
+    Healthy,
+
+    /**
+     * The host name of the printer can not be resolved
+     */
+    DnsFailure(String),
+
+    /**
+     * The host refused the connection (nothing listens on the printer port)
+     */
+    ConnectionRefused,
+
+    /**
+     * The printer did not answer before the timeout
+     */
+    TimedOut,
+
+    /**
+     * Any other connection error (like an unreachable network)
+     */
+    ConnectionFailed(String),
+
+    /**
+     * The TLS handshake with an ipps:// printer failed
+     */
+    TlsError(String),
+
+    /**
+     * The printer answered the Get-Printer-Attributes request with an error (or not with IPP)
+     */
+    IppError(String),
+
+    /**
+     * The printer is stopped (printer-state is stopped), with its printer-state-message
+     */
+    PrinterStopped(String),
+
+    /**
+     * The printer does not accept new jobs (printer-is-accepting-jobs is false)
+     */
+    NotAcceptingJobs,
+
+    /**
+     * The device URI can not be checked over the network (like usb:// or file:// devices) and no print
+     * queue state is known
+     */
+    UnsupportedUri(String),
+}
+
+/**
+ * Result of a printer health check
+ */
+#[derive(Debug, Clone)]
+pub struct HealthCheck {
+    /**
+     * The printer answered on its port
+     */
+    pub reachable: bool,
+
+    /**
+     * Time taken by the printer to answer, once its host name is resolved: the TCP connection for
+     * socket:// and lpd:// printers, the whole Get-Printer-Attributes request for ipp:// and ipps:// printers
+     */
+    pub latency: Option<Duration>,
+    pub diagnosis: Diagnosis,
+
+    /**
+     * State reported by IPP printers or by the print queue (unknown for the other ones)
+     */
+    pub state: PrinterState,
+    pub state_reasons: Vec<String>,
+    pub accepting_jobs: Option<bool>,
+}
+
+impl HealthCheck {
+    fn new(diagnosis: Diagnosis) -> HealthCheck {
+        HealthCheck {
+            reachable: false,
+            latency: None,
+            diagnosis,
+            state: PrinterState::UNKNOWN,
+            state_reasons: vec![],
+            accepting_jobs: None,
+        }
+    }
+
+    /**
+     * Returns if the printer is reachable and accepts jobs
+     */
+    pub fn is_healthy(&self) -> bool {
+        self.diagnosis == Diagnosis::Healthy
+    }
+}
+
+/**
+ * Check if the printer of a device URI is reachable, before sending jobs to it
+ * socket:// and lpd:// printers are checked with a TCP connection, ipp:// and ipps:// printers with a
+ * Get-Printer-Attributes request, looking at their state and printer-is-accepting-jobs
+ * The host name resolution and the whole check are limited by the timeout
+ */
+pub fn check(uri: &str, timeout: Duration) -> HealthCheck {
+    let device_uri = DeviceUri::parse(uri);
+    let (host, port) = match (device_uri.host(), device_uri.port()) {
+        (Some(host), Some(port)) => (host.to_string(), port),
+        _ => return HealthCheck::new(Diagnosis::UnsupportedUri(uri.to_string())),
+    };
+
+    let deadline = Instant::now() + timeout;
+    let address = match resolve(&host, port, timeout) {
+        Ok(address) => address,
+        Err(diagnosis) => return HealthCheck::new(diagnosis),
+    };
+
+    let started = Instant::now();
+    let stream = match TcpStream::connect_timeout(&address, remaining(deadline)) {
+        Ok(stream) => stream,
+        Err(error) => return HealthCheck::new(connection_diagnosis(&error)),
+    };
+
+    let mut health = HealthCheck::new(Diagnosis::Healthy);
+    health.reachable = true;
+    health.latency = Some(started.elapsed());
+
+    let printer_uri = uri.split('?').next().unwrap_or(uri);
+    let attributes = match device_uri {
+        DeviceUri::Ipp { ref path, .. } => {
+            match get_printer_attributes(stream, &host, port, path, printer_uri, deadline) {
+                Ok(attributes) => attributes,
+                Err(diagnosis) => {
+                    health.diagnosis = diagnosis;
+                    return health;
+                }
+            }
+        }
+        DeviceUri::Ipps { .. } => {
+            drop(stream);
+            match get_printer_attributes_tls(&host, port, printer_uri, remaining(deadline)) {
+                Ok(attributes) => attributes,
+                Err(diagnosis) => {
+                    health.diagnosis = diagnosis;
+                    return health;
+                }
+            }
+        }
+        _ => return health,
+    };
+
+    health.latency = Some(started.elapsed());
+    read_state(&mut health, &attributes);
+    health
+}
+
+/**
+ * Check a print queue and the printer of its device URI, like `check`, then fold in the printer-state,
+ * printer-state-reasons and printer-is-accepting-jobs attributes of the queue: a stopped queue or a
+ * queue rejecting jobs is reported even when the printer itself is healthy
+ * Devices that can not be checked over the network (like usb:// and dnssd:// ones) are diagnosed from
+ * the queue state alone, reported unreachable when the queue says the printer is offline
+ */
+pub fn check_queue(uri: &str, queue: &PrinterAttributes, timeout: Duration) -> HealthCheck {
+    let device_uri = DeviceUri::parse(uri);
+    let mut health = if device_uri.host().is_some() && device_uri.port().is_some() {
+        check(uri, timeout)
+    } else if queue.first("printer-state").is_none() {
+        return HealthCheck::new(Diagnosis::UnsupportedUri(uri.to_string()));
+    } else if queue
+        .get("printer-state-reasons")
+        .iter()
+        .any(|reason| reason.starts_with("offline"))
+    {
+        HealthCheck::new(Diagnosis::ConnectionFailed(
+            "the print queue reports the printer offline".to_string(),
+        ))
+    } else {
+        let mut health = HealthCheck::new(Diagnosis::Healthy);
+        health.reachable = true;
+        health
+    };
+
+    let mut queue_health = HealthCheck::new(Diagnosis::Healthy);
+    read_state(&mut queue_health, queue);
+
+    if health.state == PrinterState::UNKNOWN || queue_health.state == PrinterState::PAUSED {
+        health.state = queue_health.state;
+    }
+    for reason in queue_health.state_reasons {
+        if !health.state_reasons.contains(&reason) {
+            health.state_reasons.push(reason);
+        }
+    }
+    if health.accepting_jobs.is_none() || queue_health.accepting_jobs == Some(false) {
+        health.accepting_jobs = queue_health.accepting_jobs;
+    }
+    if health.diagnosis == Diagnosis::Healthy {
+        health.diagnosis = queue_health.diagnosis;
+    }
+
+    health
+}
+
+/**
+ * Read the printer-state, printer-state-reasons and printer-is-accepting-jobs attributes into the
+ * health check, diagnosing stopped printers and printers that do not accept jobs
+ */
+fn read_state(health: &mut HealthCheck, attributes: &PrinterAttributes) {
+    health.state = match attributes.first("printer-state") {
+        Some("3") => PrinterState::READY,
+        Some("4") => PrinterState::PRINTING,
+        Some("5") => PrinterState::PAUSED,
+        _ => PrinterState::UNKNOWN,
+    };
+    health.state_reasons = attributes
+        .get("printer-state-reasons")
+        .iter()
+        .filter(|reason| reason.as_str() != "none")
+        .cloned()
+        .collect();
+    health.accepting_jobs = match attributes.first("printer-is-accepting-jobs") {
+        Some("true") => Some(true),
+        Some("false") => Some(false),
+        _ => None,
+    };
+
+    if health.state == PrinterState::PAUSED {
+        let message = attributes.first("printer-state-message").unwrap_or("");
+        health.diagnosis = Diagnosis::PrinterStopped(message.to_string());
+    } else if health.accepting_jobs == Some(false) {
+        health.diagnosis = Diagnosis::NotAcceptingJobs;
+    }
+}
+
+/**
+ * Returns the time left before the deadline (at least a millisecond, zero timeouts are invalid)
+ */
+fn remaining(deadline: Instant) -> Duration {
+    deadline
+        .saturating_duration_since(Instant::now())
+        .max(Duration::from_millis(1))
+}
+
+/**
+ * Resolve the first address of the host before the timeout
+ * The system resolver can not be cancelled: after a timeout, its thread ends when the resolution does
+ */
+fn resolve(host: &str, port: u16, timeout: Duration) -> Result<SocketAddr, Diagnosis> {
+    let address = host.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(address) = address.parse::<IpAddr>() {
+        return Ok(SocketAddr::new(address, port));
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let name = host.to_string();
+    thread::spawn(move || {
+        let result = (name.as_str(), port)
+            .to_socket_addrs()
+            .map(|mut addresses| addresses.next());
+        let _ = sender.send(result);
+    });
+
+    match receiver.recv_timeout(timeout) {
+        Ok(Ok(Some(address))) => Ok(address),
+        Ok(Ok(None)) => Err(Diagnosis::DnsFailure(format!(
+            "no address found for host {}",
+            host
+        ))),
+        Ok(Err(error)) => Err(Diagnosis::DnsFailure(format!(
+            "failed to resolve {}: {}",
+            host, error
+        ))),
+        Err(_) => Err(Diagnosis::DnsFailure(format!(
+            "timed out resolving {}",
+            host
+        ))),
+    }
+}
+
+fn connection_diagnosis(error: &std::io::Error) -> Diagnosis {
+    match error.kind() {
+        ErrorKind::ConnectionRefused => Diagnosis::ConnectionRefused,
+        ErrorKind::TimedOut | ErrorKind::WouldBlock => Diagnosis::TimedOut,
+        _ => Diagnosis::ConnectionFailed(error.to_string()),
+    }
+}
+
+/**
+ * Send a Get-Printer-Attributes request over HTTP to an ipp:// printer and decode its response
+ */
+fn get_printer_attributes(
+    mut stream: TcpStream,
+    host: &str,
+    port: u16,
+    path: &str,
+    printer_uri: &str,
+    deadline: Instant,
+) -> Result<PrinterAttributes, Diagnosis> {
+    let request = ipp::get_printer_attributes_request(printer_uri, &REQUESTED_ATTRIBUTES);
+    let mut message = format!(
+        "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/ipp\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        if path.is_empty() { "/" } else { path },
+        host,
+        port,
+        request.len()
+    )
+    .into_bytes();
+    message.extend(request);
+
+    stream
+        .set_write_timeout(Some(remaining(deadline)))
+        .and_then(|_| stream.write_all(&message))
+        .map_err(|error| connection_diagnosis(&error))?;
+
+    let body = read_response(&mut stream, deadline)?;
+    PrinterAttributes::from_response(&body).map_err(Diagnosis::IppError)
+}
+
+/**
+ * Read an HTTP response up to its end (its Content-Length, its last chunk or the connection close)
+ * and returns its body, so printers keeping the connection open are not waited for
+ */
+fn read_response(stream: &mut TcpStream, deadline: Instant) -> Result<Vec<u8>, Diagnosis> {
+    let mut response = Vec::new();
+    let mut buffer = [0; 4096];
+
+    loop {
+        if Instant::now() >= deadline {
+            return Err(Diagnosis::TimedOut);
+        }
+
+        let length = stream
+            .set_read_timeout(Some(remaining(deadline)))
+            .and_then(|_| stream.read(&mut buffer))
+            .map_err(|error| connection_diagnosis(&error))?;
+        response.extend_from_slice(&buffer[..length]);
+
+        if let Some(body) = http_body(&response, length == 0).map_err(Diagnosis::IppError)? {
+            return Ok(body);
+        }
+        if response.len() > MAX_RESPONSE_SIZE {
+            return Err(Diagnosis::IppError(format!(
+                "the printer response is larger than {} bytes",
+                MAX_RESPONSE_SIZE
+            )));
+        }
+    }
+}
+
+/**
+ * Send a Get-Printer-Attributes request to an ipps:// printer with the print system TLS support
+ */
+fn get_printer_attributes_tls(
+    host: &str,
+    port: u16,
+    printer_uri: &str,
+    timeout: Duration,
+) -> Result<PrinterAttributes, Diagnosis> {
+    let timeout_ms = timeout.as_millis().clamp(1, i32::MAX as u128) as i32;
+
+    #[cfg(target_family = "unix")]
+    let result = crate::unix::get_printer_attributes_tls(
+        host,
+        port,
+        printer_uri,
+        &REQUESTED_ATTRIBUTES,
+        timeout_ms,
+    );
+    #[cfg(target_family = "windows")]
+    let result = crate::windows::get_printer_attributes_tls(
+        host,
+        port,
+        printer_uri,
+        &REQUESTED_ATTRIBUTES,
+        timeout_ms,
+    );
+
+    let values = result
+        .map_err(Diagnosis::TlsError)?
+        .map_err(Diagnosis::IppError)?;
+
+    let mut attributes = PrinterAttributes::default();
+    for (name, values) in &values {
+        let values: Vec<&str> = values.iter().map(String::as_str).collect();
+        attributes.set(name, &values);
+    }
+    Ok(attributes)
+}
+
+/**
+ * Returns the body of an HTTP response (with a chunked transfer encoding or not), or None when more
+ * data is needed and the connection is not closed yet
+ */
+fn http_body(response: &[u8], closed: bool) -> Result<Option<Vec<u8>>, String> {
+    let incomplete = || {
+        if closed {
+            Err("the printer closed the connection before the end of its response".to_string())
+        } else {
+            Ok(None)
+        }
+    };
+
+    let header_end = match response.windows(4).position(|window| window == b"\r\n\r\n") {
+        Some(header_end) => header_end,
+        None => return incomplete(),
+    };
+    let header = String::from_utf8_lossy(&response[..header_end]);
+    let body = &response[header_end + 4..];
+
+    let status = header.lines().next().unwrap_or("");
+    if status.split_whitespace().nth(1) != Some("200") {
+        return Err(format!("the printer answered {}", status.trim()));
+    }
+
+    let header_value = |name: &str| {
+        header
+            .lines()
+            .skip(1)
+            .find_map(|line| match line.split_once(':') {
+                Some((key, value)) if key.trim().eq_ignore_ascii_case(name) => Some(value.trim()),
+                _ => None,
+            })
+    };
+
+    let chunked = header_value("transfer-encoding")
+        .map(|value| value.to_ascii_lowercase().contains("chunked"))
+        .unwrap_or(false);
+    if !chunked {
+        return match header_value("content-length").map(str::parse::<usize>) {
+            Some(Ok(length)) if body.len() >= length => Ok(Some(body[..length].to_vec())),
+            Some(Ok(_)) => incomplete(),
+            Some(Err(_)) => Err("invalid HTTP Content-Length from the printer".to_string()),
+            None if closed => Ok(Some(body.to_vec())),
+            None => Ok(None),
+        };
+    }
+
+    let mut data = Vec::new();
+    let mut rest = body;
+    loop {
+        let line_end = match rest.windows(2).position(|window| window == b"\r\n") {
+            Some(line_end) => line_end,
+            None => return incomplete(),
+        };
+        let size_text = String::from_utf8_lossy(&rest[..line_end]);
+        let size = usize::from_str_radix(size_text.split(';').next().unwrap_or("").trim(), 16)
+            .map_err(|_| "invalid HTTP chunk from the printer".to_string())?;
+        rest = &rest[line_end + 2..];
+
+        if size == 0 {
+            return Ok(Some(data));
+        }
+        if rest.len() < size {
+            return incomplete();
+        }
+        data.extend_from_slice(&rest[..size]);
+        rest = rest.get(size + 2..).unwrap_or(&[]);
+    }
+}
@@ -0,0 +1,417 @@
+use crate::device_uri::DeviceUri;
+use crate::printer_job::{JobState, PrintJob};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+/**
+ * Counter of the jobs sent by this process, giving the job numbers (000 to 999)
+ */
+static NEXT_JOB_NUMBER: AtomicU32 = AtomicU32::new(0);
+
+/**
+ * Options of the LPD (lpd://) backend
+ * They can be given as query of the printer URI, as on the CUPS lpd backend:
+ * lpd://host/queue?format=l&banner=on&contimeout=10&timeout=60
+ */
+#[derive(Debug, Clone)]
+pub struct LpdOptions {
+    /**
+     * User name written on the control file (P) and used to remove jobs
+     */
+    pub user: String,
+
+    /**
+     * Host name written on the control file (H) and on the control/data file names
+     */
+    pub host: String,
+
+    /**
+     * Number of copies (the data file is listed once per copy on the control file)
+     */
+    pub copies: u32,
+
+    /**
+     * Print file format (l is raw data printed as is, f is formatted text...)
+     */
+    pub format: char,
+
+    /**
+     * Request a banner page (L) with the user name
+     */
+    pub banner: bool,
+
+    /**
+     * Time limit to open the connection with the server (contimeout)
+     */
+    pub connect_timeout: Duration,
+
+    /**
+     * Time limit of each write or read (timeout)
+     */
+    pub timeout: Duration,
+}
+
+impl Default for LpdOptions {
+    fn default() -> LpdOptions {
+        LpdOptions {
+            user: local_user_name(),
+            host: local_host_name(),
+            copies: 1,
+            format: 'l',
+            banner: false,
+            connect_timeout: Duration::from_secs(10),
+            timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+impl LpdOptions {
+    /**
+     * Read the options from the query of a lpd:// URI (missing options keep their default value)
+     */
+    pub fn from_uri(uri: &str) -> Result<LpdOptions, String> {
+        let mut options = LpdOptions::default();
+        let query = match uri.split_once('?') {
+            Some((_, query)) => query,
+            None => return Ok(options),
+        };
+
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, "true"));
+            let invalid = || format!("invalid value {:?} for lpd option {}", value, name);
+
+            match name.to_ascii_lowercase().as_str() {
+                "contimeout" => {
+                    options.connect_timeout =
+                        Duration::from_secs(value.parse().map_err(|_| invalid())?)
+                }
+                "timeout" => {
+                    options.timeout = Duration::from_secs(value.parse().map_err(|_| invalid())?)
+                }
+                "format" => {
+                    let mut chars = value.chars();
+                    options.format = match (chars.next(), chars.next()) {
+                        (Some(format), None) if "cdfglnoprtv".contains(format) => format,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "banner" => {
+                    options.banner = match value.to_ascii_lowercase().as_str() {
+                        "true" | "yes" | "on" | "1" => true,
+                        "false" | "no" | "off" | "0" => false,
+                        _ => return Err(invalid()),
+                    }
+                }
+                // Options of the CUPS backend without meaning here (like snmp) are ignored
+                _ => {}
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/**
+ * Returns the host, port and queue name of a lpd:// URI
+ */
+pub fn parse_address(uri: &str) -> Result<(String, u16, String), String> {
+    match DeviceUri::parse(uri) {
+        DeviceUri::Lpd { queue, .. } if queue.is_empty() => {
+            Err(format!("missing queue name on {}", uri))
+        }
+        DeviceUri::Lpd { host, port, queue } => Ok((host, port, queue)),
+        _ => Err(format!("{} is not a valid lpd:// URI", uri)),
+    }
+}
+
+/**
+ * Send a document to a LPD queue with the "receive a printer job" command
+ * Returns the job number given to the document
+ */
+pub fn print(
+    uri: &str,
+    buffer: &[u8],
+    job_name: Option<&str>,
+    options: &LpdOptions,
+) -> Result<u32, String> {
+    let (host, port, queue) = parse_address(uri)?;
+    // Jobs of other processes on the same host start from another number
+    let job_number =
+        std::process::id().wrapping_add(NEXT_JOB_NUMBER.fetch_add(1, Ordering::Relaxed)) % 1000;
+
+    let error = |error: std::io::Error| {
+        format!(
+            "failure on send document to {}:{}/{}: {}",
+            host, port, queue, error
+        )
+    };
+
+    let mut stream = connect(&host, port, options).map_err(error)?;
+
+    send_command(&mut stream, 0x02, &queue, &[]).map_err(error)?;
+    read_ack(&mut stream).map_err(error)?;
+
+    // Only 31 characters of the host name are allowed on file names, which can not contain spaces
+    let short_host: String = options
+        .host
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(31)
+        .collect();
+    let data_file_name = format!("dfA{:03}{}", job_number, short_host);
+    let control_file_name = format!("cfA{:03}{}", job_number, short_host);
+    let control_file = control_file(&data_file_name, job_name, options);
+
+    send_file(
+        &mut stream,
+        0x02,
+        &control_file_name,
+        control_file.as_bytes(),
+    )
+    .map_err(error)?;
+    send_file(&mut stream, 0x03, &data_file_name, buffer).map_err(error)?;
+
+    Ok(job_number)
+}
+
+/**
+ * Returns the text of the queue state reported by the server ("send queue state" command)
+ */
+pub fn queue_state(uri: &str, long: bool, options: &LpdOptions) -> Result<String, String> {
+    let (host, port, queue) = parse_address(uri)?;
+    let error = |error: std::io::Error| {
+        format!(
+            "failure on read queue state of {}:{}/{}: {}",
+            host, port, queue, error
+        )
+    };
+
+    let mut stream = connect(&host, port, options).map_err(error)?;
+    let command = if long { 0x04 } else { 0x03 };
+    send_command(&mut stream, command, &queue, &[]).map_err(error)?;
+
+    let mut state = Vec::new();
+    stream.read_to_end(&mut state).map_err(error)?;
+    Ok(String::from_utf8_lossy(&state).to_string())
+}
+
+/**
+ * Returns the jobs listed on the short queue state
+ */
+pub fn get_jobs(uri: &str, options: &LpdOptions) -> Result<Vec<PrintJob>, String> {
+    let (_, _, queue) = parse_address(uri)?;
+    let state = queue_state(uri, false, options)?;
+    Ok(parse_queue_state(&queue, &state))
+}
+
+/**
+ * Remove jobs from the queue with the "remove jobs" command (all jobs of the user when empty)
+ */
+pub fn remove_jobs(uri: &str, job_numbers: &[u32], options: &LpdOptions) -> Result<(), String> {
+    let (host, port, queue) = parse_address(uri)?;
+    let error = |error: std::io::Error| {
+        format!(
+            "failure on remove jobs of {}:{}/{}: {}",
+            host, port, queue, error
+        )
+    };
+
+    let mut operands = vec![options.user.clone()];
+    operands.extend(job_numbers.iter().map(|number| number.to_string()));
+
+    let mut stream = connect(&host, port, options).map_err(error)?;
+    send_command(&mut stream, 0x05, &queue, &operands).map_err(error)?;
+
+    // The server does not answer this command, it just close the connection
+    let mut answer = Vec::new();
+    stream.read_to_end(&mut answer).map_err(error)?;
+    Ok(())
+}
+
+/**
+ * Build the control file of a job
+ */
+fn control_file(data_file_name: &str, job_name: Option<&str>, options: &LpdOptions) -> String {
+    let job_name = job_name.unwrap_or(data_file_name);
+    let mut lines = vec![
+        format!("H{}", control_field(&options.host, 31)),
+        format!("P{}", control_field(&options.user, 31)),
+        format!("J{}", control_field(job_name, 99)),
+    ];
+
+    if options.banner {
+        lines.push(format!("C{}", control_field(&options.host, 99)));
+        lines.push(format!("L{}", control_field(&options.user, 99)));
+    }
+
+    for _ in 0..options.copies.max(1) {
+        lines.push(format!("{}{}", options.format, data_file_name));
+    }
+
+    lines.push(format!("U{}", data_file_name));
+    lines.push(format!("N{}", control_field(job_name, 99)));
+
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+/**
+ * Returns a value of a control file line without control characters (a line feed would start another
+ * command), truncated to the length allowed by RFC 1179
+ */
+fn control_field(value: &str, max_length: usize) -> String {
+    value
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(max_length)
+        .collect()
+}
+
+/**
+ * Parse the lines of a short queue state, like:
+ * Rank   Owner   Job  File(s)                   Total Size
+ * active root    12   report.pdf                2048 bytes
+ */
+fn parse_queue_state(queue: &str, state: &str) -> Vec<PrintJob> {
+    let mut jobs = Vec::new();
+
+    for line in state.lines() {
+        let columns: Vec<&str> = line.split_whitespace().collect();
+        if columns.len() < 5 || !columns[columns.len() - 1].starts_with("byte") {
+            continue;
+        }
+
+        let id = columns[2];
+        if id.parse::<u32>().is_err() {
+            continue;
+        }
+
+        let job_state = if columns[0] == "active" {
+            JobState::PROCESSING
+        } else {
+            JobState::PENDING
+        };
+
+        jobs.push(PrintJob {
+            id: id.to_string(),
+            dest: queue.to_string(),
+            title: columns[3..columns.len() - 2].join(" "),
+            user: columns[1].to_string(),
+            format: "".to_string(),
+            state: job_state,
+            size: columns[columns.len() - 2].to_string(),
+            priority: columns[0].to_string(),
+            completed_time: "".to_string(),
+            creation_time: "".to_string(),
+            processing_time: "".to_string(),
+        });
+    }
+
+    jobs
+}
+
+/**
+ * Connect to the first reachable address of the host
+ */
+fn connect(host: &str, port: u16, options: &LpdOptions) -> std::io::Result<TcpStream> {
+    let mut last_error = std::io::Error::new(
+        ErrorKind::NotFound,
+        format!("no address found for host {}", host),
+    );
+
+    for address in (host, port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&address, options.connect_timeout) {
+            Ok(stream) => {
+                stream.set_read_timeout(Some(options.timeout))?;
+                stream.set_write_timeout(Some(options.timeout))?;
+                return Ok(stream);
+            }
+            Err(error) => last_error = error,
+        }
+    }
+
+    Err(last_error)
+}
+
+/**
+ * Write a command line: code, queue name and operands separated by spaces
+ */
+fn send_command(
+    stream: &mut TcpStream,
+    code: u8,
+    queue: &str,
+    operands: &[String],
+) -> std::io::Result<()> {
+    let mut command = vec![code];
+    command.extend_from_slice(queue.as_bytes());
+    for operand in operands {
+        command.push(b' ');
+        command.extend_from_slice(operand.as_bytes());
+    }
+    command.push(b'\n');
+    stream.write_all(&command)
+}
+
+/**
+ * Send a control (0x02) or data (0x03) file of the job being received by the server
+ */
+fn send_file(stream: &mut TcpStream, code: u8, name: &str, content: &[u8]) -> std::io::Result<()> {
+    let command = format!("{}{} {}\n", code as char, content.len(), name);
+    stream.write_all(command.as_bytes())?;
+    read_ack(stream)?;
+
+    stream.write_all(content)?;
+    stream.write_all(&[0])?;
+    read_ack(stream)
+}
+
+/**
+ * Read the acknowledgement byte sent by the server (zero on success)
+ */
+fn read_ack(stream: &mut TcpStream) -> std::io::Result<()> {
+    let mut ack = [0; 1];
+    stream.read_exact(&mut ack)?;
+
+    if ack[0] != 0 {
+        return Err(std::io::Error::other(format!(
+            "the server refused the command (code {})",
+            ack[0]
+        )));
+    }
+
+    Ok(())
+}
+
+/**
+ * Returns the name of the user running the process
+ */
+fn local_user_name() -> String {
+    ["USER", "USERNAME", "LOGNAME"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+        .unwrap_or_else(|| "printers".to_string())
+}
+
+/**
+ * Returns the name of the local host
+ */
+fn local_host_name() -> String {
+    #[cfg(target_family = "unix")]
+    {
+        let mut buffer = [0u8; 256];
+        let result =
+            unsafe { libc::gethostname(buffer.as_mut_ptr() as *mut libc::c_char, buffer.len()) };
+
+        if result == 0 {
+            let length = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+            if length > 0 {
+                return String::from_utf8_lossy(&buffer[..length]).to_string();
+            }
+        }
+    }
+
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| "localhost".to_string())
+}
@@ -0,0 +1,70 @@
+use crate::print_options::PrintOptions;
+use crate::printer_job::PrintJob;
+
+pub mod health;
+pub mod lpd;
+pub mod snmp;
+pub mod socket;
+
+/**
+ * Returns if the printer name is an URI printed directly by the lib network backends
+ * (without the system print service)
+ */
+pub fn is_network_uri(printer_name: &str) -> bool {
+    printer_name.starts_with("socket://") || printer_name.starts_with("lpd://")
+}
+
+/**
+ * Print a buffer on a network printer addressed by URI
+ * Returns the job number given by LPD servers (socket:// printers have no job numbers)
+ */
+pub fn print(
+    uri: &str,
+    buffer: &[u8],
+    job_name: Option<&str>,
+    options: &PrintOptions,
+) -> Result<Option<u32>, String> {
+    let copies = options.copies.unwrap_or(1).max(1);
+
+    if uri.starts_with("socket://") {
+        let socket_options = socket::SocketOptions::from_uri(uri)?;
+
+        // As on the CUPS socket backend, copies are made sending the document again
+        for _ in 0..copies {
+            socket::print(uri, buffer, &socket_options)?;
+        }
+        return Ok(None);
+    }
+
+    if uri.starts_with("lpd://") {
+        let mut lpd_options = lpd::LpdOptions::from_uri(uri)?;
+        lpd_options.copies = copies;
+        return lpd::print(uri, buffer, job_name, &lpd_options).map(Some);
+    }
+
+    Err(format!("unsupported printer URI {}", uri))
+}
+
+/**
+ * Get the jobs on the queue of a network printer (only LPD servers report their queue)
+ */
+pub fn print_queue(uri: &str) -> Vec<PrintJob> {
+    if uri.starts_with("lpd://") {
+        let options = lpd::LpdOptions::from_uri(uri).unwrap_or_default();
+        return lpd::get_jobs(uri, &options).unwrap_or_default();
+    }
+
+    vec![]
+}
+
+/**
+ * Cancel a job on the queue of a network printer (only LPD servers can remove jobs)
+ */
+pub fn cancel_job(uri: &str, job_id: i32) -> bool {
+    if uri.starts_with("lpd://") && job_id >= 0 {
+        let options = lpd::LpdOptions::from_uri(uri).unwrap_or_default();
+        return lpd::remove_jobs(uri, &[job_id as u32], &options).is_ok();
+    }
+
+    false
+}
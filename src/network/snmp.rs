@@ -0,0 +1,1006 @@
+use crate::device_uri::DeviceUri;
+use crate::printer::PrinterState;
+use crate::supplies::{self, Supply};
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::time::Duration;
+
+/**
+ * Objects of the Host Resources MIB (RFC 2790) and the Printer MIB (RFC 3805) read for the printer status
+ */
+const HR_DEVICE_STATUS: &str = "1.3.6.1.2.1.25.3.2.1.5.1";
+const HR_PRINTER_STATUS: &str = "1.3.6.1.2.1.25.3.5.1.1.1";
+const HR_PRINTER_DETECTED_ERROR_STATE: &str = "1.3.6.1.2.1.25.3.5.1.2.1";
+const PRT_MARKER_LIFE_COUNT: &str = "1.3.6.1.2.1.43.10.2.1.4.1.1";
+const PRT_MARKER_SUPPLIES_ENTRY: &str = "1.3.6.1.2.1.43.11.1.1";
+const PRT_MARKER_COLORANT_VALUE: &str = "1.3.6.1.2.1.43.12.1.1.4";
+const PRT_ALERT_ENTRY: &str = "1.3.6.1.2.1.43.18.1.1";
+
+/**
+ * BER tags of the SNMP messages
+ */
+const TAG_INTEGER: u8 = 0x02;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_NULL: u8 = 0x05;
+const TAG_OBJECT_ID: u8 = 0x06;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_IP_ADDRESS: u8 = 0x40;
+const TAG_COUNTER32: u8 = 0x41;
+const TAG_GAUGE32: u8 = 0x42;
+const TAG_TIME_TICKS: u8 = 0x43;
+const TAG_COUNTER64: u8 = 0x46;
+const TAG_NO_SUCH_OBJECT: u8 = 0x80;
+const TAG_NO_SUCH_INSTANCE: u8 = 0x81;
+const TAG_END_OF_MIB_VIEW: u8 = 0x82;
+
+/**
+ * Error status of SNMPv1 GetNext responses past the last object (noSuchName)
+ */
+const ERROR_NO_SUCH_NAME: i64 = 2;
+
+/**
+ * Names of the hrPrinterDetectedErrorState bits (from the most significant bit of the first byte)
+ * as IPP printer-state-reasons keywords
+ */
+const ERROR_STATE_REASONS: [&str; 13] = [
+    "media-low",
+    "media-empty",
+    "toner-low",
+    "toner-empty",
+    "door-open",
+    "media-jam",
+    "offline",
+    "service-needed",
+    "input-tray-missing",
+    "output-tray-missing",
+    "marker-supply-missing",
+    "output-area-almost-full",
+    "output-area-full",
+];
+
+/**
+ * Names of the prtMarkerSuppliesType values, starting from other(1)
+ */
+const SUPPLY_TYPES: [&str; 34] = [
+    "other",
+    "unknown",
+    "toner",
+    "wasteToner",
+    "ink",
+    "inkCartridge",
+    "inkRibbon",
+    "wasteInk",
+    "opc",
+    "developer",
+    "fuserOil",
+    "solidWax",
+    "ribbonWax",
+    "wasteWax",
+    "fuser",
+    "coronaWire",
+    "fuserOilWick",
+    "cleanerUnit",
+    "fuserCleaningPad",
+    "transferUnit",
+    "tonerCartridge",
+    "fuserOiler",
+    "water",
+    "wasteWater",
+    "glueWaterAdditive",
+    "wastePaper",
+    "bindingSupply",
+    "bandingSupply",
+    "stitchingWire",
+    "shrinkWrap",
+    "paperWrap",
+    "staples",
+    "inserts",
+    "covers",
+];
+
+/**
+ * SNMP protocol version
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SnmpVersion {
+    V1,
+    V2c,
+}
+
+impl SnmpVersion {
+    fn number(&self) -> i64 {
+        match self {
+            SnmpVersion::V1 => 0,
+            SnmpVersion::V2c => 1,
+        }
+    }
+}
+
+/**
+ * Options of the SNMP status queries
+ * They can be given as query of the printer URI:
+ * socket://host:9100?community=public&snmp-version=2c (snmp=false disables them, as on the CUPS backends)
+ */
+#[derive(Debug, Clone)]
+pub struct SnmpOptions {
+    /**
+     * Query the printer with SNMP (snmp)
+     */
+    pub enabled: bool,
+
+    /**
+     * Community name sent with the requests (community)
+     */
+    pub community: String,
+
+    /**
+     * Protocol version, 1 or 2c (snmp-version)
+     */
+    pub version: SnmpVersion,
+
+    /**
+     * UDP port of the agent (snmp-port)
+     */
+    pub port: u16,
+
+    /**
+     * Time limit to receive each response (snmp-timeout)
+     */
+    pub timeout: Duration,
+
+    /**
+     * Number of times a request is sent again without response
+     */
+    pub retries: u32,
+}
+
+impl Default for SnmpOptions {
+    fn default() -> SnmpOptions {
+        SnmpOptions {
+            enabled: true,
+            community: "public".to_string(),
+            version: SnmpVersion::V1,
+            port: 161,
+            timeout: Duration::from_secs(2),
+            retries: 2,
+        }
+    }
+}
+
+impl SnmpOptions {
+    /**
+     * Read the options from the query of a printer URI (missing options keep their default value)
+     */
+    pub fn from_uri(uri: &str) -> Result<SnmpOptions, String> {
+        let mut options = SnmpOptions::default();
+        let query = match uri.split_once('?') {
+            Some((_, query)) => query,
+            None => return Ok(options),
+        };
+
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, "true"));
+            let invalid = || format!("invalid value {:?} for SNMP option {}", value, name);
+
+            match name.to_ascii_lowercase().as_str() {
+                "snmp" => {
+                    options.enabled = match value.to_ascii_lowercase().as_str() {
+                        "true" | "yes" | "on" | "1" => true,
+                        "false" | "no" | "off" | "0" => false,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "community" => options.community = crate::device_uri::percent_decode(value),
+                "snmp-version" => {
+                    options.version = match value.to_ascii_lowercase().as_str() {
+                        "1" | "v1" => SnmpVersion::V1,
+                        "2c" | "v2c" => SnmpVersion::V2c,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "snmp-port" => options.port = value.parse().map_err(|_| invalid())?,
+                "snmp-timeout" => {
+                    options.timeout = Duration::from_secs(value.parse().map_err(|_| invalid())?)
+                }
+                // Options of the print backends are ignored
+                _ => {}
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/**
+ * Value of an SNMP variable binding
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Integer(i64),
+    OctetString(Vec<u8>),
+    Null,
+    ObjectId(Vec<u32>),
+    IpAddress([u8; 4]),
+    Counter32(u32),
+    Gauge32(u32),
+    TimeTicks(u32),
+    Counter64(u64),
+    NoSuchObject,
+    NoSuchInstance,
+    EndOfMibView,
+}
+
+impl Value {
+    /**
+     * Returns the value as a number (integers, counters, gauges and time ticks)
+     */
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Integer(value) => Some(*value),
+            Value::Counter32(value) | Value::Gauge32(value) | Value::TimeTicks(value) => {
+                Some(*value as i64)
+            }
+            Value::Counter64(value) => Some(*value as i64),
+            _ => None,
+        }
+    }
+
+    /**
+     * Returns the value as text (octet strings)
+     */
+    pub fn as_text(&self) -> Option<String> {
+        match self {
+            Value::OctetString(bytes) => Some(
+                String::from_utf8_lossy(bytes)
+                    .trim_end_matches('\0')
+                    .trim()
+                    .to_string(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Type of an SNMP PDU
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PduType {
+    GetRequest,
+    GetNextRequest,
+    Response,
+}
+
+impl PduType {
+    fn tag(&self) -> u8 {
+        match self {
+            PduType::GetRequest => 0xa0,
+            PduType::GetNextRequest => 0xa1,
+            PduType::Response => 0xa2,
+        }
+    }
+}
+
+/**
+ * An SNMP v1/v2c message with its PDU
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub version: SnmpVersion,
+    pub community: String,
+    pub pdu_type: PduType,
+    pub request_id: i32,
+    pub error_status: i64,
+    pub error_index: i64,
+    pub bindings: Vec<(Vec<u32>, Value)>,
+}
+
+impl Message {
+    /**
+     * Encode the message with BER
+     */
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bindings = Vec::new();
+        for (oid, value) in &self.bindings {
+            let mut binding = encode_oid(oid);
+            binding.extend(encode_value(value));
+            bindings.extend(tlv(TAG_SEQUENCE, &binding));
+        }
+
+        let mut pdu = tlv(TAG_INTEGER, &integer_bytes(self.request_id as i64));
+        pdu.extend(tlv(TAG_INTEGER, &integer_bytes(self.error_status)));
+        pdu.extend(tlv(TAG_INTEGER, &integer_bytes(self.error_index)));
+        pdu.extend(tlv(TAG_SEQUENCE, &bindings));
+
+        let mut message = tlv(TAG_INTEGER, &integer_bytes(self.version.number()));
+        message.extend(tlv(TAG_OCTET_STRING, self.community.as_bytes()));
+        message.extend(tlv(self.pdu_type.tag(), &pdu));
+
+        tlv(TAG_SEQUENCE, &message)
+    }
+
+    /**
+     * Decode a BER encoded message
+     */
+    pub fn decode(data: &[u8]) -> Result<Message, String> {
+        let mut reader = Reader { data, position: 0 };
+        let mut message = reader.expect(TAG_SEQUENCE)?;
+
+        let version = match message.integer()? {
+            0 => SnmpVersion::V1,
+            1 => SnmpVersion::V2c,
+            version => return Err(format!("unsupported SNMP version {}", version + 1)),
+        };
+        let community = String::from_utf8_lossy(message.expect(TAG_OCTET_STRING)?.data).to_string();
+
+        let (tag, content) = message.tlv()?;
+        let pdu_type = match tag {
+            0xa0 => PduType::GetRequest,
+            0xa1 => PduType::GetNextRequest,
+            0xa2 => PduType::Response,
+            tag => return Err(format!("unsupported SNMP PDU 0x{:02x}", tag)),
+        };
+
+        let mut pdu = Reader {
+            data: content,
+            position: 0,
+        };
+        let request_id = pdu.integer()? as i32;
+        let error_status = pdu.integer()?;
+        let error_index = pdu.integer()?;
+
+        let mut list = pdu.expect(TAG_SEQUENCE)?;
+        let mut bindings = Vec::new();
+        while !list.is_empty() {
+            let mut binding = list.expect(TAG_SEQUENCE)?;
+            let oid = decode_oid(binding.expect(TAG_OBJECT_ID)?.data)?;
+            let (tag, value) = binding.tlv()?;
+            bindings.push((oid, decode_value(tag, value)?));
+        }
+
+        Ok(Message {
+            version,
+            community,
+            pdu_type,
+            request_id,
+            error_status,
+            error_index,
+            bindings,
+        })
+    }
+}
+
+/**
+ * Parse a dotted object identifier, like "1.3.6.1.2.1.1.1.0"
+ */
+pub fn parse_oid(text: &str) -> Result<Vec<u32>, String> {
+    text.trim_start_matches('.')
+        .split('.')
+        .map(|number| {
+            number
+                .parse()
+                .map_err(|_| format!("invalid object identifier {}", text))
+        })
+        .collect()
+}
+
+/**
+ * Client of an SNMP agent over UDP
+ */
+pub struct SnmpClient {
+    socket: UdpSocket,
+    options: SnmpOptions,
+    request_id: i32,
+}
+
+impl SnmpClient {
+    /**
+     * Open an UDP socket for the agent of a host
+     */
+    pub fn connect(host: &str, options: &SnmpOptions) -> Result<SnmpClient, String> {
+        let address = (host, options.port)
+            .to_socket_addrs()
+            .map_err(|error| format!("failed to resolve {}: {}", host, error))?
+            .next()
+            .ok_or_else(|| format!("no address found for host {}", host))?;
+
+        let bind = if address.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = UdpSocket::bind(bind)
+            .and_then(|socket| socket.connect(address).map(|_| socket))
+            .and_then(|socket| {
+                socket
+                    .set_read_timeout(Some(options.timeout))
+                    .map(|_| socket)
+            })
+            .map_err(|error| format!("failed to open SNMP socket for {}: {}", host, error))?;
+
+        Ok(SnmpClient {
+            socket,
+            options: options.clone(),
+            request_id: 0,
+        })
+    }
+
+    /**
+     * Get the values of objects (the missing objects are returned as NoSuchObject, NoSuchInstance or Null)
+     */
+    pub fn get(&mut self, oids: &[Vec<u32>]) -> Result<Vec<(Vec<u32>, Value)>, String> {
+        let response = self.request(PduType::GetRequest, oids)?;
+
+        // SNMPv1 agents answer noSuchName for the whole request when an object is missing
+        if response.error_status == ERROR_NO_SUCH_NAME && oids.len() > 1 {
+            let mut bindings = Vec::new();
+            for oid in oids {
+                bindings.extend(self.get(std::slice::from_ref(oid))?);
+            }
+            return Ok(bindings);
+        }
+        if response.error_status == ERROR_NO_SUCH_NAME {
+            return Ok(vec![(oids[0].clone(), Value::NoSuchObject)]);
+        }
+        if response.error_status != 0 {
+            return Err(format!(
+                "the SNMP agent returned the error status {}",
+                response.error_status
+            ));
+        }
+
+        Ok(response.bindings)
+    }
+
+    /**
+     * Get the values of every object under a prefix (a table or a column) with GetNext requests
+     */
+    pub fn walk(&mut self, prefix: &[u32]) -> Result<Vec<(Vec<u32>, Value)>, String> {
+        let mut values = Vec::new();
+        let mut oid = prefix.to_vec();
+
+        loop {
+            let response = self.request(PduType::GetNextRequest, &[oid.clone()])?;
+            if response.error_status == ERROR_NO_SUCH_NAME {
+                break;
+            }
+            if response.error_status != 0 {
+                return Err(format!(
+                    "the SNMP agent returned the error status {}",
+                    response.error_status
+                ));
+            }
+
+            let (next, value) = match response.bindings.into_iter().next() {
+                Some(binding) => binding,
+                None => break,
+            };
+            if value == Value::EndOfMibView || !next.starts_with(prefix) || next <= oid {
+                break;
+            }
+
+            oid = next.clone();
+            values.push((next, value));
+        }
+
+        Ok(values)
+    }
+
+    /**
+     * Send a request and wait its response (sent again after each timeout)
+     */
+    fn request(&mut self, pdu_type: PduType, oids: &[Vec<u32>]) -> Result<Message, String> {
+        self.request_id = self.request_id.wrapping_add(1) & 0x7fff_ffff;
+        let request = Message {
+            version: self.options.version,
+            community: self.options.community.clone(),
+            pdu_type,
+            request_id: self.request_id,
+            error_status: 0,
+            error_index: 0,
+            bindings: oids.iter().map(|oid| (oid.clone(), Value::Null)).collect(),
+        }
+        .encode();
+
+        let mut buffer = vec![0; 65535];
+        for _ in 0..=self.options.retries {
+            self.socket
+                .send(&request)
+                .map_err(|error| format!("failed to send SNMP request: {}", error))?;
+
+            loop {
+                let length = match self.socket.recv(&mut buffer) {
+                    Ok(length) => length,
+                    Err(error)
+                        if error.kind() == ErrorKind::WouldBlock
+                            || error.kind() == ErrorKind::TimedOut =>
+                    {
+                        break
+                    }
+                    Err(error) => return Err(format!("failed to read SNMP response: {}", error)),
+                };
+
+                // Late responses of previous requests are dropped
+                match Message::decode(&buffer[..length]) {
+                    Ok(response)
+                        if response.pdu_type == PduType::Response
+                            && response.request_id == self.request_id =>
+                    {
+                        return Ok(response)
+                    }
+                    _ => continue,
+                }
+            }
+        }
+
+        Err("the SNMP agent did not respond".to_string())
+    }
+}
+
+/**
+ * Severity of a printer alert (prtAlertSeverityLevel)
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlertSeverity {
+    Critical,
+    Warning,
+    Other,
+}
+
+/**
+ * An active alert of the printer (a row of prtAlertTable)
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    pub severity: AlertSeverity,
+
+    /**
+     * Alert code (prtAlertCode), like 8 for a jam or 1101 for an empty toner
+     */
+    pub code: i64,
+
+    /**
+     * Group of the printer subunit with the alert (prtAlertGroup), like 8 for inputs or 11 for marker supplies
+     */
+    pub group: i64,
+    pub description: String,
+}
+
+impl Alert {
+    /**
+     * Returns the IPP printer-state-reasons keyword of the alert code, when there is one
+     */
+    pub fn reason(&self) -> Option<&'static str> {
+        let reason = match self.code {
+            3 => "cover-open",
+            5 => "interlock-open",
+            8 => "media-jam",
+            501 => "door-open",
+            801 => "input-tray-missing",
+            807 => "media-low",
+            808 => "media-empty",
+            901 => "output-tray-missing",
+            902 => "output-area-almost-full",
+            903 => "output-area-full",
+            1101 => "toner-empty",
+            1104 => "toner-low",
+            _ => return None,
+        };
+        Some(reason)
+    }
+}
+
+/**
+ * Status of a printer read with SNMP
+ */
+#[derive(Debug, Clone)]
+pub struct PrinterStatus {
+    pub state: PrinterState,
+
+    /**
+     * IPP printer-state-reasons keywords (like "media-empty" or "toner-low"), empty when there is none
+     */
+    pub state_reasons: Vec<String>,
+    pub alerts: Vec<Alert>,
+
+    /**
+     * Number of pages printed during the life of the printer (prtMarkerLifeCount)
+     */
+    pub page_count: Option<u64>,
+    pub supplies: Vec<Supply>,
+}
+
+/**
+ * Get the status of the printer of a device URI (socket://, lpd://, ipp://...) with SNMP
+ * The options are read from the URI query
+ */
+pub fn status_from_uri(uri: &str) -> Result<PrinterStatus, String> {
+    let options = SnmpOptions::from_uri(uri)?;
+    if !options.enabled {
+        return Err(format!("SNMP is disabled by the printer URI {}", uri));
+    }
+
+    match DeviceUri::parse(uri).host() {
+        Some(host) => status(host, &options),
+        None => Err(format!("{} is not a network printer URI", uri)),
+    }
+}
+
+/**
+ * Get the status of a printer with SNMP: state, state reasons, alerts, page counter and supplies
+ */
+pub fn status(host: &str, options: &SnmpOptions) -> Result<PrinterStatus, String> {
+    let mut client = SnmpClient::connect(host, options)?;
+    let oids = [
+        HR_DEVICE_STATUS,
+        HR_PRINTER_STATUS,
+        HR_PRINTER_DETECTED_ERROR_STATE,
+        PRT_MARKER_LIFE_COUNT,
+    ];
+    let values: Vec<Value> = client
+        .get(&oids.iter().map(|oid| oid_of(oid)).collect::<Vec<_>>())?
+        .into_iter()
+        .map(|(_, value)| value)
+        .collect();
+    let value = |index: usize| values.get(index).unwrap_or(&Value::Null);
+
+    let state = match (value(0).as_i64(), value(1).as_i64()) {
+        // down(5)
+        (Some(5), _) => PrinterState::PAUSED,
+        // printing(4)
+        (_, Some(4)) => PrinterState::PRINTING,
+        // idle(3) and warmup(5)
+        (_, Some(3)) | (_, Some(5)) => PrinterState::READY,
+        _ => PrinterState::UNKNOWN,
+    };
+
+    let mut state_reasons = Vec::new();
+    if let Value::OctetString(bits) = value(2) {
+        for (index, reason) in ERROR_STATE_REASONS.iter().enumerate() {
+            match bits.get(index / 8) {
+                Some(byte) if byte & (0x80 >> (index % 8)) != 0 => {
+                    state_reasons.push(reason.to_string())
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let alerts = get_alerts(&mut client)?;
+    for reason in alerts.iter().filter_map(Alert::reason) {
+        if !state_reasons.iter().any(|known| known == reason) {
+            state_reasons.push(reason.to_string());
+        }
+    }
+
+    Ok(PrinterStatus {
+        state,
+        state_reasons,
+        alerts,
+        page_count: value(3).as_i64().map(|count| count.max(0) as u64),
+        supplies: get_supplies(&mut client)?,
+    })
+}
+
+/**
+ * Read the active alerts of prtAlertTable
+ */
+fn get_alerts(client: &mut SnmpClient) -> Result<Vec<Alert>, String> {
+    let rows = get_table(client, PRT_ALERT_ENTRY)?;
+
+    Ok(rows
+        .values()
+        .map(|columns| {
+            let number = |column: u32| columns.get(&column).and_then(Value::as_i64);
+            Alert {
+                severity: match number(2) {
+                    Some(3) => AlertSeverity::Critical,
+                    Some(4) | Some(5) => AlertSeverity::Warning,
+                    _ => AlertSeverity::Other,
+                },
+                code: number(7).unwrap_or(2),
+                group: number(4).unwrap_or(2),
+                description: columns.get(&8).and_then(Value::as_text).unwrap_or_default(),
+            }
+        })
+        .collect())
+}
+
+/**
+ * Read the supplies of prtMarkerSuppliesTable, with their colorant names of prtMarkerColorantTable
+ */
+fn get_supplies(client: &mut SnmpClient) -> Result<Vec<Supply>, String> {
+    let rows = get_table(client, PRT_MARKER_SUPPLIES_ENTRY)?;
+    let colorants: BTreeMap<Vec<u32>, String> = client
+        .walk(&oid_of(PRT_MARKER_COLORANT_VALUE))?
+        .into_iter()
+        .filter_map(|(oid, value)| {
+            let row = oid[oid.len() - 2..].to_vec();
+            value.as_text().map(|name| (row, name))
+        })
+        .collect();
+
+    let mut values = Vec::new();
+    let mut descriptions = Vec::new();
+    for (row, columns) in &rows {
+        let number = |column: u32| columns.get(&column).and_then(Value::as_i64);
+        let supply_type = number(5)
+            .and_then(|index| SUPPLY_TYPES.get((index as usize).wrapping_sub(1)))
+            .unwrap_or(&"unknown");
+
+        let colorant = match (row.first(), number(3)) {
+            (Some(device), Some(index)) if index > 0 => colorants.get(&vec![*device, index as u32]),
+            _ => None,
+        };
+
+        values.push(format!(
+            "type={};maxcapacity={};level={};colorantname={};",
+            supply_type,
+            number(8).unwrap_or(-2),
+            number(9).unwrap_or(-2),
+            colorant.map(String::as_str).unwrap_or("unknown")
+        ));
+        descriptions.push(columns.get(&6).and_then(Value::as_text).unwrap_or_default());
+    }
+
+    Ok(supplies::from_printer_supply(&values, &descriptions))
+}
+
+/**
+ * Read a table under its entry object, as columns by row index
+ */
+fn get_table(
+    client: &mut SnmpClient,
+    entry: &str,
+) -> Result<BTreeMap<Vec<u32>, BTreeMap<u32, Value>>, String> {
+    let entry = oid_of(entry);
+    let mut rows: BTreeMap<Vec<u32>, BTreeMap<u32, Value>> = BTreeMap::new();
+
+    for (oid, value) in client.walk(&entry)? {
+        if oid.len() > entry.len() + 1 {
+            rows.entry(oid[entry.len() + 1..].to_vec())
+                .or_default()
+                .insert(oid[entry.len()], value);
+        }
+    }
+
+    Ok(rows)
+}
+
+fn oid_of(text: &str) -> Vec<u32> {
+    parse_oid(text).unwrap_or_default()
+}
+
+/**
+ * Encode a tag, its length and its content
+ */
+fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![tag];
+    let length = content.len();
+
+    if length < 0x80 {
+        bytes.push(length as u8);
+    } else {
+        let length_bytes: Vec<u8> = length
+            .to_be_bytes()
+            .iter()
+            .copied()
+            .skip_while(|byte| *byte == 0)
+            .collect();
+        bytes.push(0x80 | length_bytes.len() as u8);
+        bytes.extend(length_bytes);
+    }
+
+    bytes.extend_from_slice(content);
+    bytes
+}
+
+/**
+ * Two's complement bytes of an integer, without the redundant leading bytes
+ */
+fn integer_bytes(value: i64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let mut start = 0;
+    while start < 7
+        && ((bytes[start] == 0 && bytes[start + 1] & 0x80 == 0)
+            || (bytes[start] == 0xff && bytes[start + 1] & 0x80 != 0))
+    {
+        start += 1;
+    }
+    bytes[start..].to_vec()
+}
+
+/**
+ * Bytes of an unsigned integer (a leading zero is kept when the high bit is set)
+ */
+fn unsigned_bytes(value: u64) -> Vec<u8> {
+    let mut bytes: Vec<u8> = value
+        .to_be_bytes()
+        .iter()
+        .copied()
+        .skip_while(|byte| *byte == 0)
+        .collect();
+    match bytes.first() {
+        Some(byte) if byte & 0x80 == 0 => {}
+        _ => bytes.insert(0, 0),
+    }
+    bytes
+}
+
+fn encode_oid(oid: &[u32]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    if oid.len() >= 2 {
+        encode_subidentifier(&mut bytes, oid[0] * 40 + oid[1]);
+    }
+    for number in oid.iter().skip(2) {
+        encode_subidentifier(&mut bytes, *number);
+    }
+    tlv(TAG_OBJECT_ID, &bytes)
+}
+
+/**
+ * Base 128 digits of a subidentifier, with the high bit set on all but the last one
+ */
+fn encode_subidentifier(bytes: &mut Vec<u8>, number: u32) {
+    let mut digits = vec![(number & 0x7f) as u8];
+    let mut rest = number >> 7;
+    while rest > 0 {
+        digits.push(0x80 | (rest & 0x7f) as u8);
+        rest >>= 7;
+    }
+    bytes.extend(digits.iter().rev());
+}
+
+fn encode_value(value: &Value) -> Vec<u8> {
+    match value {
+        Value::Integer(number) => tlv(TAG_INTEGER, &integer_bytes(*number)),
+        Value::OctetString(bytes) => tlv(TAG_OCTET_STRING, bytes),
+        Value::Null => tlv(TAG_NULL, &[]),
+        Value::ObjectId(oid) => encode_oid(oid),
+        Value::IpAddress(address) => tlv(TAG_IP_ADDRESS, address),
+        Value::Counter32(number) => tlv(TAG_COUNTER32, &unsigned_bytes(*number as u64)),
+        Value::Gauge32(number) => tlv(TAG_GAUGE32, &unsigned_bytes(*number as u64)),
+        Value::TimeTicks(number) => tlv(TAG_TIME_TICKS, &unsigned_bytes(*number as u64)),
+        Value::Counter64(number) => tlv(TAG_COUNTER64, &unsigned_bytes(*number)),
+        Value::NoSuchObject => tlv(TAG_NO_SUCH_OBJECT, &[]),
+        Value::NoSuchInstance => tlv(TAG_NO_SUCH_INSTANCE, &[]),
+        Value::EndOfMibView => tlv(TAG_END_OF_MIB_VIEW, &[]),
+    }
+}
+
+fn decode_oid(bytes: &[u8]) -> Result<Vec<u32>, String> {
+    let mut oid = Vec::new();
+    let mut number: u32 = 0;
+
+    for byte in bytes {
+        number = number
+            .checked_mul(128)
+            .ok_or_else(|| "invalid SNMP object identifier".to_string())?
+            | (byte & 0x7f) as u32;
+        if byte & 0x80 == 0 {
+            if oid.is_empty() {
+                let first = (number / 40).min(2);
+                oid.push(first);
+                oid.push(number - first * 40);
+            } else {
+                oid.push(number);
+            }
+            number = 0;
+        }
+    }
+
+    Ok(oid)
+}
+
+fn decode_value(tag: u8, bytes: &[u8]) -> Result<Value, String> {
+    let unsigned = || {
+        if bytes.len() > 9 {
+            return Err("invalid SNMP integer".to_string());
+        }
+        Ok(bytes
+            .iter()
+            .fold(0u64, |number, byte| (number << 8) | *byte as u64))
+    };
+
+    let value = match tag {
+        TAG_INTEGER => {
+            if bytes.is_empty() || bytes.len() > 8 {
+                return Err("invalid SNMP integer".to_string());
+            }
+            let sign = if bytes[0] & 0x80 != 0 { -1i64 } else { 0 };
+            Value::Integer(
+                bytes
+                    .iter()
+                    .fold(sign, |number, byte| (number << 8) | *byte as i64),
+            )
+        }
+        TAG_OCTET_STRING => Value::OctetString(bytes.to_vec()),
+        TAG_NULL => Value::Null,
+        TAG_OBJECT_ID => Value::ObjectId(decode_oid(bytes)?),
+        TAG_IP_ADDRESS if bytes.len() == 4 => {
+            Value::IpAddress([bytes[0], bytes[1], bytes[2], bytes[3]])
+        }
+        TAG_COUNTER32 => Value::Counter32(unsigned()? as u32),
+        TAG_GAUGE32 => Value::Gauge32(unsigned()? as u32),
+        TAG_TIME_TICKS => Value::TimeTicks(unsigned()? as u32),
+        TAG_COUNTER64 => Value::Counter64(unsigned()?),
+        TAG_NO_SUCH_OBJECT => Value::NoSuchObject,
+        TAG_NO_SUCH_INSTANCE => Value::NoSuchInstance,
+        TAG_END_OF_MIB_VIEW => Value::EndOfMibView,
+        tag => return Err(format!("unsupported SNMP value type 0x{:02x}", tag)),
+    };
+
+    Ok(value)
+}
+
+/**
+ * Reader of the BER fields of an SNMP message
+ */
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn is_empty(&self) -> bool {
+        self.position >= self.data.len()
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        let byte = *self
+            .data
+            .get(self.position)
+            .ok_or_else(|| "invalid SNMP message, the message is truncated".to_string())?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    /**
+     * Read a tag and its content
+     */
+    fn tlv(&mut self) -> Result<(u8, &'a [u8]), String> {
+        let tag = self.byte()?;
+        let mut length = self.byte()? as usize;
+
+        if length & 0x80 != 0 {
+            let count = length & 0x7f;
+            if count == 0 || count > 4 {
+                return Err("invalid SNMP message length".to_string());
+            }
+            length = 0;
+            for _ in 0..count {
+                length = (length << 8) | self.byte()? as usize;
+            }
+        }
+
+        let end = self.position + length;
+        if end > self.data.len() {
+            return Err("invalid SNMP message, the message is truncated".to_string());
+        }
+
+        let content = &self.data[self.position..end];
+        self.position = end;
+        Ok((tag, content))
+    }
+
+    /**
+     * Read a field with the expected tag, returning a reader of its content
+     */
+    fn expect(&mut self, expected: u8) -> Result<Reader<'a>, String> {
+        match self.tlv()? {
+            (tag, data) if tag == expected => Ok(Reader { data, position: 0 }),
+            (tag, _) => Err(format!(
+                "invalid SNMP message, expected the tag 0x{:02x} and found 0x{:02x}",
+                expected, tag
+            )),
+        }
+    }
+
+    fn integer(&mut self) -> Result<i64, String> {
+        match decode_value(TAG_INTEGER, self.expect(TAG_INTEGER)?.data)? {
+            Value::Integer(number) => Ok(number),
+            _ => Err("invalid SNMP integer".to_string()),
+        }
+    }
+}
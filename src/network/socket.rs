@@ -0,0 +1,200 @@
+use crate::device_uri::DeviceUri;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::thread::sleep;
+use std::time::Duration;
+
+/**
+ * Options of the AppSocket (socket://) backend
+ * They can be given as query of the printer URI, as on the CUPS socket backend:
+ * socket://host:9100?contimeout=10&timeout=60&waiteof=true&retries=3
+ */
+#[derive(Debug, Clone)]
+pub struct SocketOptions {
+    /**
+     * Time limit to open the connection with the printer (contimeout)
+     */
+    pub connect_timeout: Duration,
+
+    /**
+     * Time limit of each write or back-channel read (timeout)
+     */
+    pub timeout: Duration,
+
+    /**
+     * Wait the printer to close the connection after the document was sent, reading the back-channel (waiteof)
+     */
+    pub wait_eof: bool,
+
+    /**
+     * Number of times the connection is opened again when the printer resets it before any data was
+     * written (retries). A document partially sent is never sent again, the printer would print it twice
+     */
+    pub retries: u32,
+
+    /**
+     * Time to wait before each retry
+     */
+    pub retry_delay: Duration,
+}
+
+impl Default for SocketOptions {
+    fn default() -> SocketOptions {
+        SocketOptions {
+            connect_timeout: Duration::from_secs(10),
+            timeout: Duration::from_secs(60),
+            wait_eof: false,
+            retries: 2,
+            retry_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+impl SocketOptions {
+    /**
+     * Read the options from the query of a socket:// URI (missing options keep their default value)
+     */
+    pub fn from_uri(uri: &str) -> Result<SocketOptions, String> {
+        let mut options = SocketOptions::default();
+        let query = match uri.split_once('?') {
+            Some((_, query)) => query,
+            None => return Ok(options),
+        };
+
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, "true"));
+            let invalid = || format!("invalid value {:?} for socket option {}", value, name);
+
+            match name.to_ascii_lowercase().as_str() {
+                "contimeout" => {
+                    options.connect_timeout =
+                        Duration::from_secs(value.parse().map_err(|_| invalid())?)
+                }
+                "timeout" => {
+                    options.timeout = Duration::from_secs(value.parse().map_err(|_| invalid())?)
+                }
+                "retries" => options.retries = value.parse().map_err(|_| invalid())?,
+                "waiteof" => {
+                    options.wait_eof = match value.to_ascii_lowercase().as_str() {
+                        "true" | "yes" | "on" | "1" => true,
+                        "false" | "no" | "off" | "0" => false,
+                        _ => return Err(invalid()),
+                    }
+                }
+                // Options of the CUPS backend without meaning here (like snmp) are ignored
+                _ => {}
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/**
+ * Returns the host and port of a socket:// URI
+ */
+pub fn parse_address(uri: &str) -> Result<(String, u16), String> {
+    match DeviceUri::parse(uri) {
+        DeviceUri::Socket { host, port } => Ok((host, port)),
+        _ => Err(format!("{} is not a valid socket:// URI", uri)),
+    }
+}
+
+/**
+ * Send a document to a socket:// printer and returns the back-channel data
+ * (the back-channel is only read when wait_eof is enabled)
+ */
+pub fn print(uri: &str, buffer: &[u8], options: &SocketOptions) -> Result<Vec<u8>, String> {
+    let (host, port) = parse_address(uri)?;
+    let mut attempt = 0;
+
+    loop {
+        let mut written = 0;
+        match send(&host, port, buffer, options, &mut written) {
+            Err(error)
+                if is_connection_reset(&error) && written == 0 && attempt < options.retries =>
+            {
+                attempt += 1;
+                sleep(options.retry_delay);
+            }
+            Err(error) if written > 0 => {
+                return Err(format!(
+                    "failure on send document to {}:{} after {} of {} bytes: {}",
+                    host,
+                    port,
+                    written,
+                    buffer.len(),
+                    error
+                ))
+            }
+            result => {
+                return result.map_err(|error| {
+                    format!("failure on send document to {}:{}: {}", host, port, error)
+                })
+            }
+        }
+    }
+}
+
+/**
+ * Open the connection, write the whole document and optionally wait the printer to close it
+ * The number of bytes accepted by the connection is counted in written
+ */
+fn send(
+    host: &str,
+    port: u16,
+    buffer: &[u8],
+    options: &SocketOptions,
+    written: &mut usize,
+) -> std::io::Result<Vec<u8>> {
+    let mut stream = connect(host, port, options.connect_timeout)?;
+    stream.set_write_timeout(Some(options.timeout))?;
+    stream.set_read_timeout(Some(options.timeout))?;
+
+    while *written < buffer.len() {
+        match stream.write(&buffer[*written..]) {
+            Ok(0) => return Err(ErrorKind::WriteZero.into()),
+            Ok(length) => *written += length,
+            Err(error) if error.kind() == ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
+        }
+    }
+    stream.flush()?;
+
+    let mut back_channel = Vec::new();
+    if options.wait_eof {
+        stream.shutdown(Shutdown::Write)?;
+        stream.read_to_end(&mut back_channel)?;
+    }
+
+    Ok(back_channel)
+}
+
+/**
+ * Connect to the first reachable address of the host
+ */
+fn connect(host: &str, port: u16, timeout: Duration) -> std::io::Result<TcpStream> {
+    let mut last_error = std::io::Error::new(
+        ErrorKind::NotFound,
+        format!("no address found for host {}", host),
+    );
+
+    for address in (host, port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&address, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(error) => last_error = error,
+        }
+    }
+
+    Err(last_error)
+}
+
+/**
+ * Returns if the printer dropped the connection while the document was sent
+ */
+fn is_connection_reset(error: &std::io::Error) -> bool {
+    matches!(
+        error.kind(),
+        ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted | ErrorKind::BrokenPipe
+    )
+}
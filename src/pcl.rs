@@ -0,0 +1,430 @@
+//! PCL 5 job builder for HP and compatible laser printers
+//! Job settings (paper size, tray, duplex and copies) are sent on a PJL header,
+//! the built bytes must be sent untouched to the printer (see Printer::print_raw)
+//! PCL XL (PCL 6) streams are not generated, most PCL 6 printers also accept PCL 5 jobs
+//!
+//! ```rust
+//! use printers::pcl::{Compression, PaperSize, Pcl};
+//! use printers::print_options::Sides;
+//! use printers::raster::Bitmap;
+//!
+//! let mut bitmap = Bitmap::new(2400, 3300);
+//! bitmap.set(100, 100, true);
+//!
+//! let bytes = Pcl::new()
+//!     .job_name("Invoice")
+//!     .paper_size(PaperSize::Letter)
+//!     .sides(Sides::TwoSidedLongEdge)
+//!     .compression(Compression::DeltaRow)
+//!     .raster_page(&bitmap, 300)
+//!     .unwrap()
+//!     .text_page("Second page\nplain text")
+//!     .build();
+//! ```
+
+use crate::print_options::Sides;
+use crate::raster::Bitmap;
+
+const ESC: u8 = 0x1b;
+const FORM_FEED: u8 = 0x0c;
+
+/**
+ * Universal Exit Language, switches the printer back to PJL
+ */
+const UEL: &[u8] = b"\x1b%-12345X";
+
+/**
+ * Raster resolutions accepted by PCL 5 printers
+ */
+const RESOLUTIONS: [u32; 6] = [75, 100, 150, 200, 300, 600];
+
+/**
+ * Paper size (PJL PAPER and PCL page size command)
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaperSize {
+    Executive = 1,
+    Letter = 2,
+    Legal = 3,
+    A5 = 25,
+    A4 = 26,
+    A3 = 27,
+}
+
+impl PaperSize {
+    fn pjl_name(&self) -> &'static str {
+        match self {
+            PaperSize::Executive => "EXECUTIVE",
+            PaperSize::Letter => "LETTER",
+            PaperSize::Legal => "LEGAL",
+            PaperSize::A5 => "A5",
+            PaperSize::A4 => "A4",
+            PaperSize::A3 => "A3",
+        }
+    }
+}
+
+/**
+ * Paper source (PJL MEDIASOURCE)
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tray {
+    /**
+     * Printer default (MEDIASOURCE is not set)
+     */
+    Auto,
+
+    /**
+     * Multipurpose tray
+     */
+    Tray1,
+
+    Tray2,
+    Tray3,
+    Tray4,
+
+    /**
+     * Manual feed, the printer waits the sheets to be inserted
+     */
+    ManualFeed,
+}
+
+impl Tray {
+    fn pjl_name(&self) -> Option<&'static str> {
+        match self {
+            Tray::Auto => None,
+            Tray::Tray1 => Some("TRAY1"),
+            Tray::Tray2 => Some("TRAY2"),
+            Tray::Tray3 => Some("TRAY3"),
+            Tray::Tray4 => Some("TRAY4"),
+            Tray::ManualFeed => Some("MANUALFEED"),
+        }
+    }
+}
+
+/**
+ * Compression of the raster rows (ESC * b # M)
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /**
+     * Unencoded rows
+     */
+    None = 0,
+
+    /**
+     * TIFF PackBits, runs of repeated bytes (mode 2)
+     */
+    Tiff = 2,
+
+    /**
+     * Changes from the previous row, best for text and line art (mode 3)
+     */
+    DeltaRow = 3,
+}
+
+/**
+ * Pcl is a builder of a PCL 5 job with PJL header, pages are appended on call order
+ */
+#[derive(Debug, Clone)]
+pub struct Pcl {
+    job_name: String,
+    paper_size: PaperSize,
+    tray: Tray,
+    sides: Sides,
+    copies: u32,
+    compression: Compression,
+    pages: Vec<u8>,
+}
+
+impl Default for Pcl {
+    fn default() -> Pcl {
+        Pcl::new()
+    }
+}
+
+impl Pcl {
+    /**
+     * Create an empty job (A4 paper, printer default tray, one-sided, one copy and TIFF compression)
+     */
+    pub fn new() -> Pcl {
+        Pcl {
+            job_name: String::new(),
+            paper_size: PaperSize::A4,
+            tray: Tray::Auto,
+            sides: Sides::OneSided,
+            copies: 1,
+            compression: Compression::Tiff,
+            pages: Vec::new(),
+        }
+    }
+
+    /**
+     * Set the job name shown on the printer panel (PJL JOB NAME)
+     */
+    pub fn job_name(&mut self, name: &str) -> &mut Pcl {
+        self.job_name = name.to_string();
+        self
+    }
+
+    /**
+     * Set the paper size of the job
+     */
+    pub fn paper_size(&mut self, paper_size: PaperSize) -> &mut Pcl {
+        self.paper_size = paper_size;
+        self
+    }
+
+    /**
+     * Set the paper source of the job
+     */
+    pub fn tray(&mut self, tray: Tray) -> &mut Pcl {
+        self.tray = tray;
+        self
+    }
+
+    /**
+     * Set the duplex mode of the job (PJL DUPLEX and BINDING)
+     */
+    pub fn sides(&mut self, sides: Sides) -> &mut Pcl {
+        self.sides = sides;
+        self
+    }
+
+    /**
+     * Set the number of collated copies of the job (PJL QTY)
+     */
+    pub fn copies(&mut self, copies: u32) -> &mut Pcl {
+        self.copies = copies.max(1);
+        self
+    }
+
+    /**
+     * Set the compression of the next raster pages
+     */
+    pub fn compression(&mut self, compression: Compression) -> &mut Pcl {
+        self.compression = compression;
+        self
+    }
+
+    /**
+     * Append a page with a bitmap printed from the top left corner of the printable area
+     * at a resolution of 75, 100, 150, 200, 300 or 600 dpi
+     */
+    pub fn raster_page(&mut self, bitmap: &Bitmap, resolution: u32) -> Result<&mut Pcl, String> {
+        if !RESOLUTIONS.contains(&resolution) {
+            return Err(format!(
+                "unsupported PCL resolution {}, expected one of {:?}",
+                resolution, RESOLUTIONS
+            ));
+        }
+
+        if bitmap.width == 0 || bitmap.height == 0 {
+            return Err(format!(
+                "invalid bitmap size {}x{}",
+                bitmap.width, bitmap.height
+            ));
+        }
+
+        self.page_setup();
+        self.command(&format!("*t{}R", resolution));
+        self.command(&format!("*r{}S", bitmap.width));
+        self.command("*p0x0Y");
+        self.command("*r1A");
+        self.command(&format!("*b{}M", self.compression as u8));
+
+        let row_bytes = bitmap.row_bytes();
+        let mut seed = vec![0u8; row_bytes];
+
+        for row in bitmap.data.chunks_exact(row_bytes) {
+            let encoded = match self.compression {
+                Compression::None => trim_zeros(row).to_vec(),
+                Compression::Tiff => compress_tiff(trim_zeros(row)),
+                Compression::DeltaRow => {
+                    let encoded = compress_delta_row(row, &seed);
+                    seed.copy_from_slice(row);
+                    encoded
+                }
+            };
+
+            self.command(&format!("*b{}W", encoded.len()));
+            self.pages.extend_from_slice(&encoded);
+        }
+
+        self.command("*rC");
+        self.pages.push(FORM_FEED);
+        Ok(self)
+    }
+
+    /**
+     * Append a page of text with the Courier font at 10 characters per inch,
+     * the printer moves the lines past the bottom margin to a new page
+     * (characters out of Latin-1 are printed as "?")
+     */
+    pub fn text_page(&mut self, text: &str) -> &mut Pcl {
+        self.page_setup();
+        // Line feed also returns the carriage, ISO 8859-1 symbol set and Courier
+        self.command("&k2G");
+        self.command("(0N");
+        self.command("(s0p10h12v0s0b3T");
+
+        let encoded: Vec<u8> = text
+            .chars()
+            .filter(|&c| c != '\r')
+            .map(|c| match c as u32 {
+                0x0a | 0x09 | 0x20..=0x7e | 0xa0..=0xff => c as u8,
+                _ => b'?',
+            })
+            .collect();
+
+        self.pages.extend_from_slice(&encoded);
+        self.pages.push(FORM_FEED);
+        self
+    }
+
+    /**
+     * Returns the job bytes
+     */
+    pub fn build(&self) -> Vec<u8> {
+        let name: String = self
+            .job_name
+            .chars()
+            .map(|c| match c {
+                '"' => '\'',
+                ' '..='~' => c,
+                _ => '?',
+            })
+            .collect();
+
+        let mut pjl = format!("@PJL JOB NAME=\"{}\"\r\n", name);
+        pjl.push_str(&format!(
+            "@PJL SET PAPER={}\r\n",
+            self.paper_size.pjl_name()
+        ));
+
+        if let Some(tray) = self.tray.pjl_name() {
+            pjl.push_str(&format!("@PJL SET MEDIASOURCE={}\r\n", tray));
+        }
+
+        match self.sides {
+            Sides::OneSided => pjl.push_str("@PJL SET DUPLEX=OFF\r\n"),
+            Sides::TwoSidedLongEdge => {
+                pjl.push_str("@PJL SET DUPLEX=ON\r\n@PJL SET BINDING=LONGEDGE\r\n")
+            }
+            Sides::TwoSidedShortEdge => {
+                pjl.push_str("@PJL SET DUPLEX=ON\r\n@PJL SET BINDING=SHORTEDGE\r\n")
+            }
+        }
+
+        pjl.push_str(&format!("@PJL SET QTY={}\r\n", self.copies));
+        pjl.push_str("@PJL ENTER LANGUAGE=PCL\r\n");
+
+        let mut bytes = UEL.to_vec();
+        bytes.extend_from_slice(pjl.as_bytes());
+        bytes.extend_from_slice(&[ESC, b'E']);
+        bytes.extend_from_slice(&self.pages);
+        bytes.extend_from_slice(&[ESC, b'E']);
+        bytes.extend_from_slice(UEL);
+        bytes.extend_from_slice(format!("@PJL EOJ NAME=\"{}\"\r\n", name).as_bytes());
+        bytes.extend_from_slice(UEL);
+        bytes
+    }
+
+    /**
+     * Select the page size, portrait orientation and no top margin
+     */
+    fn page_setup(&mut self) {
+        self.command(&format!("&l{}A", self.paper_size as u8));
+        self.command("&l0O");
+        self.command("&l0E");
+    }
+
+    fn command(&mut self, command: &str) {
+        self.pages.push(ESC);
+        self.pages.extend_from_slice(command.as_bytes());
+    }
+}
+
+/**
+ * Compress a row with TIFF PackBits (mode 2): 0 to 127 copies count plus one literal bytes,
+ * 129 to 255 repeats the next byte 257 minus count times
+ */
+pub fn compress_tiff(row: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    let mut i = 0;
+
+    while i < row.len() {
+        let mut run = 1;
+        while run < 128 && i + run < row.len() && row[i + run] == row[i] {
+            run += 1;
+        }
+
+        if run > 1 {
+            encoded.push((257 - run) as u8);
+            encoded.push(row[i]);
+            i += run;
+            continue;
+        }
+
+        let mut end = i + 1;
+        while end < row.len() && end - i < 128 && (end + 1 >= row.len() || row[end] != row[end + 1])
+        {
+            end += 1;
+        }
+
+        encoded.push((end - i - 1) as u8);
+        encoded.extend_from_slice(&row[i..end]);
+        i = end;
+    }
+
+    encoded
+}
+
+/**
+ * Compress a row with delta row (mode 3), only the bytes changed from the seed row
+ * (the previous row) are sent, an empty result repeats the seed row
+ */
+pub fn compress_delta_row(row: &[u8], seed: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    let mut last = 0;
+    let mut i = 0;
+
+    while i < row.len() {
+        if row[i] == seed[i] {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < row.len() && i - start < 8 && row[i] != seed[i] {
+            i += 1;
+        }
+
+        // Command byte: bytes replaced minus one (3 bits) and offset from the last replaced byte (5 bits),
+        // offsets from 31 continue on the next bytes until one is lower than 255
+        let offset = start - last;
+        encoded.push((((i - start - 1) << 5) | offset.min(31)) as u8);
+
+        if offset >= 31 {
+            let mut remaining = offset - 31;
+            while remaining >= 255 {
+                encoded.push(255);
+                remaining -= 255;
+            }
+            encoded.push(remaining as u8);
+        }
+
+        encoded.extend_from_slice(&row[start..i]);
+        last = i;
+    }
+
+    encoded
+}
+
+/**
+ * Remove the trailing white bytes of a row, printers fill the rest of the row with white
+ */
+fn trim_zeros(row: &[u8]) -> &[u8] {
+    let end = row.iter().rposition(|&byte| byte != 0).map_or(0, |i| i + 1);
+    &row[..end]
+}
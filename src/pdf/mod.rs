@@ -0,0 +1,250 @@
+//! PDF primitives shared by the document renderers (text, images and imposition)
+
+use miniz_oxide::deflate::compress_to_vec_zlib;
+
+pub(crate) mod reader;
+
+/**
+ * Size of a page in points (1/72 inch)
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageSize {
+    pub width: f64,
+    pub height: f64,
+}
+
+impl PageSize {
+    pub const A3: PageSize = PageSize::new(841.89, 1190.55);
+    pub const A4: PageSize = PageSize::new(595.28, 841.89);
+    pub const A5: PageSize = PageSize::new(419.53, 595.28);
+    pub const LETTER: PageSize = PageSize::new(612.0, 792.0);
+    pub const LEGAL: PageSize = PageSize::new(612.0, 1008.0);
+
+    /**
+     * Create a page size in points
+     */
+    pub const fn new(width: f64, height: f64) -> PageSize {
+        PageSize { width, height }
+    }
+
+    /**
+     * Create a page size in millimeters
+     */
+    pub fn from_mm(width: f64, height: f64) -> PageSize {
+        PageSize::new(width * 72.0 / 25.4, height * 72.0 / 25.4)
+    }
+
+    /**
+     * Returns the page size with width and height swapped
+     */
+    pub fn rotated(&self) -> PageSize {
+        PageSize::new(self.height, self.width)
+    }
+
+    /**
+     * Returns if the page is wider than tall
+     */
+    pub fn is_landscape(&self) -> bool {
+        self.width > self.height
+    }
+}
+
+/**
+ * Blank space around the page content in points
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Margins {
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+    pub left: f64,
+}
+
+impl Margins {
+    /**
+     * Create margins with the same size on every side
+     */
+    pub const fn uniform(points: f64) -> Margins {
+        Margins {
+            top: points,
+            right: points,
+            bottom: points,
+            left: points,
+        }
+    }
+}
+
+/**
+ * PdfWriter writes numbered objects and the cross-reference table of a PDF file
+ */
+pub(crate) struct PdfWriter {
+    buffer: Vec<u8>,
+    offsets: Vec<usize>,
+}
+
+impl PdfWriter {
+    pub fn new() -> PdfWriter {
+        PdfWriter {
+            // The binary comment marks the file as binary for transfer programs
+            buffer: b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec(),
+            offsets: Vec::new(),
+        }
+    }
+
+    /**
+     * Allocate the number of an object written later
+     */
+    pub fn reserve(&mut self) -> usize {
+        self.offsets.push(0);
+        self.offsets.len()
+    }
+
+    /**
+     * Write a reserved object
+     */
+    pub fn object(&mut self, id: usize, content: &str) {
+        self.offsets[id - 1] = self.buffer.len();
+        self.buffer
+            .extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", id, content).as_bytes());
+    }
+
+    /**
+     * Write a reserved stream object, the data is compressed unless the dictionary has a filter
+     */
+    pub fn stream(&mut self, id: usize, dictionary: &str, data: &[u8]) {
+        let (dictionary, data) = if dictionary.contains("/Filter") {
+            (dictionary.to_string(), data.to_vec())
+        } else {
+            (
+                format!("{} /Filter /FlateDecode", dictionary),
+                compress_to_vec_zlib(data, 6),
+            )
+        };
+
+        self.offsets[id - 1] = self.buffer.len();
+        self.buffer.extend_from_slice(
+            format!(
+                "{} 0 obj\n<< {} /Length {} >>\nstream\n",
+                id,
+                dictionary.trim(),
+                data.len()
+            )
+            .as_bytes(),
+        );
+        self.buffer.extend_from_slice(&data);
+        self.buffer.extend_from_slice(b"\nendstream\nendobj\n");
+    }
+
+    /**
+     * Write the page tree and catalog for the page objects, then returns the file bytes
+     */
+    pub fn finish(mut self, pages_id: usize, page_ids: &[usize]) -> Vec<u8> {
+        let kids: Vec<String> = page_ids.iter().map(|id| format!("{} 0 R", id)).collect();
+        self.object(
+            pages_id,
+            &format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                page_ids.len()
+            ),
+        );
+
+        let catalog = self.reserve();
+        self.object(
+            catalog,
+            &format!("<< /Type /Catalog /Pages {} 0 R >>", pages_id),
+        );
+
+        let xref = self.buffer.len();
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len() + 1);
+        for offset in &self.offsets {
+            table.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        table.push_str(&format!(
+            "trailer\n<< /Size {} /Root {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+            self.offsets.len() + 1,
+            catalog,
+            xref
+        ));
+
+        self.buffer.extend_from_slice(table.as_bytes());
+        self.buffer
+    }
+}
+
+/**
+ * Returns a number with at most 2 decimals and no trailing zeros
+ */
+pub(crate) fn number(value: f64) -> String {
+    let text = format!("{:.2}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+/**
+ * Returns a literal string of PDF or PostScript, bytes out of printable ASCII are escaped as octal
+ */
+pub(crate) fn literal_string(bytes: &[u8]) -> String {
+    let mut text = String::from("(");
+
+    for &byte in bytes {
+        match byte {
+            b'(' | b')' | b'\\' => {
+                text.push('\\');
+                text.push(byte as char);
+            }
+            0x20..=0x7e => text.push(byte as char),
+            _ => text.push_str(&format!("\\{:03o}", byte)),
+        }
+    }
+
+    text.push(')');
+    text
+}
+
+/**
+ * Encode a character with WinAnsiEncoding, None for characters out of the encoding
+ */
+pub(crate) fn win_ansi(c: char) -> Option<u8> {
+    const HIGH: [(char, u8); 27] = [
+        ('€', 0x80),
+        ('‚', 0x82),
+        ('ƒ', 0x83),
+        ('„', 0x84),
+        ('…', 0x85),
+        ('†', 0x86),
+        ('‡', 0x87),
+        ('ˆ', 0x88),
+        ('‰', 0x89),
+        ('Š', 0x8a),
+        ('‹', 0x8b),
+        ('Œ', 0x8c),
+        ('Ž', 0x8e),
+        ('‘', 0x91),
+        ('’', 0x92),
+        ('“', 0x93),
+        ('”', 0x94),
+        ('•', 0x95),
+        ('–', 0x96),
+        ('—', 0x97),
+        ('˜', 0x98),
+        ('™', 0x99),
+        ('š', 0x9a),
+        ('›', 0x9b),
+        ('œ', 0x9c),
+        ('ž', 0x9e),
+        ('Ÿ', 0x9f),
+    ];
+
+    match c as u32 {
+        0x20..=0x7e | 0xa0..=0xff => Some(c as u8),
+        _ => HIGH
+            .iter()
+            .find(|(high, _)| *high == c)
+            .map(|(_, byte)| *byte),
+    }
+}
@@ -0,0 +1,963 @@
+//! Tolerant PDF reader: objects are found by scanning the file instead of trusting the
+//! cross-reference table, so incremental updates and damaged offsets are handled the same way
+
+use crate::image::unfilter;
+use miniz_oxide::inflate::decompress_to_vec_zlib;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+
+/**
+ * Deepest nesting of arrays and dictionaries read from documents, deeper documents are rejected
+ */
+const MAX_NESTING_DEPTH: usize = 256;
+
+/**
+ * PDF dictionary, the keys are names kept as raw bytes (names are not always UTF-8)
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Dictionary(BTreeMap<Vec<u8>, Object>);
+
+impl Dictionary {
+    pub fn new() -> Dictionary {
+        Dictionary::default()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Object> {
+        self.0.get(key.as_bytes())
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.0.contains_key(key.as_bytes())
+    }
+
+    pub fn insert(&mut self, key: Vec<u8>, value: Object) {
+        self.0.insert(key, value);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Vec<u8>, &Object)> {
+        self.0.iter()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Object> {
+        self.0.values()
+    }
+}
+
+/**
+ * PDF object, streams keep their raw (encoded) data
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Object {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Real(f64),
+    Name(Vec<u8>),
+    String(Vec<u8>),
+    Array(Vec<Object>),
+    Dictionary(Dictionary),
+    Stream(Dictionary, Vec<u8>),
+    Reference(u32),
+}
+
+impl Object {
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Object::Integer(value) => Some(*value as f64),
+            Object::Real(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /**
+     * Returns the name as text (None for names which are not UTF-8, which are never keywords)
+     */
+    pub fn as_name(&self) -> Option<&str> {
+        match self {
+            Object::Name(name) => std::str::from_utf8(name).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Object]> {
+        match self {
+            Object::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /**
+     * Returns the dictionary of a dictionary or stream object
+     */
+    pub fn as_dictionary(&self) -> Option<&Dictionary> {
+        match self {
+            Object::Dictionary(dictionary) | Object::Stream(dictionary, _) => Some(dictionary),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Page of a document with the attributes inherited from the page tree
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Page {
+    /**
+     * Visible area (crop box, or media box) as [x0 y0 x1 y1]
+     */
+    pub bounds: [f64; 4],
+
+    /**
+     * Clockwise rotation in degrees (0, 90, 180 or 270)
+     */
+    pub rotate: i64,
+
+    pub resources: Object,
+    pub contents: Object,
+}
+
+impl Page {
+    /**
+     * Returns the displayed width and height, with the rotation applied
+     */
+    pub fn size(&self) -> (f64, f64) {
+        let width = self.bounds[2] - self.bounds[0];
+        let height = self.bounds[3] - self.bounds[1];
+
+        if self.rotate % 180 == 0 {
+            (width, height)
+        } else {
+            (height, width)
+        }
+    }
+}
+
+/**
+ * PdfDocument is the set of objects of a PDF file and its trailer
+ */
+#[derive(Debug, Clone)]
+pub(crate) struct PdfDocument {
+    pub objects: HashMap<u32, Object>,
+    pub trailer: Dictionary,
+}
+
+impl PdfDocument {
+    /**
+     * Parse the objects of a PDF file, later definitions replace the earlier ones
+     */
+    pub fn parse(data: &[u8]) -> Result<PdfDocument, String> {
+        if !data.starts_with(b"%PDF-") {
+            return Err("not a PDF document".to_string());
+        }
+
+        let mut objects = HashMap::new();
+        let mut trailer = Dictionary::new();
+        let mut position = 0;
+
+        while position < data.len() {
+            if let Some((number, start)) = object_header(data, position) {
+                let mut parser = Parser::new(data, start);
+                if let Ok(object) = parser.object_with_stream() {
+                    if let Object::Stream(dictionary, raw) = &object {
+                        match dictionary.get("Type").and_then(Object::as_name) {
+                            Some("ObjStm") => {
+                                // Damaged object streams are skipped like the other damaged objects
+                                if let Ok(decoded) = decode_stream(dictionary, raw) {
+                                    let _ = parse_object_stream(dictionary, &decoded, &mut objects);
+                                }
+                            }
+                            Some("XRef") => merge(&mut trailer, dictionary),
+                            _ => {}
+                        }
+                    }
+
+                    objects.insert(number, object);
+                    position = parser.position;
+                    continue;
+                }
+            }
+
+            if data[position..].starts_with(b"trailer") {
+                let mut parser = Parser::new(data, position + 7);
+                if let Ok(Object::Dictionary(dictionary)) = parser.object() {
+                    merge(&mut trailer, &dictionary);
+                    position = parser.position;
+                    continue;
+                }
+            }
+
+            position += 1;
+        }
+
+        if objects.is_empty() {
+            return Err("PDF document without objects".to_string());
+        }
+
+        Ok(PdfDocument { objects, trailer })
+    }
+
+    /**
+     * Returns the object, following references
+     */
+    pub fn resolve<'a>(&'a self, object: &'a Object) -> &'a Object {
+        let mut object = object;
+        let mut depth = 0;
+
+        while let Object::Reference(number) = object {
+            depth += 1;
+            if depth > 32 {
+                return &Object::Null;
+            }
+            object = self.objects.get(number).unwrap_or(&Object::Null);
+        }
+
+        object
+    }
+
+    /**
+     * Returns an entry of a dictionary, following references
+     */
+    pub fn get<'a>(&'a self, dictionary: &'a Dictionary, key: &str) -> &'a Object {
+        dictionary
+            .get(key)
+            .map_or(&Object::Null, |object| self.resolve(object))
+    }
+
+    /**
+     * Returns if the document is encrypted
+     */
+    pub fn is_encrypted(&self) -> bool {
+        self.trailer.contains_key("Encrypt")
+    }
+
+    /**
+     * Returns the pages on document order
+     */
+    pub fn pages(&self) -> Result<Vec<Page>, String> {
+        let root = self
+            .get(&self.trailer, "Root")
+            .as_dictionary()
+            .or_else(|| {
+                // Damaged files without trailer still have a catalog object
+                self.objects
+                    .values()
+                    .filter_map(Object::as_dictionary)
+                    .find(|dictionary| {
+                        dictionary.get("Type").and_then(Object::as_name) == Some("Catalog")
+                    })
+            })
+            .ok_or("PDF document without catalog")?;
+
+        let tree = root.get("Pages").ok_or("PDF document without page tree")?;
+        let mut pages = Vec::new();
+        let inherited = Inherited {
+            bounds: None,
+            rotate: 0,
+            resources: Object::Null,
+        };
+
+        self.collect_pages(tree, &inherited, &mut pages, 0)?;
+        Ok(pages)
+    }
+
+    fn collect_pages(
+        &self,
+        node: &Object,
+        inherited: &Inherited,
+        pages: &mut Vec<Page>,
+        depth: usize,
+    ) -> Result<(), String> {
+        if depth > 64 {
+            return Err("PDF page tree too deep".to_string());
+        }
+
+        let dictionary = match self.resolve(node).as_dictionary() {
+            Some(dictionary) => dictionary,
+            None => return Ok(()),
+        };
+
+        let bounds = self
+            .rectangle(dictionary, "CropBox")
+            .or_else(|| self.rectangle(dictionary, "MediaBox"))
+            .or(inherited.bounds);
+        let rotate = self
+            .get(dictionary, "Rotate")
+            .as_number()
+            .map_or(inherited.rotate, |rotate| {
+                (rotate as i64).rem_euclid(360) / 90 * 90
+            });
+        let resources = match dictionary.get("Resources") {
+            Some(resources) => resources.clone(),
+            None => inherited.resources.clone(),
+        };
+
+        match self.get(dictionary, "Kids").as_array() {
+            Some(kids) => {
+                let inherited = Inherited {
+                    bounds,
+                    rotate,
+                    resources,
+                };
+                for kid in kids {
+                    self.collect_pages(kid, &inherited, pages, depth + 1)?;
+                }
+            }
+            None => pages.push(Page {
+                // US Letter is the default of readers for pages without media box
+                bounds: bounds.unwrap_or([0.0, 0.0, 612.0, 792.0]),
+                rotate,
+                resources,
+                contents: dictionary.get("Contents").cloned().unwrap_or(Object::Null),
+            }),
+        }
+
+        Ok(())
+    }
+
+    fn rectangle(&self, dictionary: &Dictionary, key: &str) -> Option<[f64; 4]> {
+        let items = self.get(dictionary, key).as_array()?;
+        if items.len() != 4 {
+            return None;
+        }
+
+        let mut values = [0.0; 4];
+        for (value, item) in values.iter_mut().zip(items) {
+            *value = self.resolve(item).as_number()?;
+        }
+
+        Some([
+            values[0].min(values[2]),
+            values[1].min(values[3]),
+            values[0].max(values[2]),
+            values[1].max(values[3]),
+        ])
+    }
+
+    /**
+     * Returns the decoded content of a page, multiple content streams are joined
+     */
+    pub fn page_content(&self, page: &Page) -> Result<Vec<u8>, String> {
+        let streams: Vec<&Object> = match self.resolve(&page.contents) {
+            Object::Array(items) => items.iter().map(|item| self.resolve(item)).collect(),
+            object => vec![object],
+        };
+
+        let mut content = Vec::new();
+        for stream in streams {
+            if let Object::Stream(dictionary, raw) = stream {
+                content.extend_from_slice(&self.decode(dictionary, raw)?);
+                content.push(b'\n');
+            }
+        }
+
+        Ok(content)
+    }
+
+    /**
+     * Decode the data of a stream, resolving references on its dictionary
+     */
+    pub fn decode(&self, dictionary: &Dictionary, raw: &[u8]) -> Result<Vec<u8>, String> {
+        let mut resolved = dictionary.clone();
+        for key in ["Filter", "DecodeParms"] {
+            if let Some(value) = dictionary.get(key) {
+                resolved.insert(key.as_bytes().to_vec(), self.resolve(value).clone());
+            }
+        }
+
+        decode_stream(&resolved, raw)
+    }
+}
+
+struct Inherited {
+    bounds: Option<[f64; 4]>,
+    rotate: i64,
+    resources: Object,
+}
+
+/**
+ * Add the entries of a trailer, the trailers of later revisions replace the earlier entries
+ */
+fn merge(trailer: &mut Dictionary, dictionary: &Dictionary) {
+    for (key, value) in dictionary.iter() {
+        trailer.insert(key.clone(), value.clone());
+    }
+}
+
+/**
+ * Returns the object number and the position after "N G obj" when the position starts an object
+ */
+fn object_header(data: &[u8], position: usize) -> Option<(u32, usize)> {
+    if position > 0 && !is_whitespace(data[position - 1]) {
+        return None;
+    }
+
+    let digits = |start: usize| {
+        let end = start
+            + data[start..]
+                .iter()
+                .take_while(|byte| byte.is_ascii_digit())
+                .count();
+        if end > start {
+            Some(end)
+        } else {
+            None
+        }
+    };
+    let spaces = |start: usize| {
+        let end = start
+            + data[start..]
+                .iter()
+                .take_while(|&&byte| is_whitespace(byte))
+                .count();
+        if end > start {
+            Some(end)
+        } else {
+            None
+        }
+    };
+
+    let number_end = digits(position)?;
+    let generation_start = spaces(number_end)?;
+    let generation_end = digits(generation_start)?;
+    let keyword = spaces(generation_end)?;
+
+    if !data[keyword..].starts_with(b"obj") {
+        return None;
+    }
+
+    let number = std::str::from_utf8(&data[position..number_end])
+        .ok()?
+        .parse()
+        .ok()?;
+    Some((number, keyword + 3))
+}
+
+/**
+ * Add the objects compressed in an object stream
+ * Returns an error when the object count, the offsets or the object numbers are invalid
+ */
+fn parse_object_stream(
+    dictionary: &Dictionary,
+    data: &[u8],
+    objects: &mut HashMap<u32, Object>,
+) -> Result<(), String> {
+    let invalid = || "invalid PDF object stream".to_string();
+    let integer = |key: &str| match dictionary.get(key) {
+        Some(Object::Integer(value)) => usize::try_from(*value).map_err(|_| invalid()),
+        _ => Err(invalid()),
+    };
+
+    let count = integer("N")?;
+    let first = integer("First")?;
+    if first > data.len() {
+        return Err(invalid());
+    }
+
+    let mut parser = Parser::new(data, 0);
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        match (parser.object()?, parser.object()?) {
+            (Object::Integer(number), Object::Integer(offset)) => {
+                let number = u32::try_from(number).map_err(|_| invalid())?;
+                let start = usize::try_from(offset)
+                    .ok()
+                    .and_then(|offset| first.checked_add(offset))
+                    .filter(|&start| start < data.len())
+                    .ok_or_else(invalid)?;
+                entries.push((number, start));
+            }
+            _ => return Err(invalid()),
+        }
+    }
+
+    for (number, start) in entries {
+        let mut parser = Parser::new(data, start);
+        if let Ok(object) = parser.object() {
+            objects.insert(number, object);
+        }
+    }
+
+    Ok(())
+}
+
+/**
+ * Decode stream data with FlateDecode (with PNG predictors), ASCIIHexDecode and ASCII85Decode
+ */
+pub(crate) fn decode_stream(dictionary: &Dictionary, raw: &[u8]) -> Result<Vec<u8>, String> {
+    let filters: Vec<&str> = match dictionary.get("Filter") {
+        Some(name @ Object::Name(_)) => name.as_name().into_iter().collect(),
+        Some(Object::Array(items)) => items.iter().filter_map(Object::as_name).collect(),
+        _ => vec![],
+    };
+    let parameters: Vec<Option<&Dictionary>> = match dictionary.get("DecodeParms") {
+        Some(Object::Dictionary(parameters)) => vec![Some(parameters)],
+        Some(Object::Array(items)) => items.iter().map(Object::as_dictionary).collect(),
+        _ => vec![],
+    };
+
+    let mut data = raw.to_vec();
+
+    for (index, filter) in filters.iter().enumerate() {
+        data = match *filter {
+            "FlateDecode" | "Fl" => {
+                let inflated =
+                    decompress_to_vec_zlib(&data).map_err(|_| "invalid PDF compressed stream")?;
+                match parameters.get(index).copied().flatten() {
+                    Some(parameters) => predict(parameters, inflated)?,
+                    None => inflated,
+                }
+            }
+            "ASCIIHexDecode" | "AHx" => decode_hex(&data),
+            "ASCII85Decode" | "A85" => decode_ascii85(&data)?,
+            other => return Err(format!("unsupported PDF stream filter {}", other)),
+        };
+    }
+
+    Ok(data)
+}
+
+/**
+ * Reverse the PNG predictors (10 or higher) of decoded stream data
+ */
+fn predict(parameters: &Dictionary, data: Vec<u8>) -> Result<Vec<u8>, String> {
+    let value = |key: &str, default: usize| match parameters.get(key).and_then(Object::as_number) {
+        None => Ok(default),
+        Some(number) if (0.0..=u32::MAX as f64).contains(&number) => Ok(number as usize),
+        Some(number) => Err(format!(
+            "invalid PDF predictor parameter {} {}",
+            key, number
+        )),
+    };
+
+    let predictor = value("Predictor", 1)?;
+    if predictor < 10 || data.is_empty() {
+        return Ok(data);
+    }
+
+    // The row size comes from the file, it must fit in the decoded data
+    let bits = value("Colors", 1)?
+        .checked_mul(value("BitsPerComponent", 8)?)
+        .filter(|&bits| bits > 0)
+        .ok_or("invalid PDF predictor pixel size")?;
+    let row_bytes = bits
+        .checked_mul(value("Columns", 1)?)
+        .map(|row_bits| row_bits.div_ceil(8))
+        .filter(|&row_bytes| row_bytes > 0 && row_bytes < data.len())
+        .ok_or("invalid PDF predictor row size")?;
+    let step = bits.div_ceil(8);
+    let mut previous = vec![0u8; row_bytes];
+    let mut output = Vec::with_capacity(data.len());
+
+    for row in data.chunks(row_bytes + 1) {
+        if row.len() < row_bytes + 1 {
+            break;
+        }
+        let mut line = row[1..].to_vec();
+        unfilter(row[0], &mut line, &previous, step)?;
+        output.extend_from_slice(&line);
+        previous = line;
+    }
+
+    Ok(output)
+}
+
+fn decode_hex(data: &[u8]) -> Vec<u8> {
+    let digits: Vec<u8> = data
+        .iter()
+        .take_while(|&&byte| byte != b'>')
+        .filter_map(|&byte| (byte as char).to_digit(16).map(|digit| digit as u8))
+        .collect();
+
+    digits
+        .chunks(2)
+        .map(|pair| (pair[0] << 4) | pair.get(1).copied().unwrap_or(0))
+        .collect()
+}
+
+fn decode_ascii85(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut output = Vec::new();
+    let mut group = Vec::with_capacity(5);
+
+    for &byte in data {
+        match byte {
+            b'~' => break,
+            b'z' if group.is_empty() => output.extend_from_slice(&[0, 0, 0, 0]),
+            b'!'..=b'u' => {
+                group.push(byte - b'!');
+                if group.len() == 5 {
+                    let value = group
+                        .iter()
+                        .fold(0u64, |value, &digit| value * 85 + digit as u64);
+                    output.extend_from_slice(&(value as u32).to_be_bytes());
+                    group.clear();
+                }
+            }
+            _ if is_whitespace(byte) => {}
+            _ => return Err("invalid ASCII85 data".to_string()),
+        }
+    }
+
+    if !group.is_empty() {
+        let length = group.len() - 1;
+        group.resize(5, 84);
+        let value = group
+            .iter()
+            .fold(0u64, |value, &digit| value * 85 + digit as u64);
+        output.extend_from_slice(&(value as u32).to_be_bytes()[..length]);
+    }
+
+    Ok(output)
+}
+
+pub(crate) fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, b'\0' | b'\t' | b'\n' | 0x0c | b'\r' | b' ')
+}
+
+fn is_delimiter(byte: u8) -> bool {
+    matches!(
+        byte,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+/**
+ * Parser of PDF objects from a position of the data
+ */
+pub(crate) struct Parser<'a> {
+    data: &'a [u8],
+    pub position: usize,
+
+    /**
+     * Number of arrays and dictionaries being parsed, nested in each other
+     */
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(data: &'a [u8], position: usize) -> Parser<'a> {
+        Parser {
+            data,
+            position,
+            depth: 0,
+        }
+    }
+
+    /**
+     * Enter an array or a dictionary, deeper nestings than MAX_NESTING_DEPTH are rejected
+     */
+    fn enter(&mut self) -> Result<(), String> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            return Err("invalid PDF, the objects are nested too deep".to_string());
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(byte) = self.peek() {
+            if is_whitespace(byte) {
+                self.position += 1;
+            } else if byte == b'%' {
+                while let Some(byte) = self.peek() {
+                    if byte == b'\n' || byte == b'\r' {
+                        break;
+                    }
+                    self.position += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn starts_with(&self, bytes: &[u8]) -> bool {
+        self.data[self.position.min(self.data.len())..].starts_with(bytes)
+    }
+
+    /**
+     * Parse an indirect object body, with the stream data when the dictionary is followed by "stream"
+     */
+    pub fn object_with_stream(&mut self) -> Result<Object, String> {
+        let object = self.object()?;
+        self.skip_whitespace();
+
+        let dictionary = match object {
+            Object::Dictionary(dictionary) if self.starts_with(b"stream") => dictionary,
+            object => {
+                if self.starts_with(b"endobj") {
+                    self.position += 6;
+                }
+                return Ok(object);
+            }
+        };
+
+        self.position += 6;
+        if self.starts_with(b"\r\n") {
+            self.position += 2;
+        } else if self.starts_with(b"\n") || self.starts_with(b"\r") {
+            self.position += 1;
+        }
+
+        let start = self.position;
+        let length = dictionary.get("Length").and_then(Object::as_number);
+        let declared_end = length.and_then(|length| start.checked_add(length.max(0.0) as usize));
+
+        // The declared length is trusted when "endstream" follows it, otherwise the keyword is searched
+        let end = match declared_end {
+            Some(end)
+                if end <= self.data.len() && {
+                    let mut after = Parser::new(self.data, end);
+                    after.skip_whitespace();
+                    after.starts_with(b"endstream")
+                } =>
+            {
+                end
+            }
+            _ => {
+                let found = self.data[start..]
+                    .windows(9)
+                    .position(|window| window == b"endstream")
+                    .ok_or("PDF stream without endstream")?;
+                let mut end = start + found;
+                if end > start && self.data[end - 1] == b'\n' {
+                    end -= 1;
+                }
+                if end > start && self.data[end - 1] == b'\r' {
+                    end -= 1;
+                }
+                end
+            }
+        };
+
+        let raw = self.data[start..end].to_vec();
+        self.position = end;
+        self.skip_whitespace();
+        if self.starts_with(b"endstream") {
+            self.position += 9;
+        }
+        self.skip_whitespace();
+        if self.starts_with(b"endobj") {
+            self.position += 6;
+        }
+
+        Ok(Object::Stream(dictionary, raw))
+    }
+
+    /**
+     * Parse a direct object (references "N G R" included)
+     */
+    pub fn object(&mut self) -> Result<Object, String> {
+        self.skip_whitespace();
+        let byte = self.peek().ok_or("unexpected end of PDF data")?;
+
+        match byte {
+            b'<' if self.starts_with(b"<<") => {
+                self.enter()?;
+                self.position += 2;
+                let mut dictionary = Dictionary::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.starts_with(b">>") {
+                        self.position += 2;
+                        self.depth -= 1;
+                        return Ok(Object::Dictionary(dictionary));
+                    }
+                    let key = match self.object()? {
+                        Object::Name(key) => key,
+                        _ => return Err("PDF dictionary key is not a name".to_string()),
+                    };
+                    let value = self.object()?;
+                    dictionary.insert(key, value);
+                }
+            }
+            b'<' => {
+                self.position += 1;
+                let start = self.position;
+                while self.peek().ok_or("unterminated PDF hex string")? != b'>' {
+                    self.position += 1;
+                }
+                self.position += 1;
+                Ok(Object::String(decode_hex(&self.data[start..self.position])))
+            }
+            b'[' => {
+                self.enter()?;
+                self.position += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.starts_with(b"]") {
+                        self.position += 1;
+                        self.depth -= 1;
+                        return Ok(Object::Array(items));
+                    }
+                    items.push(self.object()?);
+                }
+            }
+            b'(' => self.literal_string(),
+            b'/' => {
+                self.position += 1;
+                let start = self.position;
+                while let Some(byte) = self.peek() {
+                    if is_whitespace(byte) || is_delimiter(byte) {
+                        break;
+                    }
+                    self.position += 1;
+                }
+                Ok(Object::Name(decode_name(&self.data[start..self.position])))
+            }
+            b'0'..=b'9' | b'+' | b'-' | b'.' => self.number(),
+            _ => {
+                let start = self.position;
+                while let Some(byte) = self.peek() {
+                    if is_whitespace(byte) || is_delimiter(byte) {
+                        break;
+                    }
+                    self.position += 1;
+                }
+
+                match &self.data[start..self.position] {
+                    b"true" => Ok(Object::Boolean(true)),
+                    b"false" => Ok(Object::Boolean(false)),
+                    b"null" => Ok(Object::Null),
+                    other => Err(format!(
+                        "unexpected PDF token {}",
+                        String::from_utf8_lossy(other)
+                    )),
+                }
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Object, String> {
+        let token = self.token();
+        let text = std::str::from_utf8(token).map_err(|_| "invalid PDF number")?;
+
+        if text.contains('.') {
+            return text
+                .parse()
+                .map(Object::Real)
+                .map_err(|_| format!("invalid PDF number {}", text));
+        }
+
+        let value: i64 = text
+            .parse()
+            .map_err(|_| format!("invalid PDF number {}", text))?;
+
+        // "N G R" is a reference
+        let saved = self.position;
+        self.skip_whitespace();
+        let generation = self.token();
+        if !generation.is_empty() && generation.iter().all(u8::is_ascii_digit) {
+            self.skip_whitespace();
+            let next = self.data.get(self.position + 1).copied();
+            if self.starts_with(b"R")
+                && !matches!(next, Some(byte) if !is_whitespace(byte) && !is_delimiter(byte))
+            {
+                self.position += 1;
+                return Ok(Object::Reference(value as u32));
+            }
+        }
+
+        self.position = saved;
+        Ok(Object::Integer(value))
+    }
+
+    fn token(&mut self) -> &'a [u8] {
+        let start = self.position;
+        while let Some(byte) = self.peek() {
+            if is_whitespace(byte) || is_delimiter(byte) {
+                break;
+            }
+            self.position += 1;
+        }
+        &self.data[start..self.position]
+    }
+
+    fn literal_string(&mut self) -> Result<Object, String> {
+        self.position += 1;
+        let mut bytes = Vec::new();
+        let mut depth = 1;
+
+        loop {
+            let byte = self.peek().ok_or("unterminated PDF string")?;
+            self.position += 1;
+
+            match byte {
+                b'(' => {
+                    depth += 1;
+                    bytes.push(byte);
+                }
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(Object::String(bytes));
+                    }
+                    bytes.push(byte);
+                }
+                b'\\' => {
+                    let escaped = self.peek().ok_or("unterminated PDF string")?;
+                    self.position += 1;
+                    match escaped {
+                        b'n' => bytes.push(b'\n'),
+                        b'r' => bytes.push(b'\r'),
+                        b't' => bytes.push(b'\t'),
+                        b'b' => bytes.push(0x08),
+                        b'f' => bytes.push(0x0c),
+                        b'\r' => {
+                            if self.peek() == Some(b'\n') {
+                                self.position += 1;
+                            }
+                        }
+                        b'\n' => {}
+                        b'0'..=b'7' => {
+                            let mut value = (escaped - b'0') as u32;
+                            for _ in 0..2 {
+                                match self.peek() {
+                                    Some(digit @ b'0'..=b'7') => {
+                                        value = value * 8 + (digit - b'0') as u32;
+                                        self.position += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            bytes.push(value as u8);
+                        }
+                        other => bytes.push(other),
+                    }
+                }
+                _ => bytes.push(byte),
+            }
+        }
+    }
+}
+
+/**
+ * Decode the #xx escapes of a name
+ */
+fn decode_name(bytes: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'#'
+            && i + 2 < bytes.len()
+            && bytes[i + 1].is_ascii_hexdigit()
+            && bytes[i + 2].is_ascii_hexdigit()
+        {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+            if let Ok(value) = u8::from_str_radix(hex, 16) {
+                decoded.push(value);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    decoded
+}
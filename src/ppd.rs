@@ -0,0 +1,1307 @@
+//! PostScript Printer Description (PPD) files of CUPS queues with a PPD driver: option groups,
+//! choices, defaults, constraints, page sizes and localized text, and driverless PPD files
+//! generated from the attributes of IPP Everywhere printers
+//!
+//! ```rust
+//! use printers::ppd::Ppd;
+//!
+//! let ppd = Ppd::parse(
+//!     b"*PPD-Adobe: \"4.3\"
+//! *ModelName: \"Office Laser\"
+//! *OpenUI *Duplex/2-Sided Printing: PickOne
+//! *DefaultDuplex: None
+//! *Duplex None/Off: \"\"
+//! *Duplex DuplexNoTumble/Long Edge: \"\"
+//! *CloseUI: *Duplex
+//! *fr.Translation Duplex/Recto verso: \"\"
+//! ",
+//! )
+//! .unwrap();
+//!
+//! let duplex = ppd.option("Duplex").unwrap();
+//! assert_eq!(duplex.default.as_deref(), Some("None"));
+//! assert_eq!(duplex.choices[1].text, "Long Edge");
+//! assert_eq!(duplex.localized_text("fr"), "Recto verso");
+//! ```
+
+use crate::capabilities::Violation;
+use crate::ipp::PrinterAttributes;
+use crate::media::{MediaSize, Unit};
+use crate::print_options::{
+    ColorMode, Finishing, MediaSource, OutputBin, PrintQuality, Resolution, Sides,
+};
+use std::collections::BTreeMap;
+use std::fs;
+
+/**
+ * Parsed PPD file
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ppd {
+    /**
+     * Printer model (*ModelName)
+     */
+    pub model_name: String,
+
+    /**
+     * Name of the driver shown to users (*NickName)
+     */
+    pub nick_name: String,
+
+    /**
+     * Printer manufacturer (*Manufacturer)
+     */
+    pub manufacturer: String,
+
+    /**
+     * Language of the main text of the file (*LanguageVersion), like "English"
+     */
+    pub language: String,
+
+    /**
+     * Option groups, options outside groups are in a "General" group
+     */
+    pub groups: Vec<PpdGroup>,
+
+    /**
+     * Constraints between options (*UIConstraints and *NonUIConstraints)
+     */
+    pub constraints: Vec<PpdConstraint>,
+
+    /**
+     * Page sizes with dimensions (*PaperDimension and *ImageableArea)
+     */
+    pub page_sizes: Vec<PpdPageSize>,
+
+    /**
+     * Whether custom page sizes are supported (*CustomPageSize)
+     */
+    pub custom_page_size: bool,
+
+    /**
+     * Every statement of the file, in order
+     */
+    pub attributes: Vec<PpdAttribute>,
+}
+
+/**
+ * Group of options (*OpenGroup), like "General" or "InstallableOptions"
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PpdGroup {
+    pub name: String,
+    pub text: String,
+    pub options: Vec<PpdOption>,
+
+    /**
+     * Groups nested with *OpenSubGroup
+     */
+    pub subgroups: Vec<PpdGroup>,
+}
+
+/**
+ * Kind of user interface of an option (*OpenUI)
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PpdUi {
+    PickOne,
+    PickMany,
+    Boolean,
+}
+
+/**
+ * Option of the driver (*OpenUI), like "InputSlot" or a vendor toner saving mode
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct PpdOption {
+    /**
+     * Option keyword, like "InputSlot"
+     */
+    pub keyword: String,
+
+    /**
+     * Text shown to users, like "Media Source"
+     */
+    pub text: String,
+
+    pub ui: PpdUi,
+
+    /**
+     * Default choice (*DefaultInputSlot)
+     */
+    pub default: Option<String>,
+
+    /**
+     * Order of the option code in the job (*OrderDependency)
+     */
+    pub order: Option<f64>,
+
+    pub choices: Vec<PpdChoice>,
+
+    /**
+     * Text shown to users by language, like ("fr", "Source papier")
+     */
+    pub translations: BTreeMap<String, String>,
+}
+
+/**
+ * Choice of an option, like "Tray2"
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct PpdChoice {
+    pub name: String,
+    pub text: String,
+
+    /**
+     * PostScript or PJL code sent for the choice
+     */
+    pub code: String,
+
+    /**
+     * Text shown to users by language
+     */
+    pub translations: BTreeMap<String, String>,
+}
+
+/**
+ * Constraint forbidding two option choices together (a missing choice matches any choice
+ * other than None, False and Off)
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PpdConstraint {
+    pub option1: String,
+    pub choice1: Option<String>,
+    pub option2: String,
+    pub choice2: Option<String>,
+}
+
+/**
+ * Page size of the PPD, dimensions in points
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct PpdPageSize {
+    pub name: String,
+    pub text: String,
+    pub width: f64,
+    pub length: f64,
+
+    /**
+     * Printable area (left, bottom, right, top)
+     */
+    pub imageable_area: Option<(f64, f64, f64, f64)>,
+}
+
+/**
+ * Statement of a PPD file, like *PaperDimension Letter/US Letter: "612 792"
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PpdAttribute {
+    /**
+     * Main keyword without the star, like "PaperDimension"
+     */
+    pub keyword: String,
+
+    /**
+     * Option keyword, like "Letter" (empty when missing)
+     */
+    pub spec: String,
+
+    /**
+     * Translation of the option keyword, like "US Letter" (empty when missing)
+     */
+    pub text: String,
+
+    /**
+     * Value without the quotes
+     */
+    pub value: String,
+}
+
+impl Ppd {
+    /**
+     * Parse a PPD file content (UTF-8 or ISO Latin 1)
+     */
+    pub fn parse(data: &[u8]) -> Result<Ppd, String> {
+        let text = decode(data);
+        let attributes = read_attributes(&text);
+
+        match attributes.first() {
+            Some(attribute) if attribute.keyword == "PPD-Adobe" => {}
+            _ => return Err("invalid PPD file, the *PPD-Adobe header is missing".to_string()),
+        }
+
+        let mut ppd = Ppd::default();
+        let mut groups: Vec<PpdGroup> = Vec::new();
+        let mut option: Option<PpdOption> = None;
+        let mut defaults = BTreeMap::new();
+        let mut orders = BTreeMap::new();
+        let mut translations = Vec::new();
+        let mut dimensions = Vec::new();
+        let mut areas = BTreeMap::new();
+
+        for attribute in &attributes {
+            let keyword = attribute.keyword.as_str();
+
+            match keyword {
+                "ModelName" => ppd.model_name = attribute.value.clone(),
+                "NickName" => ppd.nick_name = attribute.value.clone(),
+                "Manufacturer" => ppd.manufacturer = attribute.value.clone(),
+                "LanguageVersion" => ppd.language = attribute.value.clone(),
+                "OpenGroup" | "OpenSubGroup" => {
+                    let (name, text) = split_translation(&attribute.value);
+                    groups.push(PpdGroup {
+                        name: name.to_string(),
+                        text: text.to_string(),
+                        ..PpdGroup::default()
+                    });
+                }
+                "CloseGroup" | "CloseSubGroup" => {
+                    if let Some(group) = groups.pop() {
+                        match groups.last_mut() {
+                            Some(parent) => parent.subgroups.push(group),
+                            None => ppd.groups.push(group),
+                        }
+                    }
+                }
+                "OpenUI" | "JCLOpenUI" => {
+                    let ui = match attribute.value.as_str() {
+                        "PickMany" => PpdUi::PickMany,
+                        "Boolean" => PpdUi::Boolean,
+                        _ => PpdUi::PickOne,
+                    };
+                    let keyword = attribute.spec.trim_start_matches('*').to_string();
+                    option = Some(PpdOption {
+                        text: if attribute.text.is_empty() {
+                            keyword.clone()
+                        } else {
+                            attribute.text.clone()
+                        },
+                        keyword,
+                        ui,
+                        default: None,
+                        order: None,
+                        choices: Vec::new(),
+                        translations: BTreeMap::new(),
+                    });
+                }
+                "CloseUI" | "JCLCloseUI" => {
+                    if let Some(option) = option.take() {
+                        match groups.last_mut() {
+                            Some(group) => group.options.push(option),
+                            None => general_group(&mut ppd.groups).options.push(option),
+                        }
+                    }
+                }
+                "OrderDependency" | "NonUIOrderDependency" => {
+                    let mut parts = attribute.value.split_whitespace();
+                    let order = parts.next().and_then(|order| order.parse::<f64>().ok());
+                    let keyword = parts.nth(1).map(|keyword| keyword.trim_start_matches('*'));
+                    if let (Some(order), Some(keyword)) = (order, keyword) {
+                        orders.insert(keyword.to_string(), order);
+                    }
+                }
+                "UIConstraints" | "NonUIConstraints" => {
+                    if let Some(constraint) = parse_constraint(&attribute.value) {
+                        ppd.constraints.push(constraint);
+                    }
+                }
+                "PaperDimension" => {
+                    let values = numbers(&attribute.value);
+                    if values.len() == 2 {
+                        dimensions.push((attribute.spec.clone(), values[0], values[1]));
+                    }
+                }
+                "ImageableArea" => {
+                    let values = numbers(&attribute.value);
+                    if values.len() == 4 {
+                        areas.insert(
+                            attribute.spec.clone(),
+                            (values[0], values[1], values[2], values[3]),
+                        );
+                    }
+                }
+                "CustomPageSize" => ppd.custom_page_size = true,
+                _ => {
+                    if let Some(name) = keyword.strip_prefix("Default") {
+                        defaults.insert(name.to_string(), attribute.value.clone());
+                    } else if let Some((language, keyword)) = localized(keyword) {
+                        translations.push((language, keyword, attribute));
+                    } else if let Some(option) = option.as_mut() {
+                        if option.keyword == keyword && !attribute.spec.is_empty() {
+                            option.choices.push(PpdChoice {
+                                name: attribute.spec.clone(),
+                                text: if attribute.text.is_empty() {
+                                    attribute.spec.clone()
+                                } else {
+                                    attribute.text.clone()
+                                },
+                                code: attribute.value.clone(),
+                                translations: BTreeMap::new(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        // Groups and options left open at the end of the file
+        if let Some(option) = option.take() {
+            match groups.last_mut() {
+                Some(group) => group.options.push(option),
+                None => general_group(&mut ppd.groups).options.push(option),
+            }
+        }
+        while let Some(group) = groups.pop() {
+            match groups.last_mut() {
+                Some(parent) => parent.subgroups.push(group),
+                None => ppd.groups.push(group),
+            }
+        }
+
+        for option in ppd.groups.iter_mut().flat_map(group_options_mut) {
+            option.default = defaults.get(&option.keyword).cloned();
+            option.order = orders.get(&option.keyword).cloned();
+        }
+
+        for (language, keyword, attribute) in translations {
+            let text = attribute.text.clone();
+            if keyword == "Translation" {
+                if let Some(option) = ppd.option_mut(&attribute.spec) {
+                    option.translations.insert(language, text);
+                }
+            } else if let Some(option) = ppd.option_mut(keyword) {
+                if let Some(choice) = option
+                    .choices
+                    .iter_mut()
+                    .find(|choice| choice.name == attribute.spec)
+                {
+                    choice.translations.insert(language, text);
+                }
+            }
+        }
+
+        let page_size_texts: BTreeMap<String, String> = ppd
+            .option("PageSize")
+            .map(|option| {
+                option
+                    .choices
+                    .iter()
+                    .map(|choice| (choice.name.clone(), choice.text.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        ppd.page_sizes = dimensions
+            .into_iter()
+            .map(|(name, width, length)| PpdPageSize {
+                text: page_size_texts
+                    .get(&name)
+                    .cloned()
+                    .unwrap_or_else(|| name.clone()),
+                imageable_area: areas.get(&name).cloned(),
+                name,
+                width,
+                length,
+            })
+            .collect();
+
+        ppd.attributes = attributes;
+        Ok(ppd)
+    }
+
+    /**
+     * Parse a PPD file
+     */
+    pub fn from_file(file_path: &str) -> Result<Ppd, String> {
+        let data = fs::read(file_path)
+            .map_err(|error| format!("failed to read {}: {}", file_path, error))?;
+        Ppd::parse(&data)
+    }
+
+    /**
+     * Returns every option of the groups and subgroups
+     */
+    pub fn options(&self) -> Vec<&PpdOption> {
+        self.groups.iter().flat_map(group_options).collect()
+    }
+
+    /**
+     * Returns an option by keyword, like "InputSlot"
+     */
+    pub fn option(&self, keyword: &str) -> Option<&PpdOption> {
+        self.options()
+            .into_iter()
+            .find(|option| option.keyword == keyword)
+    }
+
+    /**
+     * Returns the value of the first statement with a main keyword, like "cupsFilter2"
+     */
+    pub fn attribute(&self, keyword: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.keyword == keyword)
+            .map(|attribute| attribute.value.as_str())
+    }
+
+    /**
+     * Returns a page size by name, like "Letter"
+     */
+    pub fn page_size(&self, name: &str) -> Option<&PpdPageSize> {
+        self.page_sizes.iter().find(|size| size.name == name)
+    }
+
+    /**
+     * Returns the constraints broken by option choices (keyword, choice), the options not given
+     * use their default choice
+     */
+    pub fn conflicts(&self, options: &[(String, String)]) -> Vec<&PpdConstraint> {
+        let mut marked: BTreeMap<&str, &str> = BTreeMap::new();
+        for option in self.options() {
+            if let Some(default) = &option.default {
+                marked.insert(&option.keyword, default);
+            }
+        }
+        for (keyword, choice) in options {
+            marked.insert(keyword.as_str(), choice.as_str());
+        }
+
+        let is_marked = |keyword: &str, choice: &Option<String>| match marked.get(keyword) {
+            Some(marked) => match choice {
+                Some(choice) => *marked == choice.as_str(),
+                None => !["None", "False", "Off"].contains(marked),
+            },
+            None => false,
+        };
+
+        let mut conflicts: Vec<&PpdConstraint> = Vec::new();
+        for constraint in &self.constraints {
+            let reported = conflicts.iter().any(|reported| {
+                reported.option1 == constraint.option2 && reported.option2 == constraint.option1
+            });
+
+            if !reported
+                && is_marked(&constraint.option1, &constraint.choice1)
+                && is_marked(&constraint.option2, &constraint.choice2)
+            {
+                conflicts.push(constraint);
+            }
+        }
+
+        conflicts
+    }
+
+    /**
+     * Check option choices (keyword, choice) against the choices of the PPD options
+     * Returns the unknown choices of known options with the default choice as suggestion
+     * (other options are not checked, see conflicts for the constraints)
+     */
+    pub fn validate(&self, options: &[(String, String)]) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        for (keyword, choice) in options {
+            let option = match self.option(keyword) {
+                Some(option) => option,
+                None => continue,
+            };
+
+            let custom =
+                keyword == "PageSize" && self.custom_page_size && choice.starts_with("Custom.");
+            if !custom && !option.choices.iter().any(|known| &known.name == choice) {
+                violations.push(Violation {
+                    attribute: keyword.clone(),
+                    value: choice.clone(),
+                    suggestion: option.default.clone(),
+                });
+            }
+        }
+
+        violations
+    }
+
+    fn option_mut(&mut self, keyword: &str) -> Option<&mut PpdOption> {
+        self.groups
+            .iter_mut()
+            .flat_map(group_options_mut)
+            .find(|option| option.keyword == keyword)
+    }
+}
+
+impl PpdOption {
+    /**
+     * Returns the text of the option in a language (like "fr" or "pt_BR"), or the main text
+     */
+    pub fn localized_text(&self, language: &str) -> &str {
+        localized_text(&self.translations, language, &self.text)
+    }
+
+    /**
+     * Returns a choice by name
+     */
+    pub fn choice(&self, name: &str) -> Option<&PpdChoice> {
+        self.choices.iter().find(|choice| choice.name == name)
+    }
+}
+
+impl PpdChoice {
+    /**
+     * Returns the text of the choice in a language (like "fr" or "pt_BR"), or the main text
+     */
+    pub fn localized_text(&self, language: &str) -> &str {
+        localized_text(&self.translations, language, &self.text)
+    }
+}
+
+impl PpdPageSize {
+    /**
+     * Returns the media size of the page size (custom when the name is not a known media name)
+     */
+    pub fn media(&self) -> MediaSize {
+        match MediaSize::parse(&self.name) {
+            Ok(media) => media,
+            Err(_) => MediaSize::custom(self.width, self.length, Unit::Points),
+        }
+    }
+}
+
+/**
+ * Read the statements of a PPD file, values are unquoted and joined across lines
+ */
+fn read_attributes(text: &str) -> Vec<PpdAttribute> {
+    let mut attributes = Vec::new();
+    let mut lines = text.lines();
+
+    while let Some(line) = lines.next() {
+        if !line.starts_with('*') || line.starts_with("*%") || line.trim_end() == "*End" {
+            continue;
+        }
+
+        let (head, value) = match line.find(':') {
+            Some(index) => (&line[1..index], line[index + 1..].trim()),
+            None => (&line[1..], ""),
+        };
+
+        let value = match value.strip_prefix('"') {
+            Some(rest) => match rest.find('"') {
+                Some(end) => rest[..end].to_string(),
+                None => {
+                    let mut value = rest.to_string();
+                    for line in lines.by_ref() {
+                        value.push('\n');
+                        match line.find('"') {
+                            Some(end) => {
+                                value.push_str(&line[..end]);
+                                break;
+                            }
+                            None => value.push_str(line),
+                        }
+                    }
+                    value
+                }
+            },
+            None => value.to_string(),
+        };
+
+        let head = head.trim_end();
+        let (keyword, spec) = match head.find(char::is_whitespace) {
+            Some(index) => (&head[..index], head[index..].trim()),
+            None => (head, ""),
+        };
+        let (spec, text) = split_translation(spec);
+
+        attributes.push(PpdAttribute {
+            keyword: keyword.to_string(),
+            spec: spec.to_string(),
+            text: decode_hex(text),
+            value,
+        });
+    }
+
+    attributes
+}
+
+/**
+ * Split an option keyword and its translation, like "Letter/US Letter"
+ */
+fn split_translation(spec: &str) -> (&str, &str) {
+    match spec.find('/') {
+        Some(index) => (&spec[..index], &spec[index + 1..]),
+        None => (spec, ""),
+    }
+}
+
+/**
+ * Parse a constraint value, like "*Duplex *InputSlot Envelope"
+ */
+fn parse_constraint(value: &str) -> Option<PpdConstraint> {
+    let mut pairs: Vec<(String, Option<String>)> = Vec::new();
+
+    for token in value.split_whitespace() {
+        match token.strip_prefix('*') {
+            Some(keyword) => pairs.push((keyword.to_string(), None)),
+            None => {
+                let pair = pairs.last_mut()?;
+                if pair.1.is_some() {
+                    return None;
+                }
+                pair.1 = Some(token.to_string());
+            }
+        }
+    }
+
+    if pairs.len() != 2 {
+        return None;
+    }
+
+    let (option2, choice2) = pairs.pop()?;
+    let (option1, choice1) = pairs.pop()?;
+    Some(PpdConstraint {
+        option1,
+        choice1,
+        option2,
+        choice2,
+    })
+}
+
+/**
+ * Returns the language and the keyword of a localized keyword, like ("fr", "PageSize") for "fr.PageSize"
+ */
+fn localized(keyword: &str) -> Option<(String, &str)> {
+    let (language, keyword) = keyword.split_once('.')?;
+    let valid = match language.split_once('_') {
+        Some((language, country)) => {
+            language.len() == 2
+                && language.chars().all(|c| c.is_ascii_lowercase())
+                && country.len() == 2
+                && country.chars().all(|c| c.is_ascii_uppercase())
+        }
+        None => language.len() == 2 && language.chars().all(|c| c.is_ascii_lowercase()),
+    };
+
+    if valid {
+        Some((language.to_string(), keyword))
+    } else {
+        None
+    }
+}
+
+/**
+ * Returns the text of a language, falling back from "pt_BR" to "pt" and then to the main text
+ */
+fn localized_text<'a>(
+    translations: &'a BTreeMap<String, String>,
+    language: &str,
+    text: &'a str,
+) -> &'a str {
+    let base = language.split('_').next().unwrap_or(language);
+    translations
+        .get(language)
+        .or_else(|| translations.get(base))
+        .map(String::as_str)
+        .unwrap_or(text)
+}
+
+/**
+ * Returns the numbers of a value, like "612 792"
+ */
+fn numbers(value: &str) -> Vec<f64> {
+    value
+        .split_whitespace()
+        .filter_map(|number| number.parse::<f64>().ok())
+        .collect()
+}
+
+/**
+ * Returns the group of the options outside groups
+ */
+fn general_group(groups: &mut Vec<PpdGroup>) -> &mut PpdGroup {
+    let index = match groups.iter().position(|group| group.name == "General") {
+        Some(index) => index,
+        None => {
+            groups.push(PpdGroup {
+                name: "General".to_string(),
+                text: "General".to_string(),
+                ..PpdGroup::default()
+            });
+            groups.len() - 1
+        }
+    };
+    &mut groups[index]
+}
+
+fn group_options(group: &PpdGroup) -> Vec<&PpdOption> {
+    let mut options: Vec<&PpdOption> = group.options.iter().collect();
+    options.extend(group.subgroups.iter().flat_map(group_options));
+    options
+}
+
+fn group_options_mut(group: &mut PpdGroup) -> Vec<&mut PpdOption> {
+    let mut options: Vec<&mut PpdOption> = group.options.iter_mut().collect();
+    options.extend(group.subgroups.iter_mut().flat_map(group_options_mut));
+    options
+}
+
+/**
+ * Decode the file content as UTF-8, or as ISO Latin 1 when it is not valid UTF-8
+ */
+fn decode(data: &[u8]) -> String {
+    match std::str::from_utf8(data) {
+        Ok(text) => text.to_string(),
+        Err(_) => data.iter().map(|&byte| byte as char).collect(),
+    }
+}
+
+/**
+ * Decode the hexadecimal substrings of a translation, like "Envelope <23>10"
+ */
+fn decode_hex(text: &str) -> String {
+    if !text.contains('<') {
+        return text.to_string();
+    }
+
+    let mut bytes = Vec::new();
+    let mut chars = text.chars();
+    let mut utf8 = [0; 4];
+
+    while let Some(c) = chars.next() {
+        if c != '<' {
+            bytes.extend_from_slice(c.encode_utf8(&mut utf8).as_bytes());
+            continue;
+        }
+
+        let hex: String = chars.by_ref().take_while(|&c| c != '>').collect();
+        let digits: Vec<char> = hex.chars().filter(|c| !c.is_whitespace()).collect();
+        for pair in digits.chunks(2) {
+            let pair: String = pair.iter().collect();
+            if let Ok(byte) = u8::from_str_radix(&pair, 16) {
+                bytes.push(byte);
+            }
+        }
+    }
+
+    decode(&bytes)
+}
+
+/**
+ * Generate a driverless PPD file from the attributes of an IPP Everywhere printer (the
+ * Get-Printer-Attributes response), for CUPS queues created without the cups-filters helpers
+ * The jobs are sent in a format the printer supports (PDF, PostScript, PWG raster or URF)
+ */
+pub fn generate(attributes: &PrinterAttributes) -> Result<String, String> {
+    let capabilities = attributes.capabilities();
+    let formats = attributes.get("document-format-supported");
+    let filters: Vec<&str> = FILTERS
+        .iter()
+        .filter(|(format, _)| formats.iter().any(|supported| supported == format))
+        .map(|(_, filter)| *filter)
+        .collect();
+
+    if filters.is_empty() {
+        return Err(
+            "the printer supports none of the driverless formats (PDF, PostScript, PWG raster or URF)"
+                .to_string(),
+        );
+    }
+
+    let make_and_model = attributes
+        .first("printer-make-and-model")
+        .or_else(|| attributes.first("printer-info"))
+        .unwrap_or("IPP Everywhere Printer");
+    let manufacturer = make_and_model.split_whitespace().next().unwrap_or("");
+    let color = capabilities.color_modes().contains(&ColorMode::Color)
+        || attributes.first("color-supported") == Some("true");
+
+    let mut ppd = String::new();
+    let mut line = |text: String| {
+        ppd.push_str(&text);
+        ppd.push('\n');
+    };
+
+    line("*PPD-Adobe: \"4.3\"".to_string());
+    line("*FormatVersion: \"4.3\"".to_string());
+    line("*FileVersion: \"1.0\"".to_string());
+    line("*LanguageVersion: English".to_string());
+    line("*LanguageEncoding: ISOLatin1".to_string());
+    line("*PSVersion: \"(3010.000) 0\"".to_string());
+    line("*LanguageLevel: \"3\"".to_string());
+    line("*FileSystem: False".to_string());
+    line("*PCFileName: \"driverless.ppd\"".to_string());
+    line(format!("*Manufacturer: \"{}\"", quoted(manufacturer)));
+    line(format!("*ModelName: \"{}\"", quoted(make_and_model)));
+    line(format!("*Product: \"({})\"", quoted(make_and_model)));
+    line(format!(
+        "*NickName: \"{}, driverless\"",
+        quoted(make_and_model)
+    ));
+    line(format!("*ShortNickName: \"{}\"", quoted(make_and_model)));
+    line(format!("*ColorDevice: {}", boolean(color)));
+    line("*TTRasterizer: Type42".to_string());
+    line("*cupsVersion: 2.4".to_string());
+    line("*cupsLanguages: \"en\"".to_string());
+    line("*cupsIPPSupplies: True".to_string());
+    for filter in filters {
+        line(format!("*cupsFilter2: \"{}\"", filter));
+    }
+    if let Some((_, max)) = capabilities.copies() {
+        line(format!("*cupsManualCopies: {}", boolean(max <= 1)));
+        line(format!("*cupsMaxCopies: {}", max));
+    }
+
+    line("*OpenGroup: General/General".to_string());
+
+    // Page sizes with the minimum margins of the printer
+    let margin = |name: &str| {
+        attributes
+            .get(name)
+            .iter()
+            .filter_map(|margin| margin.parse::<f64>().ok())
+            .fold(None, |min: Option<f64>, margin| {
+                Some(min.map_or(margin, |min| min.min(margin)))
+            })
+            .map_or(0.0, |margin| Unit::Hundredths.convert(margin, Unit::Points))
+    };
+    let (left, bottom, right, top) = (
+        margin("media-left-margin-supported"),
+        margin("media-bottom-margin-supported"),
+        margin("media-right-margin-supported"),
+        margin("media-top-margin-supported"),
+    );
+
+    let mut sizes: Vec<(String, MediaSize)> = Vec::new();
+    for media in capabilities.media_sizes() {
+        let name = page_size_name(&media);
+        if !sizes.iter().any(|(known, _)| *known == name) {
+            sizes.push((name, media));
+        }
+    }
+    if sizes.is_empty() {
+        for name in ["iso_a4_210x297mm", "na_letter_8.5x11in"].iter() {
+            if let Ok(media) = MediaSize::parse(name) {
+                sizes.push((page_size_name(&media), media));
+            }
+        }
+    }
+
+    let default_size = attributes
+        .first("media-default")
+        .and_then(|name| MediaSize::parse(name).ok())
+        .map(|media| page_size_name(&media))
+        .filter(|name| sizes.iter().any(|(known, _)| known == name))
+        .unwrap_or_else(|| sizes[0].0.clone());
+
+    for option in ["PageSize", "PageRegion"].iter() {
+        let text = if *option == "PageSize" {
+            "Media Size"
+        } else {
+            "Page Region"
+        };
+        line(format!("*OpenUI *{}/{}: PickOne", option, text));
+        line(format!("*OrderDependency: 10 AnySetup *{}", option));
+        line(format!("*Default{}: {}", option, default_size));
+        for (name, media) in &sizes {
+            let (width, length) = points(media);
+            line(format!(
+                "*{} {}/{}: \"<</PageSize[{} {}]>>setpagedevice\"",
+                option,
+                name,
+                page_size_text(name, media),
+                width,
+                length
+            ));
+        }
+        line(format!("*CloseUI: *{}", option));
+    }
+
+    line(format!("*DefaultImageableArea: {}", default_size));
+    line(format!("*DefaultPaperDimension: {}", default_size));
+    for (name, media) in &sizes {
+        let (width, length) = points(media);
+        line(format!(
+            "*ImageableArea {}: \"{} {} {} {}\"",
+            name,
+            number(left),
+            number(bottom),
+            number(width - right),
+            number(length - top)
+        ));
+        line(format!(
+            "*PaperDimension {}: \"{} {}\"",
+            name,
+            number(width),
+            number(length)
+        ));
+    }
+
+    if let Some((min, max)) = capabilities.custom_media_range() {
+        let (min_width, min_length) = points(&min);
+        let (max_width, max_length) = points(&max);
+        line("*VariablePaperSize: True".to_string());
+        line(format!("*MaxMediaWidth: \"{}\"", number(max_width)));
+        line(format!("*MaxMediaHeight: \"{}\"", number(max_length)));
+        line(format!(
+            "*HWMargins: \"{} {} {} {}\"",
+            number(left),
+            number(bottom),
+            number(right),
+            number(top)
+        ));
+        line(
+            "*CustomPageSize True: \"pop pop pop <</PageSize[5 -2 roll]/ImagingBBox null>>setpagedevice\""
+                .to_string(),
+        );
+        line(format!(
+            "*ParamCustomPageSize Width: 1 points {} {}",
+            number(min_width),
+            number(max_width)
+        ));
+        line(format!(
+            "*ParamCustomPageSize Height: 2 points {} {}",
+            number(min_length),
+            number(max_length)
+        ));
+        line("*ParamCustomPageSize WidthOffset: 3 points 0 0".to_string());
+        line("*ParamCustomPageSize HeightOffset: 4 points 0 0".to_string());
+        line("*ParamCustomPageSize Orientation: 5 int 0 0".to_string());
+    }
+
+    // Input trays
+    let sources = capabilities.media_sources();
+    if !sources.is_empty() {
+        let choices: Vec<(String, String)> = sources
+            .iter()
+            .map(|source| (source.ppd_choice(), source.keyword()))
+            .collect();
+        let default = attributes
+            .first("media-source-default")
+            .and_then(MediaSource::from_keyword)
+            .map(|source| source.ppd_choice());
+        pick_one(
+            &mut line,
+            "InputSlot",
+            "Media Source",
+            default,
+            &choices,
+            |_| String::new(),
+        );
+    }
+
+    // Sides
+    let sides: Vec<Sides> = capabilities
+        .supported("sides")
+        .iter()
+        .filter_map(|keyword| Sides::from_keyword(keyword))
+        .collect();
+    if sides.iter().any(|sides| *sides != Sides::OneSided) {
+        let choices: Vec<(String, String)> = [
+            (Sides::OneSided, "Off"),
+            (Sides::TwoSidedLongEdge, "Long Edge (Standard)"),
+            (Sides::TwoSidedShortEdge, "Short Edge (Flip)"),
+        ]
+        .iter()
+        .filter(|(side, _)| *side == Sides::OneSided || sides.contains(side))
+        .map(|(side, text)| (side.ppd_duplex().to_string(), text.to_string()))
+        .collect();
+        let default = attributes
+            .first("sides-default")
+            .and_then(Sides::from_keyword)
+            .map(|sides| sides.ppd_duplex().to_string());
+        pick_one(
+            &mut line,
+            "Duplex",
+            "2-Sided Printing",
+            default,
+            &choices,
+            |choice| {
+                match choice {
+                    "None" => "<</Duplex false>>setpagedevice",
+                    "DuplexNoTumble" => "<</Duplex true/Tumble false>>setpagedevice",
+                    _ => "<</Duplex true/Tumble true>>setpagedevice",
+                }
+                .to_string()
+            },
+        );
+    }
+
+    // Color
+    let mut color_models = vec![("Gray".to_string(), "Grayscale".to_string())];
+    if color {
+        color_models.push(("RGB".to_string(), "Color".to_string()));
+    }
+    let default = match attributes.first("print-color-mode-default") {
+        Some(mode) if mode.contains("monochrome") || mode.contains("bi-level") => "Gray",
+        _ if color => "RGB",
+        _ => "Gray",
+    };
+    pick_one(
+        &mut line,
+        "ColorModel",
+        "Color Mode",
+        Some(default.to_string()),
+        &color_models,
+        |choice| {
+            let color_space = if choice == "RGB" { 19 } else { 18 };
+            format!(
+                "<</cupsColorSpace {}/cupsBitsPerColor 8/cupsColorOrder 0/cupsCompression 0>>setpagedevice",
+                color_space
+            )
+        },
+    );
+
+    // Quality
+    let qualities: Vec<PrintQuality> = capabilities
+        .supported("print-quality")
+        .iter()
+        .filter_map(|keyword| PrintQuality::from_keyword(keyword))
+        .collect();
+    if !qualities.is_empty() {
+        let choices: Vec<(String, String)> = qualities
+            .iter()
+            .map(|quality| {
+                (
+                    choice_name(quality.keyword()),
+                    quality.keyword().to_string(),
+                )
+            })
+            .collect();
+        let default = attributes
+            .first("print-quality-default")
+            .and_then(PrintQuality::from_keyword)
+            .map(|quality| choice_name(quality.keyword()));
+        pick_one(
+            &mut line,
+            "cupsPrintQuality",
+            "Print Quality",
+            default,
+            &choices,
+            |_| String::new(),
+        );
+    }
+
+    // Resolutions
+    let resolutions = capabilities.resolutions();
+    if !resolutions.is_empty() {
+        let choices: Vec<(String, String)> = resolutions
+            .iter()
+            .map(|resolution| (resolution.keyword(), resolution.keyword()))
+            .collect();
+        let default = attributes
+            .first("printer-resolution-default")
+            .and_then(|resolution| Resolution::parse(resolution).ok())
+            .map(|resolution| resolution.keyword());
+        pick_one(
+            &mut line,
+            "Resolution",
+            "Resolution",
+            default,
+            &choices,
+            |choice| match Resolution::parse(choice) {
+                Ok(resolution) => format!(
+                    "<</HWResolution[{} {}]>>setpagedevice",
+                    resolution.x, resolution.y
+                ),
+                Err(_) => String::new(),
+            },
+        );
+    }
+
+    // Output bins
+    let bins = capabilities.output_bins();
+    if !bins.is_empty() {
+        let choices: Vec<(String, String)> = bins
+            .iter()
+            .map(|bin| (bin.ppd_choice(), bin.keyword()))
+            .collect();
+        let default = attributes
+            .first("output-bin-default")
+            .and_then(OutputBin::from_keyword)
+            .map(|bin| bin.ppd_choice());
+        pick_one(
+            &mut line,
+            "OutputBin",
+            "Output Bin",
+            default,
+            &choices,
+            |_| String::new(),
+        );
+    }
+
+    // Finishings, mapped to the IPP finishings with *cupsIPPFinishings
+    let finishings: Vec<Finishing> = capabilities
+        .finishings()
+        .into_iter()
+        .filter(|finishing| *finishing != Finishing::None)
+        .collect();
+    if !finishings.is_empty() {
+        let mut choices = vec![("None".to_string(), "None".to_string())];
+        choices.extend(finishings.iter().map(|finishing| {
+            (
+                choice_name(finishing.keyword()),
+                finishing.keyword().to_string(),
+            )
+        }));
+        pick_one(
+            &mut line,
+            "cupsFinishing",
+            "Finishing",
+            Some("None".to_string()),
+            &choices,
+            |_| String::new(),
+        );
+        for finishing in &finishings {
+            line(format!(
+                "*cupsIPPFinishings {}/{}: \"*cupsFinishing {}\"",
+                *finishing as i32,
+                finishing.keyword(),
+                choice_name(finishing.keyword())
+            ));
+        }
+    }
+
+    line("*CloseGroup: General".to_string());
+
+    if sides.iter().any(|sides| *sides != Sides::OneSided) {
+        for source in sources
+            .iter()
+            .filter(|source| **source == MediaSource::Manual)
+        {
+            line(format!(
+                "*UIConstraints: *Duplex *InputSlot {}",
+                source.ppd_choice()
+            ));
+            line(format!(
+                "*UIConstraints: *InputSlot {} *Duplex",
+                source.ppd_choice()
+            ));
+        }
+    }
+
+    line("*% End of driverless PPD".to_string());
+    Ok(ppd)
+}
+
+/**
+ * Filters of the driverless formats, on preference order (the lower cost is preferred by CUPS)
+ */
+const FILTERS: [(&str, &str); 4] = [
+    (
+        "application/pdf",
+        "application/vnd.cups-pdf application/pdf 10 -",
+    ),
+    (
+        "application/postscript",
+        "application/vnd.cups-postscript application/postscript 20 -",
+    ),
+    (
+        "image/pwg-raster",
+        "image/pwg-raster image/pwg-raster 100 -",
+    ),
+    ("image/urf", "image/urf image/urf 150 -"),
+];
+
+/**
+ * Write a PickOne option with its choices (name, text) and the code of each choice
+ */
+fn pick_one(
+    line: &mut dyn FnMut(String),
+    keyword: &str,
+    text: &str,
+    default: Option<String>,
+    choices: &[(String, String)],
+    code: impl Fn(&str) -> String,
+) {
+    let default = default
+        .filter(|default| choices.iter().any(|(choice, _)| choice == default))
+        .unwrap_or_else(|| choices[0].0.clone());
+
+    line(format!("*OpenUI *{}/{}: PickOne", keyword, text));
+    line(format!("*OrderDependency: 10 AnySetup *{}", keyword));
+    line(format!("*Default{}: {}", keyword, default));
+    for (choice, text) in choices {
+        line(format!(
+            "*{} {}/{}: \"{}\"",
+            keyword,
+            choice,
+            quoted(text),
+            code(choice)
+        ));
+    }
+    line(format!("*CloseUI: *{}", keyword));
+}
+
+/**
+ * Returns the PPD page size name of a media size (the PWG name for sizes without an Adobe name)
+ */
+fn page_size_name(media: &MediaSize) -> String {
+    let name = media.ppd_name();
+    if name.starts_with("Custom.") {
+        let (width, length) = points(media);
+        format!("w{}h{}", width.round(), length.round())
+    } else {
+        name
+    }
+}
+
+/**
+ * Returns the text of a page size, like "A4 (210 x 297 mm)"
+ */
+fn page_size_text(name: &str, media: &MediaSize) -> String {
+    format!(
+        "{} ({} x {} mm)",
+        name,
+        number(media.width_in(Unit::Millimeters)),
+        number(media.height_in(Unit::Millimeters))
+    )
+}
+
+/**
+ * Returns the width and length of a media size in points
+ */
+fn points(media: &MediaSize) -> (f64, f64) {
+    (
+        (media.width_in(Unit::Points) * 100.0).round() / 100.0,
+        (media.height_in(Unit::Points) * 100.0).round() / 100.0,
+    )
+}
+
+/**
+ * Format a number without the useless decimals
+ */
+fn number(value: f64) -> String {
+    let value = (value * 100.0).round() / 100.0;
+    if value.fract() == 0.0 {
+        format!("{}", value as i64)
+    } else {
+        format!("{}", value)
+    }
+}
+
+fn boolean(value: bool) -> &'static str {
+    if value {
+        "True"
+    } else {
+        "False"
+    }
+}
+
+/**
+ * Returns a PPD choice name of an IPP keyword, like "StapleTopLeft" for "staple-top-left"
+ */
+fn choice_name(keyword: &str) -> String {
+    keyword
+        .split('-')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/**
+ * Replace the quotes of a text, which can not be escaped in PPD strings
+ */
+fn quoted(text: &str) -> String {
+    text.replace('"', "'")
+}
@@ -0,0 +1,1026 @@
+use crate::document::DocumentFormat;
+use crate::lp_options;
+use crate::media::MediaSize;
+
+/**
+ * Options applied to a print job
+ */
+#[derive(Debug, Clone, Default)]
+pub struct PrintOptions {
+    /**
+     * Format of the document (when empty the format is detected from the document content)
+     */
+    pub document_format: Option<DocumentFormat>,
+
+    /**
+     * Number of copies of the document (when empty the printer default is used)
+     */
+    pub copies: Option<u32>,
+
+    /**
+     * Sides of the sheet printed (when empty the printer default is used)
+     */
+    pub sides: Option<Sides>,
+
+    /**
+     * Media size (when empty the printer default is used)
+     */
+    pub media: Option<MediaSize>,
+
+    /**
+     * Input tray (when empty the printer selects it)
+     */
+    pub media_source: Option<MediaSource>,
+
+    /**
+     * Output bin (when empty the printer default is used)
+     */
+    pub output_bin: Option<OutputBin>,
+
+    /**
+     * Finishings applied to the printed document, like staples or punch holes
+     */
+    pub finishings: Vec<Finishing>,
+
+    /**
+     * Print quality (when empty the printer default is used)
+     */
+    pub quality: Option<PrintQuality>,
+
+    /**
+     * Print resolution (when empty the printer default is used)
+     */
+    pub resolution: Option<Resolution>,
+
+    /**
+     * Color mode, monochrome forces the document to print without color
+     */
+    pub color_mode: Option<ColorMode>,
+
+    /**
+     * Scaling of the document pages to the media
+     */
+    pub scaling: Option<Scaling>,
+
+    /**
+     * Orientation of the document pages on the media
+     */
+    pub orientation: Option<Orientation>,
+
+    /**
+     * Other job attributes as IPP names and values, like ("job-hold-until", "night")
+     * (sent as CUPS options, the other platforms ignore them)
+     */
+    pub attributes: Vec<(String, String)>,
+}
+
+impl PrintOptions {
+    /**
+     * Create print options from an `lp -o` option string, like "media=A4 sides=two-sided-long-edge"
+     * (see PrintOptions::from_options)
+     */
+    pub fn from_lp_options(text: &str) -> PrintOptions {
+        PrintOptions::from_options(&lp_options::parse(text))
+    }
+
+    /**
+     * Create print options from (name, value) options with IPP or CUPS names
+     * Known options with valid values are set on the typed fields, including the CUPS legacy
+     * "landscape", "fit-to-page" and "resolution" options and the PPD "PageSize", "InputSlot",
+     * "OutputBin" and "Duplex" options, the other options are kept as raw attributes verbatim
+     */
+    pub fn from_options(options: &[(String, String)]) -> PrintOptions {
+        let mut print_options = PrintOptions::default();
+
+        for (name, value) in options {
+            let typed = match name.as_str() {
+                "document-format" => match DocumentFormat::from_mime_type(value) {
+                    DocumentFormat::AUTO => false,
+                    format => {
+                        print_options.document_format = Some(format);
+                        true
+                    }
+                },
+                "copies" => set(&mut print_options.copies, value.parse().ok()),
+                "sides" => set(&mut print_options.sides, Sides::from_keyword(value)),
+                "Duplex" => set(&mut print_options.sides, Sides::from_ppd_duplex(value)),
+                "media" => print_options.set_media(value),
+                "PageSize" => set(&mut print_options.media, MediaSize::parse(value).ok()),
+                "media-source" => set(
+                    &mut print_options.media_source,
+                    MediaSource::from_keyword(value),
+                ),
+                "InputSlot" => set(
+                    &mut print_options.media_source,
+                    MediaSource::from_ppd_choice(value),
+                ),
+                "output-bin" => set(
+                    &mut print_options.output_bin,
+                    OutputBin::from_keyword(value),
+                ),
+                "OutputBin" => set(
+                    &mut print_options.output_bin,
+                    OutputBin::from_ppd_choice(value),
+                ),
+                "finishings" => {
+                    let finishings: Option<Vec<Finishing>> =
+                        value.split(',').map(Finishing::from_keyword).collect();
+                    match finishings {
+                        Some(finishings) => {
+                            print_options.finishings = finishings;
+                            true
+                        }
+                        None => false,
+                    }
+                }
+                "print-quality" => set(
+                    &mut print_options.quality,
+                    PrintQuality::from_keyword(value),
+                ),
+                "printer-resolution" | "resolution" => {
+                    set(&mut print_options.resolution, Resolution::parse(value).ok())
+                }
+                "print-color-mode" => set(
+                    &mut print_options.color_mode,
+                    ColorMode::from_keyword(value),
+                ),
+                "print-scaling" => set(&mut print_options.scaling, Scaling::from_keyword(value)),
+                "fit-to-page" if value == "true" => {
+                    set(&mut print_options.scaling, Some(Scaling::Fit))
+                }
+                "orientation-requested" => set(
+                    &mut print_options.orientation,
+                    Orientation::from_keyword(value),
+                ),
+                "landscape" if value == "true" => {
+                    set(&mut print_options.orientation, Some(Orientation::Landscape))
+                }
+                _ => false,
+            };
+
+            if !typed {
+                print_options.attributes.push((name.clone(), value.clone()));
+            }
+        }
+
+        print_options
+    }
+
+    /**
+     * Returns the options as an `lp -o` option string, with the document format and the IPP
+     * attributes (see PrintOptions::ipp_attributes)
+     */
+    pub fn lp_options(&self) -> String {
+        let mut options = Vec::new();
+        if let Some(format) = self.document_format {
+            options.push((
+                "document-format".to_string(),
+                format.mime_type().to_string(),
+            ));
+        }
+        options.extend(self.ipp_attributes());
+        lp_options::format(&options)
+    }
+
+    /**
+     * Set the media of a "media" option, a size or a size and an input tray like "A4,tray-2"
+     * (returns false when a part of the value is unknown)
+     */
+    fn set_media(&mut self, value: &str) -> bool {
+        if let Ok(media) = MediaSize::parse(value) {
+            self.media = Some(media);
+            return true;
+        }
+
+        let mut media = None;
+        let mut media_source = None;
+        for part in value.split(',') {
+            if let Ok(size) = MediaSize::parse(part) {
+                media = Some(size);
+            } else if let Some(source) =
+                MediaSource::from_keyword(part).or_else(|| MediaSource::from_ppd_choice(part))
+            {
+                media_source = Some(source);
+            } else {
+                return false;
+            }
+        }
+
+        if media.is_some() {
+            self.media = media;
+        }
+        if media_source.is_some() {
+            self.media_source = media_source;
+        }
+        true
+    }
+    /**
+     * Returns the options as IPP job attributes (name, value), without the document format
+     */
+    pub fn ipp_attributes(&self) -> Vec<(String, String)> {
+        let mut attributes = Vec::new();
+
+        if let Some(copies) = self.copies {
+            attributes.push(("copies".to_string(), copies.to_string()));
+        }
+
+        if let Some(sides) = self.sides {
+            attributes.push(("sides".to_string(), sides.keyword().to_string()));
+        }
+
+        if let Some(media) = &self.media {
+            attributes.push(("media".to_string(), media.name.clone()));
+        }
+
+        if let Some(media_source) = self.media_source {
+            attributes.push(("media-source".to_string(), media_source.keyword()));
+        }
+
+        if let Some(output_bin) = self.output_bin {
+            attributes.push(("output-bin".to_string(), output_bin.keyword()));
+        }
+
+        if !self.finishings.is_empty() {
+            let keywords: Vec<&str> = self
+                .finishings
+                .iter()
+                .map(|finishing| finishing.keyword())
+                .collect();
+            attributes.push(("finishings".to_string(), keywords.join(",")));
+        }
+
+        if let Some(quality) = self.quality {
+            attributes.push(("print-quality".to_string(), quality.keyword().to_string()));
+        }
+
+        if let Some(resolution) = self.resolution {
+            attributes.push(("printer-resolution".to_string(), resolution.keyword()));
+        }
+
+        if let Some(color_mode) = self.color_mode {
+            attributes.push((
+                "print-color-mode".to_string(),
+                color_mode.keyword().to_string(),
+            ));
+        }
+
+        if let Some(scaling) = self.scaling {
+            attributes.push(("print-scaling".to_string(), scaling.keyword().to_string()));
+        }
+
+        if let Some(orientation) = self.orientation {
+            attributes.push((
+                "orientation-requested".to_string(),
+                orientation.keyword().to_string(),
+            ));
+        }
+
+        attributes.extend(self.attributes.iter().cloned());
+        attributes
+    }
+
+    /**
+     * Returns the options as the PPD options (keyword, choice) of CUPS queues with a PPD driver,
+     * sent together with the IPP attributes for the filters reading the PPD options only
+     * (finishings are mapped by CUPS with the cupsIPPFinishings entries of the PPD, scaling and
+     * orientation are applied by the CUPS filters from the IPP attributes)
+     */
+    pub fn ppd_options(&self) -> Vec<(String, String)> {
+        let mut options = Vec::new();
+
+        if let Some(sides) = self.sides {
+            options.push(("Duplex".to_string(), sides.ppd_duplex().to_string()));
+        }
+
+        if let Some(media) = &self.media {
+            options.push(("PageSize".to_string(), media.ppd_name()));
+        }
+
+        if let Some(media_source) = self.media_source {
+            options.push(("InputSlot".to_string(), media_source.ppd_choice()));
+        }
+
+        if let Some(output_bin) = self.output_bin {
+            options.push(("OutputBin".to_string(), output_bin.ppd_choice()));
+        }
+
+        if let Some(quality) = self.quality {
+            let choice = match quality {
+                PrintQuality::Draft => "Draft",
+                PrintQuality::Normal => "Normal",
+                PrintQuality::High => "High",
+            };
+            options.push(("cupsPrintQuality".to_string(), choice.to_string()));
+        }
+
+        if let Some(resolution) = self.resolution {
+            options.push(("Resolution".to_string(), resolution.keyword()));
+        }
+
+        if let Some(color_model) = self.color_mode.and_then(|mode| mode.ppd_color_model()) {
+            options.push(("ColorModel".to_string(), color_model.to_string()));
+        }
+
+        options
+    }
+}
+
+/**
+ * Set an option from a parsed value (returns false when the value is invalid)
+ */
+fn set<T>(option: &mut Option<T>, value: Option<T>) -> bool {
+    match value {
+        Some(value) => {
+            *option = Some(value);
+            true
+        }
+        None => false,
+    }
+}
+
+/**
+ * Enum of the sides of the sheet printed (IPP "sides")
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sides {
+    /**
+     * Simplex (one-sided)
+     */
+    OneSided,
+
+    /**
+     * Duplex flipped on the long edge, like a book (two-sided-long-edge)
+     */
+    TwoSidedLongEdge,
+
+    /**
+     * Duplex flipped on the short edge, like a notepad (two-sided-short-edge)
+     */
+    TwoSidedShortEdge,
+}
+
+impl Sides {
+    /**
+     * Returns the IPP keyword
+     */
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Sides::OneSided => "one-sided",
+            Sides::TwoSidedLongEdge => "two-sided-long-edge",
+            Sides::TwoSidedShortEdge => "two-sided-short-edge",
+        }
+    }
+
+    /**
+     * Returns the sides of an IPP keyword
+     */
+    pub fn from_keyword(keyword: &str) -> Option<Sides> {
+        match keyword {
+            "one-sided" => Some(Sides::OneSided),
+            "two-sided-long-edge" => Some(Sides::TwoSidedLongEdge),
+            "two-sided-short-edge" => Some(Sides::TwoSidedShortEdge),
+            _ => None,
+        }
+    }
+
+    /**
+     * Returns the choice of the PPD Duplex option
+     */
+    pub fn ppd_duplex(&self) -> &'static str {
+        match self {
+            Sides::OneSided => "None",
+            Sides::TwoSidedLongEdge => "DuplexNoTumble",
+            Sides::TwoSidedShortEdge => "DuplexTumble",
+        }
+    }
+
+    /**
+     * Returns the sides of a PPD Duplex choice
+     */
+    pub fn from_ppd_duplex(choice: &str) -> Option<Sides> {
+        match choice {
+            "None" => Some(Sides::OneSided),
+            "DuplexNoTumble" => Some(Sides::TwoSidedLongEdge),
+            "DuplexTumble" => Some(Sides::TwoSidedShortEdge),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Enum of the print quality (IPP "print-quality")
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrintQuality {
+    Draft = 3,
+    Normal = 4,
+    High = 5,
+}
+
+impl PrintQuality {
+    /**
+     * Returns the IPP keyword
+     */
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            PrintQuality::Draft => "draft",
+            PrintQuality::Normal => "normal",
+            PrintQuality::High => "high",
+        }
+    }
+
+    /**
+     * Returns the print quality of an IPP keyword or enum value ("draft" or "3")
+     */
+    pub fn from_keyword(keyword: &str) -> Option<PrintQuality> {
+        match keyword {
+            "draft" | "3" => Some(PrintQuality::Draft),
+            "normal" | "4" => Some(PrintQuality::Normal),
+            "high" | "5" => Some(PrintQuality::High),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Print resolution in dots per inch (IPP "printer-resolution", PPD "Resolution")
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Resolution {
+    /**
+     * Cross feed (horizontal) resolution
+     */
+    pub x: u32,
+
+    /**
+     * Feed (vertical) resolution
+     */
+    pub y: u32,
+}
+
+impl Resolution {
+    /**
+     * Create a resolution with the same dots per inch on both axes
+     */
+    pub fn dpi(dpi: u32) -> Resolution {
+        Resolution { x: dpi, y: dpi }
+    }
+
+    /**
+     * Parse a resolution like "600dpi" or "600x1200dpi" (dots per centimeter are converted)
+     */
+    pub fn parse(value: &str) -> Result<Resolution, String> {
+        let invalid = || format!("invalid resolution {}", value);
+        let trimmed = value.trim();
+
+        let (text, per_cm) = match trimmed.strip_suffix("dpi") {
+            Some(text) => (text, false),
+            None => (trimmed.strip_suffix("dpcm").ok_or_else(invalid)?, true),
+        };
+
+        let (x, y) = match text.split_once('x') {
+            Some((x, y)) => (x.parse::<u32>(), y.parse::<u32>()),
+            None => (text.parse::<u32>(), text.parse::<u32>()),
+        };
+
+        let (x, y) = match (x, y) {
+            (Ok(x), Ok(y)) if x > 0 && y > 0 => (x, y),
+            _ => return Err(invalid()),
+        };
+
+        if per_cm {
+            let convert = |dots: u32| (dots as f64 * 2.54).round() as u32;
+            Ok(Resolution {
+                x: convert(x),
+                y: convert(y),
+            })
+        } else {
+            Ok(Resolution { x, y })
+        }
+    }
+
+    /**
+     * Returns the IPP and PPD name, like "600dpi" or "600x1200dpi"
+     */
+    pub fn keyword(&self) -> String {
+        if self.x == self.y {
+            format!("{}dpi", self.x)
+        } else {
+            format!("{}x{}dpi", self.x, self.y)
+        }
+    }
+}
+
+/**
+ * Enum of the color modes (IPP "print-color-mode")
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /**
+     * The printer selects the mode from the document content
+     */
+    Auto,
+    Color,
+
+    /**
+     * Shades of gray
+     */
+    Monochrome,
+
+    /**
+     * Only black and white, without gray shades
+     */
+    BiLevel,
+}
+
+impl ColorMode {
+    /**
+     * Returns the IPP keyword
+     */
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            ColorMode::Auto => "auto",
+            ColorMode::Color => "color",
+            ColorMode::Monochrome => "monochrome",
+            ColorMode::BiLevel => "bi-level",
+        }
+    }
+
+    /**
+     * Returns the color mode of an IPP keyword
+     */
+    pub fn from_keyword(keyword: &str) -> Option<ColorMode> {
+        match keyword {
+            "auto" => Some(ColorMode::Auto),
+            "color" => Some(ColorMode::Color),
+            "monochrome" => Some(ColorMode::Monochrome),
+            "bi-level" => Some(ColorMode::BiLevel),
+            _ => None,
+        }
+    }
+
+    /**
+     * Returns the choice of the PPD ColorModel option (auto has no choice)
+     */
+    pub fn ppd_color_model(&self) -> Option<&'static str> {
+        match self {
+            ColorMode::Auto => None,
+            ColorMode::Color => Some("RGB"),
+            ColorMode::Monochrome | ColorMode::BiLevel => Some("Gray"),
+        }
+    }
+}
+
+/**
+ * Enum of the scaling of the pages to the media (IPP "print-scaling")
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scaling {
+    /**
+     * Fit when the page is larger than the printable area, otherwise none
+     */
+    Auto,
+
+    /**
+     * Fit when the page is larger than the media or the size is unknown, otherwise fill
+     */
+    AutoFit,
+
+    /**
+     * Scale the page to the printable area, keeping the aspect ratio
+     */
+    Fit,
+
+    /**
+     * Scale the page to the media filling it, keeping the aspect ratio and cropping the excess
+     */
+    Fill,
+
+    /**
+     * Print the page on its actual size
+     */
+    None,
+}
+
+impl Scaling {
+    /**
+     * Returns the IPP keyword
+     */
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Scaling::Auto => "auto",
+            Scaling::AutoFit => "auto-fit",
+            Scaling::Fit => "fit",
+            Scaling::Fill => "fill",
+            Scaling::None => "none",
+        }
+    }
+
+    /**
+     * Returns the scaling of an IPP keyword
+     */
+    pub fn from_keyword(keyword: &str) -> Option<Scaling> {
+        match keyword {
+            "auto" => Some(Scaling::Auto),
+            "auto-fit" => Some(Scaling::AutoFit),
+            "fit" => Some(Scaling::Fit),
+            "fill" => Some(Scaling::Fill),
+            "none" => Some(Scaling::None),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Enum of the page orientations (IPP "orientation-requested")
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Portrait = 3,
+    Landscape = 4,
+    ReverseLandscape = 5,
+    ReversePortrait = 6,
+
+    /**
+     * The page is printed as is, without rotation
+     */
+    None = 7,
+}
+
+impl Orientation {
+    /**
+     * Returns the IPP keyword
+     */
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Orientation::Portrait => "portrait",
+            Orientation::Landscape => "landscape",
+            Orientation::ReverseLandscape => "reverse-landscape",
+            Orientation::ReversePortrait => "reverse-portrait",
+            Orientation::None => "none",
+        }
+    }
+
+    /**
+     * Returns the orientation of an IPP keyword or enum value ("landscape" or "4")
+     */
+    pub fn from_keyword(keyword: &str) -> Option<Orientation> {
+        match keyword {
+            "portrait" | "3" => Some(Orientation::Portrait),
+            "landscape" | "4" => Some(Orientation::Landscape),
+            "reverse-landscape" | "5" => Some(Orientation::ReverseLandscape),
+            "reverse-portrait" | "6" => Some(Orientation::ReversePortrait),
+            "none" | "7" => Some(Orientation::None),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Enum of the input trays (IPP "media-source", PPD "InputSlot")
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaSource {
+    Auto,
+    Main,
+    Manual,
+
+    /**
+     * Multi-purpose tray (by-pass-tray)
+     */
+    ByPassTray,
+    Envelope,
+    LargeCapacity,
+    Top,
+    Middle,
+    Bottom,
+    Side,
+    Rear,
+    Alternate,
+
+    /**
+     * Numbered tray, starting at 1 (tray-N)
+     */
+    Tray(u8),
+}
+
+/**
+ * Media sources with their IPP keyword and PPD choice, except the numbered trays
+ */
+const MEDIA_SOURCES: [(MediaSource, &str, &str); 12] = [
+    (MediaSource::Auto, "auto", "Auto"),
+    (MediaSource::Main, "main", "Main"),
+    (MediaSource::Manual, "manual", "Manual"),
+    (MediaSource::ByPassTray, "by-pass-tray", "Multipurpose"),
+    (MediaSource::Envelope, "envelope", "Envelope"),
+    (
+        MediaSource::LargeCapacity,
+        "large-capacity",
+        "LargeCapacity",
+    ),
+    (MediaSource::Top, "top", "Upper"),
+    (MediaSource::Middle, "middle", "Middle"),
+    (MediaSource::Bottom, "bottom", "Lower"),
+    (MediaSource::Side, "side", "Side"),
+    (MediaSource::Rear, "rear", "Rear"),
+    (MediaSource::Alternate, "alternate", "Alternate"),
+];
+
+impl MediaSource {
+    /**
+     * Returns the IPP keyword
+     */
+    pub fn keyword(&self) -> String {
+        match self {
+            MediaSource::Tray(number) => format!("tray-{}", number),
+            source => row(&MEDIA_SOURCES, source).1.to_string(),
+        }
+    }
+
+    /**
+     * Returns the media source of an IPP keyword
+     */
+    pub fn from_keyword(keyword: &str) -> Option<MediaSource> {
+        if let Some(number) = numbered(keyword, "tray-") {
+            return Some(MediaSource::Tray(number));
+        }
+
+        MEDIA_SOURCES
+            .iter()
+            .find(|(_, name, _)| *name == keyword)
+            .map(|(source, _, _)| *source)
+    }
+
+    /**
+     * Returns the choice of the PPD InputSlot option
+     */
+    pub fn ppd_choice(&self) -> String {
+        match self {
+            MediaSource::Tray(number) => format!("Tray{}", number),
+            source => row(&MEDIA_SOURCES, source).2.to_string(),
+        }
+    }
+
+    /**
+     * Returns the media source of a PPD InputSlot choice, with the aliases used by the drivers
+     */
+    pub fn from_ppd_choice(choice: &str) -> Option<MediaSource> {
+        if let Some(number) = numbered(&choice.to_ascii_lowercase(), "tray") {
+            return Some(MediaSource::Tray(number));
+        }
+
+        match choice.to_ascii_lowercase().as_str() {
+            "cassette" => Some(MediaSource::Main),
+            "manualfeed" => Some(MediaSource::Manual),
+            "mp" | "mptray" | "multipurposetray" | "bypass" => Some(MediaSource::ByPassTray),
+            choice => MEDIA_SOURCES
+                .iter()
+                .find(|(_, _, name)| name.eq_ignore_ascii_case(choice))
+                .map(|(source, _, _)| *source),
+        }
+    }
+}
+
+/**
+ * Enum of the output bins (IPP "output-bin", PPD "OutputBin")
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputBin {
+    Auto,
+    Top,
+    Middle,
+    Bottom,
+    Side,
+    Left,
+    Right,
+    Center,
+    Rear,
+    FaceUp,
+    FaceDown,
+    LargeCapacity,
+
+    /**
+     * Numbered bin, starting at 1 (tray-N)
+     */
+    Tray(u8),
+
+    /**
+     * Numbered stacker, starting at 1 (stacker-N)
+     */
+    Stacker(u8),
+
+    /**
+     * Numbered mailbox, starting at 1 (mailbox-N)
+     */
+    Mailbox(u8),
+}
+
+/**
+ * Output bins with their IPP keyword and PPD choice, except the numbered bins
+ */
+const OUTPUT_BINS: [(OutputBin, &str, &str); 12] = [
+    (OutputBin::Auto, "auto", "Auto"),
+    (OutputBin::Top, "top", "Upper"),
+    (OutputBin::Middle, "middle", "Middle"),
+    (OutputBin::Bottom, "bottom", "Lower"),
+    (OutputBin::Side, "side", "Side"),
+    (OutputBin::Left, "left", "Left"),
+    (OutputBin::Right, "right", "Right"),
+    (OutputBin::Center, "center", "Center"),
+    (OutputBin::Rear, "rear", "Rear"),
+    (OutputBin::FaceUp, "face-up", "FaceUp"),
+    (OutputBin::FaceDown, "face-down", "FaceDown"),
+    (OutputBin::LargeCapacity, "large-capacity", "LargeCapacity"),
+];
+
+impl OutputBin {
+    /**
+     * Returns the IPP keyword
+     */
+    pub fn keyword(&self) -> String {
+        match self {
+            OutputBin::Tray(number) => format!("tray-{}", number),
+            OutputBin::Stacker(number) => format!("stacker-{}", number),
+            OutputBin::Mailbox(number) => format!("mailbox-{}", number),
+            bin => row(&OUTPUT_BINS, bin).1.to_string(),
+        }
+    }
+
+    /**
+     * Returns the output bin of an IPP keyword
+     */
+    pub fn from_keyword(keyword: &str) -> Option<OutputBin> {
+        if let Some(number) = numbered(keyword, "tray-") {
+            return Some(OutputBin::Tray(number));
+        }
+        if let Some(number) = numbered(keyword, "stacker-") {
+            return Some(OutputBin::Stacker(number));
+        }
+        if let Some(number) = numbered(keyword, "mailbox-") {
+            return Some(OutputBin::Mailbox(number));
+        }
+
+        OUTPUT_BINS
+            .iter()
+            .find(|(_, name, _)| *name == keyword)
+            .map(|(bin, _, _)| *bin)
+    }
+
+    /**
+     * Returns the choice of the PPD OutputBin option
+     */
+    pub fn ppd_choice(&self) -> String {
+        match self {
+            OutputBin::Tray(number) => format!("Tray{}", number),
+            OutputBin::Stacker(number) => format!("Stacker{}", number),
+            OutputBin::Mailbox(number) => format!("Mailbox{}", number),
+            bin => row(&OUTPUT_BINS, bin).2.to_string(),
+        }
+    }
+
+    /**
+     * Returns the output bin of a PPD OutputBin choice
+     */
+    pub fn from_ppd_choice(choice: &str) -> Option<OutputBin> {
+        let lowercase = choice.to_ascii_lowercase();
+        if let Some(number) = numbered(&lowercase, "tray") {
+            return Some(OutputBin::Tray(number));
+        }
+        if let Some(number) = numbered(&lowercase, "stacker") {
+            return Some(OutputBin::Stacker(number));
+        }
+        if let Some(number) = numbered(&lowercase, "mailbox") {
+            return Some(OutputBin::Mailbox(number));
+        }
+
+        OUTPUT_BINS
+            .iter()
+            .find(|(_, _, name)| name.eq_ignore_ascii_case(choice))
+            .map(|(bin, _, _)| *bin)
+    }
+}
+
+/**
+ * Enum of the finishings (IPP "finishings" enum values)
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Finishing {
+    None = 3,
+    Staple = 4,
+    Punch = 5,
+    Cover = 6,
+    Bind = 7,
+    SaddleStitch = 8,
+    EdgeStitch = 9,
+    Fold = 10,
+    Trim = 11,
+    BookletMaker = 13,
+    JogOffset = 14,
+    StapleTopLeft = 20,
+    StapleBottomLeft = 21,
+    StapleTopRight = 22,
+    StapleBottomRight = 23,
+    StapleDualLeft = 28,
+    StapleDualTop = 29,
+    StapleDualRight = 30,
+    StapleDualBottom = 31,
+    BindLeft = 50,
+    BindTop = 51,
+    BindRight = 52,
+    BindBottom = 53,
+    TrimAfterPages = 60,
+    TrimAfterDocuments = 61,
+    TrimAfterCopies = 62,
+    TrimAfterJob = 63,
+    PunchDualLeft = 74,
+    PunchDualTop = 75,
+    PunchTripleLeft = 78,
+    PunchQuadLeft = 82,
+    FoldHalf = 93,
+    FoldLetter = 96,
+    FoldZ = 100,
+}
+
+/**
+ * Finishings with their IPP keyword
+ */
+const FINISHINGS: [(Finishing, &str); 34] = [
+    (Finishing::None, "none"),
+    (Finishing::Staple, "staple"),
+    (Finishing::Punch, "punch"),
+    (Finishing::Cover, "cover"),
+    (Finishing::Bind, "bind"),
+    (Finishing::SaddleStitch, "saddle-stitch"),
+    (Finishing::EdgeStitch, "edge-stitch"),
+    (Finishing::Fold, "fold"),
+    (Finishing::Trim, "trim"),
+    (Finishing::BookletMaker, "booklet-maker"),
+    (Finishing::JogOffset, "jog-offset"),
+    (Finishing::StapleTopLeft, "staple-top-left"),
+    (Finishing::StapleBottomLeft, "staple-bottom-left"),
+    (Finishing::StapleTopRight, "staple-top-right"),
+    (Finishing::StapleBottomRight, "staple-bottom-right"),
+    (Finishing::StapleDualLeft, "staple-dual-left"),
+    (Finishing::StapleDualTop, "staple-dual-top"),
+    (Finishing::StapleDualRight, "staple-dual-right"),
+    (Finishing::StapleDualBottom, "staple-dual-bottom"),
+    (Finishing::BindLeft, "bind-left"),
+    (Finishing::BindTop, "bind-top"),
+    (Finishing::BindRight, "bind-right"),
+    (Finishing::BindBottom, "bind-bottom"),
+    (Finishing::TrimAfterPages, "trim-after-pages"),
+    (Finishing::TrimAfterDocuments, "trim-after-documents"),
+    (Finishing::TrimAfterCopies, "trim-after-copies"),
+    (Finishing::TrimAfterJob, "trim-after-job"),
+    (Finishing::PunchDualLeft, "punch-dual-left"),
+    (Finishing::PunchDualTop, "punch-dual-top"),
+    (Finishing::PunchTripleLeft, "punch-triple-left"),
+    (Finishing::PunchQuadLeft, "punch-quad-left"),
+    (Finishing::FoldHalf, "fold-half"),
+    (Finishing::FoldLetter, "fold-letter"),
+    (Finishing::FoldZ, "fold-z"),
+];
+
+impl Finishing {
+    /**
+     * Returns the IPP keyword
+     */
+    pub fn keyword(&self) -> &'static str {
+        FINISHINGS
+            .iter()
+            .find(|(finishing, _)| finishing == self)
+            .map_or("none", |(_, keyword)| keyword)
+    }
+
+    /**
+     * Returns the finishing of an IPP keyword or enum value ("staple-top-left" or "20")
+     */
+    pub fn from_keyword(keyword: &str) -> Option<Finishing> {
+        let value = keyword.parse::<i32>().ok();
+        FINISHINGS
+            .iter()
+            .find(|(finishing, name)| *name == keyword || Some(*finishing as i32) == value)
+            .map(|(finishing, _)| *finishing)
+    }
+}
+
+/**
+ * Returns the number of a numbered keyword, like 2 for "tray-2" (numbers start at 1)
+ */
+fn numbered(keyword: &str, prefix: &str) -> Option<u8> {
+    keyword
+        .strip_prefix(prefix)?
+        .parse()
+        .ok()
+        .filter(|number| *number > 0)
+}
+
+/**
+ * Returns the table row of a value, every value without number has a row
+ */
+fn row<'a, T: PartialEq>(
+    table: &'a [(T, &'static str, &'static str)],
+    value: &T,
+) -> &'a (T, &'static str, &'static str) {
+    table.iter().find(|row| row.0 == *value).unwrap()
+}
@@ -1,10 +1,20 @@
+use crate::capabilities::{Capabilities, Violation};
+use crate::device_uri::DeviceUri;
+use crate::document::{Document, RawDocument};
+use crate::inspect::DocumentInfo;
+use crate::network::health::{self, HealthCheck};
+use crate::network::snmp::{self, PrinterStatus};
+use crate::ppd::Ppd;
+use crate::print_options::PrintOptions;
 use crate::printer_job::{JobState, PrintJob};
 use crate::shared::interface::{JobGetters, PlatformPrinterGetters};
+use crate::supplies::Supply;
+use std::time::Duration;
 
 /**
  * Enum of the Printer state
  */
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum PrinterState {
     /**
      * The printer is able to receive jobs (also idle)
@@ -48,7 +58,7 @@ pub struct Printer {
     pub driver_name: String,
 
     /**
-     * Uri of Print (default is empty string)
+     * Uri of Print (default is empty string), see device_uri() for the typed URI
      */
     pub uri: String,
 
@@ -133,6 +143,40 @@ impl Printer {
         return printer;
     }
 
+    /**
+     * Create a printer addressed by a device URI (like socket://host:9100 or lpd://host/queue)
+     * Documents are sent directly by the lib network backends, without the system print service
+     */
+    pub fn from_uri(uri: &str) -> Result<Printer, String> {
+        if !crate::network::is_network_uri(uri) {
+            return Err(format!("unsupported printer URI {}", uri));
+        }
+
+        let device_uri = DeviceUri::parse(uri);
+        let name = match device_uri.host() {
+            Some(host) => format!("{}:{}", host, device_uri.port().unwrap_or_default()),
+            None => uri.to_string(),
+        };
+
+        Ok(Printer {
+            name,
+            system_name: uri.to_string(),
+            driver_name: "".to_string(),
+            uri: uri.to_string(),
+            location: "".to_string(),
+            is_default: false,
+            is_shared: false,
+            state: PrinterState::UNKNOWN,
+        })
+    }
+
+    /**
+     * Returns the typed device URI of the printer
+     */
+    pub fn device_uri(&self) -> DeviceUri {
+        DeviceUri::parse(&self.uri)
+    }
+
     /**
      * Print bytes with self printer instance
      */
@@ -147,6 +191,112 @@ impl Printer {
         return crate::print_file(&self.system_name, file_path, job_name);
     }
 
+    /**
+     * Inspect a file before print_file (page count for quotas, page sizes, color use and encryption)
+     * Fails when the document can not be inspected or its format is not supported by self printer
+     */
+    pub fn preflight_file(&self, file_path: &str) -> Result<DocumentInfo, String> {
+        crate::preflight_file(&self.system_name, file_path)
+    }
+
+    /**
+     * Print bytes with self printer instance and print options
+     */
+    pub fn print_with_options(
+        &self,
+        buffer: &[u8],
+        job_name: Option<&str>,
+        options: &PrintOptions,
+    ) -> Result<bool, String> {
+        crate::print_with_options(&self.system_name, buffer, job_name, options)
+    }
+
+    /**
+     * Print specific file with self printer instance and print options
+     */
+    pub fn print_file_with_options(
+        &self,
+        file_path: &str,
+        job_name: Option<&str>,
+        options: &PrintOptions,
+    ) -> Result<bool, String> {
+        crate::print_file_with_options(&self.system_name, file_path, job_name, options)
+    }
+
+    /**
+     * Return the values supported by self printer for the job attributes (media, sides, color modes...)
+     */
+    pub fn capabilities(&self) -> Capabilities {
+        crate::get_capabilities(&self.system_name)
+    }
+
+    /**
+     * Return the PPD file of self printer, with the driver options (CUPS queues with a PPD driver)
+     */
+    pub fn ppd(&self) -> Result<Ppd, String> {
+        crate::get_ppd(&self.system_name)
+    }
+
+    /**
+     * Return the supplies of self printer (toner, ink, waste containers, drums) with their levels
+     */
+    pub fn supplies(&self) -> Vec<Supply> {
+        crate::get_supplies(&self.system_name)
+    }
+
+    /**
+     * Return the status of self printer read with SNMP from the host of its device URI
+     * (state, state reasons, alerts, page counter and supplies), for printers that do not report it to CUPS
+     */
+    pub fn snmp_status(&self) -> Result<PrinterStatus, String> {
+        snmp::status_from_uri(&self.uri)
+    }
+
+    /**
+     * Check if self printer is reachable with its device URI (a TCP connection for socket:// and lpd://,
+     * a Get-Printer-Attributes request for ipp:// and ipps://), with the latency and a diagnosis
+     * For print queues, the state of the queue and printer-is-accepting-jobs are checked too
+     */
+    pub fn health_check(&self, timeout: Duration) -> HealthCheck {
+        if crate::network::is_network_uri(&self.system_name) {
+            return health::check(&self.uri, timeout);
+        }
+
+        let mut queue = crate::get_queue_state(&self.system_name);
+        if queue.first("printer-state").is_none() {
+            let state = match self.state {
+                PrinterState::READY => "3",
+                PrinterState::PRINTING => "4",
+                PrinterState::PAUSED => "5",
+                PrinterState::UNKNOWN => "",
+            };
+            if !state.is_empty() {
+                queue.set("printer-state", &[state]);
+            }
+        }
+
+        health::check_queue(&self.uri, &queue, timeout)
+    }
+
+    /**
+     * Validate print options and a document against self printer capabilities, before printing
+     * Returns every violation with the nearest supported value (empty when the job is valid)
+     */
+    pub fn validate(
+        &self,
+        options: &PrintOptions,
+        document: &Document,
+    ) -> Result<Vec<Violation>, String> {
+        crate::validate(&self.system_name, options, document)
+    }
+
+    /**
+     * Print raw bytes (ZPL, ESC/POS, PCL...) with self printer instance bypassing the print system filters
+     */
+    pub fn print_raw(&self, buffer: &[u8], job_name: Option<&str>) -> Result<bool, String> {
+        crate::print_raw(&self.system_name, &RawDocument::from(buffer), job_name)
+    }
+
     /**
      * Return all jobs in print queue
      */
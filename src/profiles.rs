@@ -0,0 +1,505 @@
+//! Named print profiles (presets like "label-4x6" or "invoice-duplex") loaded from a TOML or JSON
+//! config file, with printer aliases and per-printer default options
+//!
+//! ```toml
+//! [printers.office]
+//! system_name = "HP_LaserJet_M404"
+//! options = "media=A4 print-color-mode=monochrome"
+//!
+//! [profiles.invoice-duplex]
+//! printer = "office"
+//! format = "pdf"
+//! copies = 2
+//! options = { sides = "two-sided-long-edge", finishings = "staple-top-left" }
+//! ```
+//!
+//! The options of a job printed with a profile are merged in this order, each step replacing the
+//! options of the previous ones with the same name:
+//! 1. the options of the printer entry (per-printer defaults)
+//! 2. the options of the profile (an `lp -o` option string or a table)
+//! 3. the format and copies of the profile
+//! 4. the job overrides (the options given when printing)
+//!
+//! Options that are still unset use the defaults of the printer queue.
+//! Printers supporting IPP job presets (job-presets-supported) also provide built-in profiles,
+//! named with the printer and the preset, like "office/draft".
+//!
+//! ```rust
+//! use printers::print_options::{PrintOptions, Sides};
+//! use printers::profiles::ProfileRegistry;
+//!
+//! let registry = ProfileRegistry::from_toml(
+//!     r#"
+//!     [printers.office]
+//!     system_name = "HP_LaserJet_M404"
+//!     options = "media=A4 sides=two-sided-long-edge"
+//!
+//!     [profiles.draft-mono]
+//!     printer = "office"
+//!     options = "print-quality=draft print-color-mode=monochrome"
+//!     "#,
+//! )
+//! .unwrap();
+//!
+//! let overrides = PrintOptions {
+//!     sides: Some(Sides::OneSided),
+//!     ..PrintOptions::default()
+//! };
+//! let (system_name, options) = registry.resolve("draft-mono", &overrides).unwrap();
+//!
+//! assert_eq!(system_name, "HP_LaserJet_M404");
+//! assert_eq!(options.sides, Some(Sides::OneSided));
+//! ```
+
+use crate::document::DocumentFormat;
+use crate::lp_options;
+use crate::print_options::PrintOptions;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/**
+ * Registry installed with set_registry, used by printers::print_with_profile
+ */
+static REGISTRY: Mutex<Option<ProfileRegistry>> = Mutex::new(None);
+
+/**
+ * Named print profile, a printer and the options of its jobs
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    /**
+     * Name of the profile, like "invoice-duplex"
+     */
+    pub name: String,
+
+    /**
+     * Printer alias or system name
+     */
+    pub printer: String,
+
+    /**
+     * Format of the documents (when empty the format is detected from the document content)
+     */
+    pub format: Option<DocumentFormat>,
+
+    /**
+     * Number of copies
+     */
+    pub copies: Option<u32>,
+
+    /**
+     * Job options as IPP or CUPS names and values (see PrintOptions::from_options)
+     */
+    pub options: Vec<(String, String)>,
+}
+
+impl Profile {
+    /**
+     * Create a profile of a printer without options
+     */
+    pub fn new(name: &str, printer: &str) -> Profile {
+        Profile {
+            name: name.to_string(),
+            printer: printer.to_string(),
+            format: None,
+            copies: None,
+            options: Vec::new(),
+        }
+    }
+
+    /**
+     * Create the built-in profile of an IPP job preset of a printer, from a job-presets-supported
+     * value like "{preset-name=draft print-quality=3}" (returns None without a preset name)
+     */
+    pub fn from_job_preset(printer: &str, preset: &str) -> Option<Profile> {
+        let members = preset.trim().strip_prefix('{')?.strip_suffix('}')?;
+
+        let mut profile = Profile::new("", printer);
+        for (name, value) in lp_options::parse(members) {
+            if name == "preset-name" {
+                profile.name = format!("{}/{}", printer, value);
+            } else {
+                profile.options.push((name, value));
+            }
+        }
+
+        if profile.name.is_empty() {
+            None
+        } else {
+            Some(profile)
+        }
+    }
+}
+
+/**
+ * Registry of the print profiles and the printer aliases of a config file
+ */
+#[derive(Debug, Clone, Default)]
+pub struct ProfileRegistry {
+    aliases: BTreeMap<String, String>,
+    defaults: BTreeMap<String, Vec<(String, String)>>,
+    profiles: BTreeMap<String, Profile>,
+}
+
+impl ProfileRegistry {
+    /**
+     * Load the registry of a TOML config
+     */
+    pub fn from_toml(text: &str) -> Result<ProfileRegistry, String> {
+        let config: Config =
+            toml::from_str(text).map_err(|error| format!("invalid profiles config: {}", error))?;
+        ProfileRegistry::from_config(config)
+    }
+
+    /**
+     * Load the registry of a JSON config, with the same structure as the TOML config
+     */
+    pub fn from_json(text: &str) -> Result<ProfileRegistry, String> {
+        let config: Config = serde_json::from_str(text)
+            .map_err(|error| format!("invalid profiles config: {}", error))?;
+        ProfileRegistry::from_config(config)
+    }
+
+    /**
+     * Load the registry of a config file, JSON when the file extension is .json, otherwise TOML
+     */
+    pub fn from_file(file_path: &str) -> Result<ProfileRegistry, String> {
+        let text = fs::read_to_string(file_path)
+            .map_err(|error| format!("failed to read {}: {}", file_path, error))?;
+
+        match Path::new(file_path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+        {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => {
+                ProfileRegistry::from_json(&text)
+            }
+            _ => ProfileRegistry::from_toml(&text),
+        }
+    }
+
+    /**
+     * Load the registry of the config file given by the PRINTERS_PROFILES environment variable,
+     * or of printers/profiles.toml (or profiles.json) in the user config directory
+     * ($XDG_CONFIG_HOME or ~/.config on unix, %APPDATA% on windows)
+     * Returns an empty registry when there is no config file
+     */
+    pub fn load() -> Result<ProfileRegistry, String> {
+        if let Ok(file_path) = env::var("PRINTERS_PROFILES") {
+            return ProfileRegistry::from_file(&file_path);
+        }
+
+        let directory = match config_directory() {
+            Some(directory) => directory.join("printers"),
+            None => return Ok(ProfileRegistry::default()),
+        };
+
+        for file_name in ["profiles.toml", "profiles.json"].iter() {
+            let file_path = directory.join(file_name);
+            if file_path.is_file() {
+                return ProfileRegistry::from_file(&file_path.to_string_lossy());
+            }
+        }
+
+        Ok(ProfileRegistry::default())
+    }
+
+    /**
+     * Add a printer alias
+     */
+    pub fn alias(&mut self, alias: &str, system_name: &str) -> &mut Self {
+        self.aliases
+            .insert(alias.to_string(), system_name.to_string());
+        self
+    }
+
+    /**
+     * Set the default options of a printer, by alias or system name
+     */
+    pub fn defaults(&mut self, printer: &str, options: Vec<(String, String)>) -> &mut Self {
+        self.defaults.insert(printer.to_string(), options);
+        self
+    }
+
+    /**
+     * Add a profile, replacing the profile with the same name
+     */
+    pub fn add(&mut self, profile: Profile) -> &mut Self {
+        self.profiles.insert(profile.name.clone(), profile);
+        self
+    }
+
+    /**
+     * Returns the profile with a name
+     */
+    pub fn profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.get(name)
+    }
+
+    /**
+     * Returns the profiles of the registry (without the built-in profiles), sorted by name
+     */
+    pub fn profiles(&self) -> Vec<&Profile> {
+        self.profiles.values().collect()
+    }
+
+    /**
+     * Returns the system name of a printer alias (other names are returned as is)
+     */
+    pub fn system_name(&self, printer: &str) -> String {
+        self.aliases
+            .get(printer)
+            .cloned()
+            .unwrap_or_else(|| printer.to_string())
+    }
+
+    /**
+     * Returns the built-in profiles of the IPP job presets of a printer, by alias or system name
+     */
+    pub fn job_presets(&self, printer: &str) -> Vec<Profile> {
+        crate::get_supported_values(&self.system_name(printer), &["job-presets"])
+            .supported("job-presets")
+            .iter()
+            .filter_map(|preset| Profile::from_job_preset(printer, preset))
+            .collect()
+    }
+
+    /**
+     * Resolve a profile (or a built-in profile like "office/draft") to the system name of its
+     * printer and the merged print options, in the precedence order of the module documentation
+     */
+    pub fn resolve(
+        &self,
+        name: &str,
+        overrides: &PrintOptions,
+    ) -> Result<(String, PrintOptions), String> {
+        let profile = match self.profiles.get(name) {
+            Some(profile) => profile.clone(),
+            None => self.job_preset(name)?,
+        };
+
+        let system_name = self.system_name(&profile.printer);
+        let mut options = Vec::new();
+
+        let defaults = self
+            .defaults
+            .get(&profile.printer)
+            .or_else(|| self.defaults.get(&system_name))
+            .into_iter()
+            .flatten();
+
+        let copies = profile
+            .copies
+            .map(|copies| ("copies".to_string(), copies.to_string()));
+
+        for (option, value) in defaults
+            .chain(profile.options.iter())
+            .chain(copies.iter())
+            .chain(overrides.ipp_attributes().iter())
+        {
+            lp_options::add(&mut options, option, value);
+        }
+
+        let mut print_options = PrintOptions::from_options(&options);
+        if let Some(format) = overrides.document_format.or(profile.format) {
+            print_options.document_format = Some(format);
+        }
+
+        Ok((system_name, print_options))
+    }
+
+    /**
+     * Print bytes with a profile and job overrides
+     */
+    pub fn print(
+        &self,
+        name: &str,
+        buffer: &[u8],
+        job_name: Option<&str>,
+        overrides: &PrintOptions,
+    ) -> Result<bool, String> {
+        let (system_name, options) = self.resolve(name, overrides)?;
+        crate::print_with_options(&system_name, buffer, job_name, &options)
+    }
+
+    /**
+     * Returns the built-in profile of a "printer/preset" name
+     */
+    fn job_preset(&self, name: &str) -> Result<Profile, String> {
+        let unknown = || format!("unknown print profile {}", name);
+        let (printer, _) = name.rsplit_once('/').ok_or_else(unknown)?;
+
+        self.job_presets(printer)
+            .into_iter()
+            .find(|profile| profile.name == name)
+            .ok_or_else(unknown)
+    }
+
+    /**
+     * Create the registry of a deserialized config
+     */
+    fn from_config(config: Config) -> Result<ProfileRegistry, String> {
+        let mut registry = ProfileRegistry::default();
+
+        for (alias, printer) in config.printers {
+            if let Some(system_name) = &printer.system_name {
+                registry.alias(&alias, system_name);
+            }
+            if let Some(options) = printer.options {
+                registry.defaults(&alias, options.into_pairs());
+            }
+        }
+
+        for (name, entry) in config.profiles {
+            let format = match &entry.format {
+                Some(format) => match parse_format(format)? {
+                    DocumentFormat::AUTO => None,
+                    format => Some(format),
+                },
+                None => None,
+            };
+
+            registry.add(Profile {
+                name: name.clone(),
+                printer: entry.printer,
+                format,
+                copies: entry.copies,
+                options: entry
+                    .options
+                    .map(ConfigOptions::into_pairs)
+                    .unwrap_or_default(),
+            });
+        }
+
+        Ok(registry)
+    }
+}
+
+/**
+ * Install the registry used by printers::print_with_profile (otherwise ProfileRegistry::load is used)
+ */
+pub fn set_registry(registry: ProfileRegistry) {
+    if let Ok(mut installed) = REGISTRY.lock() {
+        *installed = Some(registry);
+    }
+}
+
+/**
+ * Returns the installed registry, or the registry of the config file
+ */
+pub fn registry() -> Result<ProfileRegistry, String> {
+    if let Ok(installed) = REGISTRY.lock() {
+        if let Some(registry) = installed.as_ref() {
+            return Ok(registry.clone());
+        }
+    }
+    ProfileRegistry::load()
+}
+
+/**
+ * Config file structure
+ */
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Config {
+    printers: BTreeMap<String, ConfigPrinter>,
+    profiles: BTreeMap<String, ConfigProfile>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigPrinter {
+    system_name: Option<String>,
+    options: Option<ConfigOptions>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigProfile {
+    printer: String,
+    format: Option<String>,
+    copies: Option<u32>,
+    options: Option<ConfigOptions>,
+}
+
+/**
+ * Options of the config, an `lp -o` option string or a table of names and values
+ */
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ConfigOptions {
+    Text(String),
+    Table(BTreeMap<String, ConfigValue>),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ConfigValue {
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl ConfigOptions {
+    fn into_pairs(self) -> Vec<(String, String)> {
+        match self {
+            ConfigOptions::Text(text) => lp_options::parse(&text),
+            ConfigOptions::Table(table) => table
+                .into_iter()
+                .map(|(name, value)| {
+                    let value = match value {
+                        ConfigValue::Bool(value) => value.to_string(),
+                        ConfigValue::Integer(value) => value.to_string(),
+                        ConfigValue::Float(value) => value.to_string(),
+                        ConfigValue::Text(value) => value,
+                    };
+                    (name, value)
+                })
+                .collect(),
+        }
+    }
+}
+
+/**
+ * Parse a document format name (like "pdf" or "zpl") or MIME type
+ */
+fn parse_format(format: &str) -> Result<DocumentFormat, String> {
+    let document_format = match format.to_ascii_lowercase().as_str() {
+        "auto" | "application/octet-stream" => DocumentFormat::AUTO,
+        "pdf" => DocumentFormat::PDF,
+        "ps" | "postscript" => DocumentFormat::POSTSCRIPT,
+        "pcl" => DocumentFormat::PCL,
+        "pwg" => DocumentFormat::PWG,
+        "urf" => DocumentFormat::URF,
+        "jpg" | "jpeg" => DocumentFormat::JPEG,
+        "png" => DocumentFormat::PNG,
+        "text" | "txt" => DocumentFormat::TEXT,
+        "zpl" => DocumentFormat::ZPL,
+        "escpos" => DocumentFormat::ESCPOS,
+        "raw" => DocumentFormat::RAW,
+        mime_type => match DocumentFormat::from_mime_type(mime_type) {
+            DocumentFormat::AUTO => return Err(format!("unknown document format {}", format)),
+            document_format => document_format,
+        },
+    };
+    Ok(document_format)
+}
+
+/**
+ * Returns the user config directory
+ */
+fn config_directory() -> Option<PathBuf> {
+    if cfg!(target_family = "windows") {
+        return env::var_os("APPDATA").map(PathBuf::from);
+    }
+
+    match env::var_os("XDG_CONFIG_HOME") {
+        Some(directory) if !directory.is_empty() => Some(PathBuf::from(directory)),
+        _ => env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")),
+    }
+}
@@ -0,0 +1,565 @@
+//! PWG Raster (image/pwg-raster) and Apple Raster (image/urf) encoders for driverless printing
+//! IPP Everywhere and AirPrint printers accept the encoded pages without any CUPS filter,
+//! send them with Printer::print (the format is detected from the stream signature)
+//!
+//! ```rust
+//! use printers::pwg::{self, ColorSpace, PageHeader, RasterPage};
+//!
+//! let header = PageHeader::default();
+//! let mut page = RasterPage::new(2480, 3508, ColorSpace::Sgray8);
+//! page.data[..2480].fill(0);
+//!
+//! let bytes = pwg::encode_pwg(&header, &[page]).unwrap();
+//! assert_eq!(&bytes[..4], b"RaS2");
+//! ```
+
+use crate::print_options::{PrintQuality, Sides};
+use crate::raster::Bitmap;
+
+const PWG_SYNC: &[u8] = b"RaS2";
+const PWG_HEADER_NAME: &str = "PwgRaster";
+const URF_SYNC: &[u8] = b"UNIRAST\0";
+const PWG_HEADER_SIZE: usize = 1796;
+const URF_HEADER_SIZE: usize = 32;
+
+/**
+ * Largest decoded page accepted (a letter page at 1200 dpi in sRGB takes 404 MB)
+ */
+const MAX_PAGE_BYTES: usize = 1 << 30;
+
+/**
+ * Color space and depth of the page pixels
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSpace {
+    /**
+     * Red, green and blue bytes per pixel (255 is white)
+     */
+    Srgb8,
+
+    /**
+     * One luminance byte per pixel (255 is white)
+     */
+    Sgray8,
+
+    /**
+     * One bit per pixel packed MSB first, each row padded to a whole byte (1 is black),
+     * only supported by PWG raster
+     */
+    Black1,
+}
+
+impl ColorSpace {
+    /**
+     * Returns the number of bits of each pixel
+     */
+    pub fn bits_per_pixel(&self) -> u32 {
+        match self {
+            ColorSpace::Srgb8 => 24,
+            ColorSpace::Sgray8 => 8,
+            ColorSpace::Black1 => 1,
+        }
+    }
+
+    /**
+     * Returns the number of bytes of a row of pixels
+     */
+    pub fn row_bytes(&self, width: u32) -> usize {
+        (width as usize * self.bits_per_pixel() as usize).div_ceil(8)
+    }
+
+    /**
+     * Returns the byte value of a white pixel
+     */
+    fn white(&self) -> u8 {
+        match self {
+            ColorSpace::Black1 => 0x00,
+            _ => 0xff,
+        }
+    }
+
+    /**
+     * Returns the number of bytes compressed as a unit (whole bytes for 1-bit pixels)
+     */
+    fn unit(&self) -> usize {
+        (self.bits_per_pixel() as usize / 8).max(1)
+    }
+}
+
+/**
+ * RasterPage is an uncompressed page at the header resolution, rows without padding
+ * (except 1-bit rows padded to a whole byte)
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RasterPage {
+    pub width: u32,
+    pub height: u32,
+    pub color_space: ColorSpace,
+    pub data: Vec<u8>,
+}
+
+impl RasterPage {
+    /**
+     * Create a white page
+     */
+    pub fn new(width: u32, height: u32, color_space: ColorSpace) -> RasterPage {
+        RasterPage {
+            width,
+            height,
+            color_space,
+            data: vec![color_space.white(); color_space.row_bytes(width) * height as usize],
+        }
+    }
+
+    /**
+     * Create a 1-bit page from a bitmap made by the raster module
+     */
+    pub fn from_bitmap(bitmap: &Bitmap) -> RasterPage {
+        RasterPage {
+            width: bitmap.width,
+            height: bitmap.height,
+            color_space: ColorSpace::Black1,
+            data: bitmap.data.clone(),
+        }
+    }
+
+    /**
+     * Returns the number of bytes of each row
+     */
+    pub fn row_bytes(&self) -> usize {
+        self.color_space.row_bytes(self.width)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 {
+            return Err(format!("invalid page size {}x{}", self.width, self.height));
+        }
+
+        let expected = self.row_bytes() * self.height as usize;
+        if self.data.len() != expected {
+            return Err(format!(
+                "page must have {} bytes for {}x{} {:?} pixels, got {}",
+                expected,
+                self.width,
+                self.height,
+                self.color_space,
+                self.data.len()
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/**
+ * Metadata written on the header of every page
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageHeader {
+    /**
+     * Resolution in dots per inch, on both axes
+     */
+    pub resolution: u32,
+
+    /**
+     * PWG media size name, like "iso_a4_210x297mm" (PWG raster only)
+     */
+    pub media_size_name: String,
+
+    /**
+     * Media type keyword, like "stationery" (PWG raster only)
+     */
+    pub media_type: String,
+
+    /**
+     * Duplex mode
+     */
+    pub sides: Sides,
+
+    /**
+     * Number of copies (PWG raster only, URF copies are given as job options)
+     */
+    pub copies: u32,
+
+    /**
+     * Print quality
+     */
+    pub quality: PrintQuality,
+}
+
+impl Default for PageHeader {
+    fn default() -> Self {
+        PageHeader {
+            resolution: 300,
+            media_size_name: "iso_a4_210x297mm".to_string(),
+            media_type: "stationery".to_string(),
+            sides: Sides::OneSided,
+            copies: 1,
+            quality: PrintQuality::Normal,
+        }
+    }
+}
+
+/**
+ * Encode pages as a PWG raster stream (PWG 5102.4)
+ */
+pub fn encode_pwg(header: &PageHeader, pages: &[RasterPage]) -> Result<Vec<u8>, String> {
+    validate_header(header)?;
+
+    for text in [&header.media_size_name, &header.media_type] {
+        if text.len() > 63 {
+            return Err(format!("header string too long: {}", text));
+        }
+    }
+
+    let mut stream = PWG_SYNC.to_vec();
+
+    for page in pages {
+        page.validate()?;
+
+        let mut bytes = [0u8; PWG_HEADER_SIZE];
+        let mut put = |offset: usize, value: u32| {
+            bytes[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+        };
+
+        let (color_space, colors) = match page.color_space {
+            ColorSpace::Srgb8 => (19, 3),
+            ColorSpace::Sgray8 => (18, 1),
+            ColorSpace::Black1 => (3, 1),
+        };
+        let bits = page.color_space.bits_per_pixel();
+
+        put(272, (header.sides != Sides::OneSided) as u32);
+        put(276, header.resolution);
+        put(280, header.resolution);
+        put(340, header.copies.max(1));
+        put(352, points(page.width, header.resolution));
+        put(356, points(page.height, header.resolution));
+        put(368, (header.sides == Sides::TwoSidedShortEdge) as u32);
+        put(372, page.width);
+        put(376, page.height);
+        put(384, bits / colors);
+        put(388, bits);
+        put(392, page.row_bytes() as u32);
+        put(400, color_space);
+        put(420, colors);
+        put(452, pages.len() as u32);
+        put(456, 1);
+        put(460, 1);
+        put(484, header.quality as u32);
+
+        put_string(&mut bytes, 0, PWG_HEADER_NAME);
+        put_string(&mut bytes, 128, &header.media_type);
+        put_string(&mut bytes, 1732, &header.media_size_name);
+
+        stream.extend_from_slice(&bytes);
+        compress(page, &mut stream);
+    }
+
+    Ok(stream)
+}
+
+/**
+ * Encode pages as an Apple raster (URF) stream, only 8 bits gray and RGB pages are supported
+ */
+pub fn encode_urf(header: &PageHeader, pages: &[RasterPage]) -> Result<Vec<u8>, String> {
+    validate_header(header)?;
+
+    let mut stream = URF_SYNC.to_vec();
+    stream.extend_from_slice(&(pages.len() as u32).to_be_bytes());
+
+    for page in pages {
+        page.validate()?;
+
+        let color_space = match page.color_space {
+            ColorSpace::Sgray8 => 0,
+            ColorSpace::Srgb8 => 1,
+            ColorSpace::Black1 => return Err("URF does not support 1-bit pages".to_string()),
+        };
+
+        let mut bytes = [0u8; URF_HEADER_SIZE];
+        bytes[0] = page.color_space.bits_per_pixel() as u8;
+        bytes[1] = color_space;
+        bytes[2] = match header.sides {
+            Sides::OneSided => 1,
+            Sides::TwoSidedShortEdge => 2,
+            Sides::TwoSidedLongEdge => 3,
+        };
+        bytes[3] = header.quality as u8;
+        bytes[12..16].copy_from_slice(&page.width.to_be_bytes());
+        bytes[16..20].copy_from_slice(&page.height.to_be_bytes());
+        bytes[20..24].copy_from_slice(&header.resolution.to_be_bytes());
+
+        stream.extend_from_slice(&bytes);
+        compress(page, &mut stream);
+    }
+
+    Ok(stream)
+}
+
+/**
+ * Decode a PWG raster stream to the header and pixels of each page
+ */
+pub fn decode_pwg(bytes: &[u8]) -> Result<Vec<(PageHeader, RasterPage)>, String> {
+    if !bytes.starts_with(PWG_SYNC) {
+        return Err("not a PWG raster stream".to_string());
+    }
+
+    let mut pages = Vec::new();
+    let mut position = PWG_SYNC.len();
+
+    while position < bytes.len() {
+        let header = bytes
+            .get(position..position + PWG_HEADER_SIZE)
+            .ok_or("truncated PWG page header")?;
+        position += PWG_HEADER_SIZE;
+
+        if get_string(header, 0) != PWG_HEADER_NAME {
+            return Err("invalid PWG page header, PwgRaster is missing".to_string());
+        }
+
+        let get = |offset: usize| read_u32(header, offset);
+
+        let color_space = match (get(400), get(388)) {
+            (19, 24) => ColorSpace::Srgb8,
+            (18, 8) => ColorSpace::Sgray8,
+            (3, 1) => ColorSpace::Black1,
+            (space, bits) => {
+                return Err(format!(
+                    "unsupported PWG color space {} with {} bits per pixel",
+                    space, bits
+                ))
+            }
+        };
+
+        let sides = match (get(272), get(368)) {
+            (0, _) => Sides::OneSided,
+            (_, 0) => Sides::TwoSidedLongEdge,
+            _ => Sides::TwoSidedShortEdge,
+        };
+
+        let page_header = PageHeader {
+            resolution: get(276),
+            media_size_name: get_string(header, 1732),
+            media_type: get_string(header, 128),
+            sides,
+            copies: get(340).max(1),
+            quality: PrintQuality::from_keyword(&get(484).to_string())
+                .unwrap_or(PrintQuality::Normal),
+        };
+
+        let page = decompress(bytes, &mut position, get(372), get(376), color_space)?;
+        if page.row_bytes() != get(392) as usize {
+            return Err(format!("invalid PWG bytes per line {}", get(392)));
+        }
+
+        pages.push((page_header, page));
+    }
+
+    Ok(pages)
+}
+
+/**
+ * Decode an Apple raster (URF) stream to the header and pixels of each page
+ * (media size, media type and copies are not stored on URF and keep their default values)
+ */
+pub fn decode_urf(bytes: &[u8]) -> Result<Vec<(PageHeader, RasterPage)>, String> {
+    if !bytes.starts_with(URF_SYNC) || bytes.len() < URF_SYNC.len() + 4 {
+        return Err("not an URF stream".to_string());
+    }
+
+    let mut position = URF_SYNC.len();
+    let count = read_u32(bytes, position);
+    position += 4;
+
+    let mut pages = Vec::new();
+
+    for _ in 0..count {
+        let header = bytes
+            .get(position..position + URF_HEADER_SIZE)
+            .ok_or("truncated URF page header")?;
+        position += URF_HEADER_SIZE;
+
+        let get = |offset: usize| read_u32(header, offset);
+
+        let color_space = match (header[1], header[0]) {
+            (0, 8) => ColorSpace::Sgray8,
+            (1, 24) => ColorSpace::Srgb8,
+            (space, bits) => {
+                return Err(format!(
+                    "unsupported URF color space {} with {} bits per pixel",
+                    space, bits
+                ))
+            }
+        };
+
+        let sides = match header[2] {
+            2 => Sides::TwoSidedShortEdge,
+            3 => Sides::TwoSidedLongEdge,
+            _ => Sides::OneSided,
+        };
+
+        let page_header = PageHeader {
+            resolution: get(20),
+            sides,
+            quality: PrintQuality::from_keyword(&header[3].to_string())
+                .unwrap_or(PrintQuality::Normal),
+            ..PageHeader::default()
+        };
+
+        let page = decompress(bytes, &mut position, get(12), get(16), color_space)?;
+        pages.push((page_header, page));
+    }
+
+    Ok(pages)
+}
+
+fn validate_header(header: &PageHeader) -> Result<(), String> {
+    if header.resolution == 0 {
+        return Err("invalid resolution 0".to_string());
+    }
+
+    Ok(())
+}
+
+/**
+ * Returns the size in points (1/72 inch) of a number of pixels
+ */
+fn points(pixels: u32, resolution: u32) -> u32 {
+    ((pixels as u64 * 72 + resolution as u64 / 2) / resolution as u64) as u32
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+fn put_string(bytes: &mut [u8], offset: usize, text: &str) {
+    bytes[offset..offset + text.len()].copy_from_slice(text.as_bytes());
+}
+
+fn get_string(bytes: &[u8], offset: usize) -> String {
+    let field = &bytes[offset..offset + 64];
+    let end = field.iter().position(|&byte| byte == 0).unwrap_or(64);
+    String::from_utf8_lossy(&field[..end]).to_string()
+}
+
+/**
+ * Compress the page rows: a line repeat count byte (repeats minus one) followed by the
+ * row as runs, 0 to 127 repeats the next unit (count minus one), 129 to 255 copies
+ * 257 minus count literal units
+ */
+fn compress(page: &RasterPage, stream: &mut Vec<u8>) {
+    let row_bytes = page.row_bytes();
+    let unit = page.color_space.unit();
+    let rows: Vec<&[u8]> = page.data.chunks_exact(row_bytes).collect();
+    let mut y = 0;
+
+    while y < rows.len() {
+        let mut repeat = 1;
+        while repeat < 256 && y + repeat < rows.len() && rows[y + repeat] == rows[y] {
+            repeat += 1;
+        }
+
+        stream.push((repeat - 1) as u8);
+
+        let units: Vec<&[u8]> = rows[y].chunks_exact(unit).collect();
+        let mut x = 0;
+
+        while x < units.len() {
+            let mut run = 1;
+            while run < 128 && x + run < units.len() && units[x + run] == units[x] {
+                run += 1;
+            }
+
+            if run > 1 {
+                stream.push((run - 1) as u8);
+                stream.extend_from_slice(units[x]);
+                x += run;
+                continue;
+            }
+
+            let mut literal = 1;
+            while literal < 128
+                && x + literal < units.len()
+                && (x + literal + 1 >= units.len() || units[x + literal] != units[x + literal + 1])
+            {
+                literal += 1;
+            }
+
+            stream.push((257 - literal) as u8);
+            for bytes in &units[x..x + literal] {
+                stream.extend_from_slice(bytes);
+            }
+            x += literal;
+        }
+
+        y += repeat;
+    }
+}
+
+/**
+ * Decompress the rows of a page starting at position, which is moved past the page data
+ */
+fn decompress(
+    bytes: &[u8],
+    position: &mut usize,
+    width: u32,
+    height: u32,
+    color_space: ColorSpace,
+) -> Result<RasterPage, String> {
+    // The size comes from the stream, it is checked before the page is allocated
+    match color_space.row_bytes(width).checked_mul(height as usize) {
+        Some(size) if size > 0 && size <= MAX_PAGE_BYTES => {}
+        _ => return Err(format!("invalid raster page size {}x{}", width, height)),
+    }
+
+    let mut page = RasterPage::new(width, height, color_space);
+    let row_bytes = page.row_bytes();
+    let unit = color_space.unit();
+    let truncated = || "truncated raster data".to_string();
+    let mut next = |count: usize| -> Result<&[u8], String> {
+        let slice = bytes
+            .get(*position..*position + count)
+            .ok_or_else(truncated)?;
+        *position += count;
+        Ok(slice)
+    };
+
+    let mut y = 0;
+    while y < height as usize {
+        let repeat = next(1)?[0] as usize + 1;
+        let mut row = Vec::with_capacity(row_bytes);
+
+        while row.len() < row_bytes {
+            let code = next(1)?[0];
+
+            match code {
+                0..=127 => {
+                    let pixel = next(unit)?;
+                    for _ in 0..=code {
+                        row.extend_from_slice(pixel);
+                    }
+                }
+                128 => row.resize(row_bytes, color_space.white()),
+                _ => row.extend_from_slice(next((257 - code as usize) * unit)?),
+            }
+        }
+
+        if row.len() != row_bytes {
+            return Err(format!("raster row overflow at line {}", y));
+        }
+
+        for _ in 0..repeat.min(height as usize - y) {
+            let start = y * row_bytes;
+            page.data[start..start + row_bytes].copy_from_slice(&row);
+            y += 1;
+        }
+    }
+
+    Ok(page)
+}
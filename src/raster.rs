@@ -0,0 +1,380 @@
+//! Image rasterization for thermal and label printers
+//! Converts RGB/grayscale pixels to 1-bit bitmaps at the printer dot width, ready for
+//! EscPos::bitmap (GS v 0) and Zpl::bitmap (^GF)
+//!
+//! ```rust
+//! use printers::raster::{rasterize, Dither, PixelFormat};
+//!
+//! // 2x2 RGB image scaled to 4 dots of width
+//! let pixels = [0, 0, 0, 255, 255, 255, 255, 255, 255, 0, 0, 0];
+//! let bitmap = rasterize(&pixels, 2, 2, PixelFormat::Rgb, 4, Dither::FloydSteinberg).unwrap();
+//!
+//! assert_eq!((bitmap.width, bitmap.height), (4, 4));
+//! ```
+
+/**
+ * Layout of the pixels given to the rasterization (8 bits per channel, rows without padding)
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    /**
+     * One luminance byte per pixel
+     */
+    Gray,
+
+    /**
+     * Red, green and blue bytes per pixel
+     */
+    Rgb,
+
+    /**
+     * Red, green, blue and alpha bytes per pixel (transparent pixels are white)
+     */
+    Rgba,
+}
+
+impl PixelFormat {
+    /**
+     * Returns the number of bytes of each pixel
+     */
+    pub fn channels(&self) -> usize {
+        match self {
+            PixelFormat::Gray => 1,
+            PixelFormat::Rgb => 3,
+            PixelFormat::Rgba => 4,
+        }
+    }
+}
+
+/**
+ * Method used to reduce the grayscale pixels to black and white dots
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dither {
+    /**
+     * Pixels darker than the level are black
+     */
+    Threshold(u8),
+
+    /**
+     * Floyd–Steinberg error diffusion
+     */
+    FloydSteinberg,
+
+    /**
+     * Atkinson error diffusion (spreads 3/4 of the error, keeping more contrast)
+     */
+    Atkinson,
+
+    /**
+     * Ordered dithering with a 4x4 Bayer matrix
+     */
+    Bayer4,
+
+    /**
+     * Ordered dithering with an 8x8 Bayer matrix
+     */
+    Bayer8,
+}
+
+/**
+ * Bitmap is a 1-bit image packed one bit per dot (1 is black), MSB first,
+ * each row padded to a whole byte
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bitmap {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl Bitmap {
+    /**
+     * Create a white bitmap
+     */
+    pub fn new(width: u32, height: u32) -> Bitmap {
+        let row_bytes = width.div_ceil(8) as usize;
+        Bitmap {
+            width,
+            height,
+            data: vec![0; row_bytes * height as usize],
+        }
+    }
+
+    /**
+     * Returns the number of bytes of each row
+     */
+    pub fn row_bytes(&self) -> usize {
+        self.width.div_ceil(8) as usize
+    }
+
+    /**
+     * Returns if the dot is black
+     */
+    pub fn get(&self, x: u32, y: u32) -> bool {
+        let index = y as usize * self.row_bytes() + x as usize / 8;
+        self.data[index] & (0x80 >> (x % 8)) != 0
+    }
+
+    /**
+     * Set the color of a dot (true is black)
+     */
+    pub fn set(&mut self, x: u32, y: u32, black: bool) {
+        let index = y as usize * self.row_bytes() + x as usize / 8;
+        let mask = 0x80 >> (x % 8);
+
+        if black {
+            self.data[index] |= mask;
+        } else {
+            self.data[index] &= !mask;
+        }
+    }
+}
+
+/**
+ * Convert pixels to a bitmap: scale to the target width in dots (keeping the aspect ratio),
+ * convert to grayscale and dither
+ */
+pub fn rasterize(
+    pixels: &[u8],
+    width: u32,
+    height: u32,
+    format: PixelFormat,
+    target_width: u32,
+    dither: Dither,
+) -> Result<Bitmap, String> {
+    if width == 0 || height == 0 || target_width == 0 {
+        return Err(format!(
+            "invalid image size {}x{} to {} dots",
+            width, height, target_width
+        ));
+    }
+
+    let expected = width as usize * height as usize * format.channels();
+    if pixels.len() != expected {
+        return Err(format!(
+            "image must have {} bytes for {}x{} {:?} pixels, got {}",
+            expected,
+            width,
+            height,
+            format,
+            pixels.len()
+        ));
+    }
+
+    let gray = to_grayscale(pixels, format);
+    let target_height =
+        ((height as u64 * target_width as u64 + width as u64 / 2) / width as u64).max(1) as u32;
+    let scaled = scale(&gray, width, height, target_width, target_height);
+
+    Ok(dither_gray(&scaled, target_width, target_height, dither))
+}
+
+/**
+ * Convert pixels to 8 bits luminance (ITU-R BT.601 weights, alpha composed over white)
+ */
+pub fn to_grayscale(pixels: &[u8], format: PixelFormat) -> Vec<u8> {
+    pixels
+        .chunks_exact(format.channels())
+        .map(|pixel| match format {
+            PixelFormat::Gray => pixel[0],
+            PixelFormat::Rgb => luminance(pixel[0], pixel[1], pixel[2]),
+            PixelFormat::Rgba => {
+                let gray = luminance(pixel[0], pixel[1], pixel[2]) as u32;
+                let alpha = pixel[3] as u32;
+                ((gray * alpha + 255 * (255 - alpha) + 127) / 255) as u8
+            }
+        })
+        .collect()
+}
+
+/**
+ * Resize grayscale pixels averaging the covered source area (box filter)
+ */
+pub fn scale(
+    gray: &[u8],
+    width: u32,
+    height: u32,
+    target_width: u32,
+    target_height: u32,
+) -> Vec<u8> {
+    if width == target_width && height == target_height {
+        return gray.to_vec();
+    }
+
+    let columns = weights(width, target_width);
+    let rows = weights(height, target_height);
+    let mut scaled = Vec::with_capacity(target_width as usize * target_height as usize);
+
+    for row in &rows {
+        for column in &columns {
+            let mut sum = 0.0;
+            let mut total = 0.0;
+
+            for &(y, y_weight) in row {
+                for &(x, x_weight) in column {
+                    let weight = x_weight * y_weight;
+                    sum += gray[y * width as usize + x] as f64 * weight;
+                    total += weight;
+                }
+            }
+
+            scaled.push((sum / total).round().clamp(0.0, 255.0) as u8);
+        }
+    }
+
+    scaled
+}
+
+/**
+ * Returns the source pixels (index and coverage) of each target pixel on one axis
+ * (upscaling covers a single source pixel, as nearest neighbor)
+ */
+fn weights(source: u32, target: u32) -> Vec<Vec<(usize, f64)>> {
+    let ratio = source as f64 / target as f64;
+
+    (0..target)
+        .map(|i| {
+            let start = i as f64 * ratio;
+            let end = ((i + 1) as f64 * ratio).min(source as f64);
+
+            if ratio <= 1.0 {
+                let index = ((start + end) / 2.0).floor() as usize;
+                return vec![(index.min(source as usize - 1), 1.0)];
+            }
+
+            let mut covered = Vec::new();
+            let mut position = start.floor() as usize;
+
+            while (position as f64) < end && position < source as usize {
+                let from = start.max(position as f64);
+                let to = end.min(position as f64 + 1.0);
+                if to > from {
+                    covered.push((position, to - from));
+                }
+                position += 1;
+            }
+
+            covered
+        })
+        .collect()
+}
+
+/**
+ * Reduce grayscale pixels to a bitmap
+ */
+pub fn dither_gray(gray: &[u8], width: u32, height: u32, dither: Dither) -> Bitmap {
+    let mut bitmap = Bitmap::new(width, height);
+
+    match dither {
+        Dither::Threshold(level) => {
+            for y in 0..height {
+                for x in 0..width {
+                    let value = gray[(y * width + x) as usize];
+                    bitmap.set(x, y, value < level);
+                }
+            }
+        }
+        Dither::Bayer4 => ordered(gray, &mut bitmap, &bayer_matrix(4), 4),
+        Dither::Bayer8 => ordered(gray, &mut bitmap, &bayer_matrix(8), 8),
+        Dither::FloydSteinberg => diffuse(
+            gray,
+            &mut bitmap,
+            &[(1, 0, 7), (-1, 1, 3), (0, 1, 5), (1, 1, 1)],
+            16,
+        ),
+        Dither::Atkinson => diffuse(
+            gray,
+            &mut bitmap,
+            &[
+                (1, 0, 1),
+                (2, 0, 1),
+                (-1, 1, 1),
+                (0, 1, 1),
+                (1, 1, 1),
+                (0, 2, 1),
+            ],
+            8,
+        ),
+    }
+
+    bitmap
+}
+
+/**
+ * Ordered dithering comparing each pixel with the threshold of the matrix cell
+ */
+fn ordered(gray: &[u8], bitmap: &mut Bitmap, matrix: &[u32], size: u32) {
+    let cells = size * size;
+
+    for y in 0..bitmap.height {
+        for x in 0..bitmap.width {
+            let cell = matrix[((y % size) * size + x % size) as usize];
+            let threshold = ((2 * cell + 1) * 255) / (2 * cells);
+            let value = gray[(y * bitmap.width + x) as usize] as u32;
+            bitmap.set(x, y, value < threshold);
+        }
+    }
+}
+
+/**
+ * Error diffusion with integer weights (dx, dy, weight) divided by the divisor
+ */
+fn diffuse(gray: &[u8], bitmap: &mut Bitmap, kernel: &[(i32, i32, i32)], divisor: i32) {
+    let width = bitmap.width as i32;
+    let height = bitmap.height as i32;
+    let mut values: Vec<i32> = gray.iter().map(|&value| value as i32).collect();
+
+    for y in 0..height {
+        for x in 0..width {
+            let index = (y * width + x) as usize;
+            let value = values[index];
+            let black = value < 128;
+            let error = if black { value } else { value - 255 };
+
+            bitmap.set(x as u32, y as u32, black);
+
+            for &(dx, dy, weight) in kernel {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx >= 0 && nx < width && ny < height {
+                    values[(ny * width + nx) as usize] += error * weight / divisor;
+                }
+            }
+        }
+    }
+}
+
+/**
+ * Returns a Bayer index matrix with size 4 or 8 (rows concatenated)
+ */
+fn bayer_matrix(size: u32) -> Vec<u32> {
+    let mut matrix = vec![0u32];
+    let mut current = 1;
+
+    while current < size {
+        let mut next = vec![0; (current * current * 4) as usize];
+        for y in 0..current {
+            for x in 0..current {
+                let value = matrix[(y * current + x) as usize] * 4;
+                let at = |dx: u32, dy: u32| {
+                    ((y + dy * current) * current * 2 + x + dx * current) as usize
+                };
+                next[at(0, 0)] = value;
+                next[at(1, 0)] = value + 2;
+                next[at(0, 1)] = value + 3;
+                next[at(1, 1)] = value + 1;
+            }
+        }
+        matrix = next;
+        current *= 2;
+    }
+
+    matrix
+}
+
+/**
+ * Returns the luminance of a RGB color (ITU-R BT.601)
+ */
+fn luminance(red: u8, green: u8, blue: u8) -> u8 {
+    ((299 * red as u32 + 587 * green as u32 + 114 * blue as u32 + 500) / 1000) as u8
+}
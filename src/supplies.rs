@@ -0,0 +1,232 @@
+//! Printer supplies (toner, ink, waste containers, drums) and their levels, from the marker-*
+//! attributes reported by CUPS or the printer-supply attribute of IPP printers
+//!
+//! ```rust
+//! use printers::ipp::PrinterAttributes;
+//! use printers::supplies::{self, SupplyType};
+//!
+//! let mut attributes = PrinterAttributes::default();
+//! attributes
+//!     .set("marker-names", &["Black Toner", "Drum Unit"])
+//!     .set("marker-types", &["toner", "opc"])
+//!     .set("marker-colors", &["#000000", "none"])
+//!     .set("marker-levels", &["8", "-2"])
+//!     .set("marker-low-levels", &["10", "5"]);
+//!
+//! let supplies = supplies::from_attributes(&attributes);
+//! assert_eq!(supplies[0].supply_type, SupplyType::Toner);
+//! assert!(supplies[0].is_low());
+//! assert_eq!(supplies[1].level, None);
+//! ```
+
+use crate::ipp::PrinterAttributes;
+
+/**
+ * Kind of a supply, from the marker-types keywords (RFC 3805 prtMarkerSuppliesType)
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum SupplyType {
+    Toner,
+    Ink,
+    Waste,
+    Drum,
+    Fuser,
+    Staples,
+
+    /**
+     * Any other keyword, kept as reported (like "transfer-unit" or "cleaner-unit")
+     */
+    Other(String),
+}
+
+impl SupplyType {
+    /**
+     * Parse a marker-types keyword, like "toner-cartridge" or "waste-ink"
+     * The camel case names of the printer-supply attribute ("tonerCartridge") are accepted too
+     */
+    pub fn parse(keyword: &str) -> SupplyType {
+        let keyword = keyword.trim();
+        let mut normalized = String::new();
+        for c in keyword.chars() {
+            if c.is_ascii_uppercase() {
+                normalized.push('-');
+            }
+            normalized.push(c.to_ascii_lowercase());
+        }
+
+        match normalized.as_str() {
+            "toner" | "toner-cartridge" => SupplyType::Toner,
+            "ink" | "ink-cartridge" | "ink-ribbon" | "solid-wax" | "ribbon-wax" => SupplyType::Ink,
+            "opc" | "drum" | "developer" => SupplyType::Drum,
+            "fuser" | "fuser-oil" | "fuser-oiler" | "fuser-oil-wick" | "fuser-cleaning-pad" => {
+                SupplyType::Fuser
+            }
+            "staples" | "stitching-wire" => SupplyType::Staples,
+            waste if waste.starts_with("waste-") || waste == "waste" => SupplyType::Waste,
+            _ => SupplyType::Other(keyword.to_string()),
+        }
+    }
+}
+
+/**
+ * A supply of the printer with its level in percent
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Supply {
+    pub name: String,
+    pub supply_type: SupplyType,
+
+    /**
+     * Color as "#RRGGBB" (None for supplies without color, like drums and waste containers)
+     */
+    pub color: Option<String>,
+
+    /**
+     * Remaining level in percent, None when the printer can not measure it
+     */
+    pub level: Option<u8>,
+
+    /**
+     * Level under which the supply is almost empty (or almost full for waste containers)
+     */
+    pub low_level: Option<u8>,
+    pub high_level: Option<u8>,
+}
+
+impl Supply {
+    /**
+     * Returns if the supply must be replaced soon (a waste container is low when it is almost full)
+     */
+    pub fn is_low(&self) -> bool {
+        match (&self.supply_type, self.level) {
+            (SupplyType::Waste, Some(level)) => level >= self.high_level.unwrap_or(100),
+            (_, Some(level)) => level <= self.low_level.unwrap_or(0),
+            (_, None) => false,
+        }
+    }
+
+    /**
+     * Returns if the supply is empty (or full for waste containers)
+     */
+    pub fn is_empty(&self) -> bool {
+        match (&self.supply_type, self.level) {
+            (SupplyType::Waste, Some(level)) => level >= 100,
+            (_, Some(level)) => level == 0,
+            (_, None) => false,
+        }
+    }
+}
+
+/**
+ * Returns the supplies described by the marker-* attributes (marker-names, marker-types, marker-colors,
+ * marker-levels, marker-low-levels and marker-high-levels), or else by the printer-supply attribute
+ */
+pub fn from_attributes(attributes: &PrinterAttributes) -> Vec<Supply> {
+    let names = attributes.get("marker-names");
+    if names.is_empty() {
+        return from_printer_supply(
+            attributes.get("printer-supply"),
+            attributes.get("printer-supply-description"),
+        );
+    }
+
+    let value = |name: &str, index: usize| attributes.get(name).get(index).map(String::as_str);
+
+    names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| Supply {
+            name: name.to_string(),
+            supply_type: SupplyType::parse(value("marker-types", index).unwrap_or("unknown")),
+            color: value("marker-colors", index).and_then(color),
+            level: value("marker-levels", index).and_then(percent),
+            low_level: value("marker-low-levels", index).and_then(percent),
+            high_level: value("marker-high-levels", index).and_then(percent),
+        })
+        .collect()
+}
+
+/**
+ * Returns the supplies described by printer-supply values, like
+ * "index=1;class=supplyThatIsConsumed;type=toner;unit=percent;maxcapacity=100;level=80;colorantname=black;"
+ * The names are taken from the printer-supply-description values
+ */
+pub fn from_printer_supply(values: &[String], descriptions: &[String]) -> Vec<Supply> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let field = |name: &str| {
+                value
+                    .split(';')
+                    .find_map(|field| match field.split_once('=') {
+                        Some((key, value)) if key.trim() == name => Some(value.trim()),
+                        _ => None,
+                    })
+            };
+
+            let supply_type = SupplyType::parse(field("type").unwrap_or("unknown"));
+            let colorant = field("colorantname").filter(|name| *name != "unknown");
+            let level = match (
+                field("level").and_then(|level| level.parse::<i64>().ok()),
+                field("maxcapacity").and_then(|max| max.parse::<i64>().ok()),
+            ) {
+                (Some(level), Some(max)) if level >= 0 && max > 0 => {
+                    Some((level * 100 / max).min(100) as u8)
+                }
+                _ => None,
+            };
+
+            let name = match descriptions.get(index) {
+                Some(description) if !description.is_empty() => description.clone(),
+                _ => format!("{} {}", colorant.unwrap_or(""), field("type").unwrap_or(""))
+                    .trim()
+                    .to_string(),
+            };
+
+            Supply {
+                name,
+                supply_type,
+                color: colorant.and_then(color),
+                level,
+                low_level: None,
+                high_level: None,
+            }
+        })
+        .collect()
+}
+
+/**
+ * Parse a level in percent (negative values mean unknown, unavailable or "some remaining")
+ */
+fn percent(value: &str) -> Option<u8> {
+    match value.trim().parse::<i32>() {
+        Ok(level) if level >= 0 => Some(level.min(100) as u8),
+        _ => None,
+    }
+}
+
+/**
+ * Parse a marker color ("#00FFFF", the first one of multi-color markers) or a colorant name
+ */
+fn color(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.starts_with('#') {
+        return value.get(0..7).map(|color| color.to_ascii_uppercase());
+    }
+
+    let color = match value.to_ascii_lowercase().as_str() {
+        "black" | "matte-black" | "photo-black" => "#000000",
+        "cyan" => "#00FFFF",
+        "magenta" => "#FF00FF",
+        "yellow" => "#FFFF00",
+        "red" => "#FF0000",
+        "green" => "#00FF00",
+        "blue" => "#0000FF",
+        "gray" | "grey" => "#808080",
+        "white" => "#FFFFFF",
+        _ => return None,
+    };
+
+    Some(color.to_string())
+}
@@ -0,0 +1,525 @@
+//! Plain text rendering to PDF and PostScript with a monospaced font, so text prints the
+//! same on every host without depending on the system text filters
+//!
+//! The standard fonts only have the WinAnsi (Windows-1252) characters: text with other characters
+//! (like Greek, Cyrillic or CJK text) is rejected with an error instead of printing "?"
+//!
+//! ```rust
+//! use printers::text::{self, TextOptions};
+//!
+//! let options = TextOptions {
+//!     header: Some("Daily report".to_string()),
+//!     footer: Some("Page {page} of {pages}".to_string()),
+//!     ..TextOptions::default()
+//! };
+//!
+//! let pdf = text::render_pdf("Total:\t42\n", &options).unwrap();
+//! ```
+
+use crate::pdf::{literal_string, number, win_ansi, Margins, PageSize, PdfWriter};
+
+/**
+ * Width of the Courier glyphs relative to the font size
+ */
+const CHAR_WIDTH: f64 = 0.6;
+
+/**
+ * Monospaced standard font (available on every PDF reader and PostScript printer)
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    Courier,
+    CourierBold,
+    CourierOblique,
+    CourierBoldOblique,
+}
+
+impl Font {
+    /**
+     * Returns the PostScript font name
+     */
+    pub fn name(&self) -> &'static str {
+        match self {
+            Font::Courier => "Courier",
+            Font::CourierBold => "Courier-Bold",
+            Font::CourierOblique => "Courier-Oblique",
+            Font::CourierBoldOblique => "Courier-BoldOblique",
+        }
+    }
+}
+
+/**
+ * Layout of the rendered text
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextOptions {
+    /**
+     * Size of the pages
+     */
+    pub page_size: PageSize,
+
+    /**
+     * Blank space around the text, header and footer
+     */
+    pub margins: Margins,
+
+    /**
+     * Font of the text, header and footer
+     */
+    pub font: Font,
+
+    /**
+     * Font size in points
+     */
+    pub font_size: f64,
+
+    /**
+     * Distance between lines relative to the font size
+     */
+    pub line_spacing: f64,
+
+    /**
+     * Break long lines on the last space that fits (or at the page width), otherwise they are cut
+     */
+    pub wrap: bool,
+
+    /**
+     * Columns between tab stops
+     */
+    pub tab_size: usize,
+
+    /**
+     * Text at the top of each page, "{page}" and "{pages}" are replaced by the page number and count
+     */
+    pub header: Option<String>,
+
+    /**
+     * Text centered at the bottom of each page, with the same replacements of the header
+     */
+    pub footer: Option<String>,
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        TextOptions {
+            page_size: PageSize::A4,
+            margins: Margins::uniform(36.0),
+            font: Font::Courier,
+            font_size: 10.0,
+            line_spacing: 1.2,
+            wrap: true,
+            tab_size: 8,
+            header: None,
+            footer: None,
+        }
+    }
+}
+
+/**
+ * Positions of the text on the page, in points
+ */
+struct Layout {
+    columns: usize,
+    rows: usize,
+    leading: f64,
+    char_width: f64,
+    first_line: f64,
+    header_line: f64,
+    footer_line: f64,
+}
+
+impl Layout {
+    fn new(options: &TextOptions) -> Result<Layout, String> {
+        if options.font_size <= 0.0 || options.line_spacing <= 0.0 {
+            return Err(format!(
+                "invalid font size {} with line spacing {}",
+                options.font_size, options.line_spacing
+            ));
+        }
+
+        let margins = &options.margins;
+        let leading = options.font_size * options.line_spacing;
+        let char_width = options.font_size * CHAR_WIDTH;
+        let header_line = options.page_size.height - margins.top - options.font_size;
+        let footer_line = margins.bottom;
+
+        let mut top = options.page_size.height - margins.top;
+        let mut bottom = margins.bottom;
+        if options.header.is_some() {
+            top -= 2.0 * leading;
+        }
+        if options.footer.is_some() {
+            bottom += 2.0 * leading;
+        }
+
+        let width = options.page_size.width - margins.left - margins.right;
+        let height = top - bottom - options.font_size;
+        let columns = (width / char_width).floor().max(0.0) as usize;
+        let rows = if height < 0.0 {
+            0
+        } else {
+            (height / leading).floor() as usize + 1
+        };
+
+        if columns == 0 || rows == 0 {
+            return Err("the page is too small for the margins and font size".to_string());
+        }
+
+        for template in options.header.iter().chain(options.footer.iter()) {
+            check_encoding(template)?;
+        }
+
+        Ok(Layout {
+            columns,
+            rows,
+            leading,
+            char_width,
+            first_line: top - options.font_size,
+            header_line,
+            footer_line,
+        })
+    }
+}
+
+/**
+ * Split text in pages of lines: tabs are expanded, long lines wrapped (or cut) and form feeds
+ * start a new page
+ */
+pub fn paginate(text: &str, options: &TextOptions) -> Result<Vec<Vec<String>>, String> {
+    let layout = Layout::new(options)?;
+    check_encoding(text)?;
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let mut pages: Vec<Vec<String>> = vec![Vec::new()];
+
+    for (index, chunk) in text.split('\x0c').enumerate() {
+        if index > 0 {
+            pages.push(Vec::new());
+        }
+
+        let chunk = chunk.strip_suffix('\n').unwrap_or(chunk);
+        if chunk.is_empty() {
+            continue;
+        }
+
+        for line in chunk.split('\n') {
+            let expanded = expand_tabs(line, options.tab_size.max(1));
+            let lines = if options.wrap {
+                wrap_line(&expanded, layout.columns)
+            } else {
+                vec![expanded.into_iter().take(layout.columns).collect()]
+            };
+
+            for line in lines {
+                if pages.last().unwrap().len() == layout.rows {
+                    pages.push(Vec::new());
+                }
+                pages.last_mut().unwrap().push(line);
+            }
+        }
+    }
+
+    while pages.len() > 1 && pages.last().unwrap().is_empty() {
+        pages.pop();
+    }
+
+    Ok(pages)
+}
+
+/**
+ * Render text as a PDF document
+ * Fails when the text has characters out of WinAnsiEncoding
+ */
+pub fn render_pdf(text: &str, options: &TextOptions) -> Result<Vec<u8>, String> {
+    let layout = Layout::new(options)?;
+    let pages = paginate(text, options)?;
+    let size = &options.page_size;
+
+    let mut writer = PdfWriter::new();
+    let pages_id = writer.reserve();
+    let font_id = writer.reserve();
+    writer.object(
+        font_id,
+        &format!(
+            "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+            options.font.name()
+        ),
+    );
+
+    let mut page_ids = Vec::new();
+
+    for (index, lines) in pages.iter().enumerate() {
+        let font = format!("/F1 {} Tf", number(options.font_size));
+        let mut content = format!(
+            "BT {} {} TL {} {} Td\n",
+            font,
+            number(layout.leading),
+            number(options.margins.left),
+            number(layout.first_line)
+        );
+
+        for (row, line) in lines.iter().enumerate() {
+            if row > 0 {
+                content.push_str("T* ");
+            }
+            content.push_str(&format!("{} Tj\n", encode(line)));
+        }
+        content.push_str("ET\n");
+
+        for (x, y, line) in decorations(options, &layout, index + 1, pages.len()) {
+            content.push_str(&format!(
+                "BT {} {} {} Td {} Tj ET\n",
+                font,
+                number(x),
+                number(y),
+                encode(&line)
+            ));
+        }
+
+        let content_id = writer.reserve();
+        writer.stream(content_id, "", content.as_bytes());
+
+        let page_id = writer.reserve();
+        writer.object(
+            page_id,
+            &format!(
+                "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 {} 0 R >> >> /Contents {} 0 R >>",
+                pages_id,
+                number(size.width),
+                number(size.height),
+                font_id,
+                content_id
+            ),
+        );
+        page_ids.push(page_id);
+    }
+
+    Ok(writer.finish(pages_id, &page_ids))
+}
+
+/**
+ * Render text as a PostScript document (DSC 3.0)
+ * Fails when the text has characters out of WinAnsiEncoding
+ */
+pub fn render_postscript(text: &str, options: &TextOptions) -> Result<Vec<u8>, String> {
+    let layout = Layout::new(options)?;
+    let pages = paginate(text, options)?;
+    let (width, height) = (
+        number(options.page_size.width),
+        number(options.page_size.height),
+    );
+
+    let mut document = format!(
+        "%!PS-Adobe-3.0\n\
+         %%Creator: printers\n\
+         %%Pages: {}\n\
+         %%BoundingBox: 0 0 {} {}\n\
+         %%DocumentNeededResources: font {}\n\
+         %%EndComments\n\
+         %%BeginProlog\n\
+         {}\
+         %%EndProlog\n\
+         %%BeginSetup\n\
+         << /PageSize [{} {}] >> setpagedevice\n\
+         /F /TextFont findfont {} scalefont def\n\
+         %%EndSetup\n",
+        pages.len(),
+        options.page_size.width.ceil(),
+        options.page_size.height.ceil(),
+        options.font.name(),
+        postscript_font(options.font),
+        width,
+        height,
+        number(options.font_size)
+    );
+
+    for (index, lines) in pages.iter().enumerate() {
+        document.push_str(&format!("%%Page: {0} {0}\nF setfont\n", index + 1));
+
+        for (row, line) in lines.iter().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let y = layout.first_line - row as f64 * layout.leading;
+            document.push_str(&format!(
+                "{} {} moveto {} show\n",
+                number(options.margins.left),
+                number(y),
+                encode(line)
+            ));
+        }
+
+        for (x, y, line) in decorations(options, &layout, index + 1, pages.len()) {
+            document.push_str(&format!(
+                "{} {} moveto {} show\n",
+                number(x),
+                number(y),
+                encode(&line)
+            ));
+        }
+
+        document.push_str("showpage\n");
+    }
+
+    document.push_str("%%Trailer\n%%EOF\n");
+    Ok(document.into_bytes())
+}
+
+/**
+ * Returns the position and text of the header and footer of a page
+ */
+fn decorations(
+    options: &TextOptions,
+    layout: &Layout,
+    page: usize,
+    pages: usize,
+) -> Vec<(f64, f64, String)> {
+    let fill = |template: &str| {
+        let text = template
+            .replace("{page}", &page.to_string())
+            .replace("{pages}", &pages.to_string());
+        expand_tabs(&text, options.tab_size.max(1))
+            .into_iter()
+            .take(layout.columns)
+            .collect::<String>()
+    };
+
+    let mut lines = Vec::new();
+
+    if let Some(header) = &options.header {
+        lines.push((options.margins.left, layout.header_line, fill(header)));
+    }
+
+    if let Some(footer) = &options.footer {
+        let text = fill(footer);
+        let width = options.page_size.width - options.margins.left - options.margins.right;
+        let used = text.chars().count() as f64 * layout.char_width;
+        let x = options.margins.left + (width - used) / 2.0;
+        lines.push((x, layout.footer_line, text));
+    }
+
+    lines
+}
+
+/**
+ * Returns the PostScript prolog defining TextFont, the font re-encoded with WinAnsiEncoding
+ */
+fn postscript_font(font: Font) -> String {
+    const HIGH: [(u8, &str); 30] = [
+        (0x27, "quotesingle"),
+        (0x2d, "hyphen"),
+        (0x60, "grave"),
+        (0x80, "Euro"),
+        (0x82, "quotesinglbase"),
+        (0x83, "florin"),
+        (0x84, "quotedblbase"),
+        (0x85, "ellipsis"),
+        (0x86, "dagger"),
+        (0x87, "daggerdbl"),
+        (0x88, "circumflex"),
+        (0x89, "perthousand"),
+        (0x8a, "Scaron"),
+        (0x8b, "guilsinglleft"),
+        (0x8c, "OE"),
+        (0x8e, "Zcaron"),
+        (0x91, "quoteleft"),
+        (0x92, "quoteright"),
+        (0x93, "quotedblleft"),
+        (0x94, "quotedblright"),
+        (0x95, "bullet"),
+        (0x96, "endash"),
+        (0x97, "emdash"),
+        (0x98, "tilde"),
+        (0x99, "trademark"),
+        (0x9a, "scaron"),
+        (0x9b, "guilsinglright"),
+        (0x9c, "oe"),
+        (0x9e, "zcaron"),
+        (0x9f, "Ydieresis"),
+    ];
+
+    let mut encoding = String::from("ISOLatin1Encoding 256 array copy\n");
+    for (code, name) in HIGH.iter() {
+        encoding.push_str(&format!("dup {} /{} put\n", code, name));
+    }
+
+    format!(
+        "/{} findfont dup length dict begin\n\
+         {{ 1 index /FID ne {{ def }} {{ pop pop }} ifelse }} forall\n\
+         /Encoding {}def\n\
+         currentdict end /TextFont exch definefont pop\n",
+        font.name(),
+        encoding
+    )
+}
+
+/**
+ * Fails on the first character the standard fonts can not print (out of WinAnsiEncoding)
+ */
+fn check_encoding(text: &str) -> Result<(), String> {
+    match text
+        .chars()
+        .find(|&c| !c.is_control() && win_ansi(c).is_none())
+    {
+        Some(c) => Err(format!(
+            "character {:?} (U+{:04X}) is out of the WinAnsi encoding of the standard fonts",
+            c, c as u32
+        )),
+        None => Ok(()),
+    }
+}
+
+/**
+ * Returns the line as a string literal encoded with WinAnsiEncoding (the line was checked before)
+ */
+fn encode(line: &str) -> String {
+    let bytes: Vec<u8> = line.chars().filter_map(win_ansi).collect();
+    literal_string(&bytes)
+}
+
+/**
+ * Replace tabs by spaces up to the next tab stop and remove the other control characters
+ */
+fn expand_tabs(line: &str, tab_size: usize) -> Vec<char> {
+    let mut expanded = Vec::new();
+
+    for c in line.chars() {
+        if c == '\t' {
+            let spaces = tab_size - expanded.len() % tab_size;
+            expanded.resize(expanded.len() + spaces, ' ');
+        } else if !c.is_control() {
+            expanded.push(c);
+        }
+    }
+
+    expanded
+}
+
+/**
+ * Break a line in lines of at most the columns, on the last space when possible
+ */
+fn wrap_line(line: &[char], columns: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut rest = line;
+
+    while rest.len() > columns {
+        let cut = rest[..=columns]
+            .iter()
+            .rposition(|&c| c == ' ')
+            .filter(|&position| position > 0)
+            .unwrap_or(columns);
+
+        let text: String = rest[..cut].iter().collect();
+        lines.push(text.trim_end().to_string());
+
+        rest = &rest[cut..];
+        while rest.first() == Some(&' ') {
+            rest = &rest[1..];
+        }
+    }
+
+    if !rest.is_empty() || lines.is_empty() {
+        lines.push(rest.iter().collect());
+    }
+
+    lines
+}
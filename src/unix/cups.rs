@@ -1,9 +1,10 @@
-use libc::{c_char, c_int};
+use libc::{c_char, c_int, c_uint, c_void, size_t};
 use std::{
     ffi::{CStr, CString},
     ptr,
 };
 
+use crate::ipp::AttributeValues;
 use crate::shared::interface::{JobGetters, PlatformPrinterGetters};
 
 /**
@@ -124,7 +125,7 @@ impl CupsDestT {
     fn get_option_by_key(&self, key: &str) -> String {
         let mut value = "".to_string();
 
-        for i in 1..self.num_options {
+        for i in 0..self.num_options {
             let option_ptr = unsafe { self.options.offset(i as isize) };
             let option = unsafe { &*option_ptr };
 
@@ -139,6 +140,29 @@ impl CupsDestT {
 
         return value;
     }
+
+    /**
+     * Returns all cups options of the destination (name, value)
+     */
+    fn get_options(&self) -> Vec<(String, String)> {
+        let mut options = Vec::new();
+
+        for i in 0..self.num_options {
+            let option = unsafe { &*self.options.offset(i as isize) };
+            if option.name.is_null() || option.value.is_null() {
+                continue;
+            }
+
+            let name = unsafe { CStr::from_ptr(option.name) };
+            let value = unsafe { CStr::from_ptr(option.value) };
+            options.push((
+                name.to_string_lossy().to_string(),
+                value.to_string_lossy().to_string(),
+            ));
+        }
+
+        options
+    }
 }
 
 impl PlatformPrinterGetters for CupsDestT {
@@ -204,6 +228,63 @@ impl PlatformPrinterGetters for CupsDestT {
     }
 }
 
+/**
+ * HTTP_STATUS_CONTINUE (http_status_t) returned while a document can be written
+ */
+const HTTP_STATUS_CONTINUE: c_int = 100;
+
+/**
+ * IPP_STATUS_OK_CONFLICTING (ipp_status_t), the last successful IPP status code
+ */
+const IPP_STATUS_OK_CONFLICTING: c_int = 2;
+
+/**
+ * IPP_STATUS_ERROR_OPERATION_NOT_SUPPORTED (ipp_status_t)
+ */
+const IPP_STATUS_ERROR_OPERATION_NOT_SUPPORTED: c_int = 0x0501;
+
+/**
+ * IPP_OP_VALIDATE_JOB (ipp_op_t)
+ */
+const IPP_OP_VALIDATE_JOB: c_int = 0x0004;
+
+/**
+ * IPP_OP_GET_PRINTER_ATTRIBUTES (ipp_op_t)
+ */
+const IPP_OP_GET_PRINTER_ATTRIBUTES: c_int = 0x000b;
+
+/**
+ * HTTP_ENCRYPTION_ALWAYS (http_encryption_t), TLS is negotiated when the connection is opened
+ */
+const HTTP_ENCRYPTION_ALWAYS: c_int = 3;
+
+/**
+ * AF_UNSPEC, any address family of the host
+ */
+const AF_UNSPEC: c_int = 0;
+
+/**
+ * CUPS_DEST_FLAGS_NONE, connect to the destination as cupsConnectDest does by default
+ */
+const CUPS_DEST_FLAGS_NONE: c_uint = 0;
+
+/**
+ * HTTP_URI_CODING_ALL (http_uri_coding_t), escape every URI component
+ */
+const HTTP_URI_CODING_ALL: c_int = 0x0f;
+
+/**
+ * Group and value tags (ipp_tag_t) of the attributes sent and read
+ */
+const IPP_TAG_OPERATION: c_int = 0x01;
+const IPP_TAG_JOB: c_int = 0x02;
+const IPP_TAG_PRINTER: c_int = 0x04;
+const IPP_TAG_UNSUPPORTED_GROUP: c_int = 0x05;
+const IPP_TAG_NAME: c_int = 0x42;
+const IPP_TAG_KEYWORD: c_int = 0x44;
+const IPP_TAG_URI: c_int = 0x45;
+const IPP_TAG_MIMETYPE: c_int = 0x49;
+
 #[link(name = "cups")]
 extern "C" {
     fn cupsGetDests(dests: *mut *mut CupsDestT) -> c_int;
@@ -211,9 +292,117 @@ extern "C" {
         printer_name: *const c_char,
         filename: *const c_char,
         title: *const c_char,
-        options: i32,
-    ) -> i32;
+        num_options: c_int,
+        options: *mut CupsOptionT,
+    ) -> c_int;
+    fn cupsAddOption(
+        name: *const c_char,
+        value: *const c_char,
+        num_options: c_int,
+        options: *mut *mut CupsOptionT,
+    ) -> c_int;
+    fn cupsFreeOptions(num_options: c_int, options: *mut CupsOptionT);
+    fn cupsCreateJob(
+        http: *mut c_void,
+        name: *const c_char,
+        title: *const c_char,
+        num_options: c_int,
+        options: *mut CupsOptionT,
+    ) -> c_int;
+    fn cupsStartDocument(
+        http: *mut c_void,
+        name: *const c_char,
+        job_id: c_int,
+        docname: *const c_char,
+        format: *const c_char,
+        last_document: c_int,
+    ) -> c_int;
+    fn cupsWriteRequestData(http: *mut c_void, buffer: *const c_char, length: size_t) -> c_int;
+    fn cupsFinishDocument(http: *mut c_void, name: *const c_char) -> c_int;
+    fn cupsGetNamedDest(
+        http: *mut c_void,
+        name: *const c_char,
+        instance: *const c_char,
+    ) -> *mut CupsDestT;
+    fn cupsCopyDestInfo(http: *mut c_void, dest: *mut CupsDestT) -> *mut c_void;
+    fn cupsFreeDestInfo(dinfo: *mut c_void);
+    fn cupsFindDestSupported(
+        http: *mut c_void,
+        dest: *mut CupsDestT,
+        dinfo: *mut c_void,
+        option: *const c_char,
+    ) -> *mut c_void;
+    fn ippAttributeString(attr: *mut c_void, buffer: *mut c_char, bufsize: size_t) -> size_t;
     fn cupsFreeDests(num_dests: c_int, dests: *const CupsDestT);
+    fn ippNewRequest(op: c_int) -> *mut c_void;
+    fn ippAddString(
+        ipp: *mut c_void,
+        group: c_int,
+        value_tag: c_int,
+        name: *const c_char,
+        language: *const c_char,
+        value: *const c_char,
+    ) -> *mut c_void;
+    fn ippAddStrings(
+        ipp: *mut c_void,
+        group: c_int,
+        value_tag: c_int,
+        name: *const c_char,
+        num_values: c_int,
+        language: *const c_char,
+        values: *const *const c_char,
+    ) -> *mut c_void;
+    fn httpConnect2(
+        host: *const c_char,
+        port: c_int,
+        addrlist: *mut c_void,
+        family: c_int,
+        encryption: c_int,
+        blocking: c_int,
+        msec: c_int,
+        cancel: *mut c_int,
+    ) -> *mut c_void;
+    fn httpClose(http: *mut c_void);
+    fn cupsConnectDest(
+        dest: *mut CupsDestT,
+        flags: c_uint,
+        msec: c_int,
+        cancel: *mut c_int,
+        resource: *mut c_char,
+        resourcesize: size_t,
+        cb: *const c_void,
+        user_data: *mut c_void,
+    ) -> *mut c_void;
+    fn httpAssembleURIf(
+        encoding: c_int,
+        uri: *mut c_char,
+        urilen: c_int,
+        scheme: *const c_char,
+        username: *const c_char,
+        host: *const c_char,
+        port: c_int,
+        resourcef: *const c_char,
+        ...
+    ) -> c_int;
+    fn ippPort() -> c_int;
+    fn cupsEncodeOptions2(
+        ipp: *mut c_void,
+        num_options: c_int,
+        options: *mut CupsOptionT,
+        group_tag: c_int,
+    );
+    fn cupsDoRequest(
+        http: *mut c_void,
+        request: *mut c_void,
+        resource: *const c_char,
+    ) -> *mut c_void;
+    fn ippGetStatusCode(ipp: *mut c_void) -> c_int;
+    fn ippFirstAttribute(ipp: *mut c_void) -> *mut c_void;
+    fn ippNextAttribute(ipp: *mut c_void) -> *mut c_void;
+    fn ippGetGroupTag(attr: *mut c_void) -> c_int;
+    fn ippGetName(attr: *mut c_void) -> *const c_char;
+    fn ippDelete(ipp: *mut c_void);
+    fn cupsUser() -> *const c_char;
     fn cupsGetJobs(
         jobs: *mut *mut CupsJobS,
         name: *const c_char,
@@ -223,6 +412,7 @@ extern "C" {
     fn cupsFreeJobs(num_jobs: c_int, jobs: *const CupsJobS);
     fn cupsCancelJob(printer_name: *const c_char, job_id: c_int) -> c_int;
     fn cupsLastErrorString() -> *const c_char;
+    fn cupsGetPPD2(http: *mut c_void, name: *const c_char) -> *const c_char;
 }
 // http: *mut libc::c_void,
 
@@ -249,20 +439,530 @@ pub fn get_dests() -> Vec<&'static CupsDestT> {
 }
 
 /**
- * Send an file to printer
+ * Send an file to printer with a list of CUPS options (name, value)
  */
-pub fn print_file(printer_name: &str, file_path: &str, job_name: Option<&str>) -> bool {
+pub fn print_file(
+    printer_name: &str,
+    file_path: &str,
+    job_name: Option<&str>,
+    options: &[(String, String)],
+) -> bool {
     unsafe {
         let printer_name = CString::new(printer_name).unwrap();
         let filename = CString::new(file_path).unwrap();
         let title = CString::new(job_name.unwrap_or(file_path)).unwrap();
 
-        let result = cupsPrintFile(printer_name.as_ptr(), filename.as_ptr(), title.as_ptr(), 0);
+        let (num_options, options_ptr) = add_options(options);
+
+        let result = cupsPrintFile(
+            printer_name.as_ptr(),
+            filename.as_ptr(),
+            title.as_ptr(),
+            num_options,
+            options_ptr,
+        );
+
+        cupsFreeOptions(num_options, options_ptr);
         //println!("jobId: {}", result);
         return result != 0;
     }
 }
 
+/**
+ * Send a buffer to printer as a single document job, without writing a temp file
+ * Using cupsCreateJob, cupsStartDocument, cupsWriteRequestData and cupsFinishDocument
+ */
+pub fn print_buffer(
+    printer_name: &str,
+    buffer: &[u8],
+    job_name: Option<&str>,
+    format: &str,
+    options: &[(String, String)],
+) -> bool {
+    let printer_name = CString::new(printer_name).unwrap();
+    let title = CString::new(job_name.unwrap_or("raw document")).unwrap();
+    let format = CString::new(format).unwrap();
+
+    unsafe {
+        let (num_options, options_ptr) = add_options(options);
+        let job_id = cupsCreateJob(
+            ptr::null_mut(),
+            printer_name.as_ptr(),
+            title.as_ptr(),
+            num_options,
+            options_ptr,
+        );
+        cupsFreeOptions(num_options, options_ptr);
+
+        if job_id == 0 {
+            return false;
+        }
+
+        let status = cupsStartDocument(
+            ptr::null_mut(),
+            printer_name.as_ptr(),
+            job_id,
+            title.as_ptr(),
+            format.as_ptr(),
+            1,
+        );
+
+        // The created job would otherwise stay held on the queue
+        if status != HTTP_STATUS_CONTINUE {
+            cupsCancelJob(printer_name.as_ptr(), job_id);
+            return false;
+        }
+
+        let status = cupsWriteRequestData(
+            ptr::null_mut(),
+            buffer.as_ptr() as *const c_char,
+            buffer.len(),
+        );
+
+        let result = cupsFinishDocument(ptr::null_mut(), printer_name.as_ptr());
+        status == HTTP_STATUS_CONTINUE && result <= IPP_STATUS_OK_CONFLICTING
+    }
+}
+
+/**
+ * Build a CUPS options array (must be released with cupsFreeOptions)
+ */
+unsafe fn add_options(options: &[(String, String)]) -> (c_int, *mut CupsOptionT) {
+    let mut num_options: c_int = 0;
+    let mut options_ptr: *mut CupsOptionT = ptr::null_mut();
+
+    for (name, value) in options {
+        let name = CString::new(name.as_str()).unwrap();
+        let value = CString::new(value.as_str()).unwrap();
+        num_options = cupsAddOption(name.as_ptr(), value.as_ptr(), num_options, &mut options_ptr);
+    }
+
+    (num_options, options_ptr)
+}
+
+/**
+ * Returns the values supported by the destination for an option (like "document-format")
+ */
+pub fn get_dest_supported(printer_name: &str, option: &str) -> Vec<String> {
+    get_dest_supported_values(printer_name, &[option])
+        .pop()
+        .unwrap_or_default()
+}
+
+/**
+ * Returns the values supported by the destination for each option, querying the destination once
+ * Using cupsCopyDestInfo and cupsFindDestSupported
+ */
+pub fn get_dest_supported_values(printer_name: &str, options: &[&str]) -> Vec<Vec<String>> {
+    let printer_name = CString::new(printer_name).unwrap();
+    let mut values = vec![Vec::new(); options.len()];
+
+    unsafe {
+        let dest = cupsGetNamedDest(ptr::null_mut(), printer_name.as_ptr(), ptr::null());
+        if dest.is_null() {
+            return values;
+        }
+
+        let dinfo = cupsCopyDestInfo(ptr::null_mut(), dest);
+        if !dinfo.is_null() {
+            for (index, option) in options.iter().enumerate() {
+                let option = CString::new(*option).unwrap();
+                let attr = cupsFindDestSupported(ptr::null_mut(), dest, dinfo, option.as_ptr());
+                if !attr.is_null() {
+                    values[index] = split_attribute_values(&attribute_string(attr));
+                }
+            }
+            cupsFreeDestInfo(dinfo);
+        }
+
+        cupsFreeDests(1, dest);
+    }
+
+    values
+}
+
+/**
+ * Returns the values of the destination attributes starting with a prefix (like "marker-")
+ * Using cupsGetNamedDest, the server reports them with the destination options
+ */
+pub fn get_dest_attributes(printer_name: &str, prefix: &str) -> Vec<(String, Vec<String>)> {
+    let printer_name = CString::new(printer_name).unwrap();
+    let mut attributes = Vec::new();
+
+    unsafe {
+        let dest = cupsGetNamedDest(ptr::null_mut(), printer_name.as_ptr(), ptr::null());
+        if dest.is_null() {
+            return attributes;
+        }
+
+        for (name, value) in (*dest).get_options() {
+            if name.starts_with(prefix) {
+                attributes.push((name, split_attribute_values(&value)));
+            }
+        }
+
+        cupsFreeDests(1, dest);
+    }
+
+    attributes
+}
+
+/**
+ * Send a Validate-Job request for the destination with a list of CUPS options (name, value)
+ * Returns None when the server does not support the operation, otherwise the unsupported attributes (name, value)
+ */
+pub fn validate_job(
+    printer_name: &str,
+    format: &str,
+    options: &[(String, String)],
+) -> Option<Result<Vec<(String, String)>, String>> {
+    let format = CString::new(format).unwrap();
+    let names = [
+        CString::new("printer-uri").unwrap(),
+        CString::new("requesting-user-name").unwrap(),
+        CString::new("document-format").unwrap(),
+    ];
+
+    unsafe {
+        let (http, uri, resource) = match connect_dest(printer_name) {
+            Ok(connection) => connection,
+            Err(error) => return Some(Err(error)),
+        };
+
+        let request = ippNewRequest(IPP_OP_VALIDATE_JOB);
+        ippAddString(
+            request,
+            IPP_TAG_OPERATION,
+            IPP_TAG_URI,
+            names[0].as_ptr(),
+            ptr::null(),
+            uri.as_ptr(),
+        );
+        ippAddString(
+            request,
+            IPP_TAG_OPERATION,
+            IPP_TAG_NAME,
+            names[1].as_ptr(),
+            ptr::null(),
+            cupsUser(),
+        );
+        ippAddString(
+            request,
+            IPP_TAG_OPERATION,
+            IPP_TAG_MIMETYPE,
+            names[2].as_ptr(),
+            ptr::null(),
+            format.as_ptr(),
+        );
+
+        let (num_options, options_ptr) = add_options(options);
+        cupsEncodeOptions2(request, num_options, options_ptr, IPP_TAG_JOB);
+        cupsFreeOptions(num_options, options_ptr);
+
+        // The request is released by cupsDoRequest
+        let response = cupsDoRequest(http, request, resource.as_ptr());
+        httpClose(http);
+        if response.is_null() {
+            return None;
+        }
+
+        let status = ippGetStatusCode(response);
+        let mut unsupported = Vec::new();
+        let mut attr = ippFirstAttribute(response);
+
+        while !attr.is_null() {
+            if ippGetGroupTag(attr) == IPP_TAG_UNSUPPORTED_GROUP && !ippGetName(attr).is_null() {
+                let name = CStr::from_ptr(ippGetName(attr))
+                    .to_string_lossy()
+                    .to_string();
+                unsupported.push((name, attribute_string(attr)));
+            }
+            attr = ippNextAttribute(response);
+        }
+
+        ippDelete(response);
+
+        if status == IPP_STATUS_ERROR_OPERATION_NOT_SUPPORTED {
+            None
+        } else if status > IPP_STATUS_OK_CONFLICTING && unsupported.is_empty() {
+            Some(Err(get_last_error()))
+        } else {
+            Some(Ok(unsupported))
+        }
+    }
+}
+
+/**
+ * Connect to the server of a destination, returns the connection with the printer URI and the
+ * resource path to send requests to
+ * The destination URI and connection handle remote destinations, classes and CUPS_SERVER
+ */
+unsafe fn connect_dest(printer_name: &str) -> Result<(*mut c_void, CString, CString), String> {
+    let c_printer_name = CString::new(printer_name).unwrap();
+    let dest = cupsGetNamedDest(ptr::null_mut(), c_printer_name.as_ptr(), ptr::null());
+    if dest.is_null() {
+        return Err(format!("printer {} not found", printer_name));
+    }
+
+    let mut uri = (*dest).get_option_by_key("printer-uri-supported");
+    if uri.is_empty() {
+        uri = local_printer_uri(printer_name);
+    }
+
+    let mut resource: [c_char; 1024] = [0; 1024];
+    let http = cupsConnectDest(
+        dest,
+        CUPS_DEST_FLAGS_NONE,
+        30000,
+        ptr::null_mut(),
+        resource.as_mut_ptr(),
+        resource.len(),
+        ptr::null(),
+        ptr::null_mut(),
+    );
+    cupsFreeDests(1, dest);
+    if http.is_null() {
+        return Err(get_last_error());
+    }
+
+    let resource = CStr::from_ptr(resource.as_ptr()).to_owned();
+    Ok((http, CString::new(uri).unwrap(), resource))
+}
+
+/**
+ * Returns the URI of a queue of the local server, with the printer name escaped
+ */
+fn local_printer_uri(printer_name: &str) -> String {
+    let scheme = CString::new("ipp").unwrap();
+    let host = CString::new("localhost").unwrap();
+    let resource = CString::new("/printers/%s").unwrap();
+    let printer_name = CString::new(printer_name).unwrap();
+    let mut uri: [c_char; 1024] = [0; 1024];
+
+    unsafe {
+        httpAssembleURIf(
+            HTTP_URI_CODING_ALL,
+            uri.as_mut_ptr(),
+            uri.len() as c_int,
+            scheme.as_ptr(),
+            ptr::null(),
+            host.as_ptr(),
+            ippPort(),
+            resource.as_ptr(),
+            printer_name.as_ptr(),
+        );
+        CStr::from_ptr(uri.as_ptr()).to_string_lossy().to_string()
+    }
+}
+
+/**
+ * Send a Get-Printer-Attributes request to an IPP printer over TLS (ipps://) asking some attributes
+ * Returns an error when the connection or its TLS handshake fails, otherwise the result of the request
+ * with the printer attributes (name, values)
+ */
+pub fn get_printer_attributes_tls(
+    host: &str,
+    port: u16,
+    printer_uri: &str,
+    requested: &[&str],
+    timeout_ms: i32,
+) -> Result<Result<AttributeValues, String>, String> {
+    let c_host = CString::new(host).unwrap();
+    let uri = CString::new(printer_uri).unwrap();
+    let resource = CString::new(match printer_uri.split_once("://") {
+        Some((_, rest)) => match rest.find('/') {
+            Some(index) => rest[index..].split('?').next().unwrap_or("/"),
+            None => "/",
+        },
+        None => "/",
+    })
+    .unwrap();
+
+    unsafe {
+        let http = httpConnect2(
+            c_host.as_ptr(),
+            port as c_int,
+            ptr::null_mut(),
+            AF_UNSPEC,
+            HTTP_ENCRYPTION_ALWAYS,
+            1,
+            timeout_ms,
+            ptr::null_mut(),
+        );
+        if http.is_null() {
+            return Err(get_last_error());
+        }
+
+        let result = request_printer_attributes(http, &uri, &resource, requested);
+        httpClose(http);
+        Ok(result)
+    }
+}
+
+/**
+ * Send a Get-Printer-Attributes request for the destination asking some attributes
+ * (like printer-supply, which CUPS does not report with the destination options)
+ * Returns the printer attributes (name, values)
+ */
+pub fn get_dest_printer_attributes(
+    printer_name: &str,
+    requested: &[&str],
+) -> Result<AttributeValues, String> {
+    unsafe {
+        let (http, uri, resource) = connect_dest(printer_name)?;
+        let result = request_printer_attributes(http, &uri, &resource, requested);
+        httpClose(http);
+        result
+    }
+}
+
+/**
+ * Send a Get-Printer-Attributes request on a connection and read the printer attributes of the response
+ */
+unsafe fn request_printer_attributes(
+    http: *mut c_void,
+    uri: &CStr,
+    resource: &CStr,
+    requested: &[&str],
+) -> Result<AttributeValues, String> {
+    let names = [
+        CString::new("printer-uri").unwrap(),
+        CString::new("requested-attributes").unwrap(),
+    ];
+    let requested: Vec<CString> = requested
+        .iter()
+        .map(|name| CString::new(*name).unwrap())
+        .collect();
+    let requested_ptrs: Vec<*const c_char> = requested.iter().map(|name| name.as_ptr()).collect();
+
+    let request = ippNewRequest(IPP_OP_GET_PRINTER_ATTRIBUTES);
+    ippAddString(
+        request,
+        IPP_TAG_OPERATION,
+        IPP_TAG_URI,
+        names[0].as_ptr(),
+        ptr::null(),
+        uri.as_ptr(),
+    );
+    if !requested_ptrs.is_empty() {
+        ippAddStrings(
+            request,
+            IPP_TAG_OPERATION,
+            IPP_TAG_KEYWORD,
+            names[1].as_ptr(),
+            requested_ptrs.len() as c_int,
+            ptr::null(),
+            requested_ptrs.as_ptr(),
+        );
+    }
+
+    // The request is released by cupsDoRequest
+    let response = cupsDoRequest(http, request, resource.as_ptr());
+    if response.is_null() {
+        return Err(get_last_error());
+    }
+
+    let status = ippGetStatusCode(response);
+    let mut attributes: AttributeValues = Vec::new();
+    let mut attr = ippFirstAttribute(response);
+
+    while !attr.is_null() {
+        if ippGetGroupTag(attr) == IPP_TAG_PRINTER && !ippGetName(attr).is_null() {
+            let name = CStr::from_ptr(ippGetName(attr))
+                .to_string_lossy()
+                .to_string();
+            attributes.push((name, split_attribute_values(&attribute_string(attr))));
+        }
+        attr = ippNextAttribute(response);
+    }
+
+    ippDelete(response);
+
+    if status > IPP_STATUS_OK_CONFLICTING {
+        Err(format!(
+            "the printer returned the IPP status 0x{:04x} {}",
+            status,
+            get_last_error()
+        ))
+    } else {
+        Ok(attributes)
+    }
+}
+
+/**
+ * Returns the values of an IPP attribute as text (multiple values are separated by commas)
+ */
+unsafe fn attribute_string(attr: *mut c_void) -> String {
+    let length = ippAttributeString(attr, ptr::null_mut(), 0);
+    let mut buffer: Vec<u8> = vec![0; length + 1];
+    ippAttributeString(attr, buffer.as_mut_ptr() as *mut c_char, buffer.len());
+
+    CStr::from_bytes_until_nul(&buffer)
+        .map(|value| value.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/**
+ * Split the comma separated values returned by ippAttributeString (commas can be escaped by a backslash)
+ * Collection values like "{preset-name=draft print-quality=3}" are kept as is
+ */
+fn split_attribute_values(text: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut value = String::new();
+    let mut chars = text.chars();
+    let mut depth = 0;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if depth > 0 => {
+                value.push(c);
+                value.extend(chars.next());
+            }
+            '\\' => value.extend(chars.next()),
+            '{' => {
+                depth += 1;
+                value.push(c);
+            }
+            '}' if depth > 0 => {
+                depth -= 1;
+                value.push(c);
+            }
+            ',' if depth == 0 => values.push(std::mem::take(&mut value)),
+            _ => value.push(c),
+        }
+    }
+
+    if !value.is_empty() {
+        values.push(value);
+    }
+
+    values
+}
+
+/**
+ * Get the PPD file of the destination from the CUPS server (the temporary copy is removed)
+ */
+pub fn get_ppd(printer_name: &str) -> Result<Vec<u8>, String> {
+    let name = CString::new(printer_name).unwrap();
+
+    unsafe {
+        let file_name = cupsGetPPD2(ptr::null_mut(), name.as_ptr());
+        if file_name.is_null() {
+            return Err(format!(
+                "failed to get the PPD of {}: {}",
+                printer_name,
+                get_last_error()
+            ));
+        }
+
+        let file_path = CStr::from_ptr(file_name).to_string_lossy().to_string();
+        let data = std::fs::read(&file_path)
+            .map_err(|error| format!("failed to read {}: {}", file_path, error));
+        let _ = std::fs::remove_file(&file_path);
+        data
+    }
+}
+
 /**
  * Free the allocated memory for dests
  */
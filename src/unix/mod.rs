@@ -1,3 +1,7 @@
+use crate::document::DocumentFormat;
+use crate::ipp::AttributeValues;
+use crate::ppd::Ppd;
+use crate::print_options::PrintOptions;
 use crate::printer::{Printer, PrinterState};
 use crate::printer_job::{JobState, PrintJob};
 use std::str;
@@ -43,8 +47,14 @@ pub fn print(
     printer_system_name: &str,
     file_path: &str,
     job_name: Option<&str>,
+    options: &PrintOptions,
 ) -> Result<bool, String> {
-    let result = cups::print_file(printer_system_name, file_path, job_name);
+    let result = cups::print_file(
+        printer_system_name,
+        file_path,
+        job_name,
+        &get_cups_options(printer_system_name, options),
+    );
     return if result {
         Result::Ok(true)
     } else {
@@ -52,6 +62,150 @@ pub fn print(
     };
 }
 
+/**
+ * Print a raw document on unix systems using CUPS (application/vnd.cups-raw bypass all filters)
+ */
+pub fn print_raw(
+    printer_system_name: &str,
+    buffer: &[u8],
+    job_name: Option<&str>,
+) -> Result<bool, String> {
+    let raw = DocumentFormat::RAW.mime_type();
+
+    // Drivers can declare filters for raw documents, the data would not reach the printer untouched
+    if let Ok(ppd) = get_ppd(printer_system_name).and_then(|data| Ppd::parse(&data)) {
+        let raw_filter = ppd.attributes.iter().find(|attribute| {
+            (attribute.keyword == "cupsFilter" || attribute.keyword == "cupsFilter2")
+                && attribute.value.split_whitespace().next() == Some(raw)
+        });
+
+        if let Some(filter) = raw_filter {
+            return Err(format!(
+                "printer {} does not accept raw documents (the queue driver filters {} with \"{}\")",
+                printer_system_name, raw, filter.value
+            ));
+        }
+    }
+
+    let options = vec![("raw".to_string(), "true".to_string())];
+    if cups::print_buffer(printer_system_name, buffer, job_name, raw, &options) {
+        Ok(true)
+    } else {
+        Err(format!(
+            "failure on send raw document to printer: {}",
+            get_last_error()
+        ))
+    }
+}
+
+/**
+ * Convert the print options to CUPS options (name, value) for the printer
+ */
+fn get_cups_options(printer_system_name: &str, options: &PrintOptions) -> Vec<(String, String)> {
+    let mut cups_options = Vec::new();
+
+    if let Some(format) = options.document_format {
+        if format != DocumentFormat::AUTO {
+            cups_options.push((
+                "document-format".to_string(),
+                format.mime_type().to_string(),
+            ));
+        }
+    }
+
+    cups_options.extend(options.ipp_attributes());
+
+    // PPD options only go to queues with a PPD driver, with the choices of the PPD
+    // (PPD options given as raw attributes are kept)
+    if let Ok(ppd) = get_ppd(printer_system_name).and_then(|data| Ppd::parse(&data)) {
+        for (keyword, choice) in options.ppd_options() {
+            let supported = match ppd.option(&keyword) {
+                Some(option) => {
+                    option.choice(&choice).is_some()
+                        || (keyword == "PageSize"
+                            && ppd.custom_page_size
+                            && choice.starts_with("Custom."))
+                }
+                None => false,
+            };
+
+            if supported && !cups_options.iter().any(|(name, _)| *name == keyword) {
+                cups_options.push((keyword, choice));
+            }
+        }
+    }
+
+    cups_options
+}
+
+/**
+ * Get the document formats (MIME types) supported by the printer using CUPS
+ */
+pub fn get_supported_document_formats(printer_system_name: &str) -> Vec<String> {
+    cups::get_dest_supported(printer_system_name, "document-format")
+}
+
+/**
+ * Get the values supported by the printer for job attributes using CUPS
+ */
+pub fn get_supported_values(printer_system_name: &str, attributes: &[&str]) -> Vec<Vec<String>> {
+    cups::get_dest_supported_values(printer_system_name, attributes)
+}
+
+/**
+ * Get the attributes of the printer starting with a prefix (like "marker-") using CUPS
+ */
+pub fn get_attributes(printer_system_name: &str, prefix: &str) -> Vec<(String, Vec<String>)> {
+    cups::get_dest_attributes(printer_system_name, prefix)
+}
+
+/**
+ * Get printer attributes with a Get-Printer-Attributes request to the destination using CUPS
+ */
+pub fn get_printer_attributes(
+    printer_system_name: &str,
+    requested: &[&str],
+) -> Result<AttributeValues, String> {
+    cups::get_dest_printer_attributes(printer_system_name, requested)
+}
+
+/**
+ * Get the attributes of an IPP printer over TLS (ipps://) using CUPS
+ * Returns an error when the TLS connection fails, otherwise the result of the Get-Printer-Attributes request
+ */
+pub fn get_printer_attributes_tls(
+    host: &str,
+    port: u16,
+    printer_uri: &str,
+    requested: &[&str],
+    timeout_ms: i32,
+) -> Result<Result<AttributeValues, String>, String> {
+    cups::get_printer_attributes_tls(host, port, printer_uri, requested, timeout_ms)
+}
+
+/**
+ * Get the PPD file of the printer from the CUPS server
+ */
+pub fn get_ppd(printer_system_name: &str) -> Result<Vec<u8>, String> {
+    cups::get_ppd(printer_system_name)
+}
+
+/**
+ * Validate a job with the CUPS server using an IPP Validate-Job request
+ * Returns None when the server does not support it, otherwise the unsupported attributes (name, value)
+ */
+pub fn validate_job(
+    printer_system_name: &str,
+    format: DocumentFormat,
+    options: &PrintOptions,
+) -> Option<Result<Vec<(String, String)>, String>> {
+    cups::validate_job(
+        printer_system_name,
+        format.mime_type(),
+        &options.ipp_attributes(),
+    )
+}
+
 /**
  * Get print queue on unix systems using CUPS
  */
@@ -1,4 +1,8 @@
 use crate::{
+    document::DocumentFormat,
+    ipp::AttributeValues,
+    media::MediaSize,
+    print_options::{ColorMode, MediaSource, Orientation, PrintOptions, Scaling, Sides},
     printer::{Printer, PrinterState},
     printer_job::PrintJob,
     shared::interface::PlatformPrinterGetters,
@@ -45,18 +49,81 @@ pub fn get_printers() -> Vec<Printer> {
 pub fn print(
     printer_system_name: &str,
     file_path: &str,
-    _job_name: Option<&str>,
+    job_name: Option<&str>,
+    options: &PrintOptions,
 ) -> Result<bool, String> {
+    // Printer languages can't be opened by SumatraPDF and are written to the spooler as is
+    if matches!(options.document_format, Some(format) if format.is_raw()) {
+        let buffer = std::fs::read(file_path).map_err(|error| error.to_string())?;
+        return print_raw(printer_system_name, &buffer, job_name.or(Some(file_path)));
+    }
+
     let dir: std::path::PathBuf = env::temp_dir();
 
     let sumatra_pdf_path = format!("{}SumatraPDF.exe", dir.display());
     //let sumatra_pdf_path = "src\\windows\\lib\\SumatraPDF.exe";
-    let status = Command::new(&sumatra_pdf_path)
+    let mut command = Command::new(&sumatra_pdf_path);
+    command
         .arg("-silent")
         .arg("-print-to")
-        .arg(printer_system_name)
-        .arg(file_path)
-        .spawn();
+        .arg(printer_system_name);
+
+    let mut settings = Vec::new();
+
+    if let Some(copies) = options.copies {
+        settings.push(format!("{}x", copies));
+    }
+
+    if let Some(sides) = options.sides {
+        settings.push(
+            match sides {
+                Sides::OneSided => "simplex",
+                Sides::TwoSidedLongEdge => "duplexlong",
+                Sides::TwoSidedShortEdge => "duplexshort",
+            }
+            .to_string(),
+        );
+    }
+
+    // Other sizes are left to the printer default, SumatraPDF rejects the names it does not know
+    if let Some(paper) = options.media.as_ref().and_then(sumatra_paper) {
+        settings.push(format!("paper={}", paper));
+    }
+
+    if let Some(MediaSource::Tray(number)) = options.media_source {
+        settings.push(format!("bin={}", number));
+    }
+
+    match options.color_mode {
+        Some(ColorMode::Color) => settings.push("color".to_string()),
+        Some(ColorMode::Monochrome) | Some(ColorMode::BiLevel) => {
+            settings.push("monochrome".to_string())
+        }
+        _ => {}
+    }
+
+    match options.orientation {
+        Some(Orientation::Portrait) | Some(Orientation::ReversePortrait) => {
+            settings.push("portrait".to_string())
+        }
+        Some(Orientation::Landscape) | Some(Orientation::ReverseLandscape) => {
+            settings.push("landscape".to_string())
+        }
+        _ => {}
+    }
+
+    match options.scaling {
+        Some(Scaling::Fit) | Some(Scaling::Fill) => settings.push("fit".to_string()),
+        Some(Scaling::Auto) | Some(Scaling::AutoFit) => settings.push("shrink".to_string()),
+        Some(Scaling::None) => settings.push("noscale".to_string()),
+        None => {}
+    }
+
+    if !settings.is_empty() {
+        command.arg("-print-settings").arg(settings.join(","));
+    }
+
+    let status = command.arg(file_path).spawn();
 
     return if status.is_ok() {
         Result::Ok(true)
@@ -65,6 +132,125 @@ pub fn print(
     };
 }
 
+/**
+ * Returns the name of a media size for the SumatraPDF paper setting, which only knows a few sizes
+ */
+fn sumatra_paper(media: &MediaSize) -> Option<&'static str> {
+    const PAPERS: [(&str, &str); 9] = [
+        ("A2", "A2"),
+        ("A3", "A3"),
+        ("A4", "A4"),
+        ("A5", "A5"),
+        ("A6", "A6"),
+        ("Letter", "letter"),
+        ("Legal", "legal"),
+        ("Tabloid", "tabloid"),
+        ("Statement", "statement"),
+    ];
+
+    let name = media.ppd_name();
+    PAPERS
+        .iter()
+        .find(|(ppd_name, _)| *ppd_name == name)
+        .map(|(_, paper)| *paper)
+}
+
+/**
+ * Print a raw document on windows writing it to the spooler with the RAW datatype
+ */
+pub fn print_raw(
+    printer_system_name: &str,
+    buffer: &[u8],
+    job_name: Option<&str>,
+) -> Result<bool, String> {
+    let printer = winspool::enum_printers()
+        .into_iter()
+        .find(|printer| printer.get_system_name() == printer_system_name)
+        .ok_or_else(|| format!("printer {} not found", printer_system_name))?;
+
+    // Drivers with print processors without RAW support (XPS only drivers) would render the data
+    let datatypes = winspool::get_print_processor_datatypes(&printer.get_print_processor());
+    if !datatypes.is_empty() && !datatypes.iter().any(|datatype| datatype == "RAW") {
+        return Err(format!(
+            "printer {} does not accept raw documents (the print processor only accepts {})",
+            printer_system_name,
+            datatypes.join(", ")
+        ));
+    }
+
+    winspool::write_raw(
+        printer_system_name,
+        buffer,
+        job_name.unwrap_or("raw document"),
+    )?;
+
+    Ok(true)
+}
+
+/**
+ * Get the document formats supported by the printer (unknown on windows, the check is skipped)
+ */
+pub fn get_supported_document_formats(_printer_system_name: &str) -> Vec<String> {
+    vec![]
+}
+
+/**
+ * Get the values supported by the printer for job attributes (unknown on windows, the checks are skipped)
+ */
+pub fn get_supported_values(_printer_system_name: &str, attributes: &[&str]) -> Vec<Vec<String>> {
+    vec![vec![]; attributes.len()]
+}
+
+/**
+ * Get the attributes of the printer starting with a prefix (unknown on windows)
+ */
+pub fn get_attributes(_printer_system_name: &str, _prefix: &str) -> Vec<(String, Vec<String>)> {
+    vec![]
+}
+
+/**
+ * Get printer attributes with a Get-Printer-Attributes request, not available on windows
+ */
+pub fn get_printer_attributes(
+    _printer_system_name: &str,
+    _requested: &[&str],
+) -> Result<AttributeValues, String> {
+    Err("Get-Printer-Attributes requests are only available with CUPS".to_string())
+}
+
+/**
+ * Get the attributes of an IPP printer over TLS (ipps://), not available on windows
+ */
+pub fn get_printer_attributes_tls(
+    _host: &str,
+    _port: u16,
+    _printer_uri: &str,
+    _requested: &[&str],
+    _timeout_ms: i32,
+) -> Result<Result<AttributeValues, String>, String> {
+    Ok(Err(
+        "IPP requests over TLS are only available with CUPS".to_string()
+    ))
+}
+
+/**
+ * Get the PPD file of the printer (windows drivers do not use PPD files)
+ */
+pub fn get_ppd(_printer_system_name: &str) -> Result<Vec<u8>, String> {
+    Err("PPD files are only available with CUPS".to_string())
+}
+
+/**
+ * Validate a job with the printer (not available on windows, only the local checks are made)
+ */
+pub fn validate_job(
+    _printer_system_name: &str,
+    _format: DocumentFormat,
+    _options: &PrintOptions,
+) -> Option<Result<Vec<(String, String)>, String>> {
+    None
+}
+
 /**
  * Get print queue on windows systems using winspool
  */
@@ -19,6 +19,43 @@ extern "system" {
 
     fn GetDefaultPrinterW(pszBuffer: *mut wchar_t, pcchBuffer: *mut c_ulong) -> c_int;
 
+    fn OpenPrinterW(
+        pPrinterName: *const wchar_t,
+        phPrinter: *mut *mut c_void,
+        pDefault: *mut PrinterDefaults,
+    ) -> c_int;
+
+    fn StartDocPrinterW(
+        hPrinter: *mut c_void,
+        Level: c_ulong,
+        pDocInfo: *const DocInfo1w,
+    ) -> c_ulong;
+
+    fn StartPagePrinter(hPrinter: *mut c_void) -> c_int;
+
+    fn WritePrinter(
+        hPrinter: *mut c_void,
+        pBuf: *const c_void,
+        cbBuf: c_ulong,
+        pcWritten: *mut c_ulong,
+    ) -> c_int;
+
+    fn EndPagePrinter(hPrinter: *mut c_void) -> c_int;
+
+    fn EndDocPrinter(hPrinter: *mut c_void) -> c_int;
+
+    fn ClosePrinter(hPrinter: *mut c_void) -> c_int;
+
+    fn EnumPrintProcessorDatatypesW(
+        pName: *const wchar_t,
+        pPrintProcessorName: *const wchar_t,
+        Level: c_ulong,
+        pDatatypes: *mut u8,
+        cbBuf: c_ulong,
+        pcbNeeded: *mut c_ulong,
+        pcReturned: *mut c_ulong,
+    ) -> c_int;
+
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -65,6 +102,20 @@ struct AddJobInfo2 {
     status: *mut wchar_t,
 }
 
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+struct DocInfo1w {
+    pDocName: *const wchar_t,
+    pOutputFile: *const wchar_t,
+    pDatatype: *const wchar_t,
+}
+
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+struct DatatypesInfo1w {
+    pName: *mut wchar_t,
+}
+
 impl PrinterInfo2w {
     /**
      * Returns a string of wchar_t pointer
@@ -84,6 +135,13 @@ impl PrinterInfo2w {
         }
         return String::from_utf16_lossy(&vec);
     }
+
+    /**
+     * Returns the name of the print processor used by the printer
+     */
+    pub fn get_print_processor(&self) -> String {
+        self.get_wchar_t_value(self.pPrintProcessor)
+    }
 }
 
 impl PlatformPrinterGetters for PrinterInfo2w {
@@ -123,10 +181,20 @@ impl PlatformPrinterGetters for PrinterInfo2w {
     }
 
     /**
-     * Return the printer uri
+     * Return the printer uri built from the port name
+     * (URL ports are returned as is, standard TCP/IP ports "IP_host" as socket://host)
      */
     fn get_uri(&self) -> String {
-        return "".to_string();
+        let port_name = self.get_wchar_t_value(self.pPortName);
+
+        if port_name.contains("://") {
+            return port_name;
+        }
+
+        match port_name.strip_prefix("IP_") {
+            Some(host) => format!("socket://{}", host),
+            None => "".to_string(),
+        }
     }
 
     /**
@@ -202,3 +270,112 @@ pub fn enum_printers() -> Vec<PrinterInfo2w> {
 
     return buffer;
 }
+
+/**
+ * Returns a null terminated wide string
+ */
+fn to_wchar_t(value: &str) -> Vec<wchar_t> {
+    value
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .map(|c| c as wchar_t)
+        .collect()
+}
+
+/**
+ * Returns the datatypes (RAW, EMF, TEXT...) accepted by a print processor
+ */
+pub fn get_print_processor_datatypes(print_processor: &str) -> Vec<String> {
+    let print_processor = to_wchar_t(print_processor);
+    let mut bytes_needed: c_ulong = 0;
+    let mut count_datatypes: c_ulong = 0;
+
+    unsafe {
+        EnumPrintProcessorDatatypesW(
+            ptr::null(),
+            print_processor.as_ptr(),
+            1,
+            ptr::null_mut(),
+            0,
+            &mut bytes_needed,
+            &mut count_datatypes,
+        );
+
+        // The buffer is allocated with the datatypes info alignment
+        let mut buffer: Vec<DatatypesInfo1w> =
+            Vec::with_capacity(bytes_needed as usize / std::mem::size_of::<DatatypesInfo1w>() + 1);
+
+        let result = EnumPrintProcessorDatatypesW(
+            ptr::null(),
+            print_processor.as_ptr(),
+            1,
+            buffer.as_mut_ptr() as *mut u8,
+            bytes_needed,
+            &mut bytes_needed,
+            &mut count_datatypes,
+        );
+
+        if result == 0 {
+            return vec![];
+        }
+
+        slice::from_raw_parts(buffer.as_ptr(), count_datatypes as usize)
+            .iter()
+            .map(|datatype| {
+                let mut name: Vec<u16> = Vec::new();
+                let mut i = 0;
+                while *datatype.pName.offset(i) != 0 {
+                    name.push(*datatype.pName.offset(i) as u16);
+                    i += 1;
+                }
+                String::from_utf16_lossy(&name)
+            })
+            .collect()
+    }
+}
+
+/**
+ * Write a buffer to the printer spooler using the RAW datatype (the driver does not process the data)
+ */
+pub fn write_raw(printer_name: &str, buffer: &[u8], job_name: &str) -> Result<(), String> {
+    let printer_name = to_wchar_t(printer_name);
+    let doc_name = to_wchar_t(job_name);
+    let datatype = to_wchar_t("RAW");
+
+    let doc_info = DocInfo1w {
+        pDocName: doc_name.as_ptr(),
+        pOutputFile: ptr::null(),
+        pDatatype: datatype.as_ptr(),
+    };
+
+    unsafe {
+        let mut handle: *mut c_void = ptr::null_mut();
+        if OpenPrinterW(printer_name.as_ptr(), &mut handle, ptr::null_mut()) == 0 {
+            return Err("failure to open printer".to_string());
+        }
+
+        if StartDocPrinterW(handle, 1, &doc_info) == 0 {
+            ClosePrinter(handle);
+            return Err("failure to start raw document".to_string());
+        }
+
+        let mut written: c_ulong = 0;
+        let result = StartPagePrinter(handle) != 0
+            && WritePrinter(
+                handle,
+                buffer.as_ptr() as *const c_void,
+                buffer.len() as c_ulong,
+                &mut written,
+            ) != 0
+            && EndPagePrinter(handle) != 0;
+
+        EndDocPrinter(handle);
+        ClosePrinter(handle);
+
+        if !result || written as usize != buffer.len() {
+            return Err("failure to write raw document to printer".to_string());
+        }
+    }
+
+    Ok(())
+}
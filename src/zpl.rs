@@ -0,0 +1,352 @@
+//! ZPL II label builder for Zebra and compatible label printers
+//! The built bytes must be sent untouched to the printer (see Printer::print_raw)
+//!
+//! ```rust
+//! use printers::zpl::{Rotation, Zpl};
+//!
+//! let mut label = Zpl::new(203);
+//! let width = label.mm(100.0);
+//!
+//! let bytes = label
+//!     .print_width(width)
+//!     .label_length_mm(150.0)
+//!     .text(50, 50, '0', Rotation::Normal, 40, 40, "Ship to: ACME")
+//!     .code128(50, 120, Rotation::Normal, 100, true, "1Z999AA10123456784")
+//!     .build();
+//! ```
+
+use crate::raster::Bitmap;
+
+/**
+ * Orientation of fields and barcodes
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    /**
+     * Normal (N)
+     */
+    Normal,
+
+    /**
+     * Rotated 90 degrees clockwise (R)
+     */
+    Rotated90,
+
+    /**
+     * Inverted 180 degrees (I)
+     */
+    Inverted,
+
+    /**
+     * Read from bottom up, 270 degrees (B)
+     */
+    Rotated270,
+}
+
+impl Rotation {
+    fn code(&self) -> char {
+        match self {
+            Rotation::Normal => 'N',
+            Rotation::Rotated90 => 'R',
+            Rotation::Inverted => 'I',
+            Rotation::Rotated270 => 'B',
+        }
+    }
+}
+
+/**
+ * QR code error correction level
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QrErrorCorrection {
+    L,
+    M,
+    Q,
+    H,
+}
+
+/**
+ * Zpl is a builder of a ZPL II label (the format is enclosed by ^XA and ^XZ on build)
+ * Positions and sizes are in dots, use mm() to convert millimeters on the printer resolution
+ */
+#[derive(Debug, Clone)]
+pub struct Zpl {
+    dpi: u32,
+    setup: String,
+    fields: String,
+}
+
+impl Zpl {
+    /**
+     * Create an empty label for a printer resolution in dots per inch (152, 203, 300 or 600)
+     */
+    pub fn new(dpi: u32) -> Zpl {
+        Zpl {
+            dpi,
+            setup: String::new(),
+            fields: String::new(),
+        }
+    }
+
+    /**
+     * Convert millimeters to dots on the label resolution
+     */
+    pub fn mm(&self, millimeters: f64) -> u32 {
+        mm_to_dots(millimeters, self.dpi)
+    }
+
+    /**
+     * Set the print width in dots (^PW)
+     */
+    pub fn print_width(&mut self, dots: u32) -> &mut Zpl {
+        self.setup.push_str(&format!("^PW{}", dots));
+        self
+    }
+
+    /**
+     * Set the print width in millimeters (^PW)
+     */
+    pub fn print_width_mm(&mut self, millimeters: f64) -> &mut Zpl {
+        self.print_width(self.mm(millimeters))
+    }
+
+    /**
+     * Set the label length in dots (^LL)
+     */
+    pub fn label_length(&mut self, dots: u32) -> &mut Zpl {
+        self.setup.push_str(&format!("^LL{}", dots));
+        self
+    }
+
+    /**
+     * Set the label length in millimeters (^LL)
+     */
+    pub fn label_length_mm(&mut self, millimeters: f64) -> &mut Zpl {
+        self.label_length(self.mm(millimeters))
+    }
+
+    /**
+     * Set the print darkness from 0 to 30 (~SD)
+     */
+    pub fn darkness(&mut self, darkness: u8) -> &mut Zpl {
+        self.setup.push_str(&format!("~SD{:02}", darkness.min(30)));
+        self
+    }
+
+    /**
+     * Set the number of labels printed (^PQ)
+     */
+    pub fn quantity(&mut self, quantity: u32) -> &mut Zpl {
+        self.setup.push_str(&format!("^PQ{}", quantity.max(1)));
+        self
+    }
+
+    /**
+     * Append ZPL commands without any processing
+     */
+    pub fn raw(&mut self, commands: &str) -> &mut Zpl {
+        self.fields.push_str(commands);
+        self
+    }
+
+    /**
+     * Print text with a font (0 is the scalable font, A to Z and 1 to 9 the bitmap fonts)
+     * and the character height and width in dots (^FO, ^A, ^FD)
+     */
+    #[allow(clippy::too_many_arguments)]
+    pub fn text(
+        &mut self,
+        x: u32,
+        y: u32,
+        font: char,
+        rotation: Rotation,
+        height: u32,
+        width: u32,
+        text: &str,
+    ) -> &mut Zpl {
+        let command = format!("^A{}{},{},{}", font, rotation.code(), height, width);
+        self.field(x, y, &command, text)
+    }
+
+    /**
+     * Draw a box with a border thickness in dots, a thickness equal to the box size draws a filled box (^GB)
+     */
+    pub fn graphic_box(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        thickness: u32,
+    ) -> &mut Zpl {
+        let thickness = thickness.max(1);
+        let command = format!(
+            "^GB{},{},{}",
+            width.max(thickness),
+            height.max(thickness),
+            thickness
+        );
+        self.field(x, y, &command, "")
+    }
+
+    /**
+     * Draw a horizontal line (^GB)
+     */
+    pub fn horizontal_line(&mut self, x: u32, y: u32, length: u32, thickness: u32) -> &mut Zpl {
+        self.graphic_box(x, y, length, thickness, thickness)
+    }
+
+    /**
+     * Draw a vertical line (^GB)
+     */
+    pub fn vertical_line(&mut self, x: u32, y: u32, length: u32, thickness: u32) -> &mut Zpl {
+        self.graphic_box(x, y, thickness, length, thickness)
+    }
+
+    /**
+     * Print a Code 128 barcode with height in dots and optional interpretation line (^BC)
+     */
+    pub fn code128(
+        &mut self,
+        x: u32,
+        y: u32,
+        rotation: Rotation,
+        height: u32,
+        interpretation: bool,
+        data: &str,
+    ) -> &mut Zpl {
+        let interpretation = if interpretation { 'Y' } else { 'N' };
+        let command = format!("^BC{},{},{},N,N", rotation.code(), height, interpretation);
+        self.field(x, y, &command, data)
+    }
+
+    /**
+     * Print a GS1-128 barcode (^BC on UCC/EAN mode)
+     * Application identifiers are given between parentheses, like "(01)09501101530003(10)ABC"
+     */
+    pub fn gs1_128(
+        &mut self,
+        x: u32,
+        y: u32,
+        rotation: Rotation,
+        height: u32,
+        data: &str,
+    ) -> &mut Zpl {
+        let command = format!("^BC{},{},Y,N,N,D", rotation.code(), height);
+        self.field(x, y, &command, data)
+    }
+
+    /**
+     * Print a Data Matrix ECC 200 symbol with module size in dots (^BX)
+     */
+    pub fn data_matrix(
+        &mut self,
+        x: u32,
+        y: u32,
+        rotation: Rotation,
+        module_size: u32,
+        data: &str,
+    ) -> &mut Zpl {
+        let command = format!("^BX{},{},200", rotation.code(), module_size.max(1));
+        self.field(x, y, &command, data)
+    }
+
+    /**
+     * Print a QR code (model 2) with magnification from 1 to 10 (^BQ)
+     */
+    pub fn qr_code(
+        &mut self,
+        x: u32,
+        y: u32,
+        magnification: u32,
+        error_correction: QrErrorCorrection,
+        data: &str,
+    ) -> &mut Zpl {
+        let command = format!("^BQN,2,{}", magnification.clamp(1, 10));
+        let data = format!("{:?}A,{}", error_correction, data);
+        self.field(x, y, &command, &data)
+    }
+
+    /**
+     * Print a bitmap (^GF as ASCII hexadecimal)
+     * Data is packed one bit per dot (1 is black), MSB first, each row padded to a whole byte
+     */
+    pub fn graphic(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        data: &[u8],
+    ) -> Result<&mut Zpl, String> {
+        let row_bytes = width.div_ceil(8);
+
+        if width == 0 || height == 0 {
+            return Err(format!("invalid graphic size {}x{}", width, height));
+        }
+
+        if data.len() != (row_bytes * height) as usize {
+            return Err(format!(
+                "graphic data must have {} bytes for {}x{} dots, got {}",
+                row_bytes * height,
+                width,
+                height,
+                data.len()
+            ));
+        }
+
+        let hex: String = data.iter().map(|byte| format!("{:02X}", byte)).collect();
+        let command = format!("^GFA,{0},{0},{1},{2}", data.len(), row_bytes, hex);
+
+        self.fields
+            .push_str(&format!("^FO{},{}{}^FS", x, y, command));
+        Ok(self)
+    }
+
+    /**
+     * Print a bitmap made by the raster module (^GF)
+     */
+    pub fn bitmap(&mut self, x: u32, y: u32, bitmap: &Bitmap) -> Result<&mut Zpl, String> {
+        self.graphic(x, y, bitmap.width, bitmap.height, &bitmap.data)
+    }
+
+    /**
+     * Returns the label commands
+     */
+    pub fn build(&self) -> Vec<u8> {
+        // ^CI28 selects UTF-8 encoding for the field data
+        format!("^XA^CI28{}{}^XZ", self.setup, self.fields).into_bytes()
+    }
+
+    /**
+     * Append a field at a position, the data is escaped with ^FH when it has control characters
+     */
+    fn field(&mut self, x: u32, y: u32, command: &str, data: &str) -> &mut Zpl {
+        self.fields.push_str(&format!("^FO{},{}{}", x, y, command));
+
+        if !data.is_empty() {
+            if data.contains(['^', '~', '_']) {
+                let escaped: String = data
+                    .chars()
+                    .map(|c| match c {
+                        '^' | '~' | '_' => format!("_{:02X}", c as u8),
+                        _ => c.to_string(),
+                    })
+                    .collect();
+                self.fields.push_str(&format!("^FH_^FD{}", escaped));
+            } else {
+                self.fields.push_str(&format!("^FD{}", data));
+            }
+        }
+
+        self.fields.push_str("^FS");
+        self
+    }
+}
+
+/**
+ * Convert millimeters to dots on a resolution in dots per inch
+ */
+pub fn mm_to_dots(millimeters: f64, dpi: u32) -> u32 {
+    (millimeters * dpi as f64 / 25.4).round().max(0.0) as u32
+}
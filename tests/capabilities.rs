@@ -0,0 +1,172 @@
+use printers::capabilities::{self, Capabilities, Violation};
+use printers::document::{Document, DocumentFormat};
+use printers::print_options::{PrintOptions, Sides};
+
+/**
+ * Returns the capabilities of an A4 only simplex monochrome laser printer
+ */
+fn laser_printer() -> Capabilities {
+    let values = |values: &[&str]| values.iter().map(|value| value.to_string()).collect();
+
+    let mut capabilities = Capabilities::default();
+    capabilities
+        .set(
+            "document-format",
+            values(&["application/postscript", "application/pdf"]),
+        )
+        .set("copies", values(&["1-99"]))
+        .set(
+            "media",
+            values(&["iso_a4_210x297mm", "iso_a5_148x210mm", "na_letter_8.5x11in"]),
+        )
+        .set("sides", values(&["one-sided"]))
+        .set(
+            "print-color-mode",
+            values(&["monochrome", "auto-monochrome"]),
+        )
+        .set(
+            "printer-resolution",
+            values(&["300dpi", "600dpi", "1200x600dpi"]),
+        )
+        .set("finishings", values(&["none", "staple", "punch"]))
+        .set("print-quality", values(&["normal", "high"]));
+    capabilities
+}
+
+/**
+ * Returns print options with raw job attributes
+ */
+fn attributes(attributes: &[(&str, &str)]) -> PrintOptions {
+    PrintOptions {
+        attributes: attributes
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+        ..PrintOptions::default()
+    }
+}
+
+#[test]
+pub fn test_validate_suggestions() {
+    let mut options = attributes(&[
+        ("media", "iso_a3_297x420mm"),
+        ("print-color-mode", "color"),
+        ("printer-resolution", "1200dpi"),
+        ("print-quality", "draft"),
+    ]);
+    options.sides = Some(Sides::TwoSidedShortEdge);
+
+    let violations = capabilities::validate(&laser_printer(), &options, DocumentFormat::PDF);
+    let suggestions: Vec<(&str, &str, Option<&str>)> = violations
+        .iter()
+        .map(|violation| {
+            (
+                violation.attribute.as_str(),
+                violation.value.as_str(),
+                violation.suggestion.as_deref(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        suggestions,
+        vec![
+            ("sides", "two-sided-short-edge", Some("one-sided")),
+            ("media", "iso_a3_297x420mm", Some("iso_a4_210x297mm")),
+            ("print-color-mode", "color", Some("auto-monochrome")),
+            ("printer-resolution", "1200dpi", Some("1200x600dpi")),
+            ("print-quality", "draft", Some("normal")),
+        ]
+    );
+}
+
+#[test]
+pub fn test_validate_copies_formats_and_finishings() {
+    let mut options = attributes(&[("finishings", "staple,bind"), ("media", "iso-a5")]);
+    options.copies = Some(150);
+
+    let violations = capabilities::validate(&laser_printer(), &options, DocumentFormat::PNG);
+    assert_eq!(
+        violations,
+        vec![
+            Violation {
+                attribute: "document-format".to_string(),
+                value: "image/png".to_string(),
+                suggestion: Some("application/pdf".to_string()),
+            },
+            Violation {
+                attribute: "copies".to_string(),
+                value: "150".to_string(),
+                suggestion: Some("99".to_string()),
+            },
+            Violation {
+                attribute: "finishings".to_string(),
+                value: "bind".to_string(),
+                suggestion: Some("none".to_string()),
+            },
+            Violation {
+                attribute: "media".to_string(),
+                value: "iso-a5".to_string(),
+                suggestion: Some("iso_a5_148x210mm".to_string()),
+            },
+        ]
+    );
+    assert_eq!(
+        violations[1].to_string(),
+        "copies 150 is not supported by the printer, the nearest supported value is 99"
+    );
+
+    // The document format given on the options replaces the detected format
+    options.document_format = Some(DocumentFormat::POSTSCRIPT);
+    options.copies = Some(2);
+    options.attributes.clear();
+    assert!(capabilities::validate(&laser_printer(), &options, DocumentFormat::PNG).is_empty());
+}
+
+#[test]
+pub fn test_validate_unreported_attributes() {
+    let mut options = attributes(&[
+        ("output-bin", "face-up"),
+        ("printer-resolution", "600x600dpi"),
+        ("print-quality", "5"),
+    ]);
+    options.copies = Some(1000);
+
+    // Printers without capabilities accept everything
+    assert!(
+        capabilities::validate(&Capabilities::default(), &options, DocumentFormat::PDF).is_empty()
+    );
+
+    // Equivalent values are accepted
+    options.copies = None;
+    assert!(capabilities::validate(&laser_printer(), &options, DocumentFormat::AUTO).is_empty());
+
+    assert_eq!(laser_printer().copies(), Some((1, 99)));
+    assert!(laser_printer().is_supported("printer-resolution", "600dpi"));
+    assert!(!laser_printer().is_supported("copies", "many"));
+}
+
+#[test]
+pub fn test_document() {
+    let document = Document::from(&b"%PDF-1.7\n"[..]);
+    assert_eq!(document.format, DocumentFormat::PDF);
+    assert_eq!(
+        Document::new(b"^XA^XZ".to_vec()).format,
+        DocumentFormat::ZPL
+    );
+    assert!(Document::from_file("/path/to/missing.pdf").is_err());
+
+    let options = PrintOptions {
+        copies: Some(2),
+        sides: Some(Sides::TwoSidedLongEdge),
+        ..attributes(&[("media", "na_letter_8.5x11in")])
+    };
+    assert_eq!(
+        options.ipp_attributes(),
+        vec![
+            ("copies".to_string(), "2".to_string()),
+            ("sides".to_string(), "two-sided-long-edge".to_string()),
+            ("media".to_string(), "na_letter_8.5x11in".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,89 @@
+use printers::device_uri::{percent_decode, DeviceUri};
+
+#[test]
+pub fn test_parse_network_device_uris() {
+    assert_eq!(
+        DeviceUri::parse("ipp://user@printer.local:8631/printers/Office%20Laser"),
+        DeviceUri::Ipp {
+            host: "printer.local".to_string(),
+            port: 8631,
+            path: "/printers/Office Laser".to_string(),
+        }
+    );
+    assert_eq!(
+        DeviceUri::parse("ipps://[fe80::1]/ipp/print"),
+        DeviceUri::Ipps {
+            host: "fe80::1".to_string(),
+            port: 631,
+            path: "/ipp/print".to_string(),
+        }
+    );
+    assert_eq!(
+        DeviceUri::parse("socket://10.0.0.5?waiteof=false"),
+        DeviceUri::Socket {
+            host: "10.0.0.5".to_string(),
+            port: 9100,
+        }
+    );
+    assert_eq!(
+        DeviceUri::parse("lpd://server:1515/labels?format=l"),
+        DeviceUri::Lpd {
+            host: "server".to_string(),
+            port: 1515,
+            queue: "labels".to_string(),
+        }
+    );
+}
+
+#[test]
+pub fn test_parse_local_and_discovered_device_uris() {
+    assert_eq!(
+        DeviceUri::parse("usb://Zebra%20Technologies/ZTC%20GK420d?serial=28J162101383"),
+        DeviceUri::Usb {
+            make: "Zebra Technologies".to_string(),
+            model: "ZTC GK420d".to_string(),
+            serial: Some("28J162101383".to_string()),
+        }
+    );
+    assert_eq!(
+        DeviceUri::parse(
+            "dnssd://HP%20LaserJet%20400%20M401dne%20(5B9F2A)._ipp._tcp.local./?uuid=434e"
+        ),
+        DeviceUri::DnsSd {
+            service_name: "HP LaserJet 400 M401dne (5B9F2A)".to_string(),
+            service_type: "_ipp._tcp".to_string(),
+            domain: "local".to_string(),
+        }
+    );
+    assert_eq!(
+        DeviceUri::parse("dnssd://Label%2E1%20%40%20host._pdl-datastream._tcp.local/"),
+        DeviceUri::DnsSd {
+            service_name: "Label.1 @ host".to_string(),
+            service_type: "_pdl-datastream._tcp".to_string(),
+            domain: "local".to_string(),
+        }
+    );
+    assert_eq!(
+        DeviceUri::parse("file:///dev/usb/lp0"),
+        DeviceUri::File {
+            path: "/dev/usb/lp0".to_string(),
+        }
+    );
+}
+
+#[test]
+pub fn test_parse_other_device_uris() {
+    let uri = DeviceUri::parse("hp:/usb/OfficeJet?serial=1");
+    assert_eq!(uri.scheme(), "hp");
+    assert_eq!(uri.host(), None);
+
+    assert!(matches!(DeviceUri::parse(""), DeviceUri::Other { .. }));
+    assert!(matches!(
+        DeviceUri::parse("socket://host:port"),
+        DeviceUri::Other { .. }
+    ));
+
+    assert_eq!(DeviceUri::parse("socket://h:9101").host(), Some("h"));
+    assert_eq!(percent_decode("a%20b%zz%4"), "a b%zz%4");
+    assert_eq!(percent_decode("%+5%-1%4F"), "%+5%-1O");
+}
@@ -0,0 +1,85 @@
+use printers::document::DocumentFormat;
+
+#[test]
+pub fn test_detect_document_formats() {
+    assert_eq!(DocumentFormat::detect(b"%PDF-1.7\n"), DocumentFormat::PDF);
+    assert_eq!(
+        DocumentFormat::detect(b"%!PS-Adobe-3.0\n"),
+        DocumentFormat::POSTSCRIPT
+    );
+    assert_eq!(
+        DocumentFormat::detect(b"\x1bE\x1b&l26A"),
+        DocumentFormat::PCL
+    );
+    assert_eq!(
+        DocumentFormat::detect(b"RaS2PwgRaster\0"),
+        DocumentFormat::PWG
+    );
+    assert_eq!(
+        DocumentFormat::detect(b"UNIRAST\0\0\0\0\x01"),
+        DocumentFormat::URF
+    );
+    assert_eq!(
+        DocumentFormat::detect(&[0xFF, 0xD8, 0xFF, 0xE0]),
+        DocumentFormat::JPEG
+    );
+    assert_eq!(
+        DocumentFormat::detect(b"\x89PNG\r\n\x1a\n\0\0"),
+        DocumentFormat::PNG
+    );
+    assert_eq!(
+        DocumentFormat::detect("Olá, mundo\n".as_bytes()),
+        DocumentFormat::TEXT
+    );
+    assert_eq!(
+        DocumentFormat::detect(&[0x00, 0x01, 0x02]),
+        DocumentFormat::AUTO
+    );
+}
+
+#[test]
+pub fn test_detect_raw_label_and_receipt_data() {
+    // Printable ZPL must not be detected as plain text
+    let zpl = b"\n^XA\n^FO50,50^A0N,50,50^FDHello^FS\n^XZ\n";
+    assert_eq!(DocumentFormat::detect(zpl), DocumentFormat::ZPL);
+    assert_eq!(
+        DocumentFormat::detect(b"~TA000~JSN^XA^XZ"),
+        DocumentFormat::ZPL
+    );
+
+    assert_eq!(
+        DocumentFormat::detect(b"\x1b@Hello\n\x1dV\x00"),
+        DocumentFormat::ESCPOS
+    );
+    assert_eq!(
+        DocumentFormat::detect(b"\x1bE\x01Bold"),
+        DocumentFormat::ESCPOS
+    );
+
+    let pjl = b"\x1b%-12345X@PJL JOB\r\n@PJL ENTER LANGUAGE = POSTSCRIPT\r\n%!PS";
+    assert_eq!(DocumentFormat::detect(pjl), DocumentFormat::POSTSCRIPT);
+}
+
+#[test]
+pub fn test_document_format_mime_types() {
+    assert_eq!(DocumentFormat::ZPL.mime_type(), "application/vnd.cups-raw");
+    assert_eq!(
+        DocumentFormat::from_mime_type("application/PDF"),
+        DocumentFormat::PDF
+    );
+    assert_eq!(
+        DocumentFormat::from_mime_type("text/plain; charset=utf-8"),
+        DocumentFormat::TEXT
+    );
+}
+
+#[test]
+pub fn test_raw_document() {
+    use printers::document::RawDocument;
+
+    let document = RawDocument::from("^XA^FDLabel^FS^XZ".as_bytes());
+    assert_eq!(document.data, b"^XA^FDLabel^FS^XZ".to_vec());
+    assert!(DocumentFormat::detect(&document.data).is_raw());
+    assert_eq!(DocumentFormat::RAW.mime_type(), "application/vnd.cups-raw");
+    assert!(!DocumentFormat::PDF.is_raw());
+}
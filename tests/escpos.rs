@@ -0,0 +1,122 @@
+use printers::escpos::{
+    Alignment, Barcode, BarcodeText, CodePage, Cut, DrawerPin, EscPos, QrErrorCorrection, Underline,
+};
+
+#[test]
+pub fn test_escpos_text_styles() {
+    let bytes = EscPos::new()
+        .initialize()
+        .align(Alignment::Center)
+        .bold(true)
+        .underline(Underline::Double)
+        .double_size(true)
+        .line("Hi")
+        .unwrap()
+        .size(1, 3)
+        .feed(3)
+        .cut(Cut::Partial)
+        .cash_drawer(DrawerPin::Pin2, 25, 250)
+        .build();
+
+    assert_eq!(
+        bytes,
+        vec![
+            0x1b, b'@', 0x1b, b'a', 1, 0x1b, b'E', 1, 0x1b, b'-', 2, 0x1d, b'!', 0x11, b'H', b'i',
+            b'\n', 0x1d, b'!', 0x02, 0x1b, b'd', 3, 0x1d, b'V', 1, 0x1b, b'p', 0, 25, 250,
+        ]
+    );
+}
+
+#[test]
+pub fn test_escpos_code_pages() {
+    let bytes = EscPos::new()
+        .text("é")
+        .unwrap()
+        .code_page(CodePage::PC858)
+        .text("€Ø")
+        .unwrap()
+        .code_page(CodePage::WPC1252)
+        .text("€ÿ")
+        .unwrap()
+        .build();
+
+    assert_eq!(
+        bytes,
+        vec![0x82, 0x1b, b't', 19, 0xd5, 0x9d, 0x1b, b't', 16, 0x80, 0xff]
+    );
+
+    // Characters out of the code page are rejected, unless a replacement is set
+    let mut receipt = EscPos::new();
+    assert_eq!(
+        receipt.code_page(CodePage::WPC1252).text("ÿ✓").err(),
+        Some("character '✓' (U+2713) is out of the code page WPC1252".to_string())
+    );
+    assert_eq!(receipt.build(), vec![0x1b, b't', 16]);
+
+    let bytes = EscPos::new()
+        .replace_unmappable('?')
+        .text("é✓")
+        .unwrap()
+        .build();
+    assert_eq!(bytes, vec![0x82, b'?']);
+    assert!(EscPos::new().replace_unmappable('✓').text("✓").is_err());
+}
+
+#[test]
+pub fn test_escpos_barcodes() {
+    let bytes = EscPos::new()
+        .barcode(Barcode::Code128, "AB12", 80, 3, BarcodeText::Below)
+        .unwrap()
+        .build();
+
+    assert_eq!(
+        bytes,
+        vec![
+            0x1d, b'h', 80, 0x1d, b'w', 3, 0x1d, b'H', 2, 0x1d, b'k', 73, 6, b'{', b'B', b'A',
+            b'B', b'1', b'2',
+        ]
+    );
+
+    assert!(EscPos::new()
+        .barcode(Barcode::Ean13, "12345", 80, 3, BarcodeText::None)
+        .is_err());
+}
+
+#[test]
+pub fn test_escpos_qr_code() {
+    let bytes = EscPos::new()
+        .qr_code("hi", 6, QrErrorCorrection::M)
+        .unwrap()
+        .build();
+
+    assert_eq!(
+        bytes,
+        vec![
+            0x1d, b'(', b'k', 4, 0, 49, 65, 50, 0, // model 2
+            0x1d, b'(', b'k', 3, 0, 49, 67, 6, // module size
+            0x1d, b'(', b'k', 3, 0, 49, 69, 49, // error correction
+            0x1d, b'(', b'k', 5, 0, 49, 80, 48, b'h', b'i', // store data
+            0x1d, b'(', b'k', 3, 0, 49, 81, 48, // print
+        ]
+    );
+}
+
+#[test]
+pub fn test_escpos_raster_image() {
+    // 10x2 dots image, 2 bytes per row
+    let data = [0xff, 0xc0, 0x80, 0x40];
+    let bytes = EscPos::new().image(10, 2, &data).unwrap().build();
+
+    assert_eq!(
+        bytes,
+        vec![0x1d, b'v', b'0', 0, 2, 0, 2, 0, 0xff, 0xc0, 0x80, 0x40]
+    );
+
+    assert!(EscPos::new().image(10, 2, &data[..3]).is_err());
+
+    // Images taller than a band are split on many GS v 0 commands
+    let tall = vec![0xaa; 300];
+    let bytes = EscPos::new().image(8, 300, &tall).unwrap().build();
+    assert_eq!(bytes.len(), 8 + 256 + 8 + 44);
+    assert_eq!(&bytes[264..272], &[0x1d, b'v', b'0', 0, 1, 0, 44, 0]);
+}
@@ -0,0 +1,268 @@
+use printers::ipp::{self, PrinterAttributes};
+use printers::network::health::{self, Diagnosis};
+use printers::printer::{Printer, PrinterState};
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/**
+ * Returns a successful Get-Printer-Attributes response with the state of the printer
+ */
+fn response(state: i32, reasons: &[&str], accepting_jobs: bool) -> Vec<u8> {
+    let mut bytes = vec![2, 0, 0, 0, 0, 0, 0, 1, 0x01];
+    ipp::write_attribute(&mut bytes, 0x47, "attributes-charset", b"utf-8");
+    ipp::write_attribute(&mut bytes, 0x48, "attributes-natural-language", b"en");
+    bytes.push(0x04);
+    ipp::write_attribute(&mut bytes, 0x23, "printer-state", &state.to_be_bytes());
+    for (index, reason) in reasons.iter().enumerate() {
+        let name = if index == 0 {
+            "printer-state-reasons"
+        } else {
+            ""
+        };
+        ipp::write_attribute(&mut bytes, 0x44, name, reason.as_bytes());
+    }
+    ipp::write_attribute(&mut bytes, 0x41, "printer-state-message", b"Cover open");
+    ipp::write_attribute(
+        &mut bytes,
+        0x22,
+        "printer-is-accepting-jobs",
+        &[accepting_jobs as u8],
+    );
+    bytes.push(0x03);
+    bytes
+}
+
+/**
+ * Start a loopback stand-in IPP printer answering one request with an HTTP response, then keeping
+ * the connection open until the client closes it
+ * Returns the port of the printer and the received request
+ */
+fn start_printer(http_response: Vec<u8>) -> (u16, thread::JoinHandle<Vec<u8>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = Vec::new();
+        let mut buffer = [0; 4096];
+
+        // Read the headers and the whole body of the request
+        loop {
+            let length = stream.read(&mut buffer).unwrap();
+            request.extend_from_slice(&buffer[..length]);
+            let text = String::from_utf8_lossy(&request).to_string();
+            if let Some(header_end) = text.find("\r\n\r\n") {
+                let content_length: usize = text
+                    .lines()
+                    .find_map(|line| line.strip_prefix("Content-Length: "))
+                    .unwrap()
+                    .parse()
+                    .unwrap();
+                if request.len() >= header_end + 4 + content_length {
+                    break;
+                }
+            }
+        }
+
+        stream.write_all(&http_response).unwrap();
+        let _ = stream.read(&mut buffer);
+        request
+    });
+
+    (port, server)
+}
+
+fn http_response(body: &[u8]) -> Vec<u8> {
+    let mut bytes = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/ipp\r\nContent-Length: {}\r\n\r\n",
+        body.len()
+    )
+    .into_bytes();
+    bytes.extend_from_slice(body);
+    bytes
+}
+
+#[test]
+pub fn test_socket_health_check() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let printer = Printer::from_uri(&format!("socket://127.0.0.1:{}", port)).unwrap();
+    let health = printer.health_check(TIMEOUT);
+    assert!(health.reachable);
+    assert!(health.is_healthy());
+    assert!(health.latency.is_some());
+    assert_eq!(health.state, PrinterState::UNKNOWN);
+
+    // Nothing listens anymore on the port
+    drop(listener);
+    let health = printer.health_check(TIMEOUT);
+    assert!(!health.reachable);
+    assert_eq!(health.diagnosis, Diagnosis::ConnectionRefused);
+    assert_eq!(health.latency, None);
+}
+
+#[test]
+pub fn test_ipp_health_check() {
+    let (port, server) = start_printer(http_response(&response(3, &["none"], true)));
+    let uri = format!("ipp://127.0.0.1:{}/ipp/print", port);
+
+    let health = health::check(&uri, TIMEOUT);
+    assert!(health.is_healthy());
+    assert_eq!(health.state, PrinterState::READY);
+    assert_eq!(health.accepting_jobs, Some(true));
+    assert!(health.state_reasons.is_empty());
+
+    let request = server.join().unwrap();
+    let text = String::from_utf8_lossy(&request);
+    assert!(text.starts_with("POST /ipp/print HTTP/1.1\r\n"));
+    let expected = ipp::get_printer_attributes_request(
+        &uri,
+        &[
+            "printer-state",
+            "printer-state-reasons",
+            "printer-state-message",
+            "printer-is-accepting-jobs",
+        ],
+    );
+    assert!(request.ends_with(&expected));
+
+    // A stopped printer
+    let (port, _) = start_printer(http_response(&response(
+        5,
+        &["cover-open-error", "paused"],
+        false,
+    )));
+    let health = health::check(&format!("ipp://127.0.0.1:{}/ipp/print", port), TIMEOUT);
+    assert!(health.reachable);
+    assert_eq!(health.state, PrinterState::PAUSED);
+    assert_eq!(
+        health.diagnosis,
+        Diagnosis::PrinterStopped("Cover open".to_string())
+    );
+    assert_eq!(health.state_reasons, vec!["cover-open-error", "paused"]);
+
+    // An idle printer that rejects jobs, answering with a chunked body
+    let body = response(3, &["none"], false);
+    let mut chunked = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n".to_vec();
+    for chunk in body.chunks(20) {
+        chunked.extend(format!("{:x}\r\n", chunk.len()).into_bytes());
+        chunked.extend_from_slice(chunk);
+        chunked.extend_from_slice(b"\r\n");
+    }
+    chunked.extend_from_slice(b"0\r\n\r\n");
+    let (port, _) = start_printer(chunked);
+    let health = health::check(&format!("ipp://127.0.0.1:{}/", port), TIMEOUT);
+    assert_eq!(health.diagnosis, Diagnosis::NotAcceptingJobs);
+    assert_eq!(health.accepting_jobs, Some(false));
+}
+
+#[test]
+pub fn test_ipp_errors() {
+    let (port, _) = start_printer(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_vec());
+    let health = health::check(&format!("ipp://127.0.0.1:{}/ipp/missing", port), TIMEOUT);
+    assert!(health.reachable);
+    assert_eq!(
+        health.diagnosis,
+        Diagnosis::IppError("the printer answered HTTP/1.1 404 Not Found".to_string())
+    );
+
+    let mut body = vec![2, 0, 0x04, 0x01, 0, 0, 0, 1, 0x01];
+    ipp::write_attribute(&mut body, 0x47, "attributes-charset", b"utf-8");
+    body.push(0x03);
+    let (port, _) = start_printer(http_response(&body));
+    let health = health::check(&format!("ipp://127.0.0.1:{}/ipp/print", port), TIMEOUT);
+    assert_eq!(
+        health.diagnosis,
+        Diagnosis::IppError("the printer returned the IPP status 0x0401".to_string())
+    );
+}
+
+#[test]
+pub fn test_unsupported_and_unresolved_uris() {
+    let health = health::check("usb://HP/LaserJet%20M404?serial=123", TIMEOUT);
+    assert!(!health.reachable);
+    assert_eq!(
+        health.diagnosis,
+        Diagnosis::UnsupportedUri("usb://HP/LaserJet%20M404?serial=123".to_string())
+    );
+
+    let health = health::check("lpd://printer.invalid/queue", TIMEOUT);
+    assert!(!health.reachable);
+    assert!(matches!(health.diagnosis, Diagnosis::DnsFailure(_)));
+}
+
+#[test]
+pub fn test_queue_health_check() {
+    let mut queue = PrinterAttributes::default();
+    queue
+        .set("printer-state", &["3"])
+        .set("printer-state-reasons", &["none"])
+        .set("printer-is-accepting-jobs", &["true"]);
+
+    // Devices that can not be checked over the network are diagnosed from the queue state
+    for uri in &[
+        "usb://HP/LaserJet%20M404?serial=123",
+        "dnssd://HP%20LaserJet._ipp._tcp.local./?uuid=1234",
+    ] {
+        let health = health::check_queue(uri, &queue, TIMEOUT);
+        assert!(health.reachable);
+        assert!(health.is_healthy());
+        assert_eq!(health.state, PrinterState::READY);
+        assert_eq!(health.accepting_jobs, Some(true));
+    }
+
+    queue.set("printer-state-reasons", &["offline-report"]);
+    let health = health::check_queue("usb://HP/LaserJet%20M404", &queue, TIMEOUT);
+    assert!(!health.reachable);
+    assert_eq!(
+        health.diagnosis,
+        Diagnosis::ConnectionFailed("the print queue reports the printer offline".to_string())
+    );
+    assert_eq!(health.state_reasons, vec!["offline-report"]);
+
+    let health = health::check_queue(
+        "usb://HP/LaserJet%20M404",
+        &PrinterAttributes::default(),
+        TIMEOUT,
+    );
+    assert_eq!(
+        health.diagnosis,
+        Diagnosis::UnsupportedUri("usb://HP/LaserJet%20M404".to_string())
+    );
+
+    // A reachable socket printer behind a stopped queue, then behind a queue rejecting jobs
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let uri = format!(
+        "socket://127.0.0.1:{}",
+        listener.local_addr().unwrap().port()
+    );
+    queue
+        .set("printer-state", &["5"])
+        .set("printer-state-reasons", &["paused"]);
+    let health = health::check_queue(&uri, &queue, TIMEOUT);
+    assert!(health.reachable);
+    assert!(health.latency.is_some());
+    assert_eq!(health.state, PrinterState::PAUSED);
+    assert_eq!(health.diagnosis, Diagnosis::PrinterStopped("".to_string()));
+
+    queue
+        .set("printer-state", &["3"])
+        .set("printer-state-reasons", &["none"])
+        .set("printer-is-accepting-jobs", &["false"]);
+    let health = health::check_queue(&uri, &queue, TIMEOUT);
+    assert!(health.reachable);
+    assert_eq!(health.diagnosis, Diagnosis::NotAcceptingJobs);
+    assert_eq!(health.accepting_jobs, Some(false));
+
+    // The printer diagnosis comes first
+    drop(listener);
+    let health = health::check_queue(&uri, &queue, TIMEOUT);
+    assert_eq!(health.diagnosis, Diagnosis::ConnectionRefused);
+    assert_eq!(health.accepting_jobs, Some(false));
+}
@@ -0,0 +1,208 @@
+use miniz_oxide::deflate::compress_to_vec_zlib;
+use miniz_oxide::inflate::decompress_to_vec_zlib;
+use printers::image::{self, ImageOptions, Placement};
+use printers::pdf::{Margins, PageSize};
+use printers::raster::PixelFormat;
+
+/**
+ * Returns a PNG file with the scanlines (filter byte included) and extra chunks (CRC is not checked)
+ */
+fn png(header: [u8; 13], scanlines: &[u8], chunks: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+    let mut bytes = b"\x89PNG\r\n\x1a\n".to_vec();
+    let compressed = compress_to_vec_zlib(scanlines, 6);
+    let mut all = vec![(b"IHDR", &header[..])];
+    all.extend_from_slice(chunks);
+    all.push((b"IDAT", &compressed));
+    all.push((b"IEND", &[]));
+
+    for (kind, data) in all {
+        bytes.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(kind);
+        bytes.extend_from_slice(data);
+        bytes.extend_from_slice(&[0, 0, 0, 0]);
+    }
+
+    bytes
+}
+
+fn header(width: u8, height: u8, depth: u8, color_type: u8, interlace: u8) -> [u8; 13] {
+    [
+        0, 0, 0, width, 0, 0, 0, height, depth, color_type, 0, 0, interlace,
+    ]
+}
+
+/**
+ * Returns a JPEG file header (the image data is not needed to wrap it)
+ */
+fn jpeg(width: u16, height: u16, dpi: u16) -> Vec<u8> {
+    let mut bytes = vec![0xff, 0xd8, 0xff, 0xe0, 0, 16];
+    bytes.extend_from_slice(b"JFIF\0\x01\x01\x01");
+    bytes.extend_from_slice(&dpi.to_be_bytes());
+    bytes.extend_from_slice(&dpi.to_be_bytes());
+    bytes.extend_from_slice(&[0, 0, 0xff, 0xc0, 0, 17, 8]);
+    bytes.extend_from_slice(&height.to_be_bytes());
+    bytes.extend_from_slice(&width.to_be_bytes());
+    bytes.extend_from_slice(&[3, 1, 0x22, 0, 2, 0x11, 1, 3, 0x11, 1]);
+    bytes.extend_from_slice(&[0xff, 0xda, 0, 2, 0xff, 0xd9]);
+    bytes
+}
+
+/**
+ * Returns the decompressed content of the page streams
+ */
+fn contents(pdf: &[u8]) -> String {
+    let find = |from: usize, sequence: &[u8]| {
+        pdf[from..]
+            .windows(sequence.len())
+            .position(|window| window == sequence)
+            .map(|position| from + position)
+    };
+
+    let mut contents = String::new();
+    let mut position = 0;
+
+    while let Some(start) = find(position, b">>\nstream\n") {
+        let start = start + 10;
+        let end = find(start, b"\nendstream").unwrap();
+        if let Ok(data) = decompress_to_vec_zlib(&pdf[start..end]) {
+            if data.ends_with(b"Do Q\n") {
+                contents.push_str(&String::from_utf8(data).unwrap());
+            }
+        }
+        position = end;
+    }
+
+    contents
+}
+
+#[test]
+pub fn test_png_filters_and_resolution() {
+    // Sub filter on the first row, Up on the second, Paeth on the third
+    let scanlines = [
+        1, 10, 20, 30, 5, 5, 5, //
+        2, 1, 1, 1, 1, 1, 1, //
+        4, 0, 0, 0, 0, 0, 0,
+    ];
+    let bytes = png(
+        header(2, 3, 8, 2, 0),
+        &scanlines,
+        &[(b"pHYs", &[0, 0, 0x2e, 0x23, 0, 0, 0x2e, 0x23, 1])],
+    );
+
+    let image = image::decode_png(&bytes).unwrap();
+    assert_eq!((image.width, image.height), (2, 3));
+    assert_eq!(image.format, PixelFormat::Rgb);
+    assert_eq!(image.resolution, Some(300));
+    assert_eq!(
+        image.data,
+        vec![10, 20, 30, 15, 25, 35, 11, 21, 31, 16, 26, 36, 11, 21, 31, 16, 26, 36]
+    );
+}
+
+#[test]
+pub fn test_png_color_types() {
+    // 2-bit palette with transparency of the second color
+    let bytes = png(
+        header(3, 1, 2, 3, 0),
+        &[0, 0b00_01_10_00],
+        &[
+            (b"PLTE", &[255, 0, 0, 0, 255, 0, 0, 0, 255]),
+            (b"tRNS", &[255, 0]),
+        ],
+    );
+    let image = image::decode_png(&bytes).unwrap();
+    assert_eq!(image.format, PixelFormat::Rgba);
+    assert_eq!(
+        image.data,
+        vec![255, 0, 0, 255, 0, 255, 0, 0, 0, 0, 255, 255]
+    );
+
+    // 16-bit gray keeps the high byte, 1-bit gray is scaled to 0 or 255
+    let bytes = png(header(2, 1, 16, 0, 0), &[0, 0x12, 0x34, 0xff, 0xff], &[]);
+    assert_eq!(image::decode_png(&bytes).unwrap().data, vec![0x12, 0xff]);
+
+    let bytes = png(header(3, 1, 1, 0, 0), &[0, 0b101_00000], &[]);
+    let image = image::decode_png(&bytes).unwrap();
+    assert_eq!(image.format, PixelFormat::Gray);
+    assert_eq!(image.data, vec![255, 0, 255]);
+
+    assert!(image::decode_png(&png(header(1, 1, 3, 2, 0), &[0, 0], &[])).is_err());
+    assert!(image::decode_png(b"\x89PNG\r\n\x1a\n").is_err());
+
+    // A size too large to allocate
+    let mut huge = header(1, 1, 16, 6, 0);
+    huge[0..8].copy_from_slice(&[0xff; 8]);
+    assert_eq!(
+        image::decode_png(&png(huge, &[0], &[])).unwrap_err(),
+        "invalid PNG size 4294967295x4294967295"
+    );
+}
+
+#[test]
+pub fn test_png_interlaced() {
+    // Adam7 passes of a 3x3 image: (0,0), (2,0), (0,2) (2,2), (1,0) (1,2), then the row 1
+    let scanlines = [
+        0, 0, //
+        0, 2, //
+        0, 6, 8, //
+        0, 1, 0, 7, //
+        0, 3, 4, 5,
+    ];
+    let bytes = png(header(3, 3, 8, 0, 1), &scanlines, &[]);
+    let image = image::decode_png(&bytes).unwrap();
+    assert_eq!(image.data, (0..9).collect::<Vec<u8>>());
+}
+
+#[test]
+pub fn test_images_to_pdf_placement() {
+    let options = ImageOptions {
+        page_size: PageSize::new(200.0, 300.0),
+        margins: Margins::uniform(0.0),
+        ..ImageOptions::default()
+    };
+
+    // Landscape image rotated on a portrait page and fitted
+    let landscape = jpeg(400, 200, 72);
+    let pdf = image::images_to_pdf(&[&landscape], &options).unwrap();
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.contains(
+        "/Width 400 /Height 200 /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /DCTDecode"
+    ));
+    assert!(pdf
+        .windows(landscape.len())
+        .any(|window| window == &landscape[..]));
+    assert_eq!(
+        contents(&pdf),
+        "q 0 0 200 300 re W n 0 -300 150 0 25 300 cm /Im1 Do Q\n"
+    );
+
+    // Actual size of a 144 dpi image, filled and centered without rotation
+    let options = ImageOptions {
+        auto_rotate: false,
+        placement: Placement::ActualSize,
+        ..options
+    };
+    let small = jpeg(100, 50, 144);
+    let pdf = image::images_to_pdf(&[&small, &landscape], &options).unwrap();
+    assert_eq!(
+        contents(&pdf),
+        "q 0 0 200 300 re W n 50 0 0 25 75 137.5 cm /Im1 Do Q\n\
+         q 0 0 200 300 re W n 400 0 0 200 -100 50 cm /Im1 Do Q\n"
+    );
+
+    let options = ImageOptions {
+        placement: Placement::Fill,
+        ..options
+    };
+    let png = png(header(1, 1, 8, 6, 0), &[0, 1, 2, 3, 128], &[]);
+    let pdf = image::images_to_pdf(&[&png, &small], &options).unwrap();
+    assert!(String::from_utf8_lossy(&pdf).contains("/SMask"));
+    assert_eq!(
+        contents(&pdf),
+        "q 0 0 200 300 re W n 300 0 0 300 -50 0 cm /Im1 Do Q\n\
+         q 0 0 200 300 re W n 600 0 0 300 -200 0 cm /Im1 Do Q\n"
+    );
+
+    assert!(image::images_to_pdf(&[b"%PDF-1.4"], &options).is_err());
+    assert!(image::images_to_pdf(&[], &options).is_err());
+}
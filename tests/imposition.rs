@@ -0,0 +1,248 @@
+use miniz_oxide::deflate::compress_to_vec_zlib;
+use miniz_oxide::inflate::decompress_to_vec_zlib;
+use printers::imposition::{self, parse_page_ranges, ImpositionOptions};
+use printers::pdf::PageSize;
+use printers::text::{self, TextOptions};
+
+/**
+ * Returns the decompressed content streams of the sheets (the streams drawing forms)
+ */
+fn sheets(pdf: &[u8]) -> Vec<String> {
+    let find = |from: usize, sequence: &[u8]| {
+        pdf[from..]
+            .windows(sequence.len())
+            .position(|window| window == sequence)
+            .map(|position| from + position)
+    };
+
+    let mut sheets = Vec::new();
+    let mut position = 0;
+
+    while let Some(start) = find(position, b">>\nstream\n") {
+        let start = start + 10;
+        let end = find(start, b"\nendstream").unwrap();
+        if let Ok(data) = decompress_to_vec_zlib(&pdf[start..end]) {
+            let data = String::from_utf8_lossy(&data).to_string();
+            if data.contains(" Do Q") || data.is_empty() {
+                sheets.push(data);
+            }
+        }
+        position = end;
+    }
+
+    sheets
+}
+
+/**
+ * Returns a PDF of pages with the numbers as text
+ */
+fn numbered_pages(count: usize) -> Vec<u8> {
+    let text: Vec<String> = (1..=count).map(|page| page.to_string()).collect();
+    let options = TextOptions {
+        page_size: PageSize::new(200.0, 300.0),
+        ..TextOptions::default()
+    };
+    text::render_pdf(&text.join("\x0c"), &options).unwrap()
+}
+
+#[test]
+pub fn test_page_ranges() {
+    assert_eq!(parse_page_ranges("1-3,5", 10).unwrap(), vec![0, 1, 2, 4]);
+    assert_eq!(parse_page_ranges("8-, 2", 9).unwrap(), vec![7, 8, 1]);
+    assert_eq!(parse_page_ranges("-2", 9).unwrap(), vec![0, 1]);
+    assert_eq!(parse_page_ranges("4-20", 5).unwrap(), vec![3, 4]);
+
+    // Ranges past the end of the document select nothing, open or closed
+    assert_eq!(parse_page_ranges("8-", 5).unwrap(), Vec::<usize>::new());
+    assert_eq!(parse_page_ranges("8-9", 5).unwrap(), Vec::<usize>::new());
+    assert_eq!(parse_page_ranges("2,8-", 5).unwrap(), vec![1]);
+    assert!(parse_page_ranges("0", 5).is_err());
+    assert!(parse_page_ranges("3-1", 5).is_err());
+    assert!(parse_page_ranges("a", 5).is_err());
+}
+
+#[test]
+pub fn test_impose_number_up() {
+    let pdf = numbered_pages(5);
+
+    // 2-up turns the portrait sheet to landscape with 2 columns
+    let options = ImpositionOptions {
+        number_up: 2,
+        borders: true,
+        ..ImpositionOptions::default()
+    };
+    let imposed = imposition::impose(&pdf, &options).unwrap();
+    let text = String::from_utf8_lossy(&imposed);
+    assert!(text.contains("/Count 3"));
+    assert!(text.contains("/MediaBox [0 0 300 200]"));
+    assert!(text.contains("/Subtype /Form /BBox [0 0 200 300] /Resources << /Font"));
+
+    let two_up = sheets(&imposed);
+    assert_eq!(two_up.len(), 3);
+    assert_eq!(
+        two_up[0],
+        "q 0.666667 0 0 0.666667 8.333333 0 cm /P0 Do Q\n\
+         q 0.5 w 8.333333 0 133.333333 200 re S Q\n\
+         q 0.666667 0 0 0.666667 158.333333 0 cm /P1 Do Q\n\
+         q 0.5 w 158.333333 0 133.333333 200 re S Q\n"
+    );
+    assert!(two_up[2].contains("/P4 Do") && !two_up[2].contains("/P3 Do"));
+
+    // 4-up with page ranges
+    let options = ImpositionOptions {
+        number_up: 4,
+        page_ranges: Some("2-5".to_string()),
+        ..ImpositionOptions::default()
+    };
+    let imposed = imposition::impose(&pdf, &options).unwrap();
+    assert_eq!(
+        sheets(&imposed),
+        vec![
+            "q 0.5 0 0 0.5 0 150 cm /P1 Do Q\n\
+             q 0.5 0 0 0.5 100 150 cm /P2 Do Q\n\
+             q 0.5 0 0 0.5 0 0 cm /P3 Do Q\n\
+             q 0.5 0 0 0.5 100 0 cm /P4 Do Q\n"
+        ]
+    );
+
+    let options = ImpositionOptions {
+        number_up: 3,
+        ..ImpositionOptions::default()
+    };
+    assert!(imposition::impose(&pdf, &options).is_err());
+}
+
+#[test]
+pub fn test_impose_booklet() {
+    let pdf = numbered_pages(5);
+    let options = ImpositionOptions {
+        booklet: true,
+        sheet_size: Some(PageSize::new(400.0, 300.0)),
+        ..ImpositionOptions::default()
+    };
+
+    let imposed = imposition::impose(&pdf, &options).unwrap();
+    let order: Vec<Vec<String>> = sheets(&imposed)
+        .iter()
+        .map(|sheet| {
+            sheet
+                .split_whitespace()
+                .filter(|token| token.starts_with("/P"))
+                .map(str::to_string)
+                .collect()
+        })
+        .collect();
+
+    // Padded to 8 pages: sheet 1 front (8, 1) and back (2, 7), sheet 2 front (6, 3) and back (4, 5),
+    // the pages 6 to 8 are blank
+    assert_eq!(
+        order,
+        vec![vec!["/P0"], vec!["/P1"], vec!["/P2"], vec!["/P3", "/P4"],]
+    );
+}
+
+/**
+ * Returns a PDF with the page tree and a rotated page inside an object stream,
+ * the page inherits the media box
+ */
+fn object_stream_pdf(trailer: &str) -> Vec<u8> {
+    let objects = b"2 0 3 65 << /Type /Pages /Kids [3 0 R] /Count 1 /MediaBox [0 0 100 50] >> \
+                    << /Type /Page /Parent 2 0 R /Rotate 90 /Contents 4 0 R >>";
+    let compressed = compress_to_vec_zlib(objects, 6);
+
+    let mut pdf = b"%PDF-1.5\n".to_vec();
+    pdf.extend_from_slice(
+        format!(
+            "5 0 obj\n<< /Type /ObjStm /N 2 /First 9 /Filter /FlateDecode /Length {} >>\nstream\n",
+            compressed.len()
+        )
+        .as_bytes(),
+    );
+    pdf.extend_from_slice(&compressed);
+    pdf.extend_from_slice(
+        b"\nendstream\nendobj\n1 0 obj\n<< /Type /Catalog /Pages 2 0 R >>\nendobj\n",
+    );
+
+    // Indirect length, the end of the stream is found by the keyword
+    pdf.extend_from_slice(
+        b"4 0 obj\n<< /Length 6 0 R >>\nstream\n0 0 10 10 re f\nendstream\nendobj\n",
+    );
+    pdf.extend_from_slice(format!("trailer\n<< {} >>\n%%EOF\n", trailer).as_bytes());
+    pdf
+}
+
+#[test]
+pub fn test_impose_compressed_objects_and_rotation() {
+    let pdf = object_stream_pdf("/Root 1 0 R");
+    let imposed = imposition::impose(&pdf, &ImpositionOptions::default()).unwrap();
+    let text = String::from_utf8_lossy(&imposed);
+    assert!(text.contains("/MediaBox [0 0 50 100]"));
+    assert!(text.contains("/BBox [0 0 100 50] /Resources << >>"));
+    assert_eq!(sheets(&imposed), vec!["q 0 -1 1 0 0 100 cm /P0 Do Q\n"]);
+
+    let encrypted = object_stream_pdf("/Root 1 0 R /Encrypt 7 0 R");
+    assert!(imposition::impose(&encrypted, &ImpositionOptions::default()).is_err());
+}
+
+#[test]
+pub fn test_impose_damaged_object_streams() {
+    let pdf = object_stream_pdf("/Root 1 0 R");
+    let original: &[u8] = b"/N 2 /First 9 /Filter /FlateDecode";
+    let position = pdf
+        .windows(original.len())
+        .position(|window| window == original)
+        .unwrap();
+    let replace = |dictionary: &str| {
+        let mut damaged = pdf[..position].to_vec();
+        damaged.extend_from_slice(dictionary.as_bytes());
+        damaged.extend_from_slice(&pdf[position + original.len()..]);
+        damaged
+    };
+
+    let options = ImpositionOptions::default();
+    assert!(imposition::impose(&replace("/N 2 /First 9 /Filter /FlateDecode"), &options).is_ok());
+
+    // Negative or out of range offsets, and predictor rows larger than the data
+    for dictionary in &[
+        "/N 2 /First -9 /Filter /FlateDecode",
+        "/N 2 /First 4294967296000 /Filter /FlateDecode",
+        "/N -2 /First 9 /Filter /FlateDecode",
+        "/N 2 /First 9 /Filter /FlateDecode /DecodeParms << /Predictor 12 /Colors 4294967295 \
+         /BitsPerComponent 16 /Columns 4294967295 >>",
+        "/N 2 /First 9 /Filter /FlateDecode /DecodeParms << /Predictor 12 /Columns -1 >>",
+    ] {
+        assert!(imposition::impose(&replace(dictionary), &options).is_err());
+    }
+}
+
+#[test]
+pub fn test_impose_raw_names_and_invalid_lengths() {
+    let mut pdf = b"%PDF-1.4\n1 0 obj\n<< /Type /Catalog /Pages 2 0 R >>\nendobj\n\
+        2 0 obj\n<< /Type /Pages /Kids [3 0 R] /Count 1 >>\nendobj\n\
+        3 0 obj\n<< /Type /Page /Parent 2 0 R /MediaBox [0 0 100 50] \
+        /Resources << /Font << /F#e9 5 0 R >> >> /Contents 4 0 R >>\nendobj\n"
+        .to_vec();
+
+    // A length past the end of the addressable memory, the end is found by the keyword
+    pdf.extend_from_slice(
+        b"4 0 obj\n<< /Length 100000000000000000000.0 >>\nstream\nBT /F#e9 12 Tf ET\nendstream\nendobj\n\
+        5 0 obj\n<< /Type /Font /Subtype /Type1 /BaseFont /Courier >>\nendobj\n\
+        trailer\n<< /Root 1 0 R >>\n%%EOF\n",
+    );
+
+    let imposed = imposition::impose(&pdf, &ImpositionOptions::default()).unwrap();
+    let text = String::from_utf8_lossy(&imposed);
+    assert!(text.contains("/Font << /F#e9 "));
+
+    // The page content is copied on the form
+    let find = |from: usize, sequence: &[u8]| {
+        from + imposed[from..]
+            .windows(sequence.len())
+            .position(|window| window == sequence)
+            .unwrap()
+    };
+    let start = find(find(0, b"/Subtype /Form"), b">>\nstream\n") + 10;
+    let end = find(start, b"\nendstream");
+    let content = decompress_to_vec_zlib(&imposed[start..end]).unwrap();
+    assert_eq!(content, b"BT /F#e9 12 Tf ET\n");
+}
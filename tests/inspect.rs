@@ -0,0 +1,153 @@
+use printers::document::DocumentFormat;
+use printers::image::{self, ImageOptions};
+use printers::inspect::{self, ColorUsage};
+use printers::pdf::PageSize;
+use printers::pwg::{self, ColorSpace, PageHeader, RasterPage};
+use printers::text::{self, TextOptions};
+
+/**
+ * Returns a JPEG file header with 3 components (the image data is not needed to wrap it)
+ */
+fn jpeg(width: u16, height: u16) -> Vec<u8> {
+    let mut bytes = vec![0xff, 0xd8, 0xff, 0xc0, 0, 17, 8];
+    bytes.extend_from_slice(&height.to_be_bytes());
+    bytes.extend_from_slice(&width.to_be_bytes());
+    bytes.extend_from_slice(&[3, 1, 0x22, 0, 2, 0x11, 1, 3, 0x11, 1]);
+    bytes.extend_from_slice(&[0xff, 0xda, 0, 2, 0xff, 0xd9]);
+    bytes
+}
+
+/**
+ * Returns a single page PDF with an uncompressed content stream
+ */
+fn content_pdf(content: &str, trailer: &str) -> Vec<u8> {
+    format!(
+        "%PDF-1.4\n\
+         1 0 obj\n<< /Type /Catalog /Pages 2 0 R >>\nendobj\n\
+         2 0 obj\n<< /Type /Pages /Kids [3 0 R] /Count 1 /MediaBox [0 0 612 792] >>\nendobj\n\
+         3 0 obj\n<< /Type /Page /Parent 2 0 R /Rotate 270 /Contents 4 0 R >>\nendobj\n\
+         4 0 obj\n<< /Length {} >>\nstream\n{}\nendstream\nendobj\n\
+         trailer\n<< {} >>\n%%EOF\n",
+        content.len(),
+        content,
+        trailer
+    )
+    .into_bytes()
+}
+
+#[test]
+pub fn test_inspect_pdf() {
+    let pdf = text::render_pdf("one\x0ctwo\x0cthree", &TextOptions::default()).unwrap();
+    let info = inspect::inspect_document(&pdf).unwrap();
+    assert_eq!(info.format, DocumentFormat::PDF);
+    assert_eq!(info.page_count, 3);
+    assert_eq!(info.pages[2].size, Some(PageSize::A4));
+    assert_eq!(info.color, ColorUsage::Monochrome);
+    assert!(!info.encrypted);
+
+    // Images on a RGB color space
+    let pdf = image::images_to_pdf(&[&jpeg(20, 10)], &ImageOptions::default()).unwrap();
+    let info = inspect::inspect_document(&pdf).unwrap();
+    assert_eq!(info.color, ColorUsage::Color);
+    assert_eq!(info.color_page_count(), 1);
+
+    // Gray given as RGB is monochrome, the rotation swaps the size
+    let info =
+        inspect::inspect_document(&content_pdf("0.5 0.5 0.5 rg 0 0 10 10 re f", "/Root 1 0 R"))
+            .unwrap();
+    assert_eq!(info.color, ColorUsage::Monochrome);
+    assert_eq!(info.pages[0].size, Some(PageSize::new(792.0, 612.0)));
+
+    let info =
+        inspect::inspect_document(&content_pdf("0 0 1 0 k 0 0 10 10 re f", "/Root 1 0 R")).unwrap();
+    assert_eq!(info.color, ColorUsage::Color);
+
+    // The pages of encrypted documents are counted, but their content is not checked
+    let pdf = content_pdf("1 0 0 rg", "/Root 1 0 R /Encrypt << /Filter /Standard >>");
+    let info = inspect::inspect_document(&pdf).unwrap();
+    assert!(info.encrypted);
+    assert_eq!(info.page_count, 1);
+    assert_eq!(info.color, ColorUsage::Unknown);
+}
+
+#[test]
+pub fn test_inspect_postscript() {
+    let options = TextOptions {
+        page_size: PageSize::LETTER,
+        ..TextOptions::default()
+    };
+    let postscript = text::render_postscript("one\x0ctwo", &options).unwrap();
+    let info = inspect::inspect_document(&postscript).unwrap();
+    assert_eq!(info.format, DocumentFormat::POSTSCRIPT);
+    assert_eq!(info.page_count, 2);
+    assert_eq!(info.pages[1].size, Some(PageSize::LETTER));
+    assert_eq!(info.color, ColorUsage::Monochrome);
+
+    // Page count at the end, per page color from the operators
+    let postscript = b"%!PS-Adobe-3.0\n\
+        %%Pages: (atend)\n\
+        %%DocumentMedia: Plain 595 842 0 () ()\n\
+        %%EndComments\n\
+        %%Page: 1 1\n\
+        0 setgray showpage\n\
+        %%Page: 2 2\n\
+        1 0 0 setrgbcolor 0 0 moveto showpage\n\
+        %%Trailer\n\
+        %%Pages: 2\n\
+        %%EOF\n";
+    let info = inspect::inspect_document(postscript).unwrap();
+    assert_eq!(info.page_count, 2);
+    assert_eq!(info.pages[0].size, Some(PageSize::new(595.0, 842.0)));
+    assert_eq!(info.pages[0].color, ColorUsage::Monochrome);
+    assert_eq!(info.pages[1].color, ColorUsage::Color);
+
+    // The process colors of the header apply to every page
+    let postscript =
+        b"%!PS-Adobe-3.0\n%%Pages: 1\n%%DocumentProcessColors: Cyan Black\n%%EndComments\n";
+    let info = inspect::inspect_document(postscript).unwrap();
+    assert_eq!(info.pages[0].color, ColorUsage::Color);
+    assert_eq!(info.pages[0].size, None);
+
+    assert!(inspect::inspect_document(b"%!\n0 0 moveto showpage\n").is_err());
+}
+
+#[test]
+pub fn test_inspect_raster() {
+    let mut color = RasterPage::new(600, 300, ColorSpace::Srgb8);
+    color.data[4] = 0;
+    let gray = RasterPage::new(600, 300, ColorSpace::Srgb8);
+    let header = PageHeader {
+        resolution: 300,
+        ..PageHeader::default()
+    };
+
+    for bytes in [
+        pwg::encode_pwg(&header, &[gray.clone(), color.clone()]).unwrap(),
+        pwg::encode_urf(&header, &[gray, color]).unwrap(),
+    ] {
+        let info = inspect::inspect_document(&bytes).unwrap();
+        assert_eq!(info.page_count, 2);
+        assert_eq!(info.pages[0].size, Some(PageSize::new(144.0, 72.0)));
+        assert_eq!(info.pages[0].color, ColorUsage::Monochrome);
+        assert_eq!(info.pages[1].color, ColorUsage::Color);
+        assert_eq!(info.color_page_count(), 1);
+    }
+
+    let black = RasterPage::new(8, 8, ColorSpace::Black1);
+    let info = inspect::inspect_document(&pwg::encode_pwg(&header, &[black]).unwrap()).unwrap();
+    assert_eq!(info.format, DocumentFormat::PWG);
+    assert_eq!(info.color, ColorUsage::Monochrome);
+}
+
+#[test]
+pub fn test_inspect_unsupported() {
+    assert!(inspect::inspect_document(b"plain text").is_err());
+    assert!(inspect::inspect_document(b"%PDF-1.4\ngarbage").is_err());
+    assert!(inspect::inspect_document_file("/path/to/missing.pdf").is_err());
+
+    // Deeply nested arrays are rejected instead of overflowing the stack
+    let mut nested = b"%PDF-1.4\n1 0 obj\n<< /Type /Catalog /Pages ".to_vec();
+    nested.extend(vec![b'['; 1_000_000]);
+    nested.extend_from_slice(b"\nendobj\ntrailer\n<< /Root 1 0 R >>\n%%EOF\n");
+    assert!(inspect::inspect_document(&nested).is_err());
+}
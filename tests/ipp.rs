@@ -0,0 +1,194 @@
+use printers::ipp::{self, PrinterAttributes};
+use printers::media::MediaSize;
+use printers::print_options::{MediaSource, Resolution};
+
+/**
+ * Returns an attribute of an IPP message (value tag, name and raw value)
+ */
+fn attribute(tag: u8, name: &str, value: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    ipp::write_attribute(&mut bytes, tag, name, value);
+    bytes
+}
+
+/**
+ * Returns an IPP response with a status, an operation group and a printer attributes group
+ */
+fn response(status: u16, operation: &[Vec<u8>], printer: &[Vec<u8>]) -> Vec<u8> {
+    let mut bytes = vec![2, 0];
+    bytes.extend_from_slice(&status.to_be_bytes());
+    bytes.extend_from_slice(&1u32.to_be_bytes());
+    bytes.push(0x01);
+    bytes.extend(operation.concat());
+    bytes.push(0x04);
+    bytes.extend(printer.concat());
+    bytes.push(0x03);
+    bytes
+}
+
+fn operation_attributes() -> Vec<Vec<u8>> {
+    vec![
+        attribute(0x47, "attributes-charset", b"utf-8"),
+        attribute(0x48, "attributes-natural-language", b"en"),
+    ]
+}
+
+#[test]
+pub fn test_decode_printer_attributes() {
+    let mut resolution = Vec::new();
+    resolution.extend_from_slice(&600i32.to_be_bytes());
+    resolution.extend_from_slice(&1200i32.to_be_bytes());
+    resolution.push(3);
+
+    let mut range = Vec::new();
+    range.extend_from_slice(&1i32.to_be_bytes());
+    range.extend_from_slice(&99i32.to_be_bytes());
+
+    let mut info = Vec::new();
+    info.extend_from_slice(&2u16.to_be_bytes());
+    info.extend_from_slice(b"en");
+    info.extend_from_slice(&11u16.to_be_bytes());
+    info.extend_from_slice(b"Office Ptr.");
+
+    let data = response(
+        0x0000,
+        &operation_attributes(),
+        &[
+            attribute(0x41, "printer-make-and-model", b"HP LaserJet M404"),
+            attribute(0x35, "printer-info", &info),
+            attribute(0x44, "media-supported", b"iso_a4_210x297mm"),
+            attribute(0x44, "", b"na_letter_8.5x11in"),
+            attribute(0x44, "media-source-supported", b"tray-1"),
+            attribute(0x44, "", b"tray-2"),
+            attribute(0x32, "printer-resolution-supported", &resolution),
+            attribute(0x33, "copies-supported", &range),
+            attribute(0x22, "color-supported", &[0]),
+            attribute(0x23, "finishings-supported", &4i32.to_be_bytes()),
+            attribute(0x21, "pages-per-minute", &38i32.to_be_bytes()),
+            attribute(0x13, "printer-location", b""),
+        ],
+    );
+
+    let attributes = PrinterAttributes::from_response(&data).unwrap();
+    assert_eq!(
+        attributes.first("printer-make-and-model"),
+        Some("HP LaserJet M404")
+    );
+    assert_eq!(attributes.first("printer-info"), Some("Office Ptr."));
+    assert_eq!(attributes.get("media-supported").len(), 2);
+    assert_eq!(
+        attributes.first("printer-resolution-supported"),
+        Some("600x1200dpi")
+    );
+    assert_eq!(attributes.first("copies-supported"), Some("1-99"));
+    assert_eq!(attributes.first("color-supported"), Some("false"));
+    assert_eq!(attributes.first("finishings-supported"), Some("4"));
+    assert_eq!(attributes.first("pages-per-minute"), Some("38"));
+    assert_eq!(attributes.first("printer-location"), Some(""));
+    assert_eq!(attributes.first("attributes-charset"), None);
+
+    let capabilities = attributes.capabilities();
+    assert_eq!(capabilities.copies(), Some((1, 99)));
+    assert_eq!(
+        capabilities.media_sizes(),
+        vec![
+            MediaSize::parse("A4").unwrap(),
+            MediaSize::parse("Letter").unwrap()
+        ]
+    );
+    assert_eq!(
+        capabilities.media_sources(),
+        vec![MediaSource::Tray(1), MediaSource::Tray(2)]
+    );
+    assert_eq!(
+        capabilities.resolutions(),
+        vec![Resolution { x: 600, y: 1200 }]
+    );
+}
+
+#[test]
+pub fn test_decode_collections() {
+    // media-col-database with two media sizes
+    let mut media = Vec::new();
+    for (x, y) in [(21000i32, 29700i32), (21590, 27940)].iter() {
+        media.extend(attribute(0x34, "", b""));
+        media.extend(attribute(0x4a, "", b"media-size"));
+        media.extend(attribute(0x34, "", b""));
+        media.extend(attribute(0x4a, "", b"x-dimension"));
+        media.extend(attribute(0x21, "", &x.to_be_bytes()));
+        media.extend(attribute(0x4a, "", b"y-dimension"));
+        media.extend(attribute(0x21, "", &y.to_be_bytes()));
+        media.extend(attribute(0x37, "", b""));
+        media.extend(attribute(0x4a, "", b"media-type"));
+        media.extend(attribute(0x44, "", b"stationery"));
+        media.extend(attribute(0x44, "", b"labels"));
+        media.extend(attribute(0x37, "", b""));
+    }
+
+    // The name of the first value only
+    let mut first = attribute(0x34, "media-col-database", b"");
+    first.extend_from_slice(&media[attribute(0x34, "", b"").len()..]);
+
+    let data = response(0x0000, &operation_attributes(), &[first]);
+    let attributes = PrinterAttributes::from_response(&data).unwrap();
+    assert_eq!(
+        attributes.get("media-col-database"),
+        &[
+            "{media-size={x-dimension=21000 y-dimension=29700} media-type=stationery,labels}"
+                .to_string(),
+            "{media-size={x-dimension=21590 y-dimension=27940} media-type=stationery,labels}"
+                .to_string(),
+        ]
+    );
+
+    // Collections nested in each other up to a depth
+    let nested = |depth: usize| {
+        let mut value = attribute(0x34, "nested", b"");
+        for _ in 1..depth {
+            value.extend(attribute(0x4a, "", b"member"));
+            value.extend(attribute(0x34, "", b""));
+        }
+        for _ in 0..depth {
+            value.extend(attribute(0x37, "", b""));
+        }
+        response(0x0000, &operation_attributes(), &[value])
+    };
+
+    let attributes = PrinterAttributes::from_response(&nested(32)).unwrap();
+    assert!(attributes.get("nested")[0].starts_with("{member={member="));
+    assert_eq!(
+        PrinterAttributes::from_response(&nested(33)),
+        Err("invalid IPP response, the collections are nested too deep".to_string())
+    );
+}
+
+#[test]
+pub fn test_decode_errors() {
+    let mut operation = operation_attributes();
+    operation.push(attribute(0x41, "status-message", b"Not authorized"));
+
+    let data = response(0x0403, &operation, &[]);
+    assert_eq!(
+        PrinterAttributes::from_response(&data),
+        Err("the printer returned the IPP status 0x0403 Not authorized".to_string())
+    );
+
+    let data = response(
+        0x0000,
+        &operation_attributes(),
+        &[attribute(0x44, "sides-supported", b"one-sided")],
+    );
+    assert!(PrinterAttributes::from_response(&data[..data.len() - 4]).is_err());
+    assert!(PrinterAttributes::from_response(b"HTTP/1.1").is_err());
+
+    // Successful statuses with ignored attributes
+    let data = response(
+        0x0001,
+        &operation_attributes(),
+        &[attribute(0x44, "sides-supported", b"one-sided")],
+    );
+    assert_eq!(
+        PrinterAttributes::from_response(&data).unwrap().names(),
+        vec!["sides-supported"]
+    );
+}
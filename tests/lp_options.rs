@@ -0,0 +1,146 @@
+use printers::document::DocumentFormat;
+use printers::lp_options;
+use printers::media::MediaSize;
+use printers::print_options::{
+    ColorMode, Finishing, MediaSource, Orientation, PrintOptions, Resolution, Scaling, Sides,
+};
+
+fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+pub fn test_parse_options() {
+    assert_eq!(
+        lp_options::parse(
+            "-o media=A4 -o sides=two-sided-long-edge -o fit-to-page page-ranges=1-3 -ocollate=false"
+        ),
+        pairs(&[
+            ("media", "A4"),
+            ("sides", "two-sided-long-edge"),
+            ("fit-to-page", "true"),
+            ("page-ranges", "1-3"),
+            ("collate", "false"),
+        ])
+    );
+
+    assert_eq!(
+        lp_options::parse(
+            "job-name='Monthly \"sales\" report' title=\"it's\" path=C:\\\\tmp\\ files nobanner"
+        ),
+        pairs(&[
+            ("job-name", "Monthly \"sales\" report"),
+            ("title", "it's"),
+            ("path", "C:\\tmp files"),
+            ("banner", "false"),
+        ])
+    );
+
+    // Collections are kept as is, repeated options replace the previous value
+    assert_eq!(
+        lp_options::parse(
+            "media-col={media-size={x-dimension=10160 y-dimension=15240}} copies=1 COPIES=3 no"
+        ),
+        pairs(&[
+            (
+                "media-col",
+                "{media-size={x-dimension=10160 y-dimension=15240}}"
+            ),
+            ("copies", "3"),
+            ("no", "true"),
+        ])
+    );
+
+    assert!(lp_options::parse("  \t ").is_empty());
+    assert_eq!(lp_options::parse("name="), pairs(&[("name", "")]));
+}
+
+#[test]
+pub fn test_format_options() {
+    let options = pairs(&[
+        ("fit-to-page", "true"),
+        ("collate", "false"),
+        ("notify-events", "true"),
+        ("job-name", "it's \"big\""),
+        ("page-label", ""),
+        ("media-col", "{media-source=tray-2}"),
+        ("path", "C:\\tmp"),
+    ]);
+
+    let text = lp_options::format(&options);
+    assert_eq!(
+        text,
+        "fit-to-page nocollate notify-events=true job-name=it\\'s\\ \\\"big\\\" page-label='' media-col={media-source=tray-2} path=C:\\\\tmp"
+    );
+    assert_eq!(lp_options::parse(&text), options);
+}
+
+#[test]
+pub fn test_print_options_from_lp_options() {
+    let options = PrintOptions::from_lp_options(
+        "-o media=A4,tray-2 -o sides=two-sided-long-edge -o fit-to-page -o landscape \
+         copies=2 finishings=staple-top-left,5 resolution=600dpi print-color-mode=monochrome \
+         document-format=application/pdf page-ranges=1-3 job-hold-until=night",
+    );
+
+    assert_eq!(options.document_format, Some(DocumentFormat::PDF));
+    assert_eq!(options.copies, Some(2));
+    assert_eq!(options.sides, Some(Sides::TwoSidedLongEdge));
+    assert_eq!(options.media, Some(MediaSize::parse("A4").unwrap()));
+    assert_eq!(options.media_source, Some(MediaSource::Tray(2)));
+    assert_eq!(
+        options.finishings,
+        vec![Finishing::StapleTopLeft, Finishing::Punch]
+    );
+    assert_eq!(options.resolution, Some(Resolution::dpi(600)));
+    assert_eq!(options.color_mode, Some(ColorMode::Monochrome));
+    assert_eq!(options.scaling, Some(Scaling::Fit));
+    assert_eq!(options.orientation, Some(Orientation::Landscape));
+    assert_eq!(
+        options.attributes,
+        pairs(&[("page-ranges", "1-3"), ("job-hold-until", "night")])
+    );
+
+    // Unknown values and PPD options are kept verbatim
+    let options = PrintOptions::from_lp_options(
+        "media=Letter.Fullbleed copies=all PageSize=Legal InputSlot=Upper Duplex=DuplexTumble nofit-to-page",
+    );
+    assert_eq!(options.copies, None);
+    assert_eq!(options.media, Some(MediaSize::parse("Legal").unwrap()));
+    assert_eq!(options.media_source, Some(MediaSource::Top));
+    assert_eq!(options.sides, Some(Sides::TwoSidedShortEdge));
+    assert_eq!(
+        options.attributes,
+        pairs(&[
+            ("media", "Letter.Fullbleed"),
+            ("copies", "all"),
+            ("fit-to-page", "false"),
+        ])
+    );
+}
+
+#[test]
+pub fn test_lp_options_round_trip() {
+    let options = PrintOptions {
+        document_format: Some(DocumentFormat::POSTSCRIPT),
+        copies: Some(3),
+        media: Some(MediaSize::parse("na_letter_8.5x11in").unwrap()),
+        finishings: vec![Finishing::PunchDualLeft],
+        orientation: Some(Orientation::ReversePortrait),
+        attributes: pairs(&[("job-name", "Q3 report"), ("collate", "false")]),
+        ..PrintOptions::default()
+    };
+
+    let text = options.lp_options();
+    assert_eq!(
+        text,
+        "document-format=application/postscript copies=3 media=na_letter_8.5x11in finishings=punch-dual-left orientation-requested=reverse-portrait job-name=Q3\\ report nocollate"
+    );
+
+    let parsed = PrintOptions::from_lp_options(&text);
+    assert_eq!(parsed.document_format, options.document_format);
+    assert_eq!(parsed.ipp_attributes(), options.ipp_attributes());
+}
@@ -0,0 +1,201 @@
+use printers::network;
+use printers::network::lpd::{self, LpdOptions};
+use printers::print_options::PrintOptions;
+use printers::printer::Printer;
+use printers::printer_job::JobState;
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+/**
+ * Read a LPD command line (code and operands)
+ */
+fn read_command(reader: &mut BufReader<TcpStream>) -> (u8, String) {
+    let mut line = Vec::new();
+    reader.read_until(b'\n', &mut line).unwrap();
+    let text = String::from_utf8_lossy(&line[1..line.len() - 1]).to_string();
+    (line[0], text)
+}
+
+/**
+ * Loopback LPD stand-in receiving a single job, returns the control and data files
+ */
+fn receive_job(listener: TcpListener) -> (String, Vec<u8>) {
+    let (stream, _) = listener.accept().unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+
+    let (code, queue) = read_command(&mut reader);
+    assert_eq!((code, queue.as_str()), (0x02, "labels"));
+    writer.write_all(&[0]).unwrap();
+
+    let mut control = String::new();
+    let mut data = Vec::new();
+
+    for _ in 0..2 {
+        let (code, operands) = read_command(&mut reader);
+        let (size, name) = operands.split_once(' ').unwrap();
+        writer.write_all(&[0]).unwrap();
+
+        let mut content = vec![0; size.parse::<usize>().unwrap() + 1];
+        reader.read_exact(&mut content).unwrap();
+        assert_eq!(content.pop(), Some(0));
+        writer.write_all(&[0]).unwrap();
+
+        match code {
+            0x02 => {
+                assert!(name.starts_with("cfA"));
+                control = String::from_utf8(content).unwrap();
+            }
+            0x03 => {
+                assert!(name.starts_with("dfA"));
+                data = content;
+            }
+            _ => panic!("unexpected subcommand {}", code),
+        }
+    }
+
+    (control, data)
+}
+
+#[test]
+pub fn test_lpd_parse_address() {
+    assert_eq!(
+        lpd::parse_address("lpd://server/labels?format=l").unwrap(),
+        ("server".to_string(), 515, "labels".to_string())
+    );
+    assert_eq!(
+        lpd::parse_address("lpd://10.0.0.2:1515/raw").unwrap(),
+        ("10.0.0.2".to_string(), 1515, "raw".to_string())
+    );
+    assert!(lpd::parse_address("lpd://server").is_err());
+    assert!(LpdOptions::from_uri("lpd://server/q?format=xy").is_err());
+}
+
+#[test]
+pub fn test_lpd_receive_job() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = thread::spawn(move || receive_job(listener));
+
+    let options = LpdOptions {
+        user: "alice".to_string(),
+        host: "workstation".to_string(),
+        copies: 2,
+        ..LpdOptions::default()
+    };
+
+    let uri = format!("lpd://127.0.0.1:{}/labels", port);
+    let job_number = lpd::print(&uri, b"^XA^XZ", Some("Shipping label"), &options).unwrap();
+    let (control, data) = server.join().unwrap();
+
+    let data_file = format!("dfA{:03}workstation", job_number);
+    let expected = format!(
+        "Hworkstation\nPalice\nJShipping label\nl{0}\nl{0}\nU{0}\nNShipping label\n",
+        data_file
+    );
+
+    assert_eq!(control, expected);
+    assert_eq!(data, b"^XA^XZ".to_vec());
+
+    // The next jobs of the process get other numbers
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = thread::spawn(move || receive_job(listener));
+
+    let uri = format!("lpd://127.0.0.1:{}/labels", port);
+    let next = network::print(&uri, b"^XA^XZ", None, &PrintOptions::default()).unwrap();
+    assert!(matches!(next, Some(number) if number != job_number));
+    server.join().unwrap();
+}
+
+#[test]
+pub fn test_lpd_print_through_printer() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = thread::spawn(move || receive_job(listener));
+
+    let printer = Printer::from_uri(&format!("lpd://127.0.0.1:{}/labels", port)).unwrap();
+    assert_eq!(printer.print("^XA^XZ".as_bytes(), Some("label")), Ok(true));
+
+    let (control, data) = server.join().unwrap();
+    assert!(control.contains("Jlabel\n"));
+    assert_eq!(data, b"^XA^XZ".to_vec());
+}
+
+#[test]
+pub fn test_lpd_control_file_fields() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = thread::spawn(move || receive_job(listener));
+
+    let options = LpdOptions {
+        user: "a".repeat(40),
+        host: "workstation\n".to_string(),
+        banner: true,
+        ..LpdOptions::default()
+    };
+
+    let uri = format!("lpd://127.0.0.1:{}/labels", port);
+    let job_name = format!("label\nldfA001evil\nUcfA001evil\r{}", "x".repeat(120));
+    let job_number = lpd::print(&uri, b"^XA^XZ", Some(&job_name), &options).unwrap();
+    let (control, _) = server.join().unwrap();
+
+    let data_file = format!("dfA{:03}workstation", job_number);
+    let job_name: String = format!("labelldfA001evilUcfA001evil{}", "x".repeat(120))
+        .chars()
+        .take(99)
+        .collect();
+    let expected = format!(
+        "Hworkstation\nP{1}\nJ{2}\nCworkstation\nL{3}\nl{0}\nU{0}\nN{2}\n",
+        data_file,
+        "a".repeat(31),
+        job_name,
+        "a".repeat(40)
+    );
+    assert_eq!(control, expected);
+}
+
+#[test]
+pub fn test_lpd_print_queue_and_cancel_job() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        assert_eq!(read_command(&mut reader), (0x03, "labels".to_string()));
+        let mut writer = stream;
+        writer
+            .write_all(
+                b"Rank   Owner   Job  File(s)              Total Size\n\
+                  active alice   12   label one.zpl        2048 bytes\n\
+                  1st    bob     13   receipt.bin          100 bytes\n",
+            )
+            .unwrap();
+        drop(writer);
+        drop(reader);
+
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        read_command(&mut reader)
+    });
+
+    let printer = Printer::from_uri(&format!("lpd://127.0.0.1:{}/labels", port)).unwrap();
+    let jobs = printer.print_queue(0, 0);
+
+    assert_eq!(jobs.len(), 2);
+    assert_eq!(jobs[0].id, "12");
+    assert_eq!(jobs[0].user, "alice");
+    assert_eq!(jobs[0].title, "label one.zpl");
+    assert_eq!(jobs[0].size, "2048");
+    assert!(matches!(jobs[0].state, JobState::PROCESSING));
+    assert!(matches!(jobs[1].state, JobState::PENDING));
+
+    assert!(printer.cancel_job(13));
+    let (code, operands) = server.join().unwrap();
+    assert_eq!(code, 0x05);
+    assert!(operands.starts_with("labels "));
+    assert!(operands.ends_with(" 13"));
+}
@@ -0,0 +1,118 @@
+use printers::capabilities::{self, Capabilities};
+use printers::document::DocumentFormat;
+use printers::media::{MediaSize, Unit};
+use printers::pdf::PageSize;
+use printers::print_options::PrintOptions;
+
+#[test]
+pub fn test_parse_media_names() {
+    let letter = MediaSize::parse("na_letter_8.5x11in").unwrap();
+    assert_eq!((letter.width, letter.height), (21590, 27940));
+    assert_eq!(letter.ppd_name(), "Letter");
+
+    // Legacy and PPD names of standard sizes
+    for name in ["Letter", "letter", "na_letter"] {
+        assert_eq!(MediaSize::parse(name).unwrap(), letter);
+    }
+    assert_eq!(MediaSize::parse("B5").unwrap().name, "jis_b5_182x257mm");
+    assert_eq!(
+        MediaSize::parse("Env10").unwrap().name,
+        "na_number-10_4.125x9.5in"
+    );
+    assert_eq!(MediaSize::parse("dl").unwrap().name, "iso_dl_110x220mm");
+
+    // Custom sizes keep their name
+    let label = MediaSize::parse("custom_w_100x150mm").unwrap();
+    assert_eq!((label.width, label.height), (10000, 15000));
+    assert!(label.is_custom());
+    assert_eq!(label.ppd_name(), "100x150mm");
+    assert_eq!(
+        MediaSize::custom(60.0, 40.0, Unit::Millimeters).ppd_name(),
+        "Custom.60x40mm"
+    );
+
+    assert!(MediaSize::parse("iso_a4").is_ok());
+    assert!(MediaSize::parse("iso_a4_210x0mm").is_err());
+    assert!(MediaSize::parse("a4_210x297").is_err());
+    assert!(MediaSize::parse("tray-2").is_err());
+}
+
+#[test]
+pub fn test_media_units() {
+    let label = MediaSize::custom(4.0, 6.0, Unit::Inches);
+    assert_eq!(label.name, "custom_4x6in_4x6in");
+    assert_eq!(label.width_in(Unit::Millimeters), 101.6);
+    assert_eq!(label.height_in(Unit::Points), 432.0);
+    assert_eq!(label.ppd_name(), "4x6");
+
+    let custom = MediaSize::custom(283.46, 425.2, Unit::Points);
+    assert_eq!(custom.name, "custom_100x150mm_100x150mm");
+    assert_eq!(Unit::Millimeters.convert(25.4, Unit::Inches), 1.0);
+    assert_eq!(Unit::Inches.convert(1.0, Unit::Hundredths), 2540.0);
+
+    let a4 = MediaSize::parse("A4").unwrap().page_size();
+    assert!((a4.width - PageSize::A4.width).abs() < 0.01);
+    assert!((a4.height - PageSize::A4.height).abs() < 0.01);
+
+    assert!(MediaSize::standard()
+        .iter()
+        .all(|media| media.width > 0 && media.height > 0));
+}
+
+#[test]
+pub fn test_closest_media() {
+    let supported: Vec<MediaSize> = ["iso_a4_210x297mm", "na_letter_8.5x11in", "iso_a5_148x210mm"]
+        .iter()
+        .map(|name| MediaSize::parse(name).unwrap())
+        .collect();
+
+    let closest = |name: &str| {
+        MediaSize::parse(name)
+            .unwrap()
+            .closest(&supported)
+            .map(|media| media.name.clone())
+    };
+
+    assert_eq!(closest("A3").as_deref(), Some("iso_a4_210x297mm"));
+    assert_eq!(closest("Executive").as_deref(), Some("na_letter_8.5x11in"));
+    assert_eq!(closest("A6").as_deref(), Some("iso_a5_148x210mm"));
+    // Large enough sizes are preferred
+    assert_eq!(
+        closest("custom_w_150x200mm").as_deref(),
+        Some("na_letter_8.5x11in")
+    );
+}
+
+#[test]
+pub fn test_validate_media() {
+    let mut capabilities = Capabilities::default();
+    capabilities.set(
+        "media",
+        [
+            "na_letter_8.5x11in",
+            "custom_min_25.4x25.4mm",
+            "custom_max_108x600mm",
+        ]
+        .iter()
+        .map(|name| name.to_string())
+        .collect(),
+    );
+
+    let validate = |media: &str| {
+        let options = PrintOptions {
+            media: Some(MediaSize::parse(media).unwrap()),
+            ..PrintOptions::default()
+        };
+        capabilities::validate(&capabilities, &options, DocumentFormat::PDF)
+    };
+
+    assert!(validate("Letter").is_empty());
+    assert!(validate("custom_label_4x6in").is_empty());
+
+    let violations = validate("A4");
+    assert_eq!(violations[0].value, "iso_a4_210x297mm");
+    assert_eq!(
+        violations[0].suggestion.as_deref(),
+        Some("na_letter_8.5x11in")
+    );
+}
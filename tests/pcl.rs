@@ -0,0 +1,106 @@
+use printers::document::DocumentFormat;
+use printers::pcl::{compress_delta_row, compress_tiff, Compression, PaperSize, Pcl, Tray};
+use printers::print_options::Sides;
+use printers::raster::Bitmap;
+
+/**
+ * Returns the position of a sequence of bytes
+ */
+fn find(bytes: &[u8], sequence: &[u8]) -> Option<usize> {
+    bytes
+        .windows(sequence.len())
+        .position(|window| window == sequence)
+}
+
+#[test]
+pub fn test_pcl_tiff_compression() {
+    assert_eq!(compress_tiff(&[]), Vec::<u8>::new());
+    assert_eq!(compress_tiff(&[7]), vec![0, 7]);
+    assert_eq!(compress_tiff(&[0xff; 5]), vec![252, 0xff]);
+    assert_eq!(
+        compress_tiff(&[1, 2, 3, 3, 3, 4]),
+        vec![1, 1, 2, 254, 3, 0, 4]
+    );
+
+    // Runs longer than 128 bytes are split
+    assert_eq!(compress_tiff(&[0xaa; 130]), vec![129, 0xaa, 255, 0xaa]);
+}
+
+#[test]
+pub fn test_pcl_delta_row_compression() {
+    let seed = [0u8; 40];
+
+    // Identical rows are empty
+    assert_eq!(compress_delta_row(&seed, &seed), Vec::<u8>::new());
+
+    // Two bytes replaced at offset 1, then one byte 2 bytes after them
+    let mut row = seed;
+    row[1] = 0xf0;
+    row[2] = 0x0f;
+    row[5] = 0x01;
+    assert_eq!(
+        compress_delta_row(&row, &seed),
+        vec![0b001_00001, 0xf0, 0x0f, 0b000_00010, 0x01]
+    );
+
+    // Offsets from 31 continue on the next byte, more than 8 bytes need a new command
+    let mut row = seed;
+    row[35] = 0x80;
+    assert_eq!(compress_delta_row(&row, &seed), vec![31, 4, 0x80]);
+
+    let row = [1u8; 10];
+    assert_eq!(
+        compress_delta_row(&row, &seed[..10]),
+        vec![0b111_00000, 1, 1, 1, 1, 1, 1, 1, 1, 0b001_00000, 1, 1]
+    );
+}
+
+#[test]
+pub fn test_pcl_job_header() {
+    let bytes = Pcl::new()
+        .job_name("Invoice \"42\"")
+        .paper_size(PaperSize::Letter)
+        .tray(Tray::Tray2)
+        .sides(Sides::TwoSidedShortEdge)
+        .copies(3)
+        .text_page("Hello\r\nWorld ✓")
+        .build();
+
+    let expected_start = b"\x1b%-12345X@PJL JOB NAME=\"Invoice '42'\"\r\n\
+        @PJL SET PAPER=LETTER\r\n\
+        @PJL SET MEDIASOURCE=TRAY2\r\n\
+        @PJL SET DUPLEX=ON\r\n\
+        @PJL SET BINDING=SHORTEDGE\r\n\
+        @PJL SET QTY=3\r\n\
+        @PJL ENTER LANGUAGE=PCL\r\n\x1bE\x1b&l2A";
+
+    assert!(bytes.starts_with(expected_start));
+    assert!(find(&bytes, b"Hello\nWorld ?\x0c").is_some());
+    assert!(bytes.ends_with(b"\x1bE\x1b%-12345X@PJL EOJ NAME=\"Invoice '42'\"\r\n\x1b%-12345X"));
+    assert_eq!(DocumentFormat::detect(&bytes), DocumentFormat::PCL);
+}
+
+#[test]
+pub fn test_pcl_raster_page() {
+    let mut bitmap = Bitmap::new(16, 3);
+    bitmap.set(0, 0, true);
+    bitmap.set(0, 1, true);
+    bitmap.set(15, 2, true);
+
+    let bytes = Pcl::new()
+        .compression(Compression::DeltaRow)
+        .raster_page(&bitmap, 300)
+        .unwrap()
+        .build();
+
+    let start = find(&bytes, b"\x1b*t300R").unwrap();
+    let end = find(&bytes, b"\x1b*rC\x0c").unwrap();
+
+    assert_eq!(
+        &bytes[start..end],
+        &b"\x1b*t300R\x1b*r16S\x1b*p0x0Y\x1b*r1A\x1b*b3M\
+           \x1b*b2W\x00\x80\x1b*b0W\x1b*b3W\x20\x00\x01"[..]
+    );
+
+    assert!(Pcl::new().raster_page(&bitmap, 120).is_err());
+}
@@ -0,0 +1,336 @@
+use printers::ipp::PrinterAttributes;
+use printers::media::MediaSize;
+use printers::ppd::{self, Ppd, PpdConstraint, PpdUi};
+
+const PPD: &str = r#"*PPD-Adobe: "4.3"
+*% Test PPD of a laser printer
+*FormatVersion: "4.3"
+*LanguageVersion: English
+*LanguageEncoding: ISOLatin1
+*cupsLanguages: "fr pt_BR"
+*Manufacturer: "Acme"
+*ModelName: "Acme Laser 5000"
+*NickName: "Acme Laser 5000, 1.0"
+*cupsFilter2: "application/vnd.cups-postscript application/postscript 0 -"
+*JCLBegin: "<1B>%-12345X@PJL JOB<0A>"
+*OpenGroup: General/General
+*OpenUI *PageSize/Media Size: PickOne
+*OrderDependency: 10 AnySetup *PageSize
+*DefaultPageSize: Letter
+*PageSize Letter/US Letter: "<</PageSize[612 792]>>setpagedevice"
+*PageSize A4/A4: "<</PageSize[595 842]>>setpagedevice"
+*PageSize Env10/Envelope <23>10: "<</PageSize[297 684]>>setpagedevice"
+*CloseUI: *PageSize
+*OpenUI *InputSlot/Media Source: PickOne
+*OrderDependency: 20 AnySetup *InputSlot
+*DefaultInputSlot: Tray1
+*InputSlot Tray1/Tray 1: "<</MediaPosition 0>>setpagedevice"
+*InputSlot Envelope/Envelope Feeder: "<</MediaPosition 2>>setpagedevice"
+*CloseUI: *InputSlot
+*OpenUI *Duplex/2-Sided Printing: PickOne
+*DefaultDuplex: None
+*Duplex None/Off: "<</Duplex false>>setpagedevice"
+*Duplex DuplexNoTumble/Long Edge: "
+<</Duplex true
+  /Tumble false>>setpagedevice"
+*End
+*CloseUI: *Duplex
+*CloseGroup: General
+*OpenGroup: Quality/Print Quality
+*OpenSubGroup: Toner/Toner
+*OpenUI *TonerSave/Toner Saving: Boolean
+*DefaultTonerSave: False
+*TonerSave True/On: ""
+*TonerSave False/Off: ""
+*CloseUI: *TonerSave
+*CloseSubGroup: Toner
+*CloseGroup: Quality
+*OpenUI *Collate/Collate: Boolean
+*DefaultCollate: True
+*Collate True/On: ""
+*Collate False/Off: ""
+*CloseUI: *Collate
+*UIConstraints: *Duplex *InputSlot Envelope
+*UIConstraints: *InputSlot Envelope *Duplex
+*UIConstraints: *PageSize Env10 *InputSlot Tray1
+*NonUIConstraints: *TonerSave True *Collate
+*CustomPageSize True: "pop pop pop <</PageSize[5 -2 roll]>>setpagedevice"
+*PaperDimension Letter: "612 792"
+*PaperDimension A4: "595 842"
+*PaperDimension Env10: "297 684"
+*ImageableArea Letter: "18 36 594 756"
+*fr.Translation PageSize/Taille du papier: ""
+*fr.PageSize Letter/Lettre US: ""
+*fr.Translation InputSlot/Source papier: ""
+*pt.Translation Duplex/Frente e verso: ""
+*fr.Translation TonerSave/<C9>conomie de toner: ""
+"#;
+
+#[test]
+pub fn test_parse_ppd() {
+    let ppd = Ppd::parse(PPD.as_bytes()).unwrap();
+
+    assert_eq!(ppd.model_name, "Acme Laser 5000");
+    assert_eq!(ppd.nick_name, "Acme Laser 5000, 1.0");
+    assert_eq!(ppd.manufacturer, "Acme");
+    assert_eq!(ppd.language, "English");
+    assert_eq!(
+        ppd.attribute("cupsFilter2"),
+        Some("application/vnd.cups-postscript application/postscript 0 -")
+    );
+    assert!(ppd.custom_page_size);
+
+    let groups: Vec<(&str, usize)> = ppd
+        .groups
+        .iter()
+        .map(|group| (group.name.as_str(), group.options.len()))
+        .collect();
+    assert_eq!(groups, vec![("General", 4), ("Quality", 0)]);
+    assert_eq!(ppd.groups[1].subgroups[0].options[0].keyword, "TonerSave");
+    assert_eq!(ppd.options().len(), 5);
+
+    let input_slot = ppd.option("InputSlot").unwrap();
+    assert_eq!(input_slot.text, "Media Source");
+    assert_eq!(input_slot.ui, PpdUi::PickOne);
+    assert_eq!(input_slot.default.as_deref(), Some("Tray1"));
+    assert_eq!(input_slot.order, Some(20.0));
+    assert_eq!(
+        input_slot.choice("Envelope").unwrap().code,
+        "<</MediaPosition 2>>setpagedevice"
+    );
+
+    let duplex = ppd.option("Duplex").unwrap();
+    assert_eq!(
+        duplex.choice("DuplexNoTumble").unwrap().code,
+        "\n<</Duplex true\n  /Tumble false>>setpagedevice"
+    );
+    assert_eq!(ppd.option("TonerSave").unwrap().ui, PpdUi::Boolean);
+
+    assert!(Ppd::parse(b"%!PS-Adobe-3.0\n").is_err());
+    assert!(Ppd::from_file("/path/to/missing.ppd").is_err());
+}
+
+#[test]
+pub fn test_ppd_page_sizes() {
+    let ppd = Ppd::parse(PPD.as_bytes()).unwrap();
+    assert_eq!(ppd.page_sizes.len(), 3);
+
+    let letter = ppd.page_size("Letter").unwrap();
+    assert_eq!(letter.text, "US Letter");
+    assert_eq!((letter.width, letter.length), (612.0, 792.0));
+    assert_eq!(letter.imageable_area, Some((18.0, 36.0, 594.0, 756.0)));
+    assert_eq!(
+        letter.media(),
+        MediaSize::parse("na_letter_8.5x11in").unwrap()
+    );
+
+    let envelope = ppd.page_size("Env10").unwrap();
+    assert_eq!(envelope.text, "Envelope #10");
+    assert_eq!(envelope.imageable_area, None);
+    assert_eq!(envelope.media().name, "na_number-10_4.125x9.5in");
+}
+
+#[test]
+pub fn test_ppd_translations() {
+    let ppd = Ppd::parse(PPD.as_bytes()).unwrap();
+
+    let page_size = ppd.option("PageSize").unwrap();
+    assert_eq!(page_size.localized_text("fr"), "Taille du papier");
+    assert_eq!(page_size.localized_text("fr_CA"), "Taille du papier");
+    assert_eq!(page_size.localized_text("de"), "Media Size");
+    assert_eq!(
+        page_size.choice("Letter").unwrap().localized_text("fr"),
+        "Lettre US"
+    );
+    assert_eq!(
+        ppd.option("Duplex").unwrap().localized_text("pt_BR"),
+        "Frente e verso"
+    );
+    assert_eq!(
+        ppd.option("TonerSave").unwrap().localized_text("fr"),
+        "Économie de toner"
+    );
+
+    // ISO Latin 1 files
+    let latin1: Vec<u8> = b"*PPD-Adobe: \"4.3\"\n*ModelName: \"Imprimante \xe9co\"\n".to_vec();
+    assert_eq!(Ppd::parse(&latin1).unwrap().model_name, "Imprimante éco");
+}
+
+#[test]
+pub fn test_ppd_conflicts_and_validation() {
+    let ppd = Ppd::parse(PPD.as_bytes()).unwrap();
+    assert_eq!(ppd.constraints.len(), 4);
+
+    let options = |options: &[(&str, &str)]| -> Vec<(String, String)> {
+        options
+            .iter()
+            .map(|(keyword, choice)| (keyword.to_string(), choice.to_string()))
+            .collect()
+    };
+
+    // Defaults do not conflict
+    assert!(ppd.conflicts(&[]).is_empty());
+    assert!(ppd
+        .conflicts(&options(&[("Duplex", "None"), ("InputSlot", "Envelope")]))
+        .is_empty());
+
+    // Reversed constraints are reported once
+    let conflicts = ppd.conflicts(&options(&[
+        ("Duplex", "DuplexNoTumble"),
+        ("InputSlot", "Envelope"),
+    ]));
+    assert_eq!(
+        conflicts,
+        vec![&PpdConstraint {
+            option1: "Duplex".to_string(),
+            choice1: None,
+            option2: "InputSlot".to_string(),
+            choice2: Some("Envelope".to_string()),
+        }]
+    );
+
+    let conflicts = ppd.conflicts(&options(&[("PageSize", "Env10"), ("TonerSave", "True")]));
+    assert_eq!(conflicts.len(), 2);
+
+    let violations = ppd.validate(&options(&[
+        ("InputSlot", "Tray9"),
+        ("PageSize", "Custom.4x6in"),
+        ("media", "A4"),
+    ]));
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].attribute, "InputSlot");
+    assert_eq!(violations[0].suggestion.as_deref(), Some("Tray1"));
+}
+
+/**
+ * Returns the attributes of a color IPP Everywhere printer
+ */
+fn everywhere_printer() -> PrinterAttributes {
+    let mut attributes = PrinterAttributes::default();
+    attributes
+        .set("printer-make-and-model", &["Acme \"Jet\" 200"])
+        .set(
+            "document-format-supported",
+            &[
+                "application/octet-stream",
+                "image/pwg-raster",
+                "image/urf",
+                "application/pdf",
+            ],
+        )
+        .set("copies-supported", &["1-99"])
+        .set(
+            "media-supported",
+            &[
+                "iso_a4_210x297mm",
+                "na_letter_8.5x11in",
+                "oe_photo-l_3.5x5in",
+                "custom_foo_90x130mm",
+                "custom_min_76.2x127mm",
+                "custom_max_215.9x355.6mm",
+            ],
+        )
+        .set("media-default", &["na_letter_8.5x11in"])
+        .set("media-left-margin-supported", &["635", "0"])
+        .set("media-right-margin-supported", &["635", "0"])
+        .set("media-top-margin-supported", &["423"])
+        .set("media-bottom-margin-supported", &["423"])
+        .set("media-source-supported", &["auto", "main", "manual"])
+        .set("sides-supported", &["one-sided", "two-sided-long-edge"])
+        .set("sides-default", &["two-sided-long-edge"])
+        .set(
+            "print-color-mode-supported",
+            &["auto", "monochrome", "color"],
+        )
+        .set("print-color-mode-default", &["auto"])
+        .set("print-quality-supported", &["3", "4", "5"])
+        .set("printer-resolution-supported", &["300dpi", "600dpi"])
+        .set("printer-resolution-default", &["600dpi"])
+        .set("output-bin-supported", &["face-down"])
+        .set("finishings-supported", &["3", "4", "20"]);
+    attributes
+}
+
+#[test]
+pub fn test_generate_driverless_ppd() {
+    let text = ppd::generate(&everywhere_printer()).unwrap();
+    let ppd = Ppd::parse(text.as_bytes()).unwrap();
+
+    assert_eq!(ppd.model_name, "Acme 'Jet' 200");
+    assert_eq!(ppd.manufacturer, "Acme");
+    assert_eq!(ppd.attribute("ColorDevice"), Some("True"));
+    assert_eq!(ppd.attribute("cupsManualCopies"), Some("False"));
+    let filters: Vec<&str> = ppd
+        .attributes
+        .iter()
+        .filter(|attribute| attribute.keyword == "cupsFilter2")
+        .map(|attribute| attribute.value.as_str())
+        .collect();
+    assert_eq!(
+        filters,
+        vec![
+            "application/vnd.cups-pdf application/pdf 10 -",
+            "image/pwg-raster image/pwg-raster 100 -",
+            "image/urf image/urf 150 -",
+        ]
+    );
+
+    let page_size = ppd.option("PageSize").unwrap();
+    assert_eq!(page_size.default.as_deref(), Some("Letter"));
+    let names: Vec<&str> = page_size
+        .choices
+        .iter()
+        .map(|choice| choice.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["A4", "Letter", "3.5x5", "w255h369"]);
+    assert_eq!(
+        page_size.choice("A4").unwrap().code,
+        "<</PageSize[595.28 841.89]>>setpagedevice"
+    );
+
+    // Borderless sizes when a margin is zero
+    let letter = ppd.page_size("Letter").unwrap();
+    assert_eq!((letter.width, letter.length), (612.0, 792.0));
+    assert_eq!(letter.imageable_area, Some((0.0, 11.99, 612.0, 780.01)));
+    assert!(ppd.custom_page_size);
+    assert_eq!(ppd.attribute("MaxMediaHeight"), Some("1008"));
+
+    let default = |keyword: &str| ppd.option(keyword).unwrap().default.clone().unwrap();
+    assert_eq!(default("Duplex"), "DuplexNoTumble");
+    assert_eq!(default("ColorModel"), "RGB");
+    assert_eq!(default("Resolution"), "600dpi");
+    assert_eq!(default("InputSlot"), "Auto");
+    assert_eq!(default("cupsFinishing"), "None");
+    assert_eq!(ppd.option("Duplex").unwrap().choices.len(), 2);
+    assert_eq!(ppd.option("cupsPrintQuality").unwrap().choices.len(), 3);
+    assert_eq!(ppd.option("OutputBin").unwrap().choices[0].name, "FaceDown");
+    assert_eq!(
+        ppd.option("cupsFinishing")
+            .unwrap()
+            .choices
+            .iter()
+            .map(|choice| choice.name.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["None", "Staple", "StapleTopLeft"]
+    );
+    assert_eq!(ppd.constraints.len(), 2);
+    assert_eq!(ppd.conflicts(&[]).len(), 0);
+}
+
+#[test]
+pub fn test_generate_minimal_ppd() {
+    let mut attributes = PrinterAttributes::default();
+    attributes.set("document-format-supported", &["image/urf"]);
+
+    let ppd = Ppd::parse(ppd::generate(&attributes).unwrap().as_bytes()).unwrap();
+    assert_eq!(ppd.model_name, "IPP Everywhere Printer");
+    assert_eq!(ppd.attribute("ColorDevice"), Some("False"));
+    assert_eq!(ppd.page_sizes.len(), 2);
+    assert!(!ppd.custom_page_size);
+    assert_eq!(ppd.option("ColorModel").unwrap().choices.len(), 1);
+    assert!(ppd.option("Duplex").is_none());
+    assert!(ppd.option("InputSlot").is_none());
+
+    attributes.set("document-format-supported", &["application/vnd.hp-pcl"]);
+    assert!(ppd::generate(&attributes).is_err());
+}
@@ -0,0 +1,236 @@
+use printers::capabilities::{self, Capabilities};
+use printers::document::DocumentFormat;
+use printers::media::MediaSize;
+use printers::print_options::{
+    ColorMode, Finishing, MediaSource, Orientation, OutputBin, PrintOptions, PrintQuality,
+    Resolution, Scaling, Sides,
+};
+
+#[test]
+pub fn test_media_source_and_output_bin_keywords() {
+    for source in [
+        MediaSource::Auto,
+        MediaSource::ByPassTray,
+        MediaSource::LargeCapacity,
+        MediaSource::Tray(2),
+    ] {
+        assert_eq!(MediaSource::from_keyword(&source.keyword()), Some(source));
+        assert_eq!(
+            MediaSource::from_ppd_choice(&source.ppd_choice()),
+            Some(source)
+        );
+    }
+
+    assert_eq!(MediaSource::Tray(2).keyword(), "tray-2");
+    assert_eq!(MediaSource::Tray(2).ppd_choice(), "Tray2");
+    assert_eq!(MediaSource::Bottom.ppd_choice(), "Lower");
+    assert_eq!(
+        MediaSource::from_ppd_choice("ManualFeed"),
+        Some(MediaSource::Manual)
+    );
+    assert_eq!(
+        MediaSource::from_ppd_choice("MPTray"),
+        Some(MediaSource::ByPassTray)
+    );
+    assert_eq!(MediaSource::from_keyword("tray-0"), None);
+    assert_eq!(MediaSource::from_keyword("Tray2"), None);
+
+    for bin in [
+        OutputBin::FaceDown,
+        OutputBin::Stacker(1),
+        OutputBin::Mailbox(12),
+    ] {
+        assert_eq!(OutputBin::from_keyword(&bin.keyword()), Some(bin));
+        assert_eq!(OutputBin::from_ppd_choice(&bin.ppd_choice()), Some(bin));
+    }
+    assert_eq!(OutputBin::Mailbox(12).keyword(), "mailbox-12");
+    assert_eq!(OutputBin::from_ppd_choice("Upper"), Some(OutputBin::Top));
+}
+
+#[test]
+pub fn test_finishing_keywords() {
+    assert_eq!(Finishing::StapleTopLeft.keyword(), "staple-top-left");
+    assert_eq!(Finishing::StapleTopLeft as i32, 20);
+    assert_eq!(
+        Finishing::from_keyword("20"),
+        Some(Finishing::StapleTopLeft)
+    );
+    assert_eq!(
+        Finishing::from_keyword("staple-dual-left"),
+        Some(Finishing::StapleDualLeft)
+    );
+    assert_eq!(
+        Finishing::from_keyword("punch-dual-left"),
+        Some(Finishing::PunchDualLeft)
+    );
+    assert_eq!(
+        Finishing::from_keyword("fold-half"),
+        Some(Finishing::FoldHalf)
+    );
+    assert_eq!(
+        Finishing::from_keyword("bind-left"),
+        Some(Finishing::BindLeft)
+    );
+    assert_eq!(
+        Finishing::from_keyword("trim-after-job"),
+        Some(Finishing::TrimAfterJob)
+    );
+    assert_eq!(Finishing::from_keyword("staples"), None);
+    assert_eq!(Finishing::from_keyword("1"), None);
+}
+
+#[test]
+pub fn test_ipp_attributes_and_ppd_options() {
+    let options = PrintOptions {
+        sides: Some(Sides::TwoSidedShortEdge),
+        media: Some(MediaSize::parse("Letter").unwrap()),
+        media_source: Some(MediaSource::Tray(2)),
+        output_bin: Some(OutputBin::FaceUp),
+        finishings: vec![Finishing::StapleTopLeft, Finishing::PunchDualLeft],
+        ..PrintOptions::default()
+    };
+
+    let pairs = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    };
+
+    assert_eq!(
+        options.ipp_attributes(),
+        pairs(&[
+            ("sides", "two-sided-short-edge"),
+            ("media", "na_letter_8.5x11in"),
+            ("media-source", "tray-2"),
+            ("output-bin", "face-up"),
+            ("finishings", "staple-top-left,punch-dual-left"),
+        ])
+    );
+    assert_eq!(
+        options.ppd_options(),
+        pairs(&[
+            ("Duplex", "DuplexTumble"),
+            ("PageSize", "Letter"),
+            ("InputSlot", "Tray2"),
+            ("OutputBin", "FaceUp"),
+        ])
+    );
+}
+
+#[test]
+pub fn test_validate_finishings_and_trays() {
+    let values = |values: &[&str]| values.iter().map(|value| value.to_string()).collect();
+    let mut capabilities = Capabilities::default();
+    capabilities
+        .set(
+            "media-source",
+            values(&["auto", "tray-1", "tray-2", "manual"]),
+        )
+        .set("output-bin", values(&["face-down", "unknown-bin"]))
+        .set("finishings", values(&["none", "staple-top-left", "punch"]));
+
+    assert_eq!(
+        capabilities.media_sources(),
+        vec![
+            MediaSource::Auto,
+            MediaSource::Tray(1),
+            MediaSource::Tray(2),
+            MediaSource::Manual
+        ]
+    );
+    assert_eq!(capabilities.output_bins(), vec![OutputBin::FaceDown]);
+    assert_eq!(capabilities.finishings().len(), 3);
+
+    let options = PrintOptions {
+        media_source: Some(MediaSource::Tray(3)),
+        finishings: vec![Finishing::StapleTopLeft, Finishing::StapleDualLeft],
+        ..PrintOptions::default()
+    };
+    let violations = capabilities::validate(&capabilities, &options, DocumentFormat::PDF);
+    assert_eq!(violations.len(), 2);
+    assert_eq!(violations[0].suggestion.as_deref(), Some("tray-2"));
+    assert_eq!(violations[1].value, "staple-dual-left");
+    assert_eq!(violations[1].suggestion.as_deref(), Some("staple-top-left"));
+}
+
+#[test]
+pub fn test_resolution_and_color_keywords() {
+    assert_eq!(Resolution::parse("600dpi"), Ok(Resolution::dpi(600)));
+    assert_eq!(
+        Resolution::parse("1200x600dpi"),
+        Ok(Resolution { x: 1200, y: 600 })
+    );
+    assert_eq!(Resolution::parse("118dpcm"), Ok(Resolution::dpi(300)));
+    assert_eq!(Resolution { x: 600, y: 1200 }.keyword(), "600x1200dpi");
+    assert!(Resolution::parse("0dpi").is_err());
+    assert!(Resolution::parse("high").is_err());
+
+    assert_eq!(
+        ColorMode::from_keyword("bi-level"),
+        Some(ColorMode::BiLevel)
+    );
+    assert_eq!(ColorMode::Auto.ppd_color_model(), None);
+    assert_eq!(ColorMode::BiLevel.ppd_color_model(), Some("Gray"));
+    assert_eq!(Scaling::from_keyword("auto-fit"), Some(Scaling::AutoFit));
+    assert_eq!(Orientation::from_keyword("4"), Some(Orientation::Landscape));
+    assert_eq!(Orientation::ReversePortrait as i32, 6);
+    assert_eq!(PrintQuality::from_keyword("high"), Some(PrintQuality::High));
+}
+
+#[test]
+pub fn test_quality_and_color_options() {
+    let options = PrintOptions {
+        quality: Some(PrintQuality::Draft),
+        resolution: Some(Resolution::dpi(300)),
+        color_mode: Some(ColorMode::Monochrome),
+        scaling: Some(Scaling::Fit),
+        orientation: Some(Orientation::Landscape),
+        ..PrintOptions::default()
+    };
+
+    let pairs = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    };
+
+    assert_eq!(
+        options.ipp_attributes(),
+        pairs(&[
+            ("print-quality", "draft"),
+            ("printer-resolution", "300dpi"),
+            ("print-color-mode", "monochrome"),
+            ("print-scaling", "fit"),
+            ("orientation-requested", "landscape"),
+        ])
+    );
+    assert_eq!(
+        options.ppd_options(),
+        pairs(&[
+            ("cupsPrintQuality", "Draft"),
+            ("Resolution", "300dpi"),
+            ("ColorModel", "Gray"),
+        ])
+    );
+
+    let values = |values: &[&str]| values.iter().map(|value| value.to_string()).collect();
+    let mut capabilities = Capabilities::default();
+    capabilities
+        .set("print-quality", values(&["draft", "normal"]))
+        .set("printer-resolution", values(&["300dpi", "600dpi"]))
+        .set("print-color-mode", values(&["auto", "color", "monochrome"]))
+        .set("orientation-requested", values(&["portrait"]));
+
+    assert_eq!(
+        capabilities.resolutions(),
+        vec![Resolution::dpi(300), Resolution::dpi(600)]
+    );
+    assert_eq!(capabilities.color_modes().len(), 3);
+
+    let violations = capabilities::validate(&capabilities, &options, DocumentFormat::PDF);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].attribute, "orientation-requested");
+    assert_eq!(violations[0].suggestion.as_deref(), Some("portrait"));
+}
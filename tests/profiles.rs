@@ -0,0 +1,162 @@
+#![cfg(feature = "profiles")]
+
+use printers::document::DocumentFormat;
+use printers::media::MediaSize;
+use printers::print_options::{ColorMode, PrintOptions, PrintQuality, Sides};
+use printers::profiles::{Profile, ProfileRegistry};
+use std::env;
+use std::fs;
+
+const CONFIG: &str = r#"
+[printers.office]
+system_name = "HP_LaserJet_M404"
+options = "media=A4 sides=two-sided-long-edge print-color-mode=color"
+
+[printers.labels]
+system_name = "Zebra_ZD420"
+
+[printers.Canon_G3010]
+options = { media = "Letter" }
+
+[profiles.invoice-duplex]
+printer = "office"
+format = "pdf"
+copies = 2
+options = { finishings = "staple-top-left", print-color-mode = "monochrome" }
+
+[profiles.label-4x6]
+printer = "labels"
+format = "zpl"
+options = "media=custom_4x6in_4x6in"
+
+[profiles.photo]
+printer = "Canon_G3010"
+options = "print-quality=high"
+"#;
+
+#[test]
+pub fn test_resolve_profiles() {
+    let registry = ProfileRegistry::from_toml(CONFIG).unwrap();
+    assert_eq!(registry.profiles().len(), 3);
+    assert_eq!(registry.system_name("labels"), "Zebra_ZD420");
+    assert_eq!(registry.system_name("Canon_G3010"), "Canon_G3010");
+
+    // Printer defaults < profile options < profile format and copies
+    let (system_name, options) = registry
+        .resolve("invoice-duplex", &PrintOptions::default())
+        .unwrap();
+    assert_eq!(system_name, "HP_LaserJet_M404");
+    assert_eq!(options.document_format, Some(DocumentFormat::PDF));
+    assert_eq!(options.copies, Some(2));
+    assert_eq!(options.media, Some(MediaSize::parse("A4").unwrap()));
+    assert_eq!(options.sides, Some(Sides::TwoSidedLongEdge));
+    assert_eq!(options.color_mode, Some(ColorMode::Monochrome));
+    assert_eq!(options.finishings.len(), 1);
+
+    // Job overrides replace everything
+    let overrides = PrintOptions {
+        copies: Some(5),
+        sides: Some(Sides::OneSided),
+        document_format: Some(DocumentFormat::POSTSCRIPT),
+        ..PrintOptions::default()
+    };
+    let (_, options) = registry.resolve("invoice-duplex", &overrides).unwrap();
+    assert_eq!(options.copies, Some(5));
+    assert_eq!(options.sides, Some(Sides::OneSided));
+    assert_eq!(options.document_format, Some(DocumentFormat::POSTSCRIPT));
+
+    // Defaults of printers without alias
+    let (system_name, options) = registry.resolve("photo", &PrintOptions::default()).unwrap();
+    assert_eq!(system_name, "Canon_G3010");
+    assert_eq!(options.media, Some(MediaSize::parse("Letter").unwrap()));
+    assert_eq!(options.quality, Some(PrintQuality::High));
+
+    let label = registry.profile("label-4x6").unwrap();
+    assert_eq!(label.format, Some(DocumentFormat::ZPL));
+    assert!(registry
+        .resolve("missing", &PrintOptions::default())
+        .is_err());
+}
+
+#[test]
+pub fn test_json_config() {
+    let registry = ProfileRegistry::from_json(
+        r#"{
+            "printers": { "office": { "system_name": "HP_LaserJet_M404" } },
+            "profiles": {
+                "draft-mono": {
+                    "printer": "office",
+                    "format": "application/pdf",
+                    "options": { "print-quality": 3, "print-color-mode": "monochrome", "collate": false }
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let profile = registry.profile("draft-mono").unwrap();
+    assert_eq!(profile.format, Some(DocumentFormat::PDF));
+    assert_eq!(
+        profile.options,
+        vec![
+            ("collate".to_string(), "false".to_string()),
+            ("print-color-mode".to_string(), "monochrome".to_string()),
+            ("print-quality".to_string(), "3".to_string()),
+        ]
+    );
+
+    let (_, options) = registry
+        .resolve("draft-mono", &PrintOptions::default())
+        .unwrap();
+    assert_eq!(options.quality, Some(PrintQuality::Draft));
+    assert_eq!(
+        options.attributes,
+        vec![("collate".to_string(), "false".to_string())]
+    );
+}
+
+#[test]
+pub fn test_invalid_configs() {
+    assert!(ProfileRegistry::from_toml("[profiles.draft]\noptions = \"copies=2\"").is_err());
+    assert!(ProfileRegistry::from_toml(
+        "[profiles.draft]\nprinter = \"office\"\nformat = \"docx\""
+    )
+    .is_err());
+    assert!(ProfileRegistry::from_toml("[printer.office]\nsystem_name = \"HP\"").is_err());
+    assert!(ProfileRegistry::from_json(r#"{"profile": {}}"#).is_err());
+    assert!(ProfileRegistry::from_file("/path/to/missing.toml").is_err());
+    assert_eq!(ProfileRegistry::from_toml("").unwrap().profiles().len(), 0);
+}
+
+#[test]
+pub fn test_job_presets_and_files() {
+    let preset = Profile::from_job_preset(
+        "office",
+        "{preset-name=draft print-quality=3 media-col={media-size={x-dimension=21000 y-dimension=29700}}}",
+    )
+    .unwrap();
+    assert_eq!(preset.name, "office/draft");
+    assert_eq!(preset.printer, "office");
+    assert_eq!(
+        preset.options,
+        vec![
+            ("print-quality".to_string(), "3".to_string()),
+            (
+                "media-col".to_string(),
+                "{media-size={x-dimension=21000 y-dimension=29700}}".to_string()
+            ),
+        ]
+    );
+    assert!(Profile::from_job_preset("office", "{print-quality=3}").is_none());
+    assert!(Profile::from_job_preset("office", "draft").is_none());
+
+    let file_path = env::temp_dir().join("printers-test-profiles.toml");
+    fs::write(&file_path, CONFIG).unwrap();
+
+    env::set_var("PRINTERS_PROFILES", &file_path);
+    let registry = ProfileRegistry::load();
+    env::remove_var("PRINTERS_PROFILES");
+    fs::remove_file(&file_path).unwrap();
+
+    assert_eq!(registry.unwrap().system_name("office"), "HP_LaserJet_M404");
+}
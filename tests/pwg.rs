@@ -0,0 +1,151 @@
+use printers::document::DocumentFormat;
+use printers::print_options::{PrintQuality, Sides};
+use printers::pwg::{self, ColorSpace, PageHeader, RasterPage};
+use printers::raster::Bitmap;
+
+/**
+ * Returns a page with a gradient, a flat area and repeated rows to exercise every run kind
+ */
+fn sample_page(width: u32, height: u32, color_space: ColorSpace) -> RasterPage {
+    let mut page = RasterPage::new(width, height, color_space);
+    let row_bytes = page.row_bytes();
+
+    for y in 0..height as usize {
+        if y % 7 < 3 {
+            continue;
+        }
+        for (x, byte) in page.data[y * row_bytes..(y + 1) * row_bytes]
+            .iter_mut()
+            .enumerate()
+        {
+            *byte = if x < row_bytes / 3 {
+                (x * 37 + y) as u8
+            } else {
+                0x55
+            };
+        }
+    }
+
+    page
+}
+
+#[test]
+pub fn test_pwg_round_trip() {
+    let header = PageHeader {
+        resolution: 600,
+        media_size_name: "na_letter_8.5x11in".to_string(),
+        media_type: "photographic".to_string(),
+        sides: Sides::TwoSidedShortEdge,
+        copies: 3,
+        quality: PrintQuality::High,
+    };
+
+    let pages = vec![
+        sample_page(301, 40, ColorSpace::Srgb8),
+        sample_page(300, 300, ColorSpace::Sgray8),
+        sample_page(1000, 3, ColorSpace::Black1),
+    ];
+
+    let bytes = pwg::encode_pwg(&header, &pages).unwrap();
+    assert_eq!(DocumentFormat::detect(&bytes), DocumentFormat::PWG);
+
+    let decoded = pwg::decode_pwg(&bytes).unwrap();
+    assert_eq!(decoded.len(), 3);
+
+    for ((decoded_header, decoded_page), page) in decoded.iter().zip(&pages) {
+        assert_eq!(decoded_header, &header);
+        assert_eq!(decoded_page, page);
+    }
+
+    // Page size in points, the 3 white rows are one row repeated with runs of 128 pixels
+    let first = &bytes[4..4 + 1796];
+    assert_eq!(&bytes[4..13], b"PwgRaster");
+    assert_eq!(&first[352..360], &[0, 0, 0, 36, 0, 0, 0, 5]);
+    assert_eq!(&bytes[4 + 1796..4 + 1796 + 5], &[2, 127, 0xff, 0xff, 0xff]);
+}
+
+#[test]
+pub fn test_pwg_compression() {
+    let header = PageHeader::default();
+
+    // 4 identical white rows are sent as one row repeated 4 times, one run of 10 pixels
+    let white = RasterPage::new(10, 4, ColorSpace::Sgray8);
+    let bytes = pwg::encode_pwg(&header, &[white]).unwrap();
+    assert_eq!(&bytes[4 + 1796..], &[3, 9, 0xff]);
+
+    // Literal pixels followed by a run
+    let mut page = RasterPage::new(5, 1, ColorSpace::Sgray8);
+    page.data.copy_from_slice(&[1, 2, 3, 3, 3]);
+    let bytes = pwg::encode_pwg(&header, &[page]).unwrap();
+    assert_eq!(&bytes[4 + 1796..], &[0, 255, 1, 2, 2, 3]);
+
+    // Bitmaps from the raster module are 1-bit pages
+    let mut bitmap = Bitmap::new(16, 1);
+    bitmap.set(0, 0, true);
+    let bytes = pwg::encode_pwg(&header, &[RasterPage::from_bitmap(&bitmap)]).unwrap();
+    assert_eq!(&bytes[4 + 1796..], &[0, 255, 0x80, 0x00]);
+}
+
+#[test]
+pub fn test_urf_round_trip() {
+    let header = PageHeader {
+        sides: Sides::TwoSidedLongEdge,
+        quality: PrintQuality::Draft,
+        ..PageHeader::default()
+    };
+
+    let pages = vec![
+        sample_page(600, 10, ColorSpace::Srgb8),
+        sample_page(129, 129, ColorSpace::Sgray8),
+    ];
+
+    let bytes = pwg::encode_urf(&header, &pages).unwrap();
+    assert_eq!(DocumentFormat::detect(&bytes), DocumentFormat::URF);
+    assert_eq!(&bytes[8..12], &[0, 0, 0, 2]);
+    assert_eq!(&bytes[12..16], &[24, 1, 3, 3]);
+
+    let decoded = pwg::decode_urf(&bytes).unwrap();
+    assert_eq!(decoded.len(), 2);
+
+    for ((decoded_header, decoded_page), page) in decoded.iter().zip(&pages) {
+        assert_eq!(decoded_header, &header);
+        assert_eq!(decoded_page, page);
+    }
+}
+
+#[test]
+pub fn test_raster_errors() {
+    let header = PageHeader::default();
+    let black = RasterPage::new(8, 8, ColorSpace::Black1);
+
+    assert!(pwg::encode_urf(&header, std::slice::from_ref(&black)).is_err());
+
+    let mut short = black.clone();
+    short.data.pop();
+    assert!(pwg::encode_pwg(&header, &[short]).is_err());
+
+    let mut bytes = pwg::encode_pwg(&header, &[black]).unwrap();
+    assert!(pwg::decode_pwg(&bytes[..bytes.len() - 1]).is_err());
+
+    // A page header without the PwgRaster string
+    bytes[4..13].copy_from_slice(&[0; 9]);
+    assert_eq!(
+        pwg::decode_pwg(&bytes).unwrap_err(),
+        "invalid PWG page header, PwgRaster is missing"
+    );
+    assert!(pwg::decode_pwg(b"UNIRAST\0").is_err());
+
+    // A page size too large to allocate
+    let mut urf = b"UNIRAST\0\0\0\0\x01".to_vec();
+    let mut page_header = [0; 32];
+    page_header[0] = 24;
+    page_header[1] = 1;
+    page_header[12..16].copy_from_slice(&u32::MAX.to_be_bytes());
+    page_header[16..20].copy_from_slice(&u32::MAX.to_be_bytes());
+    page_header[20..24].copy_from_slice(&300u32.to_be_bytes());
+    urf.extend_from_slice(&page_header);
+    assert_eq!(
+        pwg::decode_urf(&urf).unwrap_err(),
+        "invalid raster page size 4294967295x4294967295"
+    );
+}
@@ -0,0 +1,81 @@
+use printers::escpos::EscPos;
+use printers::raster::{dither_gray, rasterize, scale, to_grayscale, Bitmap, Dither, PixelFormat};
+use printers::zpl::Zpl;
+
+/**
+ * Returns the bitmap rows as strings ("#" is black)
+ */
+fn rows(bitmap: &Bitmap) -> Vec<String> {
+    (0..bitmap.height)
+        .map(|y| {
+            (0..bitmap.width)
+                .map(|x| if bitmap.get(x, y) { '#' } else { '.' })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+pub fn test_grayscale_and_scale() {
+    let rgb = [255, 0, 0, 0, 255, 0, 0, 0, 255, 10, 20, 30];
+    assert_eq!(to_grayscale(&rgb, PixelFormat::Rgb), vec![76, 150, 29, 18]);
+
+    // Transparent pixels are white
+    let rgba = [0, 0, 0, 0, 0, 0, 0, 255, 0, 0, 0, 128];
+    assert_eq!(to_grayscale(&rgba, PixelFormat::Rgba), vec![255, 0, 127]);
+
+    // 4x1 downscaled to 2x1 averages pairs, 2x1 upscaled to 4x1 repeats pixels
+    assert_eq!(scale(&[0, 100, 200, 255], 4, 1, 2, 1), vec![50, 228]);
+    assert_eq!(scale(&[0, 255], 2, 1, 4, 1), vec![0, 0, 255, 255]);
+}
+
+#[test]
+pub fn test_threshold_and_packing() {
+    let gray = [0, 255, 100, 200, 0, 0, 0, 0, 0, 255];
+    let bitmap = dither_gray(&gray, 10, 1, Dither::Threshold(128));
+
+    assert_eq!(bitmap.data, vec![0b1010_1111, 0b1000_0000]);
+    assert_eq!(rows(&bitmap), vec!["#.#.#####."]);
+}
+
+#[test]
+pub fn test_dithering_is_deterministic() {
+    let gray = vec![128; 8 * 4];
+
+    assert_eq!(
+        rows(&dither_gray(&gray, 8, 4, Dither::Bayer4)),
+        vec![".#.#.#.#", "#.#.#.#.", ".#.#.#.#", "#.#.#.#."]
+    );
+    assert_eq!(
+        rows(&dither_gray(&gray, 8, 4, Dither::FloydSteinberg)),
+        vec![".#.#.#.#", "#.#.#.#.", ".#.#.#.#", "#.#.#.#."]
+    );
+
+    assert_eq!(
+        rows(&dither_gray(&gray, 8, 4, Dither::Atkinson)),
+        vec![".##..##.", "#..##..#", "#..##..#", ".##..##."]
+    );
+
+    let bayer8 = dither_gray(&[64; 64], 8, 8, Dither::Bayer8);
+    assert_eq!(rows(&bayer8).concat().matches('#').count(), 48);
+}
+
+#[test]
+pub fn test_rasterize_for_printer_languages() {
+    // Black left half, white right half, scaled from 4x2 to 8 dots
+    let pixels = [0, 0, 255, 255, 0, 0, 255, 255];
+    let bitmap = rasterize(&pixels, 4, 2, PixelFormat::Gray, 8, Dither::Threshold(128)).unwrap();
+
+    assert_eq!((bitmap.width, bitmap.height), (8, 4));
+    assert_eq!(bitmap.data, vec![0xf0; 4]);
+
+    let escpos = EscPos::new().bitmap(&bitmap).unwrap().build();
+    assert_eq!(&escpos[..8], &[0x1d, b'v', b'0', 0, 1, 0, 4, 0]);
+
+    let zpl = Zpl::new(203).bitmap(0, 0, &bitmap).unwrap().build();
+    assert!(String::from_utf8(zpl)
+        .unwrap()
+        .contains("^GFA,4,4,1,F0F0F0F0^FS"));
+
+    assert!(rasterize(&pixels, 4, 3, PixelFormat::Gray, 8, Dither::Atkinson).is_err());
+}
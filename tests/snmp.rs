@@ -0,0 +1,285 @@
+use printers::network::snmp::{
+    self, AlertSeverity, Message, PduType, SnmpClient, SnmpOptions, SnmpVersion, Value,
+};
+use printers::printer::{Printer, PrinterState};
+use printers::supplies::SupplyType;
+
+use std::collections::BTreeMap;
+use std::net::UdpSocket;
+use std::thread;
+use std::time::Duration;
+
+fn oid(text: &str) -> Vec<u32> {
+    snmp::parse_oid(text).unwrap()
+}
+
+fn text(value: &str) -> Value {
+    Value::OctetString(value.as_bytes().to_vec())
+}
+
+/**
+ * Canned objects of a laser printer: printing, with a jam, low toner, an empty tray and two supplies
+ */
+fn printer_objects() -> BTreeMap<Vec<u32>, Value> {
+    let objects = vec![
+        ("1.3.6.1.2.1.1.1.0", text("HP LaserJet M404")),
+        ("1.3.6.1.2.1.25.3.2.1.5.1", Value::Integer(3)),
+        ("1.3.6.1.2.1.25.3.5.1.1.1", Value::Integer(4)),
+        // lowToner and jammed bits
+        (
+            "1.3.6.1.2.1.25.3.5.1.2.1",
+            Value::OctetString(vec![0x24, 0x00]),
+        ),
+        ("1.3.6.1.2.1.43.10.2.1.4.1.1", Value::Counter32(48213)),
+        ("1.3.6.1.2.1.43.11.1.1.3.1.1", Value::Integer(1)),
+        ("1.3.6.1.2.1.43.11.1.1.3.1.2", Value::Integer(0)),
+        ("1.3.6.1.2.1.43.11.1.1.4.1.1", Value::Integer(3)),
+        ("1.3.6.1.2.1.43.11.1.1.4.1.2", Value::Integer(4)),
+        ("1.3.6.1.2.1.43.11.1.1.5.1.1", Value::Integer(21)),
+        ("1.3.6.1.2.1.43.11.1.1.5.1.2", Value::Integer(9)),
+        (
+            "1.3.6.1.2.1.43.11.1.1.6.1.1",
+            text("Black Cartridge HP 58A"),
+        ),
+        ("1.3.6.1.2.1.43.11.1.1.6.1.2", text("Imaging Drum\0")),
+        ("1.3.6.1.2.1.43.11.1.1.8.1.1", Value::Integer(3000)),
+        ("1.3.6.1.2.1.43.11.1.1.8.1.2", Value::Integer(-2)),
+        ("1.3.6.1.2.1.43.11.1.1.9.1.1", Value::Integer(240)),
+        ("1.3.6.1.2.1.43.11.1.1.9.1.2", Value::Integer(-3)),
+        ("1.3.6.1.2.1.43.12.1.1.4.1.1", text("black")),
+        ("1.3.6.1.2.1.43.18.1.1.2.1.7", Value::Integer(3)),
+        ("1.3.6.1.2.1.43.18.1.1.4.1.7", Value::Integer(8)),
+        ("1.3.6.1.2.1.43.18.1.1.7.1.7", Value::Integer(8)),
+        ("1.3.6.1.2.1.43.18.1.1.8.1.7", text("Paper jam in tray 2")),
+        ("1.3.6.1.2.1.43.18.1.1.2.1.9", Value::Integer(4)),
+        ("1.3.6.1.2.1.43.18.1.1.4.1.9", Value::Integer(11)),
+        ("1.3.6.1.2.1.43.18.1.1.7.1.9", Value::Integer(1104)),
+        ("1.3.6.1.2.1.43.18.1.1.8.1.9", text("Black cartridge low")),
+        ("1.3.6.1.2.1.43.18.1.1.2.1.10", Value::Integer(4)),
+        ("1.3.6.1.2.1.43.18.1.1.4.1.10", Value::Integer(8)),
+        ("1.3.6.1.2.1.43.18.1.1.7.1.10", Value::Integer(808)),
+        ("1.3.6.1.2.1.43.18.1.1.8.1.10", text("Tray 1 empty")),
+        ("1.3.6.1.4.1.11.2.3.9.1.1.7.0", text("MFG:HP")),
+    ];
+
+    objects
+        .into_iter()
+        .map(|(name, value)| (oid(name), value))
+        .collect()
+}
+
+/**
+ * Start a loopback stand-in SNMP agent answering Get and GetNext requests with canned objects
+ * Requests with another community are dropped, like a real agent does
+ * Returns the port of the agent
+ */
+fn start_agent(objects: BTreeMap<Vec<u32>, Value>, community: &'static str) -> u16 {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let port = socket.local_addr().unwrap().port();
+
+    thread::spawn(move || {
+        let mut buffer = [0; 65535];
+        while let Ok((length, address)) = socket.recv_from(&mut buffer) {
+            let request = Message::decode(&buffer[..length]).unwrap();
+            if request.community != community {
+                continue;
+            }
+
+            let mut response = request.clone();
+            response.pdu_type = PduType::Response;
+            response.bindings.clear();
+
+            for (index, (name, _)) in request.bindings.iter().enumerate() {
+                let found = match request.pdu_type {
+                    PduType::GetNextRequest => objects
+                        .range(name.clone()..)
+                        .find(|(next, _)| *next > name)
+                        .map(|(next, value)| (next.clone(), value.clone())),
+                    _ => objects.get(name).map(|value| (name.clone(), value.clone())),
+                };
+
+                match (found, request.version) {
+                    (Some(binding), _) => response.bindings.push(binding),
+                    (None, SnmpVersion::V1) => {
+                        response.error_status = 2;
+                        response.error_index = index as i64 + 1;
+                        response.bindings = request.bindings.clone();
+                        break;
+                    }
+                    (None, SnmpVersion::V2c) => {
+                        let value = match request.pdu_type {
+                            PduType::GetNextRequest => Value::EndOfMibView,
+                            _ => Value::NoSuchObject,
+                        };
+                        response.bindings.push((name.clone(), value));
+                    }
+                }
+            }
+
+            socket.send_to(&response.encode(), address).unwrap();
+        }
+    });
+
+    port
+}
+
+fn options(port: u16, version: SnmpVersion) -> SnmpOptions {
+    SnmpOptions {
+        port,
+        version,
+        timeout: Duration::from_millis(500),
+        retries: 0,
+        ..SnmpOptions::default()
+    }
+}
+
+#[test]
+pub fn test_encode_decode_messages() {
+    let message = Message {
+        version: SnmpVersion::V2c,
+        community: "public".to_string(),
+        pdu_type: PduType::Response,
+        request_id: 0x1234_5678,
+        error_status: 0,
+        error_index: 0,
+        bindings: vec![
+            (oid("1.3.6.1.2.1.1.1.0"), text(&"A".repeat(300))),
+            (oid("1.3.6.1.2.1.43.11.1.1.9.1.1"), Value::Integer(-3)),
+            (oid("1.3.6.1.2.1.43.11.1.1.8.1.1"), Value::Integer(40000)),
+            (oid("1.3.6.1.2.1.1.3.0"), Value::TimeTicks(0xffff_ffff)),
+            (
+                oid("1.3.6.1.2.1.1.2.0"),
+                Value::ObjectId(oid("1.3.6.1.4.1.11.2.3.9.1")),
+            ),
+            (oid("1.3.6.1.2.1.31.1.1.1.6.1"), Value::Counter64(u64::MAX)),
+            (
+                oid("1.3.6.1.2.1.4.20.1.1.10"),
+                Value::IpAddress([10, 0, 0, 1]),
+            ),
+            (oid("1.3.6.1.2.1.1.9.0"), Value::EndOfMibView),
+        ],
+    };
+
+    let bytes = message.encode();
+    assert_eq!(&bytes[..2], &[0x30, 0x82]);
+    assert_eq!(Message::decode(&bytes).unwrap(), message);
+
+    // GetRequest of sysDescr.0 with the community public
+    let request = Message {
+        version: SnmpVersion::V1,
+        community: "public".to_string(),
+        pdu_type: PduType::GetRequest,
+        request_id: 1,
+        error_status: 0,
+        error_index: 0,
+        bindings: vec![(oid("1.3.6.1.2.1.1.1.0"), Value::Null)],
+    };
+    assert_eq!(
+        request.encode(),
+        vec![
+            0x30, 0x26, 0x02, 0x01, 0x00, 0x04, 0x06, b'p', b'u', b'b', b'l', b'i', b'c', 0xa0,
+            0x19, 0x02, 0x01, 0x01, 0x02, 0x01, 0x00, 0x02, 0x01, 0x00, 0x30, 0x0e, 0x30, 0x0c,
+            0x06, 0x08, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x01, 0x00, 0x05, 0x00,
+        ]
+    );
+
+    assert!(Message::decode(&bytes[..bytes.len() - 1]).is_err());
+    assert!(Message::decode(b"\x30\x03\x02\x01\x05").is_err());
+    assert!(snmp::parse_oid("1.3.six").is_err());
+}
+
+#[test]
+pub fn test_get_and_walk() {
+    for version in [SnmpVersion::V1, SnmpVersion::V2c].iter() {
+        let port = start_agent(printer_objects(), "public");
+        let mut client = SnmpClient::connect("127.0.0.1", &options(port, *version)).unwrap();
+
+        let values = client
+            .get(&[oid("1.3.6.1.2.1.1.1.0"), oid("1.3.6.1.2.1.1.5.0")])
+            .unwrap();
+        assert_eq!(values[0].1.as_text().as_deref(), Some("HP LaserJet M404"));
+        assert!(values[1].1.as_i64().is_none());
+
+        let column = client.walk(&oid("1.3.6.1.2.1.43.11.1.1.6")).unwrap();
+        assert_eq!(column.len(), 2);
+        assert_eq!(column[1].0, oid("1.3.6.1.2.1.43.11.1.1.6.1.2"));
+        assert_eq!(column[1].1.as_text().as_deref(), Some("Imaging Drum"));
+
+        // The end of the MIB view
+        let last = client.walk(&oid("1.3.6.1.4.1.11")).unwrap();
+        assert_eq!(last.len(), 1);
+        assert!(client.walk(&oid("1.3.6.1.4.1.99")).unwrap().is_empty());
+    }
+}
+
+#[test]
+pub fn test_printer_status() {
+    let port = start_agent(printer_objects(), "public");
+    let status = snmp::status("127.0.0.1", &options(port, SnmpVersion::V2c)).unwrap();
+
+    assert_eq!(status.state, PrinterState::PRINTING);
+    assert_eq!(
+        status.state_reasons,
+        vec!["toner-low", "media-jam", "media-empty"]
+    );
+    assert_eq!(status.page_count, Some(48213));
+
+    assert_eq!(status.alerts.len(), 3);
+    assert_eq!(status.alerts[0].severity, AlertSeverity::Critical);
+    assert_eq!(status.alerts[0].description, "Paper jam in tray 2");
+    assert_eq!(status.alerts[0].reason(), Some("media-jam"));
+    assert_eq!(status.alerts[1].severity, AlertSeverity::Warning);
+    assert_eq!(status.alerts[1].group, 11);
+    assert_eq!(status.alerts[1].reason(), Some("toner-low"));
+    assert_eq!(status.alerts[2].description, "Tray 1 empty");
+    assert_eq!(status.alerts[2].reason(), Some("media-empty"));
+
+    let mut alert = status.alerts[2].clone();
+    alert.code = 807;
+    assert_eq!(alert.reason(), Some("media-low"));
+    for code in [805, 806].iter() {
+        alert.code = *code;
+        assert_eq!(alert.reason(), None);
+    }
+
+    assert_eq!(status.supplies.len(), 2);
+    assert_eq!(status.supplies[0].name, "Black Cartridge HP 58A");
+    assert_eq!(status.supplies[0].supply_type, SupplyType::Toner);
+    assert_eq!(status.supplies[0].color.as_deref(), Some("#000000"));
+    assert_eq!(status.supplies[0].level, Some(8));
+    assert_eq!(status.supplies[1].name, "Imaging Drum");
+    assert_eq!(status.supplies[1].supply_type, SupplyType::Drum);
+    assert_eq!(status.supplies[1].level, None);
+
+    // Supplies of network printers through the printer URI
+    let printer = Printer::from_uri(&format!(
+        "socket://127.0.0.1:9100?snmp-port={}&snmp-version=2c",
+        port
+    ))
+    .unwrap();
+    assert_eq!(printer.supplies().len(), 2);
+    assert_eq!(
+        printer.snmp_status().unwrap().state_reasons,
+        status.state_reasons
+    );
+}
+
+#[test]
+pub fn test_snmp_options_and_errors() {
+    let uri_options = SnmpOptions::from_uri(
+        "socket://printer:9100?community=pr%21vate&snmp-version=2c&waiteof=false",
+    )
+    .unwrap();
+    assert_eq!(uri_options.community, "pr!vate");
+    assert_eq!(uri_options.version, SnmpVersion::V2c);
+    assert_eq!(uri_options.port, 161);
+    assert!(SnmpOptions::from_uri("socket://printer?snmp-version=3").is_err());
+
+    assert!(snmp::status_from_uri("socket://printer?snmp=false").is_err());
+    assert!(snmp::status_from_uri("usb://HP/LaserJet").is_err());
+
+    // Requests with a wrong community are not answered
+    let port = start_agent(printer_objects(), "private");
+    let result = snmp::status("127.0.0.1", &options(port, SnmpVersion::V1));
+    assert_eq!(result.unwrap_err(), "the SNMP agent did not respond");
+}
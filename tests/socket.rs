@@ -0,0 +1,110 @@
+use printers::network::socket::{self, SocketOptions};
+use printers::printer::Printer;
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+pub fn test_socket_options_from_uri() {
+    let options =
+        SocketOptions::from_uri("socket://printer:9100?contimeout=5&waiteof=false&retries=4")
+            .unwrap();
+
+    assert_eq!(options.connect_timeout, Duration::from_secs(5));
+    assert!(!options.wait_eof);
+    assert_eq!(options.retries, 4);
+    assert!(SocketOptions::from_uri("socket://printer?waiteof=maybe").is_err());
+
+    assert_eq!(
+        socket::parse_address("socket://printer").unwrap(),
+        ("printer".to_string(), 9100)
+    );
+    assert_eq!(
+        socket::parse_address("socket://[::1]:9101?waiteof=true").unwrap(),
+        ("::1".to_string(), 9101)
+    );
+}
+
+#[test]
+pub fn test_socket_print_through_printer() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut received = Vec::new();
+        stream.read_to_end(&mut received).unwrap();
+        received
+    });
+
+    let printer = Printer::from_uri(&format!("socket://127.0.0.1:{}", port)).unwrap();
+    assert_eq!(printer.name, "127.0.0.1:".to_string() + &port.to_string());
+    assert_eq!(printer.print("^XA^XZ".as_bytes(), None), Ok(true));
+
+    assert_eq!(server.join().unwrap(), b"^XA^XZ".to_vec());
+}
+
+#[test]
+pub fn test_socket_wait_eof_reads_back_channel() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut received = Vec::new();
+        stream.read_to_end(&mut received).unwrap();
+        stream.write_all(b"@PJL USTATUS JOB END").unwrap();
+        received
+    });
+
+    let uri = format!("socket://127.0.0.1:{}?waiteof=true", port);
+    let options = SocketOptions::from_uri(&uri).unwrap();
+    let back_channel = socket::print(&uri, b"\x1bE", &options).unwrap();
+
+    assert_eq!(back_channel, b"@PJL USTATUS JOB END".to_vec());
+    assert_eq!(server.join().unwrap(), b"\x1bE".to_vec());
+}
+
+#[test]
+pub fn test_socket_does_not_resend_partial_documents() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let document = vec![b'A'; 8 * 1024 * 1024];
+
+    let server = thread::spawn(move || {
+        // Closing a connection with unread data resets it
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut first = [0; 16];
+        stream.read_exact(&mut first).unwrap();
+        drop(stream);
+
+        // The document must not be sent a second time
+        listener.set_nonblocking(true).unwrap();
+        thread::sleep(Duration::from_millis(200));
+        listener.accept().is_err()
+    });
+
+    let options = SocketOptions {
+        retry_delay: Duration::from_millis(10),
+        ..SocketOptions::default()
+    };
+
+    let uri = format!("socket://127.0.0.1:{}", port);
+    let error = socket::print(&uri, &document, &options).unwrap_err();
+    assert!(error.contains(&format!("of {} bytes", document.len())));
+    assert!(server.join().unwrap());
+}
+
+#[test]
+pub fn test_socket_connection_refused() {
+    let port = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().port()
+    };
+
+    let uri = format!("socket://127.0.0.1:{}", port);
+    let result = socket::print(&uri, b"data", &SocketOptions::default());
+    assert!(result.is_err());
+}
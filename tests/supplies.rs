@@ -0,0 +1,114 @@
+use printers::ipp::PrinterAttributes;
+use printers::supplies::{self, SupplyType};
+
+#[test]
+pub fn test_marker_attributes() {
+    let mut attributes = PrinterAttributes::default();
+    attributes
+        .set(
+            "marker-names",
+            &["Cyan Ink", "Black Ink", "Waste Ink Box", "Imaging Drum"],
+        )
+        .set(
+            "marker-types",
+            &["ink-cartridge", "ink", "waste-ink", "opc"],
+        )
+        .set("marker-colors", &["#00ffff", "#000000#333333", "none", ""])
+        .set("marker-levels", &["45", "3", "92", "-3"])
+        .set("marker-low-levels", &["10", "10", "0", "5"])
+        .set("marker-high-levels", &["100", "100", "90", "100"]);
+
+    let supplies = supplies::from_attributes(&attributes);
+    assert_eq!(supplies.len(), 4);
+
+    assert_eq!(supplies[0].name, "Cyan Ink");
+    assert_eq!(supplies[0].supply_type, SupplyType::Ink);
+    assert_eq!(supplies[0].color.as_deref(), Some("#00FFFF"));
+    assert_eq!(supplies[0].level, Some(45));
+    assert!(!supplies[0].is_low());
+
+    assert_eq!(supplies[1].color.as_deref(), Some("#000000"));
+    assert!(supplies[1].is_low());
+    assert!(!supplies[1].is_empty());
+
+    // Waste containers are low when almost full
+    assert_eq!(supplies[2].supply_type, SupplyType::Waste);
+    assert_eq!(supplies[2].color, None);
+    assert_eq!(supplies[2].high_level, Some(90));
+    assert!(supplies[2].is_low());
+
+    assert_eq!(supplies[3].supply_type, SupplyType::Drum);
+    assert_eq!(supplies[3].level, None);
+    assert!(!supplies[3].is_low());
+}
+
+#[test]
+pub fn test_missing_marker_values() {
+    let mut attributes = PrinterAttributes::default();
+    attributes
+        .set("marker-names", &["Toner", "Fuser Unit"])
+        .set("marker-types", &["toner-cartridge"])
+        .set("marker-levels", &["120", "-1"]);
+
+    let supplies = supplies::from_attributes(&attributes);
+    assert_eq!(supplies[0].supply_type, SupplyType::Toner);
+    assert_eq!(supplies[0].level, Some(100));
+    assert_eq!(supplies[0].low_level, None);
+    assert_eq!(
+        supplies[1].supply_type,
+        SupplyType::Other("unknown".to_string())
+    );
+    assert_eq!(supplies[1].level, None);
+
+    assert!(supplies::from_attributes(&PrinterAttributes::default()).is_empty());
+}
+
+#[test]
+pub fn test_printer_supply() {
+    let mut attributes = PrinterAttributes::default();
+    attributes
+        .set(
+            "printer-supply",
+            &[
+                "index=1;class=supplyThatIsConsumed;type=tonerCartridge;unit=percent;maxcapacity=100;level=80;colorantname=black;",
+                "index=2;class=receptacleThatIsFilled;type=wasteToner;unit=percent;maxcapacity=200;level=50;colorantname=unknown;",
+                "index=3;class=supplyThatIsConsumed;type=transferUnit;maxcapacity=-2;level=-3;",
+            ],
+        )
+        .set(
+            "printer-supply-description",
+            &["Black Toner Cartridge", ""],
+        );
+
+    let supplies = supplies::from_attributes(&attributes);
+    assert_eq!(supplies.len(), 3);
+    assert_eq!(supplies[0].name, "Black Toner Cartridge");
+    assert_eq!(supplies[0].supply_type, SupplyType::Toner);
+    assert_eq!(supplies[0].color.as_deref(), Some("#000000"));
+    assert_eq!(supplies[0].level, Some(80));
+
+    assert_eq!(supplies[1].name, "wasteToner");
+    assert_eq!(supplies[1].supply_type, SupplyType::Waste);
+    assert_eq!(supplies[1].color, None);
+    assert_eq!(supplies[1].level, Some(25));
+
+    assert_eq!(
+        supplies[2].supply_type,
+        SupplyType::Other("transferUnit".to_string())
+    );
+    assert_eq!(supplies[2].level, None);
+}
+
+#[test]
+pub fn test_supply_types() {
+    assert_eq!(SupplyType::parse("toner"), SupplyType::Toner);
+    assert_eq!(SupplyType::parse("ribbon-wax"), SupplyType::Ink);
+    assert_eq!(SupplyType::parse("inkCartridge"), SupplyType::Ink);
+    assert_eq!(SupplyType::parse("waste-toner"), SupplyType::Waste);
+    assert_eq!(SupplyType::parse("fuser-oil"), SupplyType::Fuser);
+    assert_eq!(SupplyType::parse("staples"), SupplyType::Staples);
+    assert_eq!(
+        SupplyType::parse("cleaner-unit"),
+        SupplyType::Other("cleaner-unit".to_string())
+    );
+}
@@ -0,0 +1,119 @@
+use printers::document::DocumentFormat;
+use printers::pdf::{Margins, PageSize};
+use printers::text::{self, TextOptions};
+
+/**
+ * Returns options of a small page with 10 columns and 3 lines
+ */
+fn small_page() -> TextOptions {
+    TextOptions {
+        page_size: PageSize::new(80.0, 56.0),
+        margins: Margins::uniform(10.0),
+        font_size: 10.0,
+        line_spacing: 1.2,
+        ..TextOptions::default()
+    }
+}
+
+#[test]
+pub fn test_text_wrap_and_tabs() {
+    let options = small_page();
+    let pages = text::paginate("a\tb\nthe quick brown fox\r\nabcdefghijklmno", &options).unwrap();
+
+    assert_eq!(
+        pages,
+        vec![
+            vec!["a       b", "the quick", "brown fox"],
+            vec!["abcdefghij", "klmno"],
+        ]
+    );
+
+    let options = TextOptions {
+        wrap: false,
+        tab_size: 4,
+        ..small_page()
+    };
+    let pages = text::paginate("a\tb\nthe quick brown fox\n", &options).unwrap();
+    assert_eq!(pages, vec![vec!["a   b", "the quick "]]);
+}
+
+#[test]
+pub fn test_text_pagination() {
+    let options = small_page();
+
+    // Form feeds start a new page, trailing blank pages are removed
+    let pages = text::paginate("one\x0ctwo\n\nthree\nfour\x0c", &options).unwrap();
+    assert_eq!(
+        pages,
+        vec![vec!["one"], vec!["two", "", "three"], vec!["four"]]
+    );
+
+    assert_eq!(
+        text::paginate("", &options).unwrap(),
+        vec![Vec::<String>::new()]
+    );
+
+    // A header and a footer take 2 lines each
+    let options = TextOptions {
+        page_size: PageSize::new(80.0, 104.0),
+        header: Some("{page}/{pages}".to_string()),
+        footer: Some("end".to_string()),
+        ..small_page()
+    };
+    let pages = text::paginate("1\n2\n3\n4", &options).unwrap();
+    assert_eq!(pages, vec![vec!["1", "2", "3"], vec!["4"]]);
+
+    let options = TextOptions {
+        margins: Margins::uniform(40.0),
+        ..small_page()
+    };
+    assert!(text::paginate("x", &options).is_err());
+}
+
+#[test]
+pub fn test_text_render() {
+    let options = TextOptions {
+        page_size: PageSize::new(80.0, 104.0),
+        header: Some("Page {page}/{pages}".to_string()),
+        ..small_page()
+    };
+    let input = "(1)\n2\n3\n4\n5\n€";
+
+    let pdf = text::render_pdf(input, &options).unwrap();
+    let content = String::from_utf8_lossy(&pdf);
+    assert_eq!(DocumentFormat::detect(&pdf), DocumentFormat::PDF);
+    assert!(content.contains("/Count 2"));
+    assert!(content.contains("/BaseFont /Courier /Encoding /WinAnsiEncoding"));
+    assert!(content.contains("/MediaBox [0 0 80 104]"));
+    assert!(content.ends_with("%%EOF\n"));
+
+    let postscript = text::render_postscript(input, &options).unwrap();
+    let content = String::from_utf8(postscript.clone()).unwrap();
+    assert_eq!(
+        DocumentFormat::detect(&postscript),
+        DocumentFormat::POSTSCRIPT
+    );
+    assert!(content.contains("%%Pages: 2\n"));
+    assert!(content.contains("<< /PageSize [80 104] >> setpagedevice"));
+    assert!(content.contains("10 60 moveto (\\(1\\)) show\n"));
+    assert!(content.contains("10 84 moveto (Page 1/2) show\n"));
+    assert!(content.contains("%%Page: 2 2\nF setfont\n10 60 moveto (\\200) show\n"));
+}
+
+#[test]
+pub fn test_text_out_of_encoding() {
+    let options = small_page();
+    assert_eq!(
+        text::render_pdf("Ελλάδα", &options).unwrap_err(),
+        "character 'Ε' (U+0395) is out of the WinAnsi encoding of the standard fonts"
+    );
+    assert!(text::render_postscript("日本", &options).is_err());
+
+    let options = TextOptions {
+        page_size: PageSize::new(80.0, 104.0),
+        footer: Some("→ {page}".to_string()),
+        ..small_page()
+    };
+    assert!(text::paginate("Café\tœuvre", &options).is_err());
+    assert!(text::paginate("Café\tœuvre", &small_page()).is_ok());
+}
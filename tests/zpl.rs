@@ -0,0 +1,75 @@
+use printers::zpl::{mm_to_dots, QrErrorCorrection, Rotation, Zpl};
+
+#[test]
+pub fn test_zpl_label_setup() {
+    let mut label = Zpl::new(203);
+    assert_eq!(label.mm(100.0), 799);
+    assert_eq!(mm_to_dots(25.4, 300), 300);
+
+    let bytes = label
+        .print_width_mm(100.0)
+        .label_length(1218)
+        .darkness(15)
+        .quantity(2)
+        .build();
+
+    assert_eq!(
+        String::from_utf8(bytes).unwrap(),
+        "^XA^CI28^PW799^LL1218~SD15^PQ2^XZ"
+    );
+}
+
+#[test]
+pub fn test_zpl_fields() {
+    let bytes = Zpl::new(203)
+        .text(10, 20, '0', Rotation::Rotated90, 30, 25, "Olá")
+        .text(10, 60, 'A', Rotation::Normal, 10, 10, "50% ^off_")
+        .graphic_box(0, 0, 400, 200, 3)
+        .horizontal_line(0, 100, 400, 2)
+        .build();
+
+    assert_eq!(
+        String::from_utf8(bytes).unwrap(),
+        "^XA^CI28\
+         ^FO10,20^A0R,30,25^FDOlá^FS\
+         ^FO10,60^AAN,10,10^FH_^FD50% _5Eoff_5F^FS\
+         ^FO0,0^GB400,200,3^FS\
+         ^FO0,100^GB400,2,2^FS\
+         ^XZ"
+    );
+}
+
+#[test]
+pub fn test_zpl_barcodes() {
+    let bytes = Zpl::new(300)
+        .code128(10, 10, Rotation::Normal, 100, true, "ABC123")
+        .gs1_128(10, 150, Rotation::Normal, 80, "(01)09501101530003")
+        .data_matrix(10, 300, Rotation::Normal, 6, "DM")
+        .qr_code(10, 400, 5, QrErrorCorrection::M, "https://example.com")
+        .build();
+
+    assert_eq!(
+        String::from_utf8(bytes).unwrap(),
+        "^XA^CI28\
+         ^FO10,10^BCN,100,Y,N,N^FDABC123^FS\
+         ^FO10,150^BCN,80,Y,N,N,D^FD(01)09501101530003^FS\
+         ^FO10,300^BXN,6,200^FDDM^FS\
+         ^FO10,400^BQN,2,5^FDMA,https://example.com^FS\
+         ^XZ"
+    );
+}
+
+#[test]
+pub fn test_zpl_graphic() {
+    let bytes = Zpl::new(203)
+        .graphic(5, 5, 12, 2, &[0xff, 0xf0, 0x80, 0x10])
+        .unwrap()
+        .build();
+
+    assert_eq!(
+        String::from_utf8(bytes).unwrap(),
+        "^XA^CI28^FO5,5^GFA,4,4,2,FFF08010^FS^XZ"
+    );
+
+    assert!(Zpl::new(203).graphic(0, 0, 12, 2, &[0xff]).is_err());
+}